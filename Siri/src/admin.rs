@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use crate::audit;
+use crate::auth::{format_sqlite_timestamp, hash_password, validate_password_strength};
+use crate::config;
+use crate::error::{AuthError, AuthResult};
+use crate::events::{self, Event};
+use crate::rbac::{require_permission, Permission};
+use crate::message;
+
+/// Aplica uma alteração a um usuário ativo e registra a ação no log de auditoria
+fn admin_update(conn: &Connection, admin: &str, action: &str, username: &str, sql: &str) -> AuthResult<()> {
+    require_permission(conn, admin, Permission::ManageAccounts)?;
+
+    let tx = conn.unchecked_transaction()?;
+    if tx.execute(sql, [username])? == 0 {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    }
+    audit::record(&*tx, admin, action, Some(username))?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Desbloqueia uma conta, zerando as falhas de login
+pub fn unlock_user(conn: &Connection, admin: &str, username: &str) -> AuthResult<()> {
+    admin_update(
+        conn,
+        admin,
+        "unlock_user",
+        username,
+        "UPDATE users SET failed_attempts = 0, locked_until = NULL
+         WHERE username = ?1 AND deleted_at IS NULL",
+    )
+}
+
+/// Bloqueia uma conta até o instante informado, como se tivesse excedido as tentativas de login
+pub fn lock_user(conn: &Connection, admin: &str, username: &str, until: DateTime<Utc>) -> AuthResult<()> {
+    require_permission(conn, admin, Permission::ManageAccounts)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let updated = tx.execute(
+        "UPDATE users SET locked_until = ?1 WHERE username = ?2 AND deleted_at IS NULL",
+        [&format_sqlite_timestamp(until), username],
+    )?;
+    if updated == 0 {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    }
+    audit::record(&*tx, admin, "lock_user", Some(username))?;
+    tx.commit()?;
+    events::emit(Event::AccountLocked { username: username.to_string(), until }, Utc::now());
+    Ok(())
+}
+
+/// Exige que o usuário troque a senha no próximo login
+pub fn force_password_change(conn: &Connection, admin: &str, username: &str) -> AuthResult<()> {
+    admin_update(
+        conn,
+        admin,
+        "force_password_change",
+        username,
+        "UPDATE users SET must_change_password = 1
+         WHERE username = ?1 AND deleted_at IS NULL",
+    )
+}
+
+/// Define uma senha temporária, que o usuário precisará trocar no próximo login.
+///
+/// A conta também é desbloqueada, já que o objetivo é devolver o acesso.
+pub fn reset_password(conn: &Connection, admin: &str, username: &str, temporary_password: &str) -> AuthResult<()> {
+    require_permission(conn, admin, Permission::ManageAccounts)?;
+    validate_password_strength(temporary_password, &config::current().password, &[username])?;
+
+    let password_hash = hash_password(conn, temporary_password)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let updated = tx.execute(
+        "UPDATE users SET password_hash = ?1, password_changed_at = CURRENT_TIMESTAMP,
+                          must_change_password = 1, failed_attempts = 0, locked_until = NULL
+         WHERE username = ?2 AND deleted_at IS NULL",
+        [&password_hash, username],
+    )?;
+    if updated == 0 {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    }
+    audit::record(&*tx, admin, "reset_password", Some(username))?;
+    tx.commit()?;
+    events::emit(Event::PasswordChanged { username: username.to_string() }, Utc::now());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{login_user, LoginOutcome};
+    use crate::testing::{database, register, PASSWORD};
+
+    const WRONG_PASSWORD: &str = "Senha-Errada-99";
+
+    /// Banco com o administrador "admin" (a primeira conta) e a conta comum "alice"
+    fn accounts() -> crate::db::Database {
+        let db = database();
+        register(&db, "admin");
+        register(&db, "alice");
+        db
+    }
+
+    fn failed_attempts(conn: &Connection, username: &str) -> u32 {
+        conn.query_row("SELECT failed_attempts FROM users WHERE username = ?1", [username], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn unlock_clears_the_lock_and_the_failure_count() {
+        let db = accounts();
+        let conn = db.connection().unwrap();
+        let attempts = config::current().lockout.max_failed_attempts;
+        for _ in 0..attempts {
+            assert_eq!(login_user(&*conn, "alice", WRONG_PASSWORD).unwrap(), LoginOutcome::InvalidCredentials);
+        }
+        assert!(matches!(login_user(&*conn, "alice", PASSWORD).unwrap(), LoginOutcome::Locked { .. }));
+
+        unlock_user(&conn, "admin", "alice").unwrap();
+        let locked_until: Option<String> = conn
+            .query_row("SELECT locked_until FROM users WHERE username = 'alice'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(locked_until, None);
+        assert_eq!(failed_attempts(&conn, "alice"), 0);
+
+        // Uma falha depois do desbloqueio conta do zero, sem bloquear de novo
+        assert_eq!(login_user(&*conn, "alice", WRONG_PASSWORD).unwrap(), LoginOutcome::InvalidCredentials);
+        assert_eq!(failed_attempts(&conn, "alice"), 1);
+        assert_eq!(login_user(&*conn, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn forced_change_expires_the_password_at_the_next_login() {
+        let db = accounts();
+        let conn = db.connection().unwrap();
+        assert_eq!(login_user(&*conn, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+
+        force_password_change(&conn, "admin", "alice").unwrap();
+        assert_eq!(login_user(&*conn, "alice", PASSWORD).unwrap(), LoginOutcome::PasswordExpired);
+    }
+
+    #[test]
+    fn accounts_are_managed_only_by_admins() {
+        let db = accounts();
+        let conn = db.connection().unwrap();
+        let denied = |result: AuthResult<()>| matches!(result, Err(AuthError::PermissionDenied(_)));
+
+        assert!(denied(unlock_user(&conn, "alice", "admin")));
+        assert!(denied(lock_user(&conn, "alice", "admin", Utc::now())));
+        assert!(denied(force_password_change(&conn, "alice", "admin")));
+        assert!(denied(reset_password(&conn, "alice", "admin", "Frase-Temporaria-Longa-31")));
+        assert_eq!(login_user(&*conn, "admin", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+}
@@ -0,0 +1,335 @@
+use std::ffi::OsString;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use crate::audit::DEFAULT_AUDIT_LIMIT;
+#[cfg(unix)]
+use crate::daemon::DEFAULT_SOCKET;
+use crate::export::ExportFormat;
+use crate::genpass::DEFAULT_GENERATED_LENGTH;
+use crate::server::DEFAULT_LISTEN;
+use crate::store::UserSort;
+
+/// Argumentos de linha de comando
+#[derive(Debug, Parser)]
+#[command(name = "siri", version, about = "Sistema de autenticação Siri")]
+pub struct Args {
+    /// Emite o resultado como JSON, sem prompts interativos
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Abre a interface de tela cheia, com usuários, detalhes da conta e auditoria
+    #[arg(long, conflicts_with = "json")]
+    pub tui: bool,
+
+    /// Exibe '*' para cada caractere da senha em vez de ocultá-la totalmente
+    #[arg(long, global = true)]
+    pub mask: bool,
+
+    /// Troca emoji e molduras por rótulos ASCII (automático quando TERM=dumb)
+    #[arg(long, global = true)]
+    pub plain: bool,
+
+    /// Não colore as mensagens (o mesmo que definir NO_COLOR)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Lê a senha da primeira linha da entrada padrão em vez de um prompt
+    #[arg(long, global = true)]
+    pub password_stdin: bool,
+
+    /// Se o banco estiver corrompido, move-o para o lado e começa um banco novo
+    #[arg(long, global = true)]
+    pub recover_corrupt: bool,
+
+    /// Usa um banco SQLite em memória, descartado ao sair, sem tocar no banco em disco
+    #[arg(long, global = true)]
+    pub ephemeral: bool,
+
+    /// Caminho do banco SQLite (padrão: database.path do siri.toml ou $XDG_DATA_HOME/siri/users.db)
+    #[arg(long, global = true, value_name = "CAMINHO", conflicts_with = "ephemeral")]
+    pub db: Option<PathBuf>,
+
+    /// Grava o log de diagnóstico neste arquivo (padrão: log.file do siri.toml; sem ele, não há log)
+    #[arg(long, global = true, value_name = "CAMINHO")]
+    pub log_file: Option<PathBuf>,
+
+    /// Onde os usuários ficam guardados
+    #[arg(long, global = true, value_enum, env = "SIRI_BACKEND", default_value_t = Backend::Sqlite)]
+    pub backend: Backend,
+
+    /// String de conexão do PostgreSQL (ex.: postgres://siri@db.local/siri)
+    #[arg(long, global = true, env = "SIRI_DSN", hide_env_values = true)]
+    pub dsn: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Backends de armazenamento disponíveis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Arquivo SQLite local (veja --db)
+    Sqlite,
+    /// Servidor PostgreSQL compartilhado (requer --dsn)
+    Postgres,
+}
+
+/// Comandos não interativos (sem comando, abre o menu interativo)
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Registra um novo usuário
+    Register {
+        username: String,
+        /// Email opcional da conta
+        #[arg(long)]
+        email: Option<String>,
+    },
+    /// Verifica as credenciais de um usuário
+    Login {
+        username: String,
+        /// Código do aplicativo autenticador (ou de recuperação), para contas com dois fatores
+        #[arg(long, value_name = "CÓDIGO")]
+        totp: Option<String>,
+    },
+    /// Lista os usuários cadastrados
+    #[command(visible_alias = "users")]
+    List {
+        /// Apenas usuários cujo nome ou email contém o texto (* e ? funcionam como curingas)
+        #[arg(long, value_name = "PADRÃO")]
+        filter: Option<String>,
+        /// Campo que ordena a listagem
+        #[arg(long, value_enum, value_name = "CAMPO", default_value = "username")]
+        sort: UserSort,
+        /// Ordem decrescente
+        #[arg(long)]
+        desc: bool,
+        /// Pula os primeiros usuários, na ordem escolhida
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        /// Máximo de usuários exibidos (padrão: todos)
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Exclui um usuário (de forma reversível, até ser purgado)
+    Delete {
+        username: String,
+        /// Não pede confirmação
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Torna um usuário administrador
+    Promote { username: String },
+    /// Torna um administrador um usuário comum
+    Demote { username: String },
+    /// Desativa uma conta sem apagar seus dados; o login passa a ser recusado
+    Disable { username: String },
+    /// Reativa uma conta desativada
+    Enable { username: String },
+    /// Consulta e altera as permissões de cada papel
+    Permissions {
+        #[command(subcommand)]
+        action: PermissionsAction,
+    },
+    /// Gerencia grupos de usuários
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
+    },
+    /// Mostra estatísticas do sistema
+    Stats,
+    /// Conta as senhas armazenadas fora do formato do Argon2
+    HashHealth,
+    /// Confere banco, versão do esquema e hash de senhas (termina com 1 se algo falhar)
+    Health,
+    /// Confere a integridade do banco, os hashes gravados e as linhas órfãs (termina com 1 se houver problemas)
+    Fsck,
+    /// Exporta todos os usuários para um arquivo cifrado com uma frase secreta
+    Dump {
+        /// Arquivo de destino
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Importa uma exportação cifrada para um banco sem usuários
+    Load { path: PathBuf },
+    /// Grava uma cópia do banco SQLite sem tirar o sistema do ar
+    Backup {
+        /// Arquivo de destino (criado com permissão 600; não pode existir)
+        path: PathBuf,
+        /// Compacta a cópia com gzip
+        #[arg(long)]
+        gzip: bool,
+    },
+    /// Substitui o banco SQLite pelo conteúdo de uma cópia de segurança
+    Restore {
+        /// Cópia gravada por `siri backup` (compactada ou não)
+        path: PathBuf,
+        /// Restaura mesmo que o banco atual tenha alterações mais recentes que a cópia
+        #[arg(long)]
+        force: bool,
+        /// Não pede confirmação
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Cadastra em lote as contas de um CSV (username, password ou password_hash, email, role)
+    Import { path: PathBuf },
+    /// Aplica as migrações pendentes do esquema (também aplicadas ao iniciar)
+    Migrate {
+        /// Apenas mostra a versão do esquema e as migrações aplicadas
+        #[arg(long)]
+        status: bool,
+    },
+    /// Mede esta máquina e ajusta o custo do Argon2 ao tempo desejado
+    Calibrate {
+        /// Tempo alvo de um hash, em milissegundos
+        #[arg(long, default_value_t = 250)]
+        target_ms: u64,
+    },
+    /// Gera uma senha aleatória que cumpre a política de senhas
+    Genpass {
+        /// Número de caracteres
+        #[arg(long, default_value_t = DEFAULT_GENERATED_LENGTH)]
+        length: usize,
+        /// Sem letras minúsculas
+        #[arg(long)]
+        no_lowercase: bool,
+        /// Sem letras maiúsculas
+        #[arg(long)]
+        no_uppercase: bool,
+        /// Sem números
+        #[arg(long)]
+        no_digits: bool,
+        /// Sem caracteres especiais
+        #[arg(long)]
+        no_symbols: bool,
+    },
+    /// Gerencia o pepper das senhas
+    Pepper {
+        #[command(subcommand)]
+        action: PepperAction,
+    },
+    /// Gera um token de uso único para o usuário redefinir a senha sem a senha atual
+    ResetToken {
+        username: String,
+        /// Validade do token, em minutos
+        #[arg(long, default_value_t = 60)]
+        ttl_minutes: u32,
+    },
+    /// Define uma nova senha usando um token de redefinição
+    ResetPassword { token: String },
+    /// Envia um token de redefinição para o dono de um email verificado
+    RequestReset { email: String },
+    /// Envia um token para confirmar o email pendente de um usuário
+    RequestVerification { username: String },
+    /// Confirma um email com o token de verificação
+    VerifyEmail { token: String },
+    /// Consulta o log de auditoria, das ações mais recentes para as mais antigas
+    Audit {
+        /// Apenas ações deste autor
+        #[arg(long)]
+        actor: Option<String>,
+        /// Apenas ações com este nome (ex.: register, delete_user)
+        #[arg(long)]
+        action: Option<String>,
+        /// Apenas ações sobre este usuário
+        #[arg(long)]
+        target: Option<String>,
+        /// Máximo de entradas exibidas
+        #[arg(long, default_value_t = DEFAULT_AUDIT_LIMIT)]
+        limit: usize,
+    },
+    /// Exporta em JSON tudo o que o sistema guarda sobre um usuário (portabilidade de dados)
+    ExportUser {
+        username: String,
+        /// Grava o documento neste arquivo em vez de exibi-lo
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Exporta a tabela de usuários em CSV ou JSON, para relatórios e migração
+    Export {
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+        /// Arquivo de destino (criado com permissão 600)
+        #[arg(long)]
+        out: PathBuf,
+        /// Inclui os campos do perfil (nome de exibição, telefone e bio)
+        #[arg(long)]
+        profile: bool,
+        /// Inclui os hashes das senhas; exige --admin
+        #[arg(long, requires = "admin")]
+        include_hashes: bool,
+        /// Administrador que autoriza a exportação dos hashes (a senha dele é pedida)
+        #[arg(long, value_name = "USUÁRIO")]
+        admin: Option<String>,
+        /// Código do segundo fator do administrador, se a conta exigir
+        #[arg(long)]
+        totp: Option<String>,
+    },
+    /// Atende uma API HTTP de cadastro, login, troca de senha e listagem de usuários
+    Serve {
+        /// Endereço e porta em que o servidor escuta (ex.: 0.0.0.0:8080)
+        #[arg(long, value_name = "ENDEREÇO", default_value = DEFAULT_LISTEN)]
+        listen: SocketAddr,
+    },
+    /// Confere credenciais para serviços locais por um socket Unix (uma linha JSON por pedido)
+    #[cfg(unix)]
+    Daemon {
+        /// Caminho do socket
+        #[arg(long, value_name = "CAMINHO", default_value = DEFAULT_SOCKET)]
+        socket: PathBuf,
+        /// Também expõe `/metrics` por HTTP neste endereço (ex.: 127.0.0.1:9090)
+        #[arg(long, value_name = "ENDEREÇO")]
+        metrics_listen: Option<SocketAddr>,
+    },
+    /// Confere as credenciais do descritor 3 no protocolo checkpassword (qmail/Dovecot)
+    #[cfg(unix)]
+    Checkpassword {
+        /// Programa executado se as credenciais conferem (ex.: o checkpassword-reply do Dovecot)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, value_name = "PROGRAMA")]
+        program: Vec<OsString>,
+    },
+}
+
+/// Operações sobre o pepper
+#[derive(Debug, Subcommand)]
+pub enum PepperAction {
+    /// Troca o pepper de hashing.pepper_file por um novo; as senhas são refeitas no próximo login
+    Rotate,
+}
+
+/// Operações sobre as permissões dos papéis
+#[derive(Debug, Subcommand)]
+pub enum PermissionsAction {
+    /// Lista as permissões de cada papel
+    List,
+    /// Concede uma permissão a um papel (ex.: grant user list_users)
+    Grant { role: String, permission: String },
+    /// Revoga uma permissão de um papel
+    Revoke { role: String, permission: String },
+}
+
+/// Operações sobre grupos de usuários
+#[derive(Debug, Subcommand)]
+pub enum GroupAction {
+    /// Cria um grupo vazio
+    Create { name: String },
+    /// Apaga um grupo (as contas dos membros não são afetadas)
+    Delete {
+        name: String,
+        /// Não pede confirmação
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Adiciona um usuário a um grupo
+    Add { group: String, username: String },
+    /// Remove um usuário de um grupo
+    Remove { group: String, username: String },
+    /// Lista os grupos, ou apenas os de um usuário
+    List {
+        /// Lista os grupos deste usuário
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Lista os membros de um grupo
+    Members { group: String },
+}
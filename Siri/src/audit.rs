@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use crate::error::AuthResult;
+use crate::store::UserStore;
+
+/// Quantidade padrão de entradas retornadas por uma consulta ao log
+pub const DEFAULT_AUDIT_LIMIT: usize = 50;
+
+/// Autor das ações feitas pelos comandos não interativos, que não têm login
+pub const COMMAND_ACTOR: &str = "comando";
+
+/// Uma ação registrada no log de auditoria
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// Quem executou a ação
+    pub actor: String,
+    /// Nome estável da ação (ex.: "register", "delete_user")
+    pub action: String,
+    /// Usuário afetado, se houver
+    pub target: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filtros de uma consulta ao log de auditoria; campos `None` não filtram
+#[derive(Debug, Clone)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub target: Option<String>,
+    /// Máximo de entradas, das mais recentes para as mais antigas
+    pub limit: usize,
+}
+
+impl Default for AuditFilter {
+    fn default() -> Self {
+        AuditFilter { actor: None, action: None, target: None, limit: DEFAULT_AUDIT_LIMIT }
+    }
+}
+
+/// Registra uma ação que alterou dados no log de auditoria
+pub fn record(store: &dyn UserStore, actor: &str, action: &str, target: Option<&str>) -> AuthResult<()> {
+    store.record_audit(actor, action, target)
+}
+
+/// Consulta o log de auditoria, das entradas mais recentes para as mais antigas
+pub fn search(store: &dyn UserStore, filter: &AuditFilter) -> AuthResult<Vec<AuditEntry>> {
+    store.audit_log(filter)
+}
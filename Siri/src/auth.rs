@@ -1,20 +1,146 @@
-use rusqlite::Connection;
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
 use argon2::{
     password_hash::{
         rand_core::OsRng,
         PasswordHash, PasswordHasher, PasswordVerifier, SaltString
     },
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
-use crate::error::{AuthError, AuthResult};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use crate::audit;
+use crate::config;
+use crate::breach::check_not_breached;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::secrets_equal;
+use crate::events::{self, Event};
+use crate::metrics;
+use crate::hasher::{HashScheme, HashingConfig};
+use crate::error::{AuthError, AuthResult, PasswordIssue};
+use crate::{message, tr, validation, validation_error};
+use crate::pepper::{load_pepper, Peppers};
+use crate::rbac::is_admin;
+use crate::session::{generate_token, token_hash};
+use crate::settings::{
+    ARGON2_M_COST_KEY, ARGON2_P_COST_KEY, ARGON2_T_COST_KEY, MAX_USERS_KEY, PASSWORD_MAX_AGE_DAYS_KEY,
+};
+use crate::store::{StoredCredentials, UserStore};
+use crate::strength::{estimate_strength, StrengthScore};
+use crate::totp::{dummy_second_factor, is_totp_enabled, verify_second_factor};
+
+/// Formato usado pelo SQLite em CURRENT_TIMESTAMP
+const SQLITE_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Resultado de uma tentativa de login
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginOutcome {
+    /// Credenciais corretas, acesso liberado
+    Success,
+    /// Usuário inexistente ou senha incorreta
+    InvalidCredentials,
+    /// Conta bloqueada temporariamente por excesso de tentativas
+    Locked { until: DateTime<Utc> },
+    /// Conta desativada por um administrador
+    Disabled,
+    /// Senha correta, mas ainda falta o segundo fator de autenticação
+    RequiresSecondFactor,
+    /// Credenciais corretas, mas a senha precisa ser trocada antes de continuar
+    /// (expirou ou um administrador exigiu a troca)
+    PasswordExpired,
+}
+
+impl LoginOutcome {
+    /// Indica se a senha informada conferiu, independentemente de outras etapas pendentes
+    pub fn credentials_valid(&self) -> bool {
+        matches!(
+            self,
+            LoginOutcome::Success | LoginOutcome::RequiresSecondFactor | LoginOutcome::PasswordExpired
+        )
+    }
+
+    /// Nome estável do resultado, para saída estruturada e para o histórico de logins
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoginOutcome::Success => "success",
+            LoginOutcome::InvalidCredentials => "invalid_credentials",
+            LoginOutcome::Locked { .. } => "locked",
+            LoginOutcome::Disabled => "disabled",
+            LoginOutcome::RequiresSecondFactor => "requires_second_factor",
+            LoginOutcome::PasswordExpired => "password_expired",
+        }
+    }
+
+    /// `Ok` apenas para `Success`; os demais resultados viram o erro correspondente,
+    /// com seu próprio código de saída
+    pub fn into_result(self) -> AuthResult<()> {
+        match self {
+            LoginOutcome::Success => Ok(()),
+            LoginOutcome::InvalidCredentials => Err(AuthError::InvalidCredentials),
+            LoginOutcome::Locked { until } => Err(AuthError::AccountLocked(until)),
+            LoginOutcome::Disabled => Err(AuthError::AccountDisabled),
+            LoginOutcome::RequiresSecondFactor => Err(AuthError::SecondFactorRequired),
+            LoginOutcome::PasswordExpired => Err(AuthError::PasswordExpired),
+        }
+    }
+}
+
+/// Mensagem segura para exibir ao usuário sobre o resultado de um login.
+///
+/// Usuário inexistente e senha incorreta chegam aqui como o mesmo
+/// `InvalidCredentials` e devem continuar indistinguíveis: toda mensagem de login
+/// exibida deve passar por esta função. Bloqueio, desativação e troca obrigatória
+/// têm mensagens próprias por decisão de política.
+pub fn describe_login_outcome(outcome: &LoginOutcome) -> String {
+    let (icon, message) = match outcome {
+        LoginOutcome::Success => ("✅", tr!("Login bem-sucedido!")),
+        LoginOutcome::InvalidCredentials => ("❌", tr!("Credenciais inválidas.")),
+        LoginOutcome::Locked { .. } => ("🔒", tr!("Conta bloqueada temporariamente por excesso de tentativas.")),
+        LoginOutcome::Disabled => ("🚫", tr!("Conta desativada. Procure um administrador.")),
+        LoginOutcome::RequiresSecondFactor => ("🔐", tr!("Esta conta exige um segundo fator de autenticação.")),
+        LoginOutcome::PasswordExpired => ("⏳", tr!("Sua senha expirou ou precisa ser trocada.")),
+    };
+    format!("{} {}", icon, message)
+}
+
+/// Política de bloqueio de conta após falhas consecutivas de login
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LockoutPolicy {
+    /// Falhas consecutivas permitidas antes do bloqueio (0 = nunca bloqueia)
+    pub max_failed_attempts: u32,
+    /// Por quanto tempo a conta fica bloqueada (`lock_minutes` no arquivo de configuração)
+    #[serde(rename = "lock_minutes", deserialize_with = "crate::config::minutes")]
+    pub lock_duration: Duration,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        LockoutPolicy {
+            max_failed_attempts: 5,
+            lock_duration: Duration::minutes(15),
+        }
+    }
+}
 
 /// Configuração de validação de senha
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct PasswordConfig {
     pub min_length: usize,
     pub require_digit: bool,
     pub require_uppercase: bool,
     pub require_lowercase: bool,
     pub require_special: bool,
+    /// Força mínima estimada; senhas abaixo dela são recusadas mesmo cumprindo as regras acima
+    pub min_strength: StrengthScore,
+    /// Idade máxima da senha em dias (0 = nunca expira)
+    pub max_age_days: u32,
+    /// Limite de usuários cadastrados (None = sem limite)
+    pub max_users: Option<u32>,
+    /// Dias em que o nome de uma conta excluída continua reservado (0 = liberado na hora)
+    pub deleted_username_grace_days: u32,
 }
 
 impl Default for PasswordConfig {
@@ -25,162 +151,1233 @@ impl Default for PasswordConfig {
             require_uppercase: false,
             require_lowercase: false,
             require_special: false,
+            min_strength: StrengthScore::Fair,
+            max_age_days: 0,
+            max_users: None,
+            deleted_username_grace_days: 30,
         }
     }
 }
 
+/// Caracteres que contam como especiais para `require_special`
+pub(crate) const SPECIAL_CHARACTERS: &str = "!@#$%^&*()_+-=[]{}|;:,.<>?";
+
 /// Valida as credenciais de entrada
 fn validate_credentials(username: &str, password: &str) -> AuthResult<()> {
     if username.is_empty() {
-        return Err(AuthError::Validation("Nome de usuário não pode estar vazio".to_string()));
+        return validation_error!("Nome de usuário não pode estar vazio");
     }
     
     if password.is_empty() {
-        return Err(AuthError::Validation("Senha não pode estar vazia".to_string()));
+        return validation_error!("Senha não pode estar vazia");
     }
     
     Ok(())
 }
 
-/// Valida a força da senha com base na configuração
-fn validate_password_strength(password: &str, config: &PasswordConfig) -> AuthResult<()> {
+/// Tamanho mínimo do nome de usuário
+const USERNAME_MIN_LENGTH: usize = 3;
+
+/// Tamanho máximo do nome de usuário
+const USERNAME_MAX_LENGTH: usize = 32;
+
+/// Valida o formato do nome de usuário
+pub(crate) fn validate_username(username: &str) -> AuthResult<()> {
+    let length = username.chars().count();
+
+    if !(USERNAME_MIN_LENGTH..=USERNAME_MAX_LENGTH).contains(&length) {
+        return validation_error!(
+            "O nome de usuário deve ter entre {} e {} caracteres",
+            USERNAME_MIN_LENGTH,
+            USERNAME_MAX_LENGTH
+        );
+    }
+
+    if !username.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return validation_error!("O nome de usuário deve conter apenas letras, números, '_', '-' ou '.'");
+    }
+
+    Ok(())
+}
+
+/// Tamanho máximo de um endereço de email (RFC 5321)
+const EMAIL_MAX_LENGTH: usize = 254;
+
+/// Valida o formato básico de um endereço de email
+pub fn validate_email(email: &str) -> AuthResult<()> {
+    let invalid = || validation!("Email '{}' inválido", email);
+
+    if email.is_empty() || email.len() > EMAIL_MAX_LENGTH || email.chars().any(char::is_whitespace) {
+        return Err(invalid());
+    }
+
+    let (local, domain) = email.split_once('@').ok_or_else(invalid)?;
+
+    if local.is_empty() || domain.contains('@') {
+        return Err(invalid());
+    }
+
+    // O domínio precisa ter ao menos um ponto e nenhum rótulo vazio
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 || labels.iter().any(|label| label.is_empty()) {
+        return Err(invalid());
+    }
+
+    Ok(())
+}
+
+/// Valida a força da senha com base na configuração.
+///
+/// `user_inputs` são dados da conta (nome de usuário, email) que tornam a senha
+/// mais fácil de adivinhar se aparecerem nela.
+pub(crate) fn validate_password_strength(
+    password: &str,
+    config: &PasswordConfig,
+    user_inputs: &[&str],
+) -> AuthResult<()> {
     if password.len() < config.min_length {
-        return Err(AuthError::Validation(
-            format!("A senha deve ter pelo menos {} caracteres", config.min_length)
-        ));
+        return Err(PasswordIssue::TooShort { min_length: config.min_length }.into());
     }
     
     if config.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
-        return Err(AuthError::Validation("A senha deve conter pelo menos um número".to_string()));
+        return Err(PasswordIssue::MissingDigit.into());
     }
     
     if config.require_uppercase && !password.chars().any(|c| c.is_ascii_uppercase()) {
-        return Err(AuthError::Validation("A senha deve conter pelo menos uma letra maiúscula".to_string()));
+        return Err(PasswordIssue::MissingUppercase.into());
     }
     
     if config.require_lowercase && !password.chars().any(|c| c.is_ascii_lowercase()) {
-        return Err(AuthError::Validation("A senha deve conter pelo menos uma letra minúscula".to_string()));
+        return Err(PasswordIssue::MissingLowercase.into());
     }
     
-    if config.require_special && !password.chars().any(|c| "!@#$%^&*()_+-=[]{}|;:,.<>?".contains(c)) {
-        return Err(AuthError::Validation("A senha deve conter pelo menos um caractere especial".to_string()));
+    if config.require_special && !password.chars().any(|c| SPECIAL_CHARACTERS.contains(c)) {
+        return Err(PasswordIssue::MissingSpecial.into());
+    }
+
+    // Cumprir as regras não basta: "Senha123" tem número e maiúscula e cai em segundos
+    let report = estimate_strength(password, user_inputs);
+    if report.score < config.min_strength {
+        return Err(PasswordIssue::TooGuessable { score: report.score, hints: report.hints }.into());
     }
+
+    // Por último, por ser a única verificação que pode consultar a rede
+    check_not_breached(password)?;
     
     Ok(())
 }
 
-/// Gera o hash da senha usando Argon2
-fn hash_password(password: &str) -> AuthResult<String> {
+/// Parâmetros de custo do Argon2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Argon2Config {
+    /// Memória usada em KiB
+    pub m_cost: u32,
+    /// Número de iterações
+    pub t_cost: u32,
+    /// Grau de paralelismo
+    pub p_cost: u32,
+}
+
+impl Default for Argon2Config {
+    fn default() -> Self {
+        Argon2Config {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Limites da calibração: memória em KiB (8 MiB a 1 GiB) e iterações
+const CALIBRATION_MIN_M_COST: u32 = 8 * 1024;
+const CALIBRATION_MAX_M_COST: u32 = 1024 * 1024;
+const CALIBRATION_MAX_T_COST: u32 = 10;
+/// Número máximo de medições feitas durante a calibração
+const CALIBRATION_MAX_PROBES: u32 = 16;
+
+impl Argon2Config {
+    /// Converte para os parâmetros do crate argon2
+    pub(crate) fn params(&self) -> AuthResult<Params> {
+        Params::new(self.m_cost, self.t_cost, self.p_cost, None)
+            .map_err(|e| AuthError::PasswordHashing(message!("Parâmetros do Argon2 inválidos: {}", e)))
+    }
+
+    /// Escolhe `m_cost`/`t_cost` para que um hash leve aproximadamente `target` nesta máquina.
+    ///
+    /// A memória dobra a cada medição até passar do alvo e depois é ajustada
+    /// proporcionalmente; se nem o teto de memória alcança o alvo, aumentam-se as
+    /// iterações. São feitas no máximo `CALIBRATION_MAX_PROBES` medições.
+    pub fn calibrate(target: std::time::Duration) -> Argon2Config {
+        let mut config = Argon2Config {
+            m_cost: CALIBRATION_MIN_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        };
+        let target = target.as_secs_f64();
+
+        for _ in 0..CALIBRATION_MAX_PROBES {
+            let elapsed = match config.measure() {
+                Some(elapsed) if elapsed > 0.0 => elapsed,
+                _ => break,
+            };
+            let ratio = target / elapsed;
+
+            // Perto o suficiente do alvo
+            if (0.9..=1.1).contains(&ratio) {
+                break;
+            }
+
+            if ratio < 1.0 {
+                // Passou do alvo: reduzir a memória proporcionalmente e parar
+                let m_cost = (f64::from(config.m_cost) * ratio) as u32;
+                config.m_cost = m_cost.clamp(CALIBRATION_MIN_M_COST, CALIBRATION_MAX_M_COST);
+                break;
+            }
+
+            if config.m_cost < CALIBRATION_MAX_M_COST {
+                let m_cost = (f64::from(config.m_cost) * ratio.min(2.0)) as u32;
+                config.m_cost = m_cost.clamp(CALIBRATION_MIN_M_COST, CALIBRATION_MAX_M_COST);
+            } else if config.t_cost < CALIBRATION_MAX_T_COST {
+                config.t_cost += 1;
+            } else {
+                break;
+            }
+        }
+
+        config
+    }
+
+    /// Mede o tempo de um hash com estes parâmetros, em segundos
+    fn measure(&self) -> Option<f64> {
+        let started = std::time::Instant::now();
+        hash_password_with_config("siri-calibration-probe", self).ok()?;
+        Some(started.elapsed().as_secs_f64())
+    }
+
+    /// Carrega os parâmetros persistidos, ou os da configuração se nunca foram calibrados
+    pub fn load(store: &dyn UserStore) -> AuthResult<Argon2Config> {
+        let defaults = config::current().argon2;
+        let read = |key: &str, default: u32| -> AuthResult<u32> {
+            Ok(store.get_setting(key)?
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default))
+        };
+
+        Ok(Argon2Config {
+            m_cost: read(ARGON2_M_COST_KEY, defaults.m_cost)?,
+            t_cost: read(ARGON2_T_COST_KEY, defaults.t_cost)?,
+            p_cost: read(ARGON2_P_COST_KEY, defaults.p_cost)?,
+        })
+    }
+
+    /// Persiste os parâmetros para as próximas execuções
+    pub fn save(&self, store: &dyn UserStore) -> AuthResult<()> {
+        self.params()?;
+        store.set_setting(ARGON2_M_COST_KEY, Some(&self.m_cost.to_string()))?;
+        store.set_setting(ARGON2_T_COST_KEY, Some(&self.t_cost.to_string()))?;
+        store.set_setting(ARGON2_P_COST_KEY, Some(&self.p_cost.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Cria a instância do Argon2, usando o pepper como chave secreta quando presente
+fn argon2_instance<'a>(pepper: Option<&'a [u8]>, config: &Argon2Config) -> AuthResult<Argon2<'a>> {
+    let params = config.params()?;
+    match pepper {
+        Some(secret) => Argon2::new_with_secret(secret, Algorithm::default(), Version::default(), params)
+            .map_err(|e| AuthError::PasswordHashing(message!("Pepper inválido: {}", e))),
+        None => Ok(Argon2::new(Algorithm::default(), Version::default(), params)),
+    }
+}
+
+/// Gera o hash da senha com o esquema configurado (Argon2, por padrão)
+pub(crate) fn hash_password(store: &dyn UserStore, password: &str) -> AuthResult<String> {
+    hash_password_with(store, password, &config::current().hashing)
+}
+
+/// Gera o hash da senha com o esquema e o pepper de `hashing`
+fn hash_password_with(store: &dyn UserStore, password: &str, hashing: &HashingConfig) -> AuthResult<String> {
+    match hashing.scheme {
+        HashScheme::Argon2 => hash_argon2(password, &Argon2Config::load(store)?, load_pepper(hashing)?.as_deref()),
+        scheme => scheme.hasher().hash(password),
+    }
+}
+
+/// Gera o hash da senha usando Argon2 com os parâmetros informados
+pub(crate) fn hash_password_with_config(password: &str, config: &Argon2Config) -> AuthResult<String> {
+    hash_argon2(password, config, load_pepper(&config::current().hashing)?.as_deref())
+}
+
+/// Gera o hash Argon2 da senha com o pepper informado, sem consultar a configuração
+pub(crate) fn hash_argon2(password: &str, config: &Argon2Config, pepper: Option<&[u8]>) -> AuthResult<String> {
     let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
+    let argon2 = argon2_instance(pepper, config)?;
     
     let password_hash = argon2
         .hash_password(password.as_bytes(), &salt)
-        .map_err(|e| AuthError::PasswordHashing(format!("Erro ao hashear senha: {}", e)))?
+        .map_err(|e| AuthError::PasswordHashing(message!("Erro ao hashear senha: {}", e)))?
         .to_string();
     
     Ok(password_hash)
 }
 
-/// Verifica se a senha corresponde ao hash armazenado
-fn verify_password(password: &str, stored_hash: &str) -> AuthResult<bool> {
-    let argon2 = Argon2::default();
+/// Verifica se a senha corresponde ao hash armazenado, em qualquer esquema suportado
+pub(crate) fn verify_password(password: &str, stored_hash: &str) -> AuthResult<bool> {
+    match HashScheme::detect(stored_hash) {
+        Some(scheme) => scheme.hasher().verify(password, stored_hash),
+        None => Err(AuthError::PasswordHashing(message!("Erro ao analisar hash: esquema desconhecido"))),
+    }
+}
+
+/// Verifica se a senha corresponde a um hash Argon2, aplicando o pepper atual ou um anterior
+pub(crate) fn verify_argon2(password: &str, stored_hash: &str) -> AuthResult<bool> {
+    Ok(match_argon2_pepper(password, stored_hash)?.is_some())
+}
+
+/// Pepper com que um hash Argon2 foi verificado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PepperMatch {
+    Current,
+    /// Um pepper anterior a uma rotação: o hash deve ser refeito com o atual
+    Previous,
+}
+
+/// Verifica um hash Argon2 com o pepper atual e, se falhar, com os anteriores
+fn match_argon2_pepper(password: &str, stored_hash: &str) -> AuthResult<Option<PepperMatch>> {
+    match_argon2_peppers(password, stored_hash, &Peppers::load(&config::current().hashing)?)
+}
+
+/// Verifica um hash Argon2 com os peppers informados, do atual para os anteriores
+pub(crate) fn match_argon2_peppers(
+    password: &str,
+    stored_hash: &str,
+    peppers: &Peppers,
+) -> AuthResult<Option<PepperMatch>> {
     let parsed_hash = PasswordHash::new(stored_hash)
-        .map_err(|e| AuthError::PasswordHashing(format!("Erro ao analisar hash: {}", e)))?;
-    
-    Ok(argon2.verify_password(password.as_bytes(), &parsed_hash).is_ok())
+        .map_err(|e| AuthError::PasswordHashing(message!("Erro ao analisar hash: {}", e)))?;
+    // Os parâmetros de custo vêm do próprio hash (formato PHC)
+    let verify = |pepper: Option<&[u8]>| -> AuthResult<bool> {
+        let argon2 = argon2_instance(pepper, &Argon2Config::default())?;
+        let started = Instant::now();
+        let matches = argon2.verify_password(password.as_bytes(), &parsed_hash).is_ok();
+        metrics::observe_argon2_verify(started.elapsed());
+        Ok(matches)
+    };
+
+    if verify(peppers.current.as_deref())? {
+        return Ok(Some(PepperMatch::Current));
+    }
+    for pepper in &peppers.previous {
+        if verify(Some(pepper))? {
+            return Ok(Some(PepperMatch::Previous));
+        }
+    }
+    Ok(None)
+}
+
+/// Verifica se o valor armazenado é um hash válido do esquema informado
+pub(crate) fn is_scheme_hash(stored_hash: &str, scheme: HashScheme) -> bool {
+    if HashScheme::detect(stored_hash) != Some(scheme) {
+        return false;
+    }
+    match scheme {
+        // bcrypt não segue o formato PHC; o prefixo basta
+        HashScheme::Bcrypt => true,
+        _ => PasswordHash::new(stored_hash).is_ok(),
+    }
+}
+
+/// Como tratar senhas armazenadas fora do formato PHC, por exemplo vindas de outra ferramenta
+/// (`hashing.legacy_hashes`: "reject", "plaintext" ou "sha256")
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LegacyHashPolicy {
+    /// Nunca aceita: o login falha como credencial inválida (padrão)
+    #[default]
+    Reject,
+    /// O valor armazenado é a própria senha em texto puro
+    Plaintext,
+    /// O valor armazenado é o SHA-256 da senha, em hexadecimal
+    Sha256,
+}
+
+impl LegacyHashPolicy {
+    /// Compara a senha com um valor legado segundo a política
+    pub fn matches(&self, password: &str, stored: &str) -> bool {
+        match self {
+            LegacyHashPolicy::Reject => false,
+            LegacyHashPolicy::Plaintext => secrets_equal(password.as_bytes(), stored.as_bytes()),
+            LegacyHashPolicy::Sha256 => {
+                let digest = format!("{:x}", Sha256::digest(password.as_bytes()));
+                secrets_equal(digest.as_bytes(), stored.trim().to_lowercase().as_bytes())
+            }
+        }
+    }
+}
+
+/// Hash de referência usado pelo caminho de usuário inexistente, com o esquema e os
+/// parâmetros com que foi gerado
+static DUMMY_REFERENCE_HASH: Mutex<Option<(HashScheme, Argon2Config, String)>> = Mutex::new(None);
+
+/// Hash dummy para prevenir timing attacks.
+///
+/// Executa uma verificação completa contra um hash de referência gerado com o mesmo
+/// esquema, a mesma `Argon2Config` persistida e o mesmo pepper das senhas reais, de
+/// modo que o caminho de usuário inexistente faça o mesmo trabalho que uma senha
+/// incorreta. Gerar um hash novo a cada chamada não serve: o custo da verificação
+/// vem dos parâmetros gravados no hash comparado. A referência é guardada por
+/// processo e refeita só quando o esquema ou os parâmetros mudam.
+pub(crate) fn dummy_hash_operation(store: &dyn UserStore) {
+    let scheme = config::current().hashing.scheme;
+    let Ok(config) = Argon2Config::load(store) else {
+        return;
+    };
+
+    let reference = {
+        let mut cached = DUMMY_REFERENCE_HASH.lock().unwrap_or_else(PoisonError::into_inner);
+        match cached.as_ref() {
+            Some((cached_scheme, cached_config, hash)) if *cached_scheme == scheme && *cached_config == config => {
+                hash.clone()
+            }
+            _ => {
+                let Ok(hash) = hash_password(store, "siri-dummy-reference") else {
+                    return;
+                };
+                *cached = Some((scheme, config, hash.clone()));
+                hash
+            }
+        }
+    };
+
+    let _ = verify_password("siri-dummy-attempt", &reference);
+}
+
+/// Obtém o limite de usuários em vigor: o valor definido em tempo de execução
+/// tem prioridade sobre o da configuração
+pub fn max_users(store: &dyn UserStore, config: &PasswordConfig) -> AuthResult<Option<u32>> {
+    match store.get_setting(MAX_USERS_KEY)? {
+        Some(value) => Ok(value.parse().ok()),
+        None => Ok(config.max_users),
+    }
+}
+
+/// Define o limite de usuários em tempo de execução (None = sem limite)
+pub fn set_max_users(store: &dyn UserStore, limit: Option<u32>) -> AuthResult<()> {
+    // "none" é gravado explicitamente para sobrepor um limite vindo da configuração
+    let value = limit.map_or_else(|| "none".to_string(), |limit| limit.to_string());
+    store.set_setting(MAX_USERS_KEY, Some(&value))
+}
+
+/// Obtém a idade máxima das senhas em vigor, em dias (0 = nunca expiram): o valor
+/// definido em tempo de execução tem prioridade sobre o da configuração
+pub fn password_max_age_days(store: &dyn UserStore, config: &PasswordConfig) -> AuthResult<u32> {
+    match store.get_setting(PASSWORD_MAX_AGE_DAYS_KEY)? {
+        Some(value) => Ok(value.parse().unwrap_or(config.max_age_days)),
+        None => Ok(config.max_age_days),
+    }
+}
+
+/// Define a idade máxima das senhas em tempo de execução (0 = nunca expiram)
+pub fn set_password_max_age_days(store: &dyn UserStore, days: u32) -> AuthResult<()> {
+    // 0 é gravado explicitamente para sobrepor uma idade vinda da configuração
+    store.set_setting(PASSWORD_MAX_AGE_DAYS_KEY, Some(&days.to_string()))
 }
 
-/// Hash dummy para prevenir timing attacks
-fn dummy_hash_operation() {
-    let dummy_salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let _ = argon2.hash_password(b"dummy_password", &dummy_salt);
+/// Garante que ainda há vagas para novos cadastros
+pub(crate) fn check_registration_open(store: &dyn UserStore, config: &PasswordConfig) -> AuthResult<()> {
+    if let Some(limit) = max_users(store, config)? {
+        if store.count_users()? >= limit {
+            return Err(AuthError::PermissionDenied(message!("registro fechado: limite atingido")));
+        }
+    }
+    Ok(())
 }
 
 /// Registra um novo usuário no sistema
-pub fn register_user(conn: &Connection, username: &str, password: &str) -> AuthResult<()> {
+pub fn register_user(store: &dyn UserStore, username: &str, password: &str) -> AuthResult<()> {
+    register_user_with_email(store, username, password, None)
+}
+
+/// Registra um novo usuário no sistema, com email opcional
+pub fn register_user_with_email(
+    store: &dyn UserStore,
+    username: &str,
+    password: &str,
+    email: Option<&str>,
+) -> AuthResult<()> {
     // Validações de entrada
     validate_credentials(username, password)?;
-    
-    // Validação de força da senha
-    let config = PasswordConfig::default();
-    validate_password_strength(password, &config)?;
-    
-    // Verificar se usuário já existe primeiro (mais eficiente)
-    let user_exists: bool = conn.query_row(
-        "SELECT COUNT(*) > 0 FROM users WHERE username = ?1",
-        [username],
-        |row| row.get(0),
-    )?;
-    
-    if user_exists {
-        return Err(AuthError::Validation(format!("Usuário '{}' já existe", username)));
+    validate_username(username)?;
+
+    let email = email.map(str::trim).filter(|e| !e.is_empty());
+    if let Some(email) = email {
+        validate_email(email)?;
     }
     
-    // Gerar hash da senha
-    let password_hash = hash_password(password)?;
-    
-    // Inserir usuário no banco
-    conn.execute(
-        "INSERT INTO users (username, password_hash) VALUES (?1, ?2)",
-        [username, &password_hash],
-    )?;
+    // Validação de força da senha
+    let config = &config::current().password;
+    validate_password_strength(password, config, &[username, email.unwrap_or_default()])?;
+
+    // Gerar hash da senha (demorado), antes de abrir a transação
+    let password_hash = hash_password(store, password)?;
+
+    // A vaga, a liberação do nome e o cadastro são gravados juntos: dois cadastros
+    // simultâneos não passam do limite de contas. Nomes e emails duplicados são
+    // barrados pelas restrições UNIQUE
+    store.transaction(&mut |store| {
+        check_registration_open(store, config)?;
+        release_deleted_username(store, username, config)?;
+        store.insert_user(username, &password_hash, email)?;
+        audit::record(store, username, "register", Some(username))
+    })?;
+    events::emit(Event::UserRegistered { username: username.to_string() }, Utc::now());
     
     Ok(())
 }
 
+/// Exclui (de forma reversível) a conta de `username`, registrando a ação de `actor`.
+///
+/// A exclusão, o fim das sessões e o registro na auditoria entram na mesma transação.
+pub fn delete_user(store: &dyn UserStore, actor: &str, username: &str) -> AuthResult<()> {
+    store.transaction(&mut |store| {
+        if !store.delete_user(username)? {
+            return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+        }
+        audit::record(store, actor, "delete_user", Some(username))
+    })
+}
+
+/// Remove definitivamente uma conta excluída que ainda ocupa o nome, se a reserva expirou
+pub(crate) fn release_deleted_username(store: &dyn UserStore, username: &str, config: &PasswordConfig) -> AuthResult<()> {
+    let cutoff = Utc::now() - Duration::days(i64::from(config.deleted_username_grace_days));
+    store.release_username(username, cutoff)
+}
+
+/// O que fazer em `ensure_user` quando o usuário já existe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfExists {
+    /// Retorna erro de validação, como `register_user`
+    Fail,
+    /// Não altera nada
+    Skip,
+    /// Substitui a senha do usuário existente
+    UpdatePassword,
+}
+
+/// Resultado de `ensure_user`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnsureOutcome {
+    Created,
+    Skipped,
+    Updated,
+}
+
+/// Garante que o usuário exista, sem verificar antes de inserir.
+///
+/// A inserção se apoia na restrição UNIQUE de `username`, então duas chamadas
+/// concorrentes nunca criam o mesmo usuário duas vezes.
+pub fn ensure_user(
+    store: &dyn UserStore,
+    username: &str,
+    password: &str,
+    if_exists: IfExists,
+) -> AuthResult<EnsureOutcome> {
+    validate_credentials(username, password)?;
+    validate_username(username)?;
+
+    let config = &config::current().password;
+    validate_password_strength(password, config, &[username])?;
+
+    let password_hash = hash_password(store, password)?;
+
+    let mut outcome = EnsureOutcome::Skipped;
+    store.transaction(&mut |store| {
+        release_deleted_username(store, username, config)?;
+        let inserted = match if_exists {
+            IfExists::Fail => {
+                check_registration_open(store, config)?;
+                store.insert_user(username, &password_hash, None)?;
+                true
+            }
+            IfExists::Skip | IfExists::UpdatePassword => {
+                if !store.user_exists(username)? {
+                    check_registration_open(store, config)?;
+                }
+                store.insert_user_if_absent(username, &password_hash)?
+            }
+        };
+
+        // O nome pode estar ocupado por uma conta excluída, ainda reservada: não há
+        // conta ativa para pular nem para trocar a senha
+        if !inserted && !store.user_exists(username)? {
+            return validation_error!("Usuário '{}' foi excluído e o nome ainda está reservado", username);
+        }
+
+        outcome = if inserted {
+            audit::record(store, username, "register", Some(username))?;
+            EnsureOutcome::Created
+        } else if if_exists == IfExists::Skip {
+            EnsureOutcome::Skipped
+        } else {
+            store.set_password_hash(username, &password_hash, true)?;
+            audit::record(store, username, "change_password", Some(username))?;
+            EnsureOutcome::Updated
+        };
+        Ok(())
+    })?;
+
+    // Os eventos saem só depois da transação confirmada
+    let username = username.to_string();
+    match outcome {
+        EnsureOutcome::Created => events::emit(Event::UserRegistered { username }, Utc::now()),
+        EnsureOutcome::Updated => events::emit(Event::PasswordChanged { username }, Utc::now()),
+        EnsureOutcome::Skipped => {}
+    }
+    Ok(outcome)
+}
+
 /// Realiza o login de um usuário
-pub fn login_user(conn: &Connection, username: &str, password: &str) -> AuthResult<bool> {
-    use rusqlite::OptionalExtension;
-    
+pub fn login_user(store: &dyn UserStore, username: &str, password: &str) -> AuthResult<LoginOutcome> {
+    login_user_with_clock(store, &SystemClock, username, password)
+}
+
+/// Realiza o login de um usuário usando o relógio informado para checar a expiração da senha
+pub fn login_user_with_clock(
+    store: &dyn UserStore,
+    clock: &dyn Clock,
+    username: &str,
+    password: &str,
+) -> AuthResult<LoginOutcome> {
     // Validações de entrada
     validate_credentials(username, password)?;
     
-    // Buscar hash da senha no banco
-    let stored_hash: Option<String> = conn
-        .query_row(
-            "SELECT password_hash FROM users WHERE username = ?1",
-            [username],
-            |row| row.get(0),
-        )
-        .optional()?;
+    // Buscar hash da senha, data da última troca e estado de bloqueio no banco
+    let stored = store.get_credentials(username)?;
     
     // Verificar se usuário existe
-    let stored_hash = match stored_hash {
-        Some(hash) => hash,
+    let StoredCredentials {
+        password_hash: stored_hash,
+        changed_at,
+        locked_until,
+        status,
+        must_change_password,
+    } = match stored {
+        Some(row) => row,
         None => {
             // Hash dummy para prevenir timing attacks
-            dummy_hash_operation();
-            return Ok(false);
+            dummy_hash_operation(store);
+            return Ok(LoginOutcome::InvalidCredentials);
         }
     };
+
+    // Conta desativada: rejeita antes de checar a senha, com o mesmo custo de um hash
+    if !status.is_active() {
+        dummy_hash_operation(store);
+        return Ok(LoginOutcome::Disabled);
+    }
+
+    let now = clock.now();
+
+    // Conta bloqueada: nem verifica a senha, mas mantém o custo do hash
+    if let Some(until) = locked_until {
+        if until > now {
+            dummy_hash_operation(store);
+            return Ok(LoginOutcome::Locked { until });
+        }
+    }
     
-    // Verificar a senha
-    let is_valid = verify_password(password, &stored_hash)?;
-    
-    Ok(is_valid)
+    let password_matches = verify_and_upgrade(store, username, password, &stored_hash, &config::current().hashing)?;
+    if !password_matches {
+        register_failed_attempt(store, username, &config::current().lockout, now)?;
+        return Ok(LoginOutcome::InvalidCredentials);
+    }
+
+    // Com dois fatores ativos, a senha correta só libera a etapa do código. As falhas
+    // não são zeradas aqui, para que errar o código continue contando para o bloqueio
+    if is_totp_enabled(store, username)? {
+        return Ok(LoginOutcome::RequiresSecondFactor);
+    }
+
+    store.clear_failed_attempts(username)?;
+
+    password_age_outcome(store, must_change_password, changed_at, now)
+}
+
+/// Confere a senha de quem já está logado antes de uma operação sensível.
+///
+/// Diferente do login, não tem efeitos colaterais: não conta como tentativa (nem
+/// como falha para o bloqueio), não refaz o hash e não zera as falhas. Conta
+/// inexistente, desativada ou bloqueada resulta em `false`, com o mesmo custo de um hash.
+pub fn reauthenticate(store: &dyn UserStore, clock: &dyn Clock, username: &str, password: &str) -> AuthResult<bool> {
+    validate_credentials(username, password)?;
+
+    let Some(stored) = store.get_credentials(username)? else {
+        dummy_hash_operation(store);
+        return Ok(false);
+    };
+    let locked = stored.locked_until.is_some_and(|until| until > clock.now());
+    if !stored.status.is_active() || locked {
+        dummy_hash_operation(store);
+        return Ok(false);
+    }
+
+    let (password_matches, _) = verify_stored_hash(store, password, &stored.password_hash, &config::current().hashing)?;
+    Ok(password_matches)
+}
+
+/// Verifica a senha com o esquema do hash armazenado, indicando também se o hash
+/// deve ser refeito: outro esquema, um pepper anterior a uma rotação ou um valor
+/// legado aceito pela política
+fn verify_stored_hash(
+    store: &dyn UserStore,
+    password: &str,
+    stored_hash: &str,
+    hashing: &HashingConfig,
+) -> AuthResult<(bool, bool)> {
+    let configured = hashing.scheme;
+    Ok(match HashScheme::detect(stored_hash) {
+        Some(HashScheme::Argon2) => match match_argon2_peppers(password, stored_hash, &Peppers::load(hashing)?)? {
+            Some(PepperMatch::Current) => (true, configured != HashScheme::Argon2),
+            Some(PepperMatch::Previous) => (true, true),
+            None => (false, false),
+        },
+        Some(scheme) => (scheme.hasher().verify(password, stored_hash)?, scheme != configured),
+        None => {
+            dummy_hash_operation(store);
+            (hashing.legacy_hashes.matches(password, stored_hash), true)
+        }
+    })
+}
+
+/// Verifica a senha e, se ela confere mas o hash precisa ser refeito (ver
+/// `verify_stored_hash`), grava um novo com o esquema e o pepper de `hashing`
+pub(crate) fn verify_and_upgrade(
+    store: &dyn UserStore,
+    username: &str,
+    password: &str,
+    stored_hash: &str,
+    hashing: &HashingConfig,
+) -> AuthResult<bool> {
+    let (password_matches, needs_rehash) = verify_stored_hash(store, password, stored_hash, hashing)?;
+    if password_matches && needs_rehash {
+        let new_hash = hash_password_with(store, password, hashing)?;
+        store.set_password_hash(username, &new_hash, false)?;
+    }
+    Ok(password_matches)
+}
+
+/// Conclui um login que retornou `RequiresSecondFactor`, conferindo o código TOTP
+/// (ou um código de recuperação do segundo fator).
+///
+/// Não confere a senha: sozinha, ela não autentica ninguém. Por isso fica restrita
+/// ao crate e só é chamada pelo `AuthService`, depois que `login_user_with_clock`
+/// retornou `RequiresSecondFactor` para a mesma conta.
+pub(crate) fn login_second_factor(
+    store: &dyn UserStore,
+    clock: &dyn Clock,
+    username: &str,
+    code: &str,
+) -> AuthResult<LoginOutcome> {
+    // Usuário inexistente, desativado ou bloqueado: mesmo custo de um código conferido
+    let now = clock.now();
+    let Some(stored) = store.get_credentials(username)? else {
+        dummy_second_factor(store, code, now);
+        return Ok(LoginOutcome::InvalidCredentials);
+    };
+    if !stored.status.is_active() {
+        dummy_second_factor(store, code, now);
+        return Ok(LoginOutcome::Disabled);
+    }
+
+    if let Some(until) = stored.locked_until {
+        if until > now {
+            dummy_second_factor(store, code, now);
+            return Ok(LoginOutcome::Locked { until });
+        }
+    }
+
+    if !verify_second_factor(store, username, code, now)? {
+        register_failed_attempt(store, username, &config::current().lockout, now)?;
+        return Ok(LoginOutcome::InvalidCredentials);
+    }
+
+    store.clear_failed_attempts(username)?;
+
+    password_age_outcome(store, stored.must_change_password, stored.changed_at, now)
+}
+
+/// Resultado de um login com todos os fatores corretos, conforme a idade da senha
+fn password_age_outcome(
+    store: &dyn UserStore,
+    must_change_password: bool,
+    changed_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> AuthResult<LoginOutcome> {
+    let max_age_days = password_max_age_days(store, &config::current().password)?;
+    if must_change_password || is_password_expired(changed_at, max_age_days, now) {
+        return Ok(LoginOutcome::PasswordExpired);
+    }
+    Ok(LoginOutcome::Success)
+}
+
+/// Contabiliza uma falha de login, bloqueando a conta ao atingir o limite da política
+fn register_failed_attempt(
+    store: &dyn UserStore,
+    username: &str,
+    policy: &LockoutPolicy,
+    now: DateTime<Utc>,
+) -> AuthResult<()> {
+    let failed_attempts = store.record_failed_attempt(username)?;
+
+    let streak = config::current().webhooks.failure_streak;
+    if streak > 0 && failed_attempts == streak {
+        events::emit(Event::LoginFailureStreak { username: username.to_string(), failures: failed_attempts }, now);
+    }
+
+    if policy.max_failed_attempts > 0 && failed_attempts >= policy.max_failed_attempts {
+        let until = now + policy.lock_duration;
+        store.lock_user(username, until)?;
+        events::emit(Event::AccountLocked { username: username.to_string(), until }, now);
+    }
+
+    Ok(())
+}
+
+/// Formata um DateTime no formato de timestamp usado pelo SQLite
+pub(crate) fn format_sqlite_timestamp(value: DateTime<Utc>) -> String {
+    value.format(SQLITE_TIMESTAMP_FORMAT).to_string()
+}
+
+/// Converte um timestamp do SQLite (UTC) para DateTime
+pub(crate) fn parse_sqlite_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, SQLITE_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// Verifica se a senha ultrapassou a idade máxima permitida
+fn is_password_expired(changed_at: Option<DateTime<Utc>>, max_age_days: u32, now: DateTime<Utc>) -> bool {
+    if max_age_days == 0 {
+        return false;
+    }
+
+    match changed_at {
+        Some(changed_at) => now - changed_at >= Duration::days(i64::from(max_age_days)),
+        // Sem data conhecida não há como afirmar que expirou
+        None => false,
+    }
 }
 
 /// Altera a senha de um usuário existente
-pub fn change_password(conn: &Connection, username: &str, old_password: &str, new_password: &str) -> AuthResult<()> {
+pub fn change_password(
+    store: &dyn UserStore,
+    clock: &dyn Clock,
+    username: &str,
+    old_password: &str,
+    new_password: &str,
+) -> AuthResult<()> {
     // Primeiro, verificar se a senha atual está correta
-    if !login_user(conn, username, old_password)? {
-        return Err(AuthError::Validation("Senha atual incorreta".to_string()));
-    }
+    verify_current_password(store, clock, username, old_password)?;
     
     // Validar a nova senha
-    let config = PasswordConfig::default();
-    validate_password_strength(new_password, &config)?;
+    validate_password_strength(new_password, &config::current().password, &[username])?;
     
     // Gerar novo hash
-    let new_hash = hash_password(new_password)?;
+    let new_hash = hash_password(store, new_password)?;
     
     // Atualizar no banco
-    conn.execute(
-        "UPDATE users SET password_hash = ?1 WHERE username = ?2",
-        [&new_hash, username],
-    )?;
+    store.set_password_hash(username, &new_hash, true)?;
+    audit::record(store, username, "change_password", Some(username))?;
+    events::emit(Event::PasswordChanged { username: username.to_string() }, clock.now());
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Exclui definitivamente a própria conta, exigindo a senha atual.
+///
+/// Sessões, dois fatores, códigos de recuperação e histórico de logins são apagados
+/// junto, na mesma transação que registra a exclusão no log de auditoria. O último
+/// administrador ativo precisa promover outra conta antes.
+pub fn delete_own_account(store: &dyn UserStore, clock: &dyn Clock, username: &str, password: &str) -> AuthResult<()> {
+    verify_current_password(store, clock, username, password)?;
+
+    if is_admin(store, username)? && store.count_admins()? <= 1 {
+        return validation_error!("Você é o único administrador ativo. Promova outra conta antes de excluir a sua");
+    }
+
+    if !store.erase_user(username, username, "delete_own_account")? {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    }
+    Ok(())
+}
+
+/// Confere a senha atual antes de uma operação sensível na própria conta
+fn verify_current_password(store: &dyn UserStore, clock: &dyn Clock, username: &str, password: &str) -> AuthResult<()> {
+    match login_user_with_clock(store, clock, username, password)? {
+        LoginOutcome::Success
+        | LoginOutcome::RequiresSecondFactor
+        | LoginOutcome::PasswordExpired => {}
+        LoginOutcome::Locked { until } => return Err(AuthError::AccountLocked(until)),
+        LoginOutcome::Disabled => {
+            return Err(AuthError::PermissionDenied(message!("Conta desativada")));
+        }
+        LoginOutcome::InvalidCredentials => return Err(AuthError::InvalidCredentials),
+    }
+    Ok(())
+}
+
+/// Validade padrão de um token de redefinição de senha
+pub const RESET_TOKEN_TTL: Duration = Duration::hours(1);
+
+/// Gera um token de redefinição de senha para `username`, válido por `ttl`.
+///
+/// O token em texto puro é retornado apenas aqui; no banco fica só o seu hash.
+/// Quem chama é responsável por autorizar `requested_by` (ex.: um administrador).
+pub fn create_reset_token(
+    store: &dyn UserStore,
+    clock: &dyn Clock,
+    requested_by: &str,
+    username: &str,
+    ttl: Duration,
+) -> AuthResult<String> {
+    if ttl <= Duration::zero() {
+        return validation_error!("A validade do token deve ser positiva");
+    }
+
+    let token = generate_token();
+    store.insert_password_reset(&token_hash(&token), username, requested_by, clock.now() + ttl)?;
+    audit::record(store, requested_by, "create_reset_token", Some(username))?;
+    Ok(token)
+}
+
+/// Define uma nova senha com um token de redefinição, sem exigir a senha atual.
+///
+/// O token vale uma única vez. A conta também é desbloqueada, já que o objetivo
+/// é devolver o acesso. Retorna o nome do usuário cuja senha foi trocada.
+pub fn redeem_reset_token(
+    store: &dyn UserStore,
+    clock: &dyn Clock,
+    token: &str,
+    new_password: &str,
+) -> AuthResult<String> {
+    // Validar a nova senha antes de gastar o token
+    // O dono do token só é conhecido depois de consumi-lo
+    validate_password_strength(new_password, &config::current().password, &[])?;
+
+    let Some(username) = store.consume_password_reset(&token_hash(token.trim()), clock.now())? else {
+        return validation_error!("Token de redefinição inválido ou expirado");
+    };
+
+    let new_hash = hash_password(store, new_password)?;
+    store.set_password_hash(&username, &new_hash, true)?;
+    store.clear_failed_attempts(&username)?;
+    audit::record(store, &username, "redeem_reset_token", Some(&username))?;
+    events::emit(Event::PasswordChanged { username: username.clone() }, clock.now());
+    Ok(username)
+}
+
+/// Altera o nome de um usuário existente
+pub fn change_username(store: &dyn UserStore, current: &str, new_username: &str) -> AuthResult<()> {
+    validate_username(new_username)?;
+
+    if current == new_username {
+        return validation_error!("O novo nome é igual ao atual");
+    }
+
+    if !store.rename_user(current, new_username)? {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", current)));
+    }
+    // O alvo é o nome novo; o autor fica registrado com o nome anterior
+    audit::record(store, current, "change_username", Some(new_username))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::clock::MockClock;
+    use crate::db::Database;
+    use crate::rbac;
+    use crate::user::AccountStatus;
+    use crate::testing::{database, register, NEW_PASSWORD, PASSWORD};
+
+    #[test]
+    fn unknown_user_and_wrong_password_read_the_same() {
+        let db = database();
+        register(&db, "alice");
+
+        let unknown = login_user(&db, "ninguem", PASSWORD).unwrap();
+        let wrong_password = login_user(&db, "alice", "Senha-Errada-99").unwrap();
+        assert_eq!(unknown, LoginOutcome::InvalidCredentials);
+        assert_eq!(wrong_password, LoginOutcome::InvalidCredentials);
+        assert_eq!(
+            describe_login_outcome(&unknown).as_bytes(),
+            describe_login_outcome(&wrong_password).as_bytes()
+        );
+    }
+
+    #[test]
+    fn sqlite_timestamps_round_trip() {
+        let instant = Utc.with_ymd_and_hms(2026, 2, 28, 23, 59, 7).unwrap();
+        assert_eq!(format_sqlite_timestamp(instant), "2026-02-28 23:59:07");
+        assert_eq!(parse_sqlite_timestamp(&format_sqlite_timestamp(instant)), Some(instant));
+        assert_eq!(parse_sqlite_timestamp("2026-02-28T23:59:07Z"), None);
+    }
+
+    #[test]
+    fn ensure_user_creates_a_missing_account() {
+        let db = database();
+        assert_eq!(ensure_user(&db, "alice", PASSWORD, IfExists::Fail).unwrap(), EnsureOutcome::Created);
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn ensure_user_skips_an_existing_account_without_touching_it() {
+        let db = database();
+        register(&db, "alice");
+
+        assert_eq!(ensure_user(&db, "alice", NEW_PASSWORD, IfExists::Skip).unwrap(), EnsureOutcome::Skipped);
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn ensure_user_updates_the_password_of_an_existing_account() {
+        let db = database();
+        register(&db, "alice");
+
+        assert_eq!(
+            ensure_user(&db, "alice", NEW_PASSWORD, IfExists::UpdatePassword).unwrap(),
+            EnsureOutcome::Updated
+        );
+        assert_eq!(login_user(&db, "alice", NEW_PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn ensure_user_fails_on_an_existing_account() {
+        let db = database();
+        register(&db, "alice");
+
+        let err = ensure_user(&db, "alice", NEW_PASSWORD, IfExists::Fail).unwrap_err();
+        assert!(matches!(err, AuthError::UserAlreadyExists(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn ensure_user_does_not_skip_a_reserved_deleted_name() {
+        let db = database();
+        register(&db, "admin");
+        register(&db, "alice");
+        delete_user(&db, "admin", "alice").unwrap();
+
+        for if_exists in [IfExists::Skip, IfExists::UpdatePassword] {
+            let err = ensure_user(&db, "alice", NEW_PASSWORD, if_exists).unwrap_err();
+            assert!(matches!(err, AuthError::Validation(_)), "{:?}: {:?}", if_exists, err);
+        }
+        assert!(!db.user_exists("alice").unwrap());
+    }
+
+    #[test]
+    fn malformed_emails_are_rejected() {
+        let too_long = format!("{}@exemplo.com", "a".repeat(EMAIL_MAX_LENGTH));
+        for email in ["", "alice", "@exemplo.com", "alice@", "alice@localhost", "alice@exemplo..com",
+            "alice@@exemplo.com", "alice @exemplo.com", too_long.as_str()]
+        {
+            assert!(matches!(validate_email(email), Err(AuthError::Validation(_))), "{:?}", email);
+        }
+        validate_email("alice.silva+siri@mail.exemplo.com").unwrap();
+
+        let db = database();
+        let err = register_user_with_email(&db, "alice", PASSWORD, Some("alice@")).unwrap_err();
+        assert!(matches!(err, AuthError::Validation(_)), "{:?}", err);
+        assert!(!db.user_exists("alice").unwrap());
+    }
+
+    #[test]
+    fn emails_are_unique_regardless_of_case() {
+        let db = database();
+        register_user_with_email(&db, "alice", PASSWORD, Some("Alice@Exemplo.com")).unwrap();
+
+        let err = register_user_with_email(&db, "bob", PASSWORD, Some("alice@EXEMPLO.COM")).unwrap_err();
+        assert!(matches!(err, AuthError::Validation(_)), "{:?}", err);
+        assert!(!db.user_exists("bob").unwrap());
+    }
+
+    #[test]
+    fn accounts_are_found_by_email_in_any_case() {
+        let db = database();
+        register_user_with_email(&db, "alice", PASSWORD, Some("Alice@Exemplo.com")).unwrap();
+        register(&db, "bob");
+
+        assert_eq!(db.find_by_email("alice@exemplo.com").unwrap().as_deref(), Some("alice"));
+        assert_eq!(db.find_by_email(" ALICE@EXEMPLO.COM ").unwrap().as_deref(), Some("alice"));
+        assert_eq!(db.find_by_email("bob@exemplo.com").unwrap(), None);
+    }
+
+    #[test]
+    fn registration_is_closed_at_max_users() {
+        let db = database();
+        set_max_users(&db, Some(2)).unwrap();
+        register(&db, "alice");
+        register(&db, "bob");
+
+        let err = register_user(&db, "carol", PASSWORD).unwrap_err();
+        assert!(matches!(err, AuthError::PermissionDenied(_)), "{:?}", err);
+        assert!(!db.user_exists("carol").unwrap());
+
+        set_max_users(&db, None).unwrap();
+        register(&db, "carol");
+    }
+
+    #[test]
+    fn deleted_accounts_do_not_count_toward_max_users() {
+        let db = database();
+        set_max_users(&db, Some(2)).unwrap();
+        register(&db, "alice");
+        register(&db, "bob");
+        delete_user(&db, "alice", "bob").unwrap();
+
+        assert_eq!(db.count_users().unwrap(), 1);
+        register(&db, "carol");
+        let err = register_user(&db, "dave", PASSWORD).unwrap_err();
+        assert!(matches!(err, AuthError::PermissionDenied(_)), "{:?}", err);
+    }
+
+    /// Menor duração de algumas tentativas de login, para descontar ruído do agendador
+    fn fastest_login(db: &dyn UserStore, username: &str, password: &str, expected: LoginOutcome) -> std::time::Duration {
+        (0..3)
+            .map(|_| {
+                let started = std::time::Instant::now();
+                assert_eq!(login_user(db, username, password).unwrap(), expected);
+                started.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn disabled_account_is_refused_at_the_cost_of_a_hash() {
+        let db = database();
+        register(&db, "admin");
+        register(&db, "alice");
+        register(&db, "bob");
+        // Um custo perceptível, para que pular o hash apareça na medida
+        Argon2Config { m_cost: 4096, t_cost: 2, p_cost: 1 }.save(&db).unwrap();
+        rbac::set_status(&db, "admin", "alice", AccountStatus::Disabled).unwrap();
+
+        // A primeira chamada também calcula o hash de referência com os novos parâmetros
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Disabled);
+        let disabled = fastest_login(&db, "alice", PASSWORD, LoginOutcome::Disabled);
+        let wrong_password = fastest_login(&db, "bob", "Senha-Errada-99", LoginOutcome::InvalidCredentials);
+        assert!(disabled * 2 >= wrong_password, "{:?} contra {:?}", disabled, wrong_password);
+    }
+
+    #[test]
+    fn enabling_a_disabled_account_restores_login() {
+        let db = database();
+        register(&db, "admin");
+        register(&db, "alice");
+        rbac::set_status(&db, "admin", "alice", AccountStatus::Disabled).unwrap();
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Disabled);
+        // A conta desativada recusa até a senha errada do mesmo jeito, sem contar falhas
+        assert_eq!(login_user(&db, "alice", "Senha-Errada-99").unwrap(), LoginOutcome::Disabled);
+
+        rbac::set_status(&db, "admin", "alice", AccountStatus::Active).unwrap();
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn deleted_account_disappears_until_restored() {
+        let db = database();
+        register(&db, "admin");
+        register(&db, "alice");
+        delete_user(&db, "admin", "alice").unwrap();
+
+        assert!(!db.user_exists("alice").unwrap());
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::InvalidCredentials);
+        let deleted: Vec<String> = db.list_deleted_users().unwrap().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(deleted, ["alice"]);
+        assert!(matches!(delete_user(&db, "admin", "alice"), Err(AuthError::NotFound(_))));
+
+        assert!(db.restore_user("alice").unwrap());
+        assert!(db.list_deleted_users().unwrap().is_empty());
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn purge_removes_only_accounts_deleted_before_the_grace_period() {
+        let db = database();
+        for username in ["admin", "alice", "bob"] {
+            register(&db, username);
+        }
+        delete_user(&db, "admin", "alice").unwrap();
+        delete_user(&db, "admin", "bob").unwrap();
+        let grace = Duration::days(30);
+        db.connection()
+            .unwrap()
+            .execute(
+                "UPDATE users SET deleted_at = ?1 WHERE username = 'alice'",
+                [format_sqlite_timestamp(Utc::now() - grace - Duration::days(1))],
+            )
+            .unwrap();
+
+        assert_eq!(db.purge_deleted(Utc::now() - grace).unwrap(), 1);
+        assert!(!db.restore_user("alice").unwrap());
+        // bob ainda está no prazo: volta com a senha de antes
+        assert!(db.restore_user("bob").unwrap());
+        assert_eq!(login_user(&db, "bob", PASSWORD).unwrap(), LoginOutcome::Success);
+        // O nome de alice ficou livre para um novo cadastro
+        register(&db, "alice");
+    }
+
+    #[test]
+    fn calibration_stays_within_its_bounds() {
+        // Um alvo inalcançável para baixo para no piso de memória, sem mexer nas iterações
+        for target in [std::time::Duration::ZERO, std::time::Duration::from_micros(1)] {
+            let calibrated = Argon2Config::calibrate(target);
+            assert_eq!(calibrated.m_cost, CALIBRATION_MIN_M_COST, "{:?}", target);
+            assert_eq!(calibrated.t_cost, Params::DEFAULT_T_COST, "{:?}", target);
+        }
+
+        let calibrated = Argon2Config::calibrate(std::time::Duration::from_millis(20));
+        assert!((CALIBRATION_MIN_M_COST..=CALIBRATION_MAX_M_COST).contains(&calibrated.m_cost), "{:?}", calibrated);
+        assert!((Params::DEFAULT_T_COST..=CALIBRATION_MAX_T_COST).contains(&calibrated.t_cost), "{:?}", calibrated);
+        calibrated.params().unwrap();
+    }
+
+    #[test]
+    fn argon2_parameters_round_trip_through_the_store() {
+        let db = Database::in_memory().unwrap();
+        assert_eq!(Argon2Config::load(&db).unwrap(), config::current().argon2);
+
+        let tuned = Argon2Config { m_cost: 12 * 1024, t_cost: 3, p_cost: 2 };
+        tuned.save(&db).unwrap();
+        assert_eq!(Argon2Config::load(&db).unwrap(), tuned);
+
+        // Parâmetros que o Argon2 recusa não chegam ao banco
+        assert!(Argon2Config { m_cost: 1, t_cost: 0, p_cost: 1 }.save(&db).is_err());
+        assert_eq!(Argon2Config::load(&db).unwrap(), tuned);
+    }
+
+    #[test]
+    fn password_change_checks_the_lock_against_the_given_clock() {
+        let db = database();
+        register(&db, "admin");
+        register(&db, "alice");
+        let until = Utc::now() + Duration::hours(1);
+        db.connection()
+            .unwrap()
+            .execute("UPDATE users SET locked_until = ?1 WHERE username = 'alice'", [format_sqlite_timestamp(until)])
+            .unwrap();
+
+        let err = change_password(&db, &SystemClock, "alice", PASSWORD, NEW_PASSWORD).unwrap_err();
+        assert!(matches!(err, AuthError::AccountLocked(_)), "{:?}", err);
+
+        // Duas horas depois, pelo relógio informado, o bloqueio já venceu
+        let later = MockClock::new(until + Duration::hours(1));
+        change_password(&db, &later, "alice", PASSWORD, NEW_PASSWORD).unwrap();
+        assert_eq!(login_user_with_clock(&db, &later, "alice", NEW_PASSWORD).unwrap(), LoginOutcome::Success);
+        delete_own_account(&db, &later, "alice", NEW_PASSWORD).unwrap();
+        assert!(!db.user_exists("alice").unwrap());
+    }
+}
@@ -0,0 +1,391 @@
+//! Cópias de segurança do banco SQLite: `siri backup`, `siri restore` e as cópias
+//! automáticas de `siri serve` e `siri daemon` (seção `[backup]` da configuração).
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{Connection, OpenFlags};
+use serde::Deserialize;
+use crate::auth::parse_sqlite_timestamp;
+use crate::db::{check_integrity, create_parent_dirs, create_private_file};
+use crate::error::{AuthError, AuthResult};
+use crate::migrations::{current_version, latest_version, migrate};
+use crate::service::AuthService;
+use crate::session::generate_token;
+use crate::{message, outln, tr, validation, validation_error};
+
+/// Início de todo arquivo gzip
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Prefixo dos arquivos das cópias automáticas; só eles entram na retenção
+const SCHEDULED_PREFIX: &str = "users-";
+
+/// Seção `[backup]` do arquivo de configuração
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfig {
+    /// Diretório das cópias automáticas (None = sem cópias automáticas)
+    pub directory: Option<PathBuf>,
+    /// Intervalo entre as cópias, em minutos
+    pub interval_minutes: u64,
+    /// Quantas cópias são mantidas; as mais antigas são apagadas
+    pub keep: usize,
+    /// Compacta as cópias com gzip
+    pub gzip: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        BackupConfig { directory: None, interval_minutes: 24 * 60, keep: 7, gzip: true }
+    }
+}
+
+impl BackupConfig {
+    pub(crate) fn validate(&self) -> AuthResult<()> {
+        if self.directory.as_ref().is_some_and(|dir| dir.as_os_str().is_empty()) {
+            return validation_error!("O diretório das cópias de segurança não pode ser vazio");
+        }
+        if self.interval_minutes == 0 {
+            return validation_error!("O intervalo entre as cópias de segurança deve ser de pelo menos 1 minuto");
+        }
+        if self.keep == 0 {
+            return validation_error!("É preciso manter pelo menos uma cópia de segurança");
+        }
+        Ok(())
+    }
+}
+
+/// Cópia de segurança gravada por `backup_database`
+#[derive(Debug, Clone)]
+pub struct BackupReport {
+    /// Arquivo gravado
+    pub path: PathBuf,
+    /// Tamanho do arquivo, em bytes
+    pub bytes: u64,
+    /// Se a cópia foi compactada com gzip
+    pub compressed: bool,
+}
+
+/// Caminho temporário ao lado do destino, com o sufixo acrescentado ao nome
+fn sibling(dest: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(dest.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Copia o banco para `dest` sem interromper quem o está usando.
+///
+/// A cópia sai de um `VACUUM INTO`, que lê uma fotografia consistente do banco mesmo
+/// com o servidor no ar (no modo WAL, sem bloquear as escritas). O arquivo é gravado
+/// primeiro ao lado do destino e só então renomeado, então um destino existente é
+/// sempre uma cópia completa. Com `compress`, o resultado é compactado com gzip.
+pub fn backup_database(conn: &Connection, dest: &Path, compress: bool) -> AuthResult<BackupReport> {
+    if dest.exists() {
+        return validation_error!("O arquivo '{}' já existe", dest.display());
+    }
+    let Some(target) = dest.to_str() else {
+        return validation_error!("Caminho inválido para a cópia de segurança: '{}'", dest.display());
+    };
+    create_parent_dirs(dest)?;
+
+    let snapshot = sibling(dest, ".snapshot");
+    let partial = sibling(dest, ".partial");
+    // Sobras de uma cópia interrompida são descartadas, para serem recriadas abaixo
+    for leftover in [&snapshot, &partial] {
+        if leftover.symlink_metadata().is_ok() {
+            fs::remove_file(leftover)?;
+        }
+    }
+
+    let result = (|| -> AuthResult<()> {
+        // O VACUUM INTO aceita um arquivo vazio: criado antes, ele já nasce só com o dono
+        create_private_file(&snapshot)?;
+        conn.execute("VACUUM INTO ?1", [format!("{}.snapshot", target)])?;
+
+        if compress {
+            gzip_file(&snapshot, &partial)?;
+            fs::rename(&partial, dest)?;
+            fs::remove_file(&snapshot)?;
+        } else {
+            fs::rename(&snapshot, dest)?;
+        }
+        Ok(())
+    })();
+
+    if let Err(err) = result {
+        let _ = fs::remove_file(&snapshot);
+        let _ = fs::remove_file(&partial);
+        return Err(err);
+    }
+
+    log::info!("cópia de segurança do banco gravada em '{}'", dest.display());
+    Ok(BackupReport { path: dest.to_path_buf(), bytes: fs::metadata(dest)?.len(), compressed: compress })
+}
+
+/// Compacta `source` com gzip em um arquivo novo, acessível só pelo dono
+fn gzip_file(source: &Path, dest: &Path) -> AuthResult<()> {
+    let mut input = BufReader::new(File::open(source)?);
+    let mut encoder = GzEncoder::new(create_private_file(dest)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    Ok(())
+}
+
+/// Cópia de segurança aplicada por `restore_database`
+#[derive(Debug, Clone)]
+pub struct RestoreReport {
+    /// Contas (não excluídas) no banco restaurado
+    pub users: u32,
+    /// Versão do esquema em que a cópia foi gravada
+    pub schema_version: u32,
+    /// Migrações aplicadas à cópia antes de restaurá-la
+    pub migrated: Vec<u32>,
+}
+
+/// Substitui todo o conteúdo do banco de `live` pelo da cópia em `source`.
+///
+/// A cópia (compactada com gzip ou não) é carregada em memória, conferida com
+/// `PRAGMA integrity_check` e migrada até a versão deste binário; cópias de um
+/// esquema mais novo são recusadas. Sem `force`, também é recusada a cópia mais
+/// antiga que a última alteração do banco atual. A troca é feita pela API de backup
+/// do SQLite numa única transação: quem usa o banco vê o conteúdo antigo ou o novo,
+/// nunca uma mistura dos dois.
+pub fn restore_database(live: &mut Connection, source: &Path, force: bool) -> AuthResult<RestoreReport> {
+    if !source.is_file() {
+        return Err(AuthError::NotFound(message!("Arquivo '{}'", source.display())));
+    }
+
+    let staging = load_backup(source)?;
+    check_integrity(&staging).map_err(|problem| {
+        AuthError::CorruptDatabase(message!("A cópia '{}' está corrompida: {}", source.display(), problem))
+    })?;
+    let is_siri: bool = staging.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'users')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !is_siri {
+        return validation_error!("'{}' não é uma cópia de segurança do Siri", source.display());
+    }
+
+    let schema_version = current_version(&staging)?;
+    if schema_version > latest_version() {
+        return validation_error!(
+            "A cópia '{}' está na versão {} do esquema, mais nova que a suportada ({}). Atualize o programa",
+            source.display(),
+            schema_version,
+            latest_version()
+        );
+    }
+    let migrated = migrate(&staging)?;
+
+    if !force {
+        if let (Some(current), backup) = (last_change(live)?, last_change(&staging)?) {
+            if backup.is_none_or(|backup| current > backup) {
+                return validation_error!(
+                    "O banco atual foi alterado em {}, depois da cópia; use --force para substituí-lo mesmo assim",
+                    current.to_rfc3339_opts(SecondsFormat::Secs, true)
+                );
+            }
+        }
+    }
+
+    // Um único passo copia todas as páginas dentro de uma só transação de escrita no destino
+    match Backup::new(&staging, live)?.step(-1)? {
+        StepResult::Done => {}
+        _ => return validation_error!("O banco está ocupado; tente restaurar de novo em instantes"),
+    }
+
+    let users = live.query_row("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL", [], |row| row.get(0))?;
+    log::info!("banco restaurado a partir de '{}' (esquema {})", source.display(), schema_version);
+    Ok(RestoreReport { users, schema_version, migrated })
+}
+
+/// Carrega a cópia num banco em memória, descompactando-a antes se for gzip
+fn load_backup(source: &Path) -> AuthResult<Connection> {
+    let mut magic = [0u8; 2];
+    let compressed = File::open(source)?.read(&mut magic)? == magic.len() && magic == GZIP_MAGIC;
+
+    // O SQLite só lê bancos de arquivos: a versão gzip é descompactada num arquivo
+    // temporário de nome imprevisível, criado do zero e só com o dono
+    let unpacked = std::env::temp_dir().join(format!("siri-restore-{}.db", generate_token()));
+    let path = if compressed {
+        let mut decoder = GzDecoder::new(BufReader::new(File::open(source)?));
+        let mut output = create_private_file(&unpacked)?;
+        let copied = io::copy(&mut decoder, &mut output);
+        if let Err(err) = copied {
+            let _ = fs::remove_file(&unpacked);
+            return validation_error!("Não foi possível descompactar '{}': {}", source.display(), err);
+        }
+        unpacked.as_path()
+    } else {
+        source
+    };
+
+    let result = copy_to_memory(path);
+    if compressed {
+        let _ = fs::remove_file(&unpacked);
+    }
+    result.map_err(|err| match err {
+        rusqlite::Error::SqliteFailure(failure, _) if failure.code == rusqlite::ErrorCode::NotADatabase => {
+            validation!("'{}' não é um banco SQLite", source.display())
+        }
+        err => AuthError::from(err),
+    })
+}
+
+/// Copia um banco em arquivo, aberto só para leitura, para um banco em memória
+fn copy_to_memory(path: &Path) -> rusqlite::Result<Connection> {
+    let file = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut staging = Connection::open_in_memory()?;
+    Backup::new(&file, &mut staging)?.step(-1)?;
+    Ok(staging)
+}
+
+/// Instante da alteração mais recente nas contas, nos logins ou no log de auditoria.
+///
+/// As próprias cópias e restaurações não contam: logo depois de `siri backup`, o
+/// banco não passa a ser mais novo que a cópia só por ter registrado a ação.
+fn last_change(conn: &Connection) -> AuthResult<Option<DateTime<Utc>>> {
+    let latest: Option<String> = conn.query_row(
+        "SELECT MAX(at) FROM (
+             SELECT MAX(MAX(created_at, COALESCE(last_login_at, ''), COALESCE(password_changed_at, ''),
+                            COALESCE(deleted_at, ''))) AS at FROM users
+             UNION ALL SELECT MAX(attempted_at) FROM login_attempts
+             UNION ALL SELECT MAX(created_at) FROM audit_log WHERE action NOT IN ('backup', 'restore')
+         )",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(latest.as_deref().and_then(parse_sqlite_timestamp))
+}
+
+/// Inicia as cópias automáticas, se a configuração tiver um diretório.
+///
+/// A primeira cópia sai um intervalo depois do início; cada uma roda numa thread
+/// de bloqueio, sem atrasar as requisições. Deve ser chamada dentro do runtime do
+/// tokio de `siri serve` ou `siri daemon`.
+pub fn schedule(service: Arc<AuthService>, config: &BackupConfig) -> AuthResult<()> {
+    let Some(directory) = config.directory.clone() else {
+        return Ok(());
+    };
+    // Sem o SQLite não há o que copiar: melhor falhar ao iniciar do que a cada intervalo
+    service.database()?;
+
+    let config = config.clone();
+    let period = Duration::from_secs(config.interval_minutes * 60);
+    log::info!(
+        "cópias automáticas em '{}' a cada {} minutos, mantendo {}",
+        directory.display(),
+        config.interval_minutes,
+        config.keep
+    );
+    outln!(
+        "💾 {}",
+        tr!("Cópias de segurança em '{}' a cada {} minutos", directory.display(), config.interval_minutes)
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let service = Arc::clone(&service);
+            let (directory, config) = (directory.clone(), config.clone());
+            let result = tokio::task::spawn_blocking(move || scheduled_backup(&service, &directory, &config)).await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => log::error!("falha na cópia automática: {}", err),
+                Err(err) => log::error!("cópia automática interrompida: {}", err),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Grava uma cópia com a data no nome e apaga as que passam do limite de retenção
+fn scheduled_backup(service: &AuthService, directory: &Path, config: &BackupConfig) -> AuthResult<()> {
+    let extension = if config.gzip { "db.gz" } else { "db" };
+    let name = format!("{}{}.{}", SCHEDULED_PREFIX, Utc::now().format("%Y%m%dT%H%M%SZ"), extension);
+    backup_database(&*service.database()?.connection()?, &directory.join(name), config.gzip)?;
+    prune(directory, config.keep)
+}
+
+/// Apaga as cópias automáticas mais antigas, mantendo as `keep` mais recentes.
+///
+/// A data no nome faz a ordem alfabética ser a cronológica; outros arquivos do
+/// diretório não são tocados.
+fn prune(directory: &Path, keep: usize) -> AuthResult<()> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_backup = path.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+            name.starts_with(SCHEDULED_PREFIX) && (name.ends_with(".db") || name.ends_with(".db.gz"))
+        });
+        if is_backup {
+            backups.push(path);
+        }
+    }
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep);
+    for path in &backups[..excess] {
+        fs::remove_file(path)?;
+        log::info!("cópia antiga '{}' apagada", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, OnCorrupt};
+    use crate::store::UserStore;
+    use crate::testing::{register, CHEAP_ARGON2};
+
+    /// Banco em arquivo com uma conta
+    fn live_database(dir: &tempfile::TempDir) -> Database {
+        let db = Database::open(&dir.path().join("users.db"), OnCorrupt::Refuse).unwrap();
+        CHEAP_ARGON2.save(&db).unwrap();
+        register(&db, "alice");
+        db
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backups_are_private_to_the_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = live_database(&dir);
+        for (name, compress) in [("plain.db", false), ("compressed.db.gz", true)] {
+            let dest = dir.path().join(name);
+            backup_database(&db.connection().unwrap(), &dest, compress).unwrap();
+            assert_eq!(fs::metadata(&dest).unwrap().permissions().mode() & 0o777, 0o600, "{}", name);
+            assert!(!sibling(&dest, ".snapshot").exists());
+            assert!(!sibling(&dest, ".partial").exists());
+        }
+    }
+
+    #[test]
+    fn compressed_backup_restores_over_a_changed_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = live_database(&dir);
+        let dest = dir.path().join("copia.db.gz");
+        backup_database(&db.connection().unwrap(), &dest, true).unwrap();
+        register(&db, "bob");
+
+        let report = restore_database(&mut db.connection().unwrap(), &dest, true).unwrap();
+        assert_eq!(report.users, 1);
+        assert!(db.user_exists("alice").unwrap());
+        assert!(!db.user_exists("bob").unwrap());
+    }
+}
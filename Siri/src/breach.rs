@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use crate::config;
+use crate::error::{AuthError, AuthResult, PasswordIssue};
+use crate::validation_error;
+
+/// Endpoint de intervalos da API Pwned Passwords
+const HIBP_RANGE_URL: &str = "https://api.pwnedpasswords.com/range/";
+
+/// Tempo máximo de espera pela API, para não travar o cadastro
+const HIBP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Caracteres do hash enviados à API (k-anonimato: o restante nunca sai da máquina)
+const PREFIX_LEN: usize = 5;
+
+/// Serviço consultado em `breach.check`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BreachService {
+    /// Nenhum (padrão)
+    #[default]
+    None,
+    /// API Pwned Passwords do Have I Been Pwned
+    Hibp,
+}
+
+/// Seção `[breach]` do arquivo de configuração
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BreachConfig {
+    pub check: BreachService,
+    /// Arquivo local de hashes vazados, para uso offline (tem prioridade sobre a API)
+    pub file: Option<PathBuf>,
+    /// Aceita a senha quando a verificação falha (API fora do ar, arquivo ilegível);
+    /// por padrão, a operação é recusada
+    pub fail_open: bool,
+}
+
+impl BreachConfig {
+    pub(crate) fn validate(&self) -> AuthResult<()> {
+        if self.file.as_ref().is_some_and(|path| path.as_os_str().is_empty()) {
+            return validation_error!("O caminho do arquivo de senhas vazadas não pode ser vazio");
+        }
+        Ok(())
+    }
+}
+
+/// Onde procurar senhas vazadas
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum BreachCheck {
+    /// Nenhuma verificação (padrão)
+    #[default]
+    Disabled,
+    /// API Pwned Passwords do Have I Been Pwned
+    Hibp,
+    /// Arquivo local com um SHA-1 por linha, em hexadecimal e ordenado (como o
+    /// "ordered by hash" distribuído pelo HIBP, no formato `HASH:CONTAGEM`)
+    File(PathBuf),
+}
+
+impl BreachCheck {
+    /// Verificação da seção `[breach]`: o arquivo local tem prioridade sobre a API
+    pub fn from_config(config: &BreachConfig) -> Self {
+        match (&config.file, config.check) {
+            (Some(path), _) => BreachCheck::File(path.clone()),
+            (None, BreachService::Hibp) => BreachCheck::Hibp,
+            (None, BreachService::None) => BreachCheck::Disabled,
+        }
+    }
+
+    /// Quantas vezes a senha aparece em vazamentos conhecidos (0 se nenhuma)
+    pub fn breach_count(&self, password: &str) -> AuthResult<u64> {
+        let hash = format!("{:X}", Sha1::digest(password.as_bytes()));
+        match self {
+            BreachCheck::Disabled => Ok(0),
+            BreachCheck::Hibp => query_hibp(&hash),
+            BreachCheck::File(path) => search_file(path, &hash),
+        }
+    }
+}
+
+/// Recusa senhas que aparecem em vazamentos conhecidos, se a verificação estiver ligada
+pub fn check_not_breached(password: &str) -> AuthResult<()> {
+    check_with_config(password, &config::current().breach)
+}
+
+/// Como `check_not_breached`, com a seção `[breach]` informada.
+///
+/// Uma falha ao consultar a fonte recusa a senha, a menos que `fail_open` esteja
+/// ligado: aí a verificação é apenas ignorada, com um aviso no log.
+pub(crate) fn check_with_config(password: &str, config: &BreachConfig) -> AuthResult<()> {
+    let count = match BreachCheck::from_config(config).breach_count(password) {
+        Ok(count) => count,
+        Err(err) if config.fail_open => {
+            log::warn!("verificação de senhas vazadas falhou ({}); ignorada por breach.fail_open", err);
+            0
+        }
+        Err(err) => {
+            log::warn!("verificação de senhas vazadas falhou ({}); senha recusada", err);
+            return Err(err);
+        }
+    };
+
+    if count > 0 {
+        return Err(PasswordIssue::Breached { count }.into());
+    }
+    Ok(())
+}
+
+/// Consulta a API enviando apenas os 5 primeiros caracteres do hash
+fn query_hibp(hash: &str) -> AuthResult<u64> {
+    let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(HIBP_TIMEOUT))
+        .build()
+        .new_agent();
+
+    let body = agent
+        .get(format!("{}{}", HIBP_RANGE_URL, prefix))
+        // Respostas de tamanho uniforme, para que o tráfego não revele o prefixo
+        .header("Add-Padding", "true")
+        .header("User-Agent", concat!("siri-ferrugem/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+        .map_err(|e| AuthError::Input(std::io::Error::other(e)))?;
+
+    Ok(body
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0))
+}
+
+/// Busca binária no arquivo ordenado, sem carregá-lo na memória (ele costuma ter dezenas de GB)
+fn search_file(path: &Path, hash: &str) -> AuthResult<u64> {
+    let mut file = File::open(path)?;
+    let mut low = 0u64;
+    let mut high = file.metadata()?.len();
+
+    // Invariante: a linha procurada, se existir, começa em [low, high)
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let (line_start, line) = line_at_or_after(&mut file, mid)?;
+        let Some(line) = line.filter(|_| line_start < high) else {
+            high = mid;
+            continue;
+        };
+
+        let (candidate, count) = line.split_once(':').unwrap_or((line.as_str(), ""));
+        match candidate.trim().to_ascii_uppercase().as_str().cmp(hash) {
+            // Arquivos só com os hashes, sem a contagem, valem como um vazamento
+            Ordering::Equal => return Ok(count.trim().parse().unwrap_or(1)),
+            Ordering::Less => low = line_start + line.len() as u64 + 1,
+            Ordering::Greater => high = mid,
+        }
+    }
+    Ok(0)
+}
+
+/// Primeira linha que começa em `offset` ou depois, com a posição em que começa
+fn line_at_or_after(file: &mut File, offset: u64) -> AuthResult<(u64, Option<String>)> {
+    // Lendo a partir do byte anterior, uma linha que começa exatamente em `offset`
+    // não é confundida com o final da linha anterior
+    let skip_from = offset.saturating_sub(1);
+    file.seek(SeekFrom::Start(skip_from))?;
+    let mut reader = BufReader::new(file.by_ref());
+
+    let mut start = skip_from;
+    if offset > 0 {
+        let mut partial = Vec::new();
+        start += reader.read_until(b'\n', &mut partial)? as u64;
+    }
+
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok((start, None));
+    }
+    Ok((start, Some(line.trim_end_matches(['\r', '\n']).to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn local_file_finds_breached_passwords() {
+        let mut hashes: Vec<String> = ["123456", "senha123", "qwerty"]
+            .iter()
+            .map(|password| format!("{:X}:42", Sha1::digest(password.as_bytes())))
+            .collect();
+        hashes.sort();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "{}", hashes.join("\n")).unwrap();
+        let config = BreachConfig { file: Some(file.path().to_path_buf()), ..BreachConfig::default() };
+
+        let err = check_with_config("senha123", &config).unwrap_err();
+        assert!(matches!(err, AuthError::WeakPassword { reason: PasswordIssue::Breached { count: 42 } }), "{:?}", err);
+        check_with_config("Cavalo-Bateria-Grampo-42", &config).unwrap();
+    }
+
+    #[test]
+    fn failed_check_refuses_unless_fail_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = BreachConfig { file: Some(dir.path().join("ausente.txt")), ..BreachConfig::default() };
+
+        let err = check_with_config("senha123", &missing).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Input);
+        check_with_config("senha123", &BreachConfig { fail_open: true, ..missing }).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+//! Modo `siri checkpassword`, compatível com a interface checkpassword do qmail e do
+//! Dovecot.
+//!
+//! O chamador escreve `usuário\0senha\0` (seguidos de um carimbo de tempo, ignorado)
+//! no descritor 3. Se as credenciais conferem, o programa passado nos argumentos é
+//! executado no lugar deste processo, com `USER` definida; sem programa, o processo
+//! termina com 0. Senão, termina com 1 (recusado) ou 111 (falha temporária), e o
+//! resultado é só o código de saída: nada é escrito na saída padrão.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use zeroize::Zeroizing;
+use crate::error::{AuthError, AuthResult, ErrorKind};
+use crate::service::AuthService;
+use crate::user::AccountStatus;
+use crate::validation_error;
+
+/// Credenciais aceitas (quando não há programa a executar)
+pub const ACCEPTED: i32 = 0;
+
+/// Credenciais recusadas
+pub const REJECTED: i32 = 1;
+
+/// Falha temporária (banco indisponível, entrada malformada...): o chamador pode tentar de novo
+pub const TEMPORARY_FAILURE: i32 = 111;
+
+/// Tamanho máximo dos dados no descritor 3, como no checkpassword original
+const MAX_INPUT: u64 = 512;
+
+/// Lê `usuário\0senha\0...` do descritor 3
+fn read_credentials() -> AuthResult<(String, Zeroizing<String>)> {
+    let mut input = Zeroizing::new(Vec::new());
+    File::open("/dev/fd/3")?.take(MAX_INPUT).read_to_end(&mut input)?;
+
+    let mut fields = input.split(|byte| *byte == 0);
+    let (Some(username), Some(password)) = (fields.next(), fields.next()) else {
+        return validation_error!("Entrada do checkpassword malformada");
+    };
+    let (Ok(username), Ok(password)) = (std::str::from_utf8(username), std::str::from_utf8(password)) else {
+        return validation_error!("Entrada do checkpassword malformada");
+    };
+    Ok((username.to_string(), Zeroizing::new(password.to_string())))
+}
+
+/// Confere as credenciais, retornando se foram aceitas.
+///
+/// Com `AUTHORIZED=1` (consulta de usuário do Dovecot, sem senha), basta a conta
+/// existir e estar ativa.
+fn check(service: &AuthService, username: &str, password: &str) -> AuthResult<bool> {
+    if env::var("AUTHORIZED").is_ok_and(|value| value == "1") {
+        let active = service.store().get_status(username)? == Some(AccountStatus::Active);
+        return Ok(active);
+    }
+
+    // Só o resultado do login decide; os erros são classificados por `failure_code`
+    Ok(service.login(username, password)?.into_result().is_ok())
+}
+
+/// Código de saída de uma conferência que terminou em erro.
+///
+/// Credenciais que nem chegam a ser conferidas (usuário ou senha vazios) são
+/// recusadas: tentar de novo não mudaria a resposta. Erros de banco, de hash e de
+/// entrada são falhas temporárias.
+fn failure_code(err: &AuthError) -> i32 {
+    match err.kind() {
+        ErrorKind::Validation => REJECTED,
+        _ => TEMPORARY_FAILURE,
+    }
+}
+
+/// Executa o protocolo, retornando o código de saída.
+///
+/// Se as credenciais conferem e há um programa, ele substitui este processo e a
+/// função só retorna se não puder ser executado.
+pub fn run(service: &AuthService, program: &[OsString]) -> i32 {
+    let (username, password) = match read_credentials() {
+        Ok(credentials) => credentials,
+        Err(err) => {
+            log::error!("checkpassword: {}", err);
+            return TEMPORARY_FAILURE;
+        }
+    };
+
+    match check(service, &username, &password) {
+        Ok(true) => {}
+        Ok(false) => return REJECTED,
+        Err(err) => {
+            log::error!("checkpassword: {}", err);
+            return failure_code(&err);
+        }
+    }
+    drop(password);
+
+    let Some((name, args)) = program.split_first() else {
+        return ACCEPTED;
+    };
+    let mut command = Command::new(name);
+    command.args(args).env("USER", &username);
+    if env::var("AUTHORIZED").is_ok_and(|value| value == "1") {
+        command.env("AUTHORIZED", "2");
+    }
+    let err = command.exec();
+    log::error!("checkpassword: não foi possível executar {:?}: {}", name, err);
+    TEMPORARY_FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message;
+    use crate::testing::{database, register, PASSWORD};
+
+    #[test]
+    fn empty_credentials_are_rejected_not_retried() {
+        let service = AuthService::new(database());
+        register(service.store(), "alice");
+
+        for (username, password) in [("", PASSWORD), ("alice", "")] {
+            let err = check(&service, username, password).unwrap_err();
+            assert_eq!(failure_code(&err), REJECTED, "{:?}", err);
+        }
+        assert!(check(&service, "alice", PASSWORD).unwrap());
+        assert!(!check(&service, "alice", "Senha-Errada-99").unwrap());
+    }
+
+    #[test]
+    fn database_and_hashing_errors_are_temporary() {
+        let database = AuthError::from(rusqlite::Error::QueryReturnedNoRows);
+        let hashing = AuthError::PasswordHashing(message!("Erro ao analisar hash: {}", "salt"));
+        assert_eq!(failure_code(&database), TEMPORARY_FAILURE);
+        assert_eq!(failure_code(&hashing), TEMPORARY_FAILURE);
+    }
+}
@@ -1,18 +1,99 @@
-use std::io::{self, Write};
-use crate::auth::{register_user, login_user};
+use std::cell::RefCell;
+use std::io::StdinLock;
+use std::thread;
+use chrono::{DateTime, Duration, Utc};
+use zeroize::Zeroizing;
+use crate::audit::{self, AuditFilter};
+use crate::auth::{delete_user, describe_login_outcome, LoginOutcome};
+use crate::clock::{Clock, SystemClock};
+use crate::config;
+use crate::crypto::secrets_equal;
 use crate::db::Database;
-use crate::error::{AuthError, AuthResult};
+use crate::error::{AuthError, AuthResult, ErrorKind};
+use crate::output;
+use crate::profile::Profile;
+use crate::prompt::{PasswordEcho, Prompter, CANCEL_INPUT, MAX_PROMPT_ATTEMPTS};
+use crate::rate_limit::RateLimiter;
+use crate::rbac::{user_permissions, Permission};
+use crate::recovery::{generate_recovery_codes, reset_password_with_recovery_code};
+use crate::service::AuthService;
+use crate::session::Session;
+use crate::store::{UserOrder, UserStore};
+use crate::{outln, tr};
+use crate::user::{EmailStatus, TimestampDisplay, User};
+
+/// Prompt de códigos que aceitam tanto o aplicativo quanto um código de recuperação
+fn second_factor_prompt() -> String {
+    format!("🔢 {} ", tr!("Código do autenticador (ou de recuperação):"))
+}
+
+/// Quantas tentativas de login o histórico do menu do usuário mostra
+const LOGIN_HISTORY_LIMIT: usize = 10;
+
+/// Usuários por página na listagem do menu de administração
+const USERS_PAGE_SIZE: usize = 20;
+
+/// Largura máxima das barras do painel de estatísticas
+const STATS_BAR_WIDTH: usize = 30;
+
+/// Entrada que apaga um campo do perfil
+const CLEAR_INPUT: &str = "-";
 
 /// Estrutura para gerenciar a interface CLI
+#[allow(clippy::upper_case_acronyms)]
 pub struct CLI {
-    db: Database,
+    service: AuthService,
+    rate_limiter: RefCell<RateLimiter>,
+    prompter: RefCell<Prompter<StdinLock<'static>>>,
+    time_display: TimestampDisplay,
 }
 
 impl CLI {
-    /// Cria uma nova instância da CLI
+    /// Cria uma nova instância da CLI usando o relógio do sistema
     pub fn new() -> AuthResult<Self> {
-        let db = Database::new()?;
-        Ok(CLI { db })
+        Self::with_clock(Box::new(SystemClock))
+    }
+
+    /// Cria uma nova instância da CLI com um relógio específico
+    pub fn with_clock(clock: Box<dyn Clock>) -> AuthResult<Self> {
+        Ok(Self::with_service(AuthService::with_clock(Database::new()?, clock)))
+    }
+
+    /// Cria uma nova instância da CLI sobre um serviço já configurado
+    pub fn with_service(service: AuthService) -> Self {
+        CLI {
+            service,
+            rate_limiter: RefCell::new(RateLimiter::new()),
+            prompter: RefCell::new(Prompter::stdin(PasswordEcho::default())),
+            time_display: config::current().display.timezone,
+        }
+    }
+
+    fn store(&self) -> &dyn UserStore {
+        self.service.store()
+    }
+
+    fn db(&self) -> AuthResult<&Database> {
+        self.service.database()
+    }
+
+    /// Avisa e retorna false quando o recurso depende do SQLite e o backend é outro
+    fn sqlite_available(&self) -> bool {
+        let available = self.db().is_ok();
+        if !available {
+            outln!("⚠️  {}", tr!("Recurso disponível apenas com o backend SQLite."));
+        }
+        available
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.service.clock()
+    }
+
+    /// Define como a senha aparece enquanto é digitada
+    pub fn with_password_echo(mut self, echo: PasswordEcho) -> Self {
+        self.prompter.get_mut().set_echo(echo);
+        self
     }
 
     /// Executa o loop principal da aplicação
@@ -23,67 +104,68 @@ impl CLI {
             match self.show_menu_and_get_choice()? {
                 MenuChoice::Register => self.handle_register()?,
                 MenuChoice::Login => self.handle_login()?,
-                MenuChoice::ListUsers => self.handle_list_users()?,
+                MenuChoice::ForgotPassword => self.handle_forgot_password()?,
                 MenuChoice::Exit => {
-                    println!("👋 Encerrando o sistema. Até logo!");
+                    outln!("👋 {}", tr!("Encerrando o sistema. Até logo!"));
                     break;
                 }
                 MenuChoice::Invalid => {
-                    println!("❌ Opção inválida. Tente novamente.");
+                    outln!("❌ {}", tr!("Opção inválida. Tente novamente."));
                 }
             }
-            println!(); // Linha em branco para melhor visibilidade
+            outln!(); // Linha em branco para melhor visibilidade
         }
         Ok(())
     }
 
     /// Mostra a mensagem de boas-vindas
     fn show_welcome(&self) {
-        println!("==  Siri Ferrugem  ==");
-        println!("=====================");
-        println!("\n");
+        outln!("==  Siri Ferrugem  ==");
+        outln!("=====================");
+        outln!("\n");
     }
 
     /// Mostra o menu e obtém a escolha do usuário
     fn show_menu_and_get_choice(&self) -> AuthResult<MenuChoice> {
-        println!("📋 Escolha uma opção:");
-        println!("1️⃣  Registrar novo usuário");
-        println!("2️⃣  Fazer login");
-        println!("3️⃣  Listar usuários");
-        println!("4️⃣  Sair");
-        println!();
+        outln!("📋 {}", tr!("Escolha uma opção:"));
+        outln!("1️⃣  {}", tr!("Registrar novo usuário"));
+        outln!("2️⃣  {}", tr!("Fazer login"));
+        outln!("3️⃣  {}", tr!("Esqueci minha senha"));
+        outln!("4️⃣  {}", tr!("Sair"));
+        outln!();
         
-        print!("👉 Opção: ");
-        io::stdout().flush()?;
-        
-        let mut choice = String::new();
-        io::stdin().read_line(&mut choice)?;
+        let choice = self.read_line(&format!("👉 {} ", tr!("Opção:")))?;
         
         Ok(MenuChoice::from_str(choice.trim()))
     }
 
     /// Lida com o registro de usuário
     fn handle_register(&self) -> AuthResult<()> {
-        println!("\n📝 REGISTRO DE NOVO USUÁRIO");
-        
-        let username = self.read_username()?;
+        outln!("\n📝 {}", tr!("REGISTRO DE NOVO USUÁRIO"));
         
-        if username.is_empty() {
-            println!("⚠️  Nome de usuário não pode estar vazio.");
+        let Some(username) = self.prompt_username()? else {
             return Ok(());
-        }
+        };
         
-        let password = self.read_password("🔒 Senha (oculta): ")?;
-        let confirm_password = self.read_password("🔒 Confirme a senha (oculta): ")?;
-        
-        if password != confirm_password {
-            println!("⚠️  As senhas não coincidem.");
+        let Some(password) =
+            self.prompt_new_password(&format!("🔒 {} ", tr!("Senha (oculta):")), &format!("🔒 {} ", tr!("Confirme a senha (oculta):")), &[&username])?
+        else {
             return Ok(());
-        }
+        };
         
-        match register_user(self.db.connection(), &username, &password) {
-            Ok(_) => println!("✅ Usuário '{}' registrado com sucesso!", username),
-            Err(AuthError::Validation(msg)) => println!("⚠️  {}", msg),
+        let email = self.read_line(&format!("📧 {} ", tr!("Email (opcional, Enter para pular):")))?;
+        let email = email.trim();
+        let email = (!email.is_empty()).then_some(email);
+
+        match self.service.register(&username, &password, email) {
+            Ok(_) => {
+                outln!("✅ {}", tr!("Usuário '{}' registrado com sucesso!", username));
+                if email.is_some() {
+                    outln!("📧 {}", tr!("Confirme o email em \"Ver informações da conta\", depois do login."));
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
+            Err(AuthError::PermissionDenied(msg)) => outln!("🚫 {}", msg),
             Err(e) => return Err(e),
         }
         Ok(())
@@ -91,136 +173,1228 @@ impl CLI {
 
     /// Lida com o login de usuário
     fn handle_login(&self) -> AuthResult<()> {
-        println!("\n🔓 LOGIN");
+        outln!("\n🔓 {}", tr!("LOGIN"));
+
+        let delay = self.rate_limiter.borrow_mut().delay(self.clock().now());
+        if delay > Duration::zero() {
+            outln!("⏳ {}", tr!("Muitas tentativas malsucedidas. Aguarde {}s...", delay.num_seconds()));
+            thread::sleep(delay.to_std().unwrap_or_default());
+        }
         
         let username = self.read_username()?;
         
         if username.is_empty() {
-            println!("⚠️  Nome de usuário não pode estar vazio.");
+            outln!("⚠️  {}", tr!("Nome de usuário não pode estar vazio."));
             return Ok(());
         }
         
-        let password = self.read_password("🔒 Senha (oculta): ")?;
+        let password = self.read_password(&format!("🔒 {} ", tr!("Senha (oculta):")))?;
         
         if password.is_empty() {
-            println!("⚠️  Senha não pode estar vazia.");
+            outln!("⚠️  {}", tr!("Senha não pode estar vazia."));
             return Ok(());
         }
         
-        match login_user(self.db.connection(), &username, &password) {
-            Ok(true) => {
-                println!("✅ Login de '{}' bem-sucedido!", username);
-                // Aqui você poderia adicionar um menu pós-login
-                self.show_user_menu(&username)?;
-            },
-            Ok(false) => println!("❌ Credenciais inválidas."),
+        // Lido antes da autenticação, que o substitui pelo login de agora
+        let previous_login = self.store().find_user(&username)?.and_then(|user| user.last_login_at);
+
+        let (mut outcome, session) = self.service.login_session(&username, &password)?;
+        if outcome == LoginOutcome::RequiresSecondFactor {
+            outln!("{}", describe_login_outcome(&outcome));
+            outcome = self.prompt_second_factor(&username)?;
+        }
+        outln!("{}", describe_login_outcome(&outcome));
+        if matches!(outcome, LoginOutcome::Success | LoginOutcome::PasswordExpired) {
+            self.show_previous_login(previous_login);
+        }
+
+        match outcome {
+            LoginOutcome::Success => {}
+            LoginOutcome::PasswordExpired => {
+                outln!("🔄 {}", tr!("Defina uma nova senha para continuar."));
+                if !self.handle_change_password(&username)? {
+                    outln!("🚫 {}", tr!("Acesso negado até que a senha seja alterada."));
+                    return Ok(());
+                }
+            }
+            LoginOutcome::Locked { until } => {
+                self.rate_limiter.borrow_mut().record_failure(self.clock().now());
+                let local = until.with_timezone(&chrono::Local);
+                outln!("⏳ {}", tr!("Tente novamente após {}.", local.format("%d/%m/%Y %H:%M")));
+                return Ok(());
+            }
+            LoginOutcome::InvalidCredentials => {
+                self.rate_limiter.borrow_mut().record_failure(self.clock().now());
+                return Ok(());
+            }
+            LoginOutcome::Disabled | LoginOutcome::RequiresSecondFactor => return Ok(()),
+        }
+
+        // Após o segundo fator ou a troca de uma senha expirada o login não abriu sessão; ela é aberta agora
+        let mut session = match session {
+            Some(session) => session,
+            None => self.service.open_session(&username)?,
+        };
+        self.show_user_menu(&mut session, previous_login)?;
+        Ok(())
+    }
+
+    /// Mostra quando foi o login anterior, para o usuário perceber acessos que não reconhece
+    fn show_previous_login(&self, previous_login: Option<DateTime<Utc>>) {
+        match previous_login {
+            Some(at) => outln!("🕘 {}", tr!("Último login: {}", self.time_display.format(at))),
+            None => outln!("🆕 {}", tr!("Este é o seu primeiro login.")),
+        }
+    }
+
+    /// Lida com a redefinição de senha via token (recebido por email) ou código de recuperação
+    fn handle_forgot_password(&self) -> AuthResult<()> {
+        outln!("\n🆘 {}", tr!("ESQUECI MINHA SENHA"));
+
+        let token = self.read_line(&format!(
+            "🎟️  {} ",
+            tr!("Token de redefinição (Enter para pedir um por email):")
+        ))?;
+        if !token.trim().is_empty() {
+            return self.handle_redeem_reset_token(token.trim());
+        }
+
+        let email = self.read_line(&format!(
+            "📧 {} ",
+            tr!("Email verificado da conta (Enter para usar um código de recuperação):")
+        ))?;
+        if !email.trim().is_empty() {
+            self.service.request_password_reset(email.trim())?;
+            // A mesma resposta para qualquer email, para não revelar quais estão cadastrados
+            outln!("📨 {}", tr!("Se o email estiver verificado em uma conta, um token foi enviado para ele."));
+
+            let token = self.read_line(&format!(
+                "🎟️  {} ",
+                tr!("Digite o token recebido (Enter para deixar para depois):")
+            ))?;
+            if token.trim().is_empty() {
+                outln!("↩️  {}", tr!("O token vale por 1 hora; use-o em \"Esqueci minha senha\"."));
+                return Ok(());
+            }
+            return self.handle_redeem_reset_token(token.trim());
+        }
+
+        let identifier = self.read_line(&format!("👤 {} ", tr!("Nome de usuário ou email:")))?;
+        let identifier = identifier.trim();
+
+        if identifier.is_empty() {
+            outln!("⚠️  {}", tr!("Nome de usuário não pode estar vazio."));
+            return Ok(());
+        }
+
+        // Um email desconhecido segue o fluxo normal e falha como código inválido
+        let username = if identifier.contains('@') {
+            self.store().find_by_email(identifier)?.unwrap_or_else(|| identifier.to_string())
+        } else {
+            identifier.to_string()
+        };
+
+        let code = self.read_line(&format!("🧾 {} ", tr!("Código de recuperação:")))?;
+
+        let new_password = self.read_password(&format!("🔒 {} ", tr!("Nova senha (oculta):")))?;
+        let confirm_password = self.read_password(&format!("🔒 {} ", tr!("Confirme a nova senha (oculta):")))?;
+
+        if !secrets_equal(new_password.as_bytes(), confirm_password.as_bytes()) {
+            outln!("⚠️  {}", tr!("As senhas não coincidem."));
+            return Ok(());
+        }
+
+        match reset_password_with_recovery_code(self.store(), &username, code.trim(), &new_password) {
+            Ok(_) => outln!("✅ {}", tr!("Senha redefinida com sucesso! O código usado não vale mais.")),
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Define uma nova senha com um token de redefinição
+    fn handle_redeem_reset_token(&self, token: &str) -> AuthResult<()> {
+        let Some(new_password) =
+            self.prompt_new_password(&format!("🔒 {} ", tr!("Nova senha (oculta):")), &format!("🔒 {} ", tr!("Confirme a nova senha (oculta):")), &[])?
+        else {
+            return Ok(());
+        };
+
+        match self.service.redeem_reset_token(token, &new_password) {
+            Ok(username) => outln!("✅ {}", tr!("Senha de '{}' redefinida com sucesso! O token não vale mais.", username)),
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
             Err(e) => return Err(e),
         }
         Ok(())
     }
 
-    /// Lida com a listagem de usuários
+    /// Lida com a listagem de usuários, uma página por vez
     fn handle_list_users(&self) -> AuthResult<()> {
-        println!("\n👥 USUÁRIOS CADASTRADOS");
-        
-        let users = self.db.list_users()?;
-        
+        outln!("\n👥 {}", tr!("USUÁRIOS CADASTRADOS"));
+
+        let total = self.store().count_users()? as usize;
+        if total == 0 {
+            outln!("📭 {}", tr!("Nenhum usuário cadastrado."));
+            return Ok(());
+        }
+        outln!("📊 {}", tr!("Total de usuários: {}", total));
+
+        let pages = total.div_ceil(USERS_PAGE_SIZE);
+        let mut page = 0;
+        let mut order = UserOrder::default();
+        loop {
+            outln!();
+            for user in self.store().list_users_paged(page * USERS_PAGE_SIZE, USERS_PAGE_SIZE, order)? {
+                self.print_user_row(&user);
+            }
+
+            let direction = if order.descending { tr!("decrescente") } else { tr!("crescente") };
+            outln!(
+                "📄 {} | ↕️  {}",
+                tr!("Página {} de {}", page + 1, pages),
+                tr!("ordem: {} ({})", order.sort.label(), direction)
+            );
+            let choice = self.read_line(&format!(
+                "👉 {} ",
+                tr!("[n] próxima, [p] anterior, [s] trocar ordem, [d] inverter, [q] voltar:")
+            ))?;
+            // O fim da entrada chega como linha vazia, que também volta
+            match choice.as_str() {
+                "n" if page + 1 < pages => page += 1,
+                "p" if page > 0 => page -= 1,
+                "n" | "p" => outln!("⚠️  {}", tr!("Não há mais páginas nessa direção.")),
+                // Outra ordem recomeça da primeira página
+                "s" => (order.sort, page) = (order.sort.next(), 0),
+                "d" => (order.descending, page) = (!order.descending, 0),
+                "" | CANCEL_INPUT | "0" => return Ok(()),
+                _ => outln!("❌ {}", tr!("Opção inválida. Tente novamente.")),
+            }
+        }
+    }
+
+    /// Busca usuários pelo nome ou pelo email
+    fn handle_search_users(&self) -> AuthResult<()> {
+        outln!("\n🔎 {}", tr!("BUSCAR USUÁRIOS"));
+
+        let pattern = self.read_line(&format!("🔎 {} ", tr!("Nome ou email (* e ? como curingas):")))?;
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Ok(());
+        }
+
+        let users = self.store().search_users(pattern, UserOrder::default())?;
         if users.is_empty() {
-            println!("📭 Nenhum usuário cadastrado.");
+            outln!("📭 {}", tr!("Nenhum usuário encontrado para '{}'.", pattern));
+            return Ok(());
+        }
+        outln!("📊 {}", tr!("{} usuários encontrados", users.len()));
+        outln!();
+        for user in &users {
+            self.print_user_row(user);
+        }
+        Ok(())
+    }
+
+    /// Uma linha da listagem de usuários
+    fn print_user_row(&self, user: &User) {
+        outln!(
+            "🆔 #{:<3} | 👤 {:<20} | 🎖️  {:<13} | ⏯️  {:<10} | 📅 {}",
+            user.id,
+            user.username,
+            user.role,
+            user.status,
+            self.time_display.format(user.created_at)
+        );
+    }
+
+    /// Menu de administração do sistema
+    fn show_admin_menu(&self, session: &mut Session) -> AuthResult<()> {
+        loop {
+            if self.session_expired(session)? {
+                break;
+            }
+
+            outln!("\n🛠️  {}", tr!("ADMINISTRAÇÃO ({})", session.username));
+            outln!("1️⃣  {}", tr!("Painel de estatísticas"));
+            outln!("2️⃣  {}", tr!("Limite de cadastros"));
+            outln!("3️⃣  {}", tr!("Ativar/desativar conta"));
+            outln!("4️⃣  {}", tr!("Excluir usuário"));
+            outln!("5️⃣  {}", tr!("Restaurar usuário excluído"));
+            outln!("6️⃣  {}", tr!("Purgar usuários excluídos"));
+            outln!("7️⃣  {}", tr!("Bloquear/desbloquear usuário"));
+            outln!("8️⃣  {}", tr!("Forçar troca de senha no próximo login"));
+            outln!("9️⃣  {}", tr!("Redefinir senha"));
+            outln!("🔟 {}", tr!("Log de auditoria"));
+            outln!("1️⃣1️⃣ {}", tr!("Token de redefinição de senha"));
+            outln!("1️⃣2️⃣ {}", tr!("Validade das senhas"));
+            outln!("1️⃣3️⃣ {}", tr!("Listar usuários"));
+            outln!("1️⃣4️⃣ {}", tr!("Promover/rebaixar usuário"));
+            outln!("1️⃣5️⃣ {}", tr!("Buscar usuários"));
+            outln!("0️⃣  {}", tr!("Voltar ao menu do usuário"));
+            outln!();
+
+            let choice = self.read_line(&format!("👉 {} ", tr!("Opção:")))?;
+
+            if self.session_expired(session)? {
+                break;
+            }
+            self.service.touch_session(session)?;
+
+            if let Some(permission) = admin_option_permission(choice.trim()) {
+                match self.service.authorize(session, permission) {
+                    Ok(()) => {}
+                    Err(AuthError::PermissionDenied(msg)) => {
+                        outln!("🚫 {}", msg);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            match choice.trim() {
+                "1" => self.handle_stats()?,
+                "2" => self.handle_max_users(&session.username)?,
+                "3" => self.handle_toggle_enabled(&session.username)?,
+                "4" => self.handle_delete_user(&session.username)?,
+                "5" => self.handle_restore_user(&session.username)?,
+                "6" => self.handle_purge_deleted(&session.username)?,
+                "7" => self.handle_lock_user(&session.username)?,
+                "8" => self.handle_force_password_change(&session.username)?,
+                "9" => self.handle_admin_reset_password(&session.username)?,
+                "10" => self.handle_audit_log()?,
+                "11" => self.handle_create_reset_token(&session.username)?,
+                "12" => self.handle_password_max_age(&session.username)?,
+                "13" => self.handle_list_users()?,
+                "14" => self.handle_change_role(&session.username)?,
+                "15" => self.handle_search_users()?,
+                "0" => break,
+                _ => outln!("❌ {}", tr!("Opção inválida. Tente novamente.")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Exibe o resultado de uma ação administrativa, tratando erros esperados
+    fn report_admin_action(&self, result: AuthResult<()>, success: &str) -> AuthResult<()> {
+        match result {
+            Ok(()) => outln!("✅ {}", success),
+            Err(AuthError::NotFound(what)) => outln!("⚠️  {}", tr!("{} não encontrado.", what)),
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
+            Err(AuthError::PermissionDenied(msg)) => outln!("🚫 {}", msg),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Bloqueia uma conta por um tempo, ou desbloqueia uma conta bloqueada
+    fn handle_lock_user(&self, admin: &str) -> AuthResult<()> {
+        use crate::admin::{lock_user, unlock_user};
+
+        outln!("\n🔐 {}", tr!("BLOQUEAR/DESBLOQUEAR USUÁRIO"));
+
+        let username = self.read_username()?;
+        let Some(credentials) = self.store().get_credentials(&username)? else {
+            outln!("⚠️  {}", tr!("Usuário '{}' não encontrado.", username));
+            return Ok(());
+        };
+
+        let now = self.clock().now();
+        if let Some(until) = credentials.locked_until.filter(|until| *until > now) {
+            outln!("🔒 {}", tr!("Conta bloqueada até {}.", self.time_display.format(until)));
+            if !self.confirm(tr!("Desbloquear agora?"))? {
+                outln!("↩️  {}", tr!("Operação cancelada."));
+                return Ok(());
+            }
+            let result = unlock_user(&*self.db()?.connection()?, admin, &username);
+            return self.report_admin_action(result, &tr!("Conta '{}' desbloqueada.", username));
+        }
+
+        let input = self.read_line(&format!(
+            "⏱️  {} ",
+            tr!("Bloquear por quantos minutos? (Enter cancela):")
+        ))?;
+        let minutes = match input.trim() {
+            "" => {
+                outln!("↩️  {}", tr!("Operação cancelada."));
+                return Ok(());
+            }
+            value => match value.parse::<u32>() {
+                Ok(minutes) if minutes > 0 => minutes,
+                _ => {
+                    outln!("⚠️  {}", tr!("Valor inválido."));
+                    return Ok(());
+                }
+            },
+        };
+
+        let until = now + Duration::minutes(i64::from(minutes));
+        let result = lock_user(&*self.db()?.connection()?, admin, &username, until);
+        self.report_admin_action(
+            result,
+            &tr!("Conta '{}' bloqueada até {}.", username, self.time_display.format(until)),
+        )
+    }
+
+    /// Exige que um usuário troque a senha no próximo login
+    fn handle_force_password_change(&self, admin: &str) -> AuthResult<()> {
+        use crate::admin::force_password_change;
+
+        outln!("\n🔁 {}", tr!("FORÇAR TROCA DE SENHA"));
+
+        let username = self.read_username()?;
+        let result = force_password_change(&*self.db()?.connection()?, admin, &username);
+        self.report_admin_action(
+            result,
+            &tr!("'{}' precisará trocar a senha no próximo login.", username),
+        )
+    }
+
+    /// Define uma senha temporária para outro usuário
+    fn handle_admin_reset_password(&self, admin: &str) -> AuthResult<()> {
+        use crate::admin::reset_password;
+
+        outln!("\n🔑 {}", tr!("REDEFINIR SENHA"));
+
+        let username = self.read_username()?;
+        let Some(password) = self.prompt_new_password(
+            &format!("🔒 {} ", tr!("Senha temporária (oculta):")),
+            &format!("🔒 {} ", tr!("Confirme a senha temporária (oculta):")),
+            &[&username],
+        )?
+        else {
+            return Ok(());
+        };
+
+        let result = reset_password(&*self.db()?.connection()?, admin, &username, &password);
+        self.report_admin_action(
+            result,
+            &tr!("Senha de '{}' redefinida. A troca será exigida no próximo login.", username),
+        )
+    }
+
+    /// Exibe e permite alterar o limite de usuários cadastrados
+    fn handle_max_users(&self, admin: &str) -> AuthResult<()> {
+        use crate::auth::{max_users, set_max_users};
+
+        outln!("\n🚧 {}", tr!("LIMITE DE CADASTROS"));
+
+        let store = self.store();
+        match max_users(store, &config::current().password)? {
+            Some(limit) => outln!("📏 {}", tr!("Limite atual: {} usuários", limit)),
+            None => outln!("📏 {}", tr!("Limite atual: sem limite")),
+        }
+
+        let input = self.read_line(&format!(
+            "✏️  {} ",
+            tr!("Novo limite (número, 0 para remover, Enter para manter):")
+        ))?;
+
+        match input.trim() {
+            "" => {}
+            "0" => {
+                set_max_users(store, None)?;
+                audit::record(store, admin, "set_max_users", None)?;
+                outln!("✅ {}", tr!("Limite removido."));
+            }
+            value => match value.parse::<u32>() {
+                Ok(limit) => {
+                    set_max_users(store, Some(limit))?;
+                    audit::record(store, admin, "set_max_users", None)?;
+                    outln!("✅ {}", tr!("Limite definido para {} usuários.", limit));
+                }
+                Err(_) => outln!("⚠️  {}", tr!("Valor inválido.")),
+            },
+        }
+        Ok(())
+    }
+
+    /// Exibe e permite alterar a idade máxima das senhas
+    fn handle_password_max_age(&self, admin: &str) -> AuthResult<()> {
+        use crate::auth::{password_max_age_days, set_password_max_age_days};
+
+        outln!("\n⏳ {}", tr!("VALIDADE DAS SENHAS"));
+
+        let store = self.store();
+        match password_max_age_days(store, &config::current().password)? {
+            0 => outln!("📏 {}", tr!("Validade atual: as senhas não expiram")),
+            days => outln!("📏 {}", tr!("Validade atual: {} dias", days)),
+        }
+
+        let input = self.read_line(&format!(
+            "✏️  {} ",
+            tr!("Nova validade em dias (0 para nunca expirar, Enter para manter):")
+        ))?;
+
+        match input.trim() {
+            "" => {}
+            value => match value.parse::<u32>() {
+                Ok(days) => {
+                    set_password_max_age_days(store, days)?;
+                    audit::record(store, admin, "set_password_max_age", None)?;
+                    if days == 0 {
+                        outln!("✅ {}", tr!("As senhas não expiram mais."));
+                    } else {
+                        outln!("✅ {}", tr!("Senhas com mais de {} dias terão de ser trocadas no próximo login.", days));
+                    }
+                }
+                Err(_) => outln!("⚠️  {}", tr!("Valor inválido.")),
+            },
+        }
+        Ok(())
+    }
+
+    /// Alterna uma conta entre ativa e desativada
+    fn handle_toggle_enabled(&self, admin: &str) -> AuthResult<()> {
+        use crate::rbac::set_status;
+        use crate::user::AccountStatus;
+
+        outln!("\n⏯️  {}", tr!("ATIVAR/DESATIVAR CONTA"));
+
+        let username = self.read_username()?;
+
+        let status = match self.store().get_status(&username)? {
+            Some(AccountStatus::Active) => AccountStatus::Disabled,
+            Some(AccountStatus::Disabled) => AccountStatus::Active,
+            None => {
+                outln!("⚠️  {}", tr!("Usuário '{}' não encontrado.", username));
+                return Ok(());
+            }
+        };
+
+        match set_status(self.store(), admin, &username, status) {
+            Ok(()) if status == AccountStatus::Disabled => outln!("⏸️  {}", tr!("Conta '{}' desativada.", username)),
+            Ok(()) => outln!("▶️  {}", tr!("Conta '{}' reativada.", username)),
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Promove um usuário a administrador, ou rebaixa um administrador
+    fn handle_change_role(&self, admin: &str) -> AuthResult<()> {
+        use crate::rbac::{set_role, Role};
+
+        outln!("\n🎖️  {}", tr!("PROMOVER/REBAIXAR USUÁRIO"));
+
+        let username = self.read_username()?;
+        let Some(current) = self.store().get_role(&username)? else {
+            outln!("⚠️  {}", tr!("Usuário '{}' não encontrado.", username));
+            return Ok(());
+        };
+
+        let role = match current {
+            Role::User => Role::Admin,
+            Role::Admin => Role::User,
+        };
+        if !self.confirm(&tr!("'{}' é {}. Tornar {}?", username, current, role))? {
+            outln!("↩️  {}", tr!("Operação cancelada."));
+            return Ok(());
+        }
+
+        let result = set_role(self.store(), admin, &username, role);
+        self.report_admin_action(result, &tr!("'{}' agora é {}.", username, role))
+    }
+
+    /// Pede confirmação explícita antes de uma ação destrutiva
+    fn confirm(&self, question: &str) -> AuthResult<bool> {
+        self.prompter.borrow_mut().confirm(question)
+    }
+
+    /// Exclui (de forma reversível) um usuário
+    fn handle_delete_user(&self, admin: &str) -> AuthResult<()> {
+        outln!("\n🗑️  {}", tr!("EXCLUIR USUÁRIO"));
+
+        let username = self.read_username()?;
+
+        if !self.store().user_exists(&username)? {
+            outln!("⚠️  {}", tr!("Usuário '{}' não encontrado.", username));
+            return Ok(());
+        }
+
+        if !self.confirm(&tr!("Excluir a conta '{}'?", username))? {
+            outln!("↩️  {}", tr!("Operação cancelada."));
+            return Ok(());
+        }
+
+        match delete_user(self.store(), admin, &username) {
+            Ok(()) => outln!("✅ {}", tr!("Conta '{}' excluída. Ela pode ser restaurada até ser purgada.", username)),
+            // Excluída por outro processo enquanto a confirmação era pedida
+            Err(AuthError::NotFound(_)) => outln!("⚠️  {}", tr!("Usuário '{}' não encontrado.", username)),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Restaura um usuário excluído
+    fn handle_restore_user(&self, admin: &str) -> AuthResult<()> {
+        outln!("\n♻️  {}", tr!("RESTAURAR USUÁRIO"));
+
+        let deleted = self.store().list_deleted_users()?;
+        if deleted.is_empty() {
+            outln!("📭 {}", tr!("Nenhum usuário excluído."));
+            return Ok(());
+        }
+
+        for (username, deleted_at) in &deleted {
+            outln!("👤 {:<20} | 🗑️  {}", username, self.time_display.format(*deleted_at));
+        }
+
+        let username = self.read_username()?;
+        if self.store().restore_user(&username)? {
+            audit::record(self.store(), admin, "restore_user", Some(&username))?;
+            outln!("✅ {}", tr!("Conta '{}' restaurada.", username));
         } else {
-            println!("📊 Total de usuários: {}\n", users.len());
-            for (id, username, created_at) in users {
-                println!("🆔 #{:<3} | 👤 {:<20} | 📅 {}", id, username, created_at);
+            outln!("⚠️  {}", tr!("Nenhuma conta excluída com o nome '{}'.", username));
+        }
+        Ok(())
+    }
+
+    /// Remove definitivamente usuários excluídos há mais de N dias
+    fn handle_purge_deleted(&self, admin: &str) -> AuthResult<()> {
+        outln!("\n🔥 {}", tr!("PURGAR USUÁRIOS EXCLUÍDOS"));
+
+        let input = self.read_line(&format!(
+            "📅 {} ",
+            tr!("Purgar exclusões com mais de quantos dias? (0 = todas):")
+        ))?;
+
+        let days = match input.trim().parse::<i64>() {
+            Ok(days) if days >= 0 => days,
+            _ => {
+                outln!("⚠️  {}", tr!("Valor inválido."));
+                return Ok(());
+            }
+        };
+
+        if !self.confirm(tr!("Esta ação não pode ser desfeita. Continuar?"))? {
+            outln!("↩️  {}", tr!("Operação cancelada."));
+            return Ok(());
+        }
+
+        let purged = self.store().purge_deleted(self.clock().now() - Duration::days(days))?;
+        if purged > 0 {
+            audit::record(self.store(), admin, "purge_deleted", None)?;
+        }
+        outln!("✅ {}", tr!("{} conta(s) removida(s) definitivamente.", purged));
+        Ok(())
+    }
+
+    /// Gera um token para o usuário redefinir a própria senha
+    fn handle_create_reset_token(&self, admin: &str) -> AuthResult<()> {
+        use crate::auth::RESET_TOKEN_TTL;
+
+        outln!("\n🎟️  {}", tr!("TOKEN DE REDEFINIÇÃO DE SENHA"));
+
+        let username = self.read_username()?;
+        match self.service.create_reset_token(admin, &username) {
+            Ok(token) => {
+                outln!(
+                    "✅ {}",
+                    tr!(
+                        "Token gerado para '{}'. Ele vale por {} min e uma única vez:",
+                        username,
+                        RESET_TOKEN_TTL.num_minutes()
+                    )
+                );
+                outln!("   🎟️  {}", token);
+                outln!("📨 {}", tr!("Entregue-o ao usuário, que deve usar \"Esqueci minha senha\" no menu principal."));
             }
+            Err(AuthError::NotFound(what)) => outln!("⚠️  {}", tr!("{} não encontrado.", what)),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Consulta o log de auditoria, com filtros opcionais
+    fn handle_audit_log(&self) -> AuthResult<()> {
+        outln!("\n📜 {}", tr!("LOG DE AUDITORIA"));
+        outln!("{}", tr!("Deixe um filtro em branco para não filtrar por ele."));
+
+        let filter = AuditFilter {
+            actor: self.read_filter(&format!("👤 {} ", tr!("Autor:")))?,
+            action: self.read_filter(&format!("⚙️  {} ", tr!("Ação (ex.: register, delete_user):")))?,
+            target: self.read_filter(&format!("🎯 {} ", tr!("Usuário afetado:")))?,
+            ..AuditFilter::default()
+        };
+
+        let entries = audit::search(self.store(), &filter)?;
+        if entries.is_empty() {
+            outln!("📭 {}", tr!("Nenhuma entrada encontrada."));
+            return Ok(());
+        }
+
+        outln!("📊 {}", tr!("{} entrada(s), das mais recentes para as mais antigas:", entries.len()));
+        outln!();
+        for entry in entries {
+            outln!(
+                "📅 {} | 👤 {:<16} | ⚙️  {:<28} | 🎯 {}",
+                self.time_display.format(entry.created_at),
+                entry.actor,
+                entry.action,
+                entry.target.as_deref().unwrap_or("-")
+            );
+        }
+        Ok(())
+    }
+
+    /// Lê um filtro opcional (entrada vazia = sem filtro)
+    fn read_filter(&self, prompt: &str) -> AuthResult<Option<String>> {
+        let value = self.read_line(prompt)?;
+
+        let value = value.trim();
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    }
+
+    /// Mostra o painel de estatísticas: contas, segurança e cadastros recentes
+    fn handle_stats(&self) -> AuthResult<()> {
+        outln!("\n📈 {}", tr!("PAINEL DE ESTATÍSTICAS"));
+
+        let stats = self.db()?.get_stats(self.clock())?;
+        let health = self.db()?.scan_hash_health()?;
+
+        outln!("\n— {} —", tr!("Contas"));
+        outln!("👥 {}", tr!("Total de usuários: {}", stats.total_users));
+        outln!("🔒 {}", tr!("Contas bloqueadas: {}", stats.locked_accounts));
+        outln!("⏸️  {}", tr!("Contas desativadas: {}", stats.disabled_accounts));
+        match stats.average_account_age_days {
+            Some(days) => outln!("⏳ {}", tr!("Idade média das contas: {} dias", format!("{:.1}", days))),
+            None => outln!("⏳ {}", tr!("Idade média das contas: -")),
         }
+        match stats.latest_user {
+            Some(username) => outln!("⭐ {}", tr!("Último cadastro: {}", username)),
+            None => outln!("⭐ {}", tr!("Último cadastro: -")),
+        }
+
+        outln!("\n— {} —", tr!("Segurança"));
+        let percent = if stats.total_users == 0 {
+            0.0
+        } else {
+            stats.two_factor_users as f64 * 100.0 / stats.total_users as f64
+        };
+        outln!("🔐 {}", tr!("Com dois fatores: {} ({}%)", stats.two_factor_users, format!("{:.0}", percent)));
+        outln!("🧪 {}", tr!("Hashes fora do esquema configurado ({}): {}", health.scheme, health.legacy_users.len()));
+
+        outln!("\n— {} —", tr!("Cadastros por dia"));
+        outln!("🆕 {}", tr!("Novos nas últimas 24h: {}", stats.created_last_24h));
+        print_bars(stats.registrations_per_day.iter().map(|(day, count)| (day.to_string(), *count)));
+
+        outln!("\n— {} —", tr!("Cadastros por semana"));
+        outln!("📅 {}", tr!("Novos nos últimos 7 dias: {}", stats.created_last_7d));
+        print_bars(
+            stats.registrations_per_week.iter().map(|(week, count)| (tr!("semana de {}", week), *count)),
+        );
         Ok(())
     }
 
+    /// Pede o código do aplicativo autenticador (ou de recuperação), com até `MAX_PROMPT_ATTEMPTS` tentativas.
+    ///
+    /// Cada código errado conta como uma falha de login; o resultado final segue o do login.
+    fn prompt_second_factor(&self, username: &str) -> AuthResult<LoginOutcome> {
+        let mut outcome = LoginOutcome::InvalidCredentials;
+        for attempt in 1..=MAX_PROMPT_ATTEMPTS {
+            let code = self.read_line(&second_factor_prompt())?;
+            outcome = self.service.verify_second_factor(username, &code)?;
+            if outcome != LoginOutcome::InvalidCredentials {
+                break;
+            }
+            if attempt < MAX_PROMPT_ATTEMPTS {
+                outln!("❌ {}", tr!("Código inválido. Tente novamente."));
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Pede um nome de usuário não vazio, repetindo até o limite de tentativas
+    fn prompt_username(&self) -> AuthResult<Option<String>> {
+        self.prompter.borrow_mut().username()
+    }
+
+    /// Pede uma nova senha forte o bastante e sua confirmação, repetindo até o limite de tentativas
+    fn prompt_new_password(
+        &self,
+        prompt: &str,
+        confirm_prompt: &str,
+        user_inputs: &[&str],
+    ) -> AuthResult<Option<Zeroizing<String>>> {
+        self.prompter.borrow_mut().new_password(prompt, confirm_prompt, user_inputs)
+    }
+
+    /// Exibe o prompt e lê uma linha da entrada, sem os espaços das pontas
+    fn read_line(&self, prompt: &str) -> AuthResult<String> {
+        self.prompter.borrow_mut().read_line(prompt)
+    }
+
     /// Lê o nome de usuário
     fn read_username(&self) -> AuthResult<String> {
-        print!("👤 Nome de usuário: ");
-        io::stdout().flush()?;
-        
-        let mut username = String::new();
-        io::stdin().read_line(&mut username)?;
-        
-        Ok(username.trim().to_string())
+        self.prompter.borrow_mut().read_line(&format!("👤 {} ", tr!("Nome de usuário:")))
     }
 
     /// Lê a senha de forma segura
-    fn read_password(&self, prompt: &str) -> AuthResult<String> {
-        use rpassword::read_password;
-        
-        print!("{}", prompt);
-        io::stdout().flush()?;
-        
-        let password = read_password()?;
-        Ok(password)
+    fn read_password(&self, prompt: &str) -> AuthResult<Zeroizing<String>> {
+        self.prompter.borrow_mut().read_password(prompt)
     }
 
     /// Menu pós-login para operações do usuário
-    fn show_user_menu(&self, username: &str) -> AuthResult<()> {
+    fn show_user_menu(&self, session: &mut Session, previous_login: Option<DateTime<Utc>>) -> AuthResult<()> {
         loop {
-            println!("\n🏠 MENU DO USUÁRIO - {}", username.to_uppercase());
-            println!("1️⃣  Alterar senha");
-            println!("2️⃣  Ver informações da conta");
-            println!("3️⃣  Sair da conta");
-            println!();
-            
-            print!("👉 Opção: ");
-            io::stdout().flush()?;
+            if self.session_expired(session)? {
+                break;
+            }
+
+            let username = session.username.clone();
+            let username = username.as_str();
+            // Consultado a cada volta, para refletir promoções e rebaixamentos na hora
+            let staff = !user_permissions(self.store(), username)?.is_empty();
+
+            outln!("\n🏠 {}", tr!("MENU DO USUÁRIO - {}", username.to_uppercase()));
+            outln!("1️⃣  {}", tr!("Alterar senha"));
+            outln!("2️⃣  {}", tr!("Ver informações da conta"));
+            outln!("3️⃣  {}", tr!("Gerar códigos de recuperação"));
+            outln!("4️⃣  {}", tr!("Alterar nome de usuário"));
+            outln!("5️⃣  {}", tr!("Autenticação em dois fatores"));
+            outln!("6️⃣  {}", tr!("Histórico de acessos"));
+            outln!("7️⃣  {}", tr!("Editar perfil"));
+            outln!("8️⃣  {}", tr!("Excluir minha conta"));
+            outln!("9️⃣  {}", tr!("Sair da conta"));
+            if staff {
+                outln!("🔟 {}", tr!("Administração"));
+            }
+            outln!();
             
-            let mut choice = String::new();
-            io::stdin().read_line(&mut choice)?;
+            let choice = self.read_line(&format!("👉 {} ", tr!("Opção:")))?;
+
+            // O tempo parado no prompt também conta como inatividade
+            if self.session_expired(session)? {
+                break;
+            }
+            self.service.touch_session(session)?;
             
             match choice.trim() {
-                "1" => self.handle_change_password(username)?,
-                "2" => self.show_account_info(username)?,
-                "3" => {
-                    println!("🚪 Saindo da conta de '{}'...", username);
+                "1" => {
+                    self.handle_change_password(username)?;
+                }
+                "2" => self.show_account_info(session, previous_login)?,
+                "3" => self.handle_generate_recovery_codes(username)?,
+                "4" => self.handle_change_username(session)?,
+                "5" => self.handle_two_factor(username)?,
+                "6" => self.show_login_history(username)?,
+                "7" => self.handle_edit_profile(username)?,
+                "8" => {
+                    if self.handle_delete_own_account(username)? {
+                        break;
+                    }
+                }
+                "9" => {
+                    outln!("🚪 {}", tr!("Saindo da conta de '{}'...", username));
+                    self.service.logout(session)?;
                     break;
                 }
-                _ => println!("❌ Opção inválida. Tente novamente."),
+                "10" if staff => {
+                    if self.sqlite_available() {
+                        self.show_admin_menu(session)?;
+                    }
+                }
+                _ => outln!("❌ {}", tr!("Opção inválida. Tente novamente.")),
             }
         }
         Ok(())
     }
 
-    /// Lida com a alteração de senha
-    fn handle_change_password(&self, username: &str) -> AuthResult<()> {
-        use crate::auth::change_password;
+    /// Lida com a alteração de senha, retornando se a troca foi concluída
+    fn handle_change_password(&self, username: &str) -> AuthResult<bool> {
+        outln!("\n🔄 {}", tr!("ALTERAR SENHA"));
         
-        println!("\n🔄 ALTERAR SENHA");
+        let old_password = self.read_password(&format!("🔒 {} ", tr!("Senha atual (oculta):")))?;
+        let Some(new_password) =
+            self.prompt_new_password(&format!("🔒 {} ", tr!("Nova senha (oculta):")), &format!("🔒 {} ", tr!("Confirme a nova senha (oculta):")), &[username])?
+        else {
+            return Ok(false);
+        };
         
-        let old_password = self.read_password("🔒 Senha atual (oculta): ")?;
-        let new_password = self.read_password("🔒 Nova senha (oculta): ")?;
-        let confirm_password = self.read_password("🔒 Confirme a nova senha (oculta): ")?;
-        
-        if new_password != confirm_password {
-            println!("⚠️  As senhas não coincidem.");
+        match self.service.change_password(username, &old_password, &new_password) {
+            Ok(_) => {
+                outln!("✅ {}", tr!("Senha alterada com sucesso!"));
+                Ok(true)
+            }
+            Err(err) if err.kind() == ErrorKind::Validation => {
+                outln!("⚠️  {}", err.detail());
+                Ok(false)
+            }
+            Err(AuthError::InvalidCredentials) => {
+                outln!("⚠️  {}", tr!("Senha atual incorreta"));
+                Ok(false)
+            }
+            Err(err @ AuthError::AccountLocked(_)) => {
+                outln!("🔒 {}", err);
+                Ok(false)
+            }
+            Err(AuthError::PermissionDenied(msg)) => {
+                outln!("🚫 {}", msg);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Edita os campos do perfil, um a um
+    fn handle_edit_profile(&self, username: &str) -> AuthResult<()> {
+        outln!("\n✏️  {}", tr!("EDITAR PERFIL"));
+        outln!("ℹ️  {}", tr!("Enter mantém o valor atual; '{}' apaga o campo.", CLEAR_INPUT));
+
+        let Some(user) = self.store().find_user(username)? else {
+            return Ok(());
+        };
+        let current = Profile::of(&user);
+        let profile = Profile {
+            display_name: self.read_profile_field(&format!("🪪 {}", tr!("Nome de exibição")), &current.display_name)?,
+            email: self.read_profile_field(&format!("📧 {}", tr!("Email")), &current.email)?,
+            phone: self.read_profile_field(&format!("📞 {}", tr!("Telefone")), &current.phone)?,
+            bio: self.read_profile_field(&format!("📝 {}", tr!("Biografia")), &current.bio)?,
+        };
+        if profile == current {
+            outln!("↩️  {}", tr!("Nada foi alterado."));
             return Ok(());
         }
-        
-        match change_password(self.db.connection(), username, &old_password, &new_password) {
-            Ok(_) => println!("✅ Senha alterada com sucesso!"),
-            Err(AuthError::Validation(msg)) => println!("⚠️  {}", msg),
+
+        match self.service.update_profile(username, &profile) {
+            Ok(()) => {
+                outln!("✅ {}", tr!("Perfil atualizado."));
+                if profile.email.is_some() && profile.email != current.email {
+                    outln!("📨 {}", tr!("Confirme o novo email em \"Ver informações da conta\"."));
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Lê um campo do perfil, mostrando o valor atual: Enter o mantém e `CLEAR_INPUT` o apaga
+    fn read_profile_field(&self, label: &str, current: &Option<String>) -> AuthResult<Option<String>> {
+        let prompt = format!("{} [{}]: ", label, current.as_deref().unwrap_or("-"));
+        Ok(match self.read_filter(&prompt)? {
+            None => current.clone(),
+            Some(value) if value == CLEAR_INPUT => None,
+            Some(value) => Some(value),
+        })
+    }
+
+    /// Exclui a conta do usuário logado, retornando se a exclusão aconteceu
+    fn handle_delete_own_account(&self, username: &str) -> AuthResult<bool> {
+        outln!("\n🗑️  {}", tr!("EXCLUIR MINHA CONTA"));
+        outln!("⚠️  {}", tr!("A exclusão é definitiva: sessões, dois fatores e histórico de acessos serão apagados."));
+
+        if !self.confirm(&tr!("Excluir a conta '{}'?", username))? {
+            outln!("↩️  {}", tr!("Operação cancelada."));
+            return Ok(false);
+        }
+        let password = self.read_password(&format!("🔒 {} ", tr!("Senha atual (oculta):")))?;
+
+        match self.service.delete_own_account(username, &password) {
+            Ok(()) => {
+                outln!("👋 {}", tr!("Conta '{}' excluída. Até logo!", username));
+                Ok(true)
+            }
+            Err(err) if err.kind() == ErrorKind::Validation => {
+                outln!("⚠️  {}", err.detail());
+                Ok(false)
+            }
+            Err(AuthError::InvalidCredentials) => {
+                outln!("⚠️  {}", tr!("Senha atual incorreta"));
+                Ok(false)
+            }
+            Err(err @ AuthError::AccountLocked(_)) => {
+                outln!("🔒 {}", err);
+                Ok(false)
+            }
+            Err(AuthError::PermissionDenied(msg)) => {
+                outln!("🚫 {}", msg);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lida com a alteração do nome de usuário
+    fn handle_change_username(&self, session: &mut Session) -> AuthResult<()> {
+        use crate::auth::change_username;
+
+        outln!("\n✏️  {}", tr!("ALTERAR NOME DE USUÁRIO"));
+
+        let new_username = self.read_line(&format!("👤 {} ", tr!("Novo nome de usuário:")))?;
+        let new_username = new_username.trim();
+
+        let password = self.read_password(&format!("🔒 {} ", tr!("Senha atual (oculta):")))?;
+        if !self.service.verify_password(&session.username, &password)? {
+            outln!("❌ {}", tr!("Senha incorreta."));
+            return Ok(());
+        }
+
+        match change_username(self.store(), &session.username, new_username) {
+            Ok(_) => {
+                outln!("✅ {}", tr!("Nome de usuário alterado de '{}' para '{}'.", session.username, new_username));
+                session.username = new_username.to_string();
+            }
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
             Err(e) => return Err(e),
         }
         Ok(())
     }
 
+    /// Gera e exibe novos códigos de recuperação
+    fn handle_generate_recovery_codes(&self, username: &str) -> AuthResult<()> {
+        outln!("\n🧾 {}", tr!("CÓDIGOS DE RECUPERAÇÃO"));
+
+        let password = self.read_password(&format!("🔒 {} ", tr!("Senha atual (oculta):")))?;
+        if !self.service.verify_password(username, &password)? {
+            outln!("❌ {}", tr!("Senha incorreta."));
+            return Ok(());
+        }
+
+        let codes = generate_recovery_codes(self.store(), username)?;
+        outln!("♻️  {}", tr!("Os códigos anteriores foram invalidados."));
+        show_recovery_codes(&codes);
+        Ok(())
+    }
+
+    /// Ativa ou desativa a autenticação em dois fatores (TOTP)
+    fn handle_two_factor(&self, username: &str) -> AuthResult<()> {
+        use crate::totp::{enable_totp, generate_secret, is_totp_enabled, provisioning_uri, render_qr};
+
+        outln!("\n🔐 {}", tr!("AUTENTICAÇÃO EM DOIS FATORES"));
+
+        if is_totp_enabled(self.store(), username)? {
+            return self.show_two_factor_menu(username);
+        }
+
+        let password = self.read_password(&format!("🔒 {} ", tr!("Senha atual (oculta):")))?;
+        if !self.service.verify_password(username, &password)? {
+            outln!("❌ {}", tr!("Senha incorreta."));
+            return Ok(());
+        }
+
+        let secret = generate_secret();
+        let uri = provisioning_uri(username, &secret);
+        // O QR code é desenhado com blocos Unicode: no modo texto puro, só o link e o segredo
+        if !output::is_plain() {
+            outln!("📱 {}\n", tr!("Escaneie o QR code no aplicativo autenticador:"));
+            outln!("{}", render_qr(&uri)?);
+        }
+        outln!("🔗 {}", uri);
+        outln!("🔑 {}\n", tr!("Ou digite o segredo manualmente: {}", secret));
+
+        for _ in 0..MAX_PROMPT_ATTEMPTS {
+            let code = self.read_line(&format!("🔢 {} ", tr!("Código do autenticador:")))?;
+            if code.is_empty() {
+                outln!("↩️  {}", tr!("Operação cancelada."));
+                return Ok(());
+            }
+            match enable_totp(self.store(), username, &secret, &code, self.clock().now()) {
+                Ok(codes) => {
+                    outln!("✅ {}", tr!("Autenticação em dois fatores ativada! O código será pedido nos próximos logins."));
+                    outln!("📵 {}", tr!("Sem o aparelho, use um dos códigos de recuperação abaixo no lugar do código."));
+                    show_recovery_codes(&codes);
+                    return Ok(());
+                }
+                Err(err) if err.kind() == ErrorKind::Validation => {
+                    outln!("⚠️  {}", tr!("{} (código vazio cancela).", err.detail()))
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        outln!("🚫 {}", tr!("Tentativas esgotadas. Voltando ao menu."));
+        Ok(())
+    }
+
+    /// Opções para quem já tem a autenticação em dois fatores ativada
+    fn show_two_factor_menu(&self, username: &str) -> AuthResult<()> {
+        use crate::recovery::{remaining_recovery_codes, RECOVERY_CODE_COUNT};
+        use crate::totp::{disable_totp, verify_second_factor};
+
+        outln!("✅ {}", tr!("A autenticação em dois fatores está ativada."));
+        outln!(
+            "🧾 {}",
+            tr!(
+                "Códigos de recuperação restantes: {} de {}",
+                remaining_recovery_codes(self.store(), username)?,
+                RECOVERY_CODE_COUNT
+            )
+        );
+        outln!("1️⃣  {}", tr!("Gerar novos códigos de recuperação"));
+        outln!("2️⃣  {}", tr!("Desativar a autenticação em dois fatores"));
+        outln!("3️⃣  {}", tr!("Voltar"));
+        outln!();
+
+        let choice = self.read_line(&format!("👉 {} ", tr!("Opção:")))?;
+
+        match choice.trim() {
+            "1" => {
+                let code = self.read_line(&second_factor_prompt())?;
+                if !verify_second_factor(self.store(), username, &code, self.clock().now())? {
+                    outln!("❌ {}", tr!("Código inválido."));
+                    return Ok(());
+                }
+                let codes = generate_recovery_codes(self.store(), username)?;
+                outln!("♻️  {}", tr!("Os códigos anteriores foram invalidados."));
+                show_recovery_codes(&codes);
+            }
+            "2" => {
+                let code = self.read_line(&second_factor_prompt())?;
+                match disable_totp(self.store(), username, &code, self.clock().now()) {
+                    Ok(_) => outln!("✅ {}", tr!("Autenticação em dois fatores desativada.")),
+                    Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
+                    Err(e) => return Err(e),
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Mostra as últimas tentativas de login da conta
+    fn show_login_history(&self, username: &str) -> AuthResult<()> {
+        outln!("\n🕓 {}", tr!("HISTÓRICO DE ACESSOS (últimos {})", LOGIN_HISTORY_LIMIT));
+
+        let attempts = self.service.login_history(username, LOGIN_HISTORY_LIMIT)?;
+        if attempts.is_empty() {
+            outln!("📭 {}", tr!("Nenhuma tentativa registrada."));
+        }
+        for attempt in attempts {
+            let icon = if attempt.success { "✅" } else { "❌" };
+            outln!(
+                "{} {} | {:<22} | {}",
+                icon,
+                self.time_display.format(attempt.attempted_at),
+                attempt.outcome,
+                attempt.source
+            );
+        }
+        Ok(())
+    }
+
+    /// Verifica se a sessão ainda vale, avisando o usuário quando ela expirou ou foi encerrada
+    fn session_expired(&self, session: &mut Session) -> AuthResult<bool> {
+        if self.service.session_active(session)? {
+            return Ok(false);
+        }
+        self.service.logout(session)?;
+        outln!("⏰ {}", tr!("Sessão expirada ou encerrada. Faça login novamente."));
+        Ok(true)
+    }
+
     /// Mostra informações da conta
-    fn show_account_info(&self, username: &str) -> AuthResult<()> {
-        println!("\n👤 INFORMAÇÕES DA CONTA");
-        println!("📛 Nome de usuário: {}", username);
-        
-        // Buscar informações adicionais do banco se necessário
-        let user_count = self.db.list_users()?.len();
-        println!("👥 Total de usuários no sistema: {}", user_count);
-        
-        println!("🔐 Status: Conta ativa");
+    fn show_account_info(&self, session: &Session, previous_login: Option<DateTime<Utc>>) -> AuthResult<()> {
+        outln!("\n👤 {}", tr!("INFORMAÇÕES DA CONTA"));
+        outln!("📛 {}", tr!("Nome de usuário: {}", session.username));
+
+        let mut email_pending = false;
+        if let Some(user) = self.store().find_user(&session.username)? {
+            if let Some(name) = &user.display_name {
+                outln!("🪪 {}", tr!("Nome de exibição: {}", name));
+            }
+            if let Some(email) = &user.email {
+                let status = match user.email_status() {
+                    EmailStatus::Verified => format!("✅ {}", tr!("verificado")),
+                    _ => format!("⏳ {}", tr!("aguardando verificação")),
+                };
+                outln!("📧 {}", tr!("Email: {} ({})", email, status));
+                email_pending = user.email_status() == EmailStatus::Pending;
+            }
+            if let Some(phone) = &user.phone {
+                outln!("📞 {}", tr!("Telefone: {}", phone));
+            }
+            if let Some(bio) = &user.bio {
+                outln!("📝 {}", tr!("Biografia: {}", bio));
+            }
+            outln!("🎖️  {}", tr!("Papel: {}", user.role));
+            outln!("🔐 {}", tr!("Situação: conta {}", user.status));
+            outln!("📅 {}", tr!("Conta criada em: {}", self.time_display.format(user.created_at)));
+            match previous_login {
+                Some(at) => outln!("🕘 {}", tr!("Login anterior a esta sessão: {}", self.time_display.format(at))),
+                None => outln!("🕘 {}", tr!("Login anterior a esta sessão: nenhum")),
+            }
+            outln!("🔢 {}", tr!("Logins bem-sucedidos: {}", user.login_count));
+            let groups = self.service.user_groups(&session.username)?;
+            if !groups.is_empty() {
+                outln!("🏷️  {}", tr!("Grupos: {}", groups.join(", ")));
+            }
+        }
+
+        let elapsed = self.clock().now() - session.issued_at;
+        outln!("⏱️  {}", tr!("Sessão iniciada há {} min", elapsed.num_minutes()));
+
+        if email_pending && self.confirm(tr!("Verificar o email agora?"))? {
+            self.handle_verify_email(&session.username)?;
+        }
         Ok(())
     }
+
+    /// Envia um token de verificação e confere o token digitado
+    fn handle_verify_email(&self, username: &str) -> AuthResult<()> {
+        match self.service.send_verification_email(username) {
+            Ok(address) => outln!("📨 {}", tr!("Token de verificação enviado para {}", address)),
+            Err(err) if err.kind() == ErrorKind::Validation => {
+                outln!("⚠️  {}", err.detail());
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        }
+
+        let input = self.read_line(&format!(
+            "🎟️  {} ",
+            tr!("Digite o token recebido (Enter para deixar para depois):")
+        ))?;
+        if input.trim().is_empty() {
+            outln!("↩️  {}", tr!("O token continua valendo por 24 horas."));
+            return Ok(());
+        }
+
+        match self.service.verify_email(input.trim()) {
+            Ok(_) => outln!("✅ {}", tr!("Email verificado!")),
+            Err(err) if err.kind() == ErrorKind::Validation => outln!("⚠️  {}", err.detail()),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+}
+
+/// Exibe códigos de recuperação recém-emitidos
+fn show_recovery_codes(codes: &[String]) {
+    outln!(
+        "⚠️  {}",
+        tr!("Guarde estes códigos em local seguro. Cada um pode ser usado apenas uma vez\n    e eles não serão exibidos novamente.")
+    );
+    outln!();
+    for code in codes {
+        outln!("   🔑 {}", code);
+    }
+}
+
+/// Exibe uma contagem por linha, com barras proporcionais à maior delas
+fn print_bars(rows: impl Iterator<Item = (String, usize)>) {
+    let rows: Vec<_> = rows.collect();
+    let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1);
+    let width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+    for (label, count) in &rows {
+        let bar = "█".repeat(count * STATS_BAR_WIDTH / max);
+        outln!("   {:<width$} | {} {}", label, bar, count, width = width);
+    }
+}
+
+/// Permissão exigida por cada opção do menu de administração
+fn admin_option_permission(choice: &str) -> Option<Permission> {
+    let permission = match choice {
+        "1" => Permission::ViewStats,
+        "2" | "12" => Permission::ManageSettings,
+        "3" | "7" | "8" | "9" | "11" => Permission::ManageAccounts,
+        "4" | "5" | "6" => Permission::DeleteUser,
+        "10" => Permission::ViewAudit,
+        "13" | "15" => Permission::ListUsers,
+        "14" => Permission::ManageRoles,
+        _ => return None,
+    };
+    Some(permission)
 }
 
 /// Enum para as escolhas do menu
@@ -228,7 +1402,7 @@ impl CLI {
 enum MenuChoice {
     Register,
     Login,
-    ListUsers,
+    ForgotPassword,
     Exit,
     Invalid,
 }
@@ -238,7 +1412,7 @@ impl MenuChoice {
         match s {
             "1" => MenuChoice::Register,
             "2" => MenuChoice::Login,
-            "3" => MenuChoice::ListUsers,
+            "3" => MenuChoice::ForgotPassword,
             "4" => MenuChoice::Exit,
             _ => MenuChoice::Invalid,
         }
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use chrono::{DateTime, Duration, Utc};
+
+/// Fonte do horário atual, permitindo substituir o relógio do sistema em testes.
+///
+/// `Send + Sync` para que o serviço possa ser compartilhado entre threads (ex.: pelo servidor HTTP).
+pub trait Clock: Send + Sync {
+    /// Retorna o instante atual em UTC
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Relógio real do sistema (padrão em produção)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Relógio controlado manualmente, para testes determinísticos
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    /// Cria um relógio parado no instante informado
+    pub fn new(start: DateTime<Utc>) -> Self {
+        MockClock { now: Mutex::new(start) }
+    }
+
+    /// Define o instante atual do relógio
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+
+    /// Avança o relógio pela duração informada
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Relógio compartilhado: quem entrega um `Arc<MockClock>` ao serviço pode continuar avançando-o
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
@@ -0,0 +1,740 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
+use crate::args::{Command, GroupAction, PepperAction, PermissionsAction};
+use crate::audit::{self, AuditFilter, COMMAND_ACTOR};
+use crate::auth::{create_reset_token, delete_user, describe_login_outcome, Argon2Config};
+use crate::backup::{backup_database, restore_database};
+use crate::config;
+use crate::crypto::secrets_equal;
+use crate::db::secure_file_permissions;
+use crate::dump::{dump_users, load_users};
+use crate::error::{AuthError, AuthResult};
+use crate::export::{export_user_data, export_users, UsersExport};
+use crate::fsck;
+use crate::genpass::{generate_password, GeneratorOptions};
+use crate::groups;
+use crate::health;
+use crate::import::import_users;
+use crate::migrations::{self, latest_version, migrate};
+use crate::pepper::rotate_pepper;
+use crate::prompt::{self, PasswordEcho};
+use crate::rbac::{is_admin, set_permission, set_role, set_status, Permission, Role};
+use crate::service::AuthService;
+use crate::store::UserOrder;
+use crate::user::{AccountStatus, EmailStatus, TimestampDisplay, User};
+use crate::{message, out, tr, validation_error};
+
+/// Resultado de um comando: dados estruturados e a mensagem para humanos
+pub struct CommandOutput {
+    pub data: Value,
+    pub message: String,
+}
+
+/// Como um comando interage com quem o executa
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommandOptions {
+    /// Saída em JSON, sem prompts
+    pub json: bool,
+    /// Lê a senha da entrada padrão em vez de um prompt
+    pub password_stdin: bool,
+    /// Como a senha aparece no prompt
+    pub echo: PasswordEcho,
+}
+
+impl CommandOptions {
+    /// Lê a senha: da entrada padrão (modo JSON ou `--password-stdin`), ou de um prompt no terminal
+    fn read_password(&self, prompt: &str) -> AuthResult<Zeroizing<String>> {
+        if !self.json && !self.password_stdin {
+            return prompt::read_password(prompt, self.echo);
+        }
+
+        let mut line = Zeroizing::new(String::new());
+        io::stdin().lock().read_line(&mut line)?;
+        let password = Zeroizing::new(line.trim_end_matches(['\r', '\n']).to_string());
+
+        if password.is_empty() {
+            return validation_error!("Senha ausente: ela deve ser enviada na primeira linha da entrada padrão");
+        }
+        Ok(password)
+    }
+
+    /// Pede confirmação para uma ação destrutiva; no modo JSON, exige `--yes`
+    fn confirm(&self, question: &str, yes: bool) -> AuthResult<bool> {
+        if yes {
+            return Ok(true);
+        }
+        if self.json {
+            return validation_error!("Confirmação necessária: repita o comando com --yes");
+        }
+
+        out!("❓ {} {} ", question, tr!("(s/N):"));
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "s" | "sim" | "y" | "yes"))
+    }
+}
+
+/// Executa um comando não interativo
+pub fn run(service: &AuthService, command: Command, options: CommandOptions) -> AuthResult<CommandOutput> {
+    let store = service.store();
+
+    match command {
+        Command::Register { username, email } => {
+            let password = options.read_password(&format!("🔒 {} ", tr!("Senha:")))?;
+            service.register(&username, &password, email.as_deref())?;
+
+            Ok(CommandOutput {
+                data: json!({ "username": username }),
+                message: format!("✅ {}", tr!("Usuário '{}' registrado com sucesso!", username)),
+            })
+        }
+        Command::Login { username, totp } => {
+            let password = options.read_password(&format!("🔒 {} ", tr!("Senha:")))?;
+            let outcome = service.login_with_second_factor(&username, &password, totp.as_deref())?;
+
+            // Falhas viram erros, para que o código de saída diga o motivo
+            outcome.into_result()?;
+
+            Ok(CommandOutput {
+                data: json!({ "username": username, "authenticated": true, "outcome": outcome.name() }),
+                message: describe_login_outcome(&outcome),
+            })
+        }
+        Command::Delete { username, yes } => {
+            if !store.user_exists(&username)? {
+                return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+            }
+            if !options.confirm(&tr!("Excluir a conta '{}'?", username), yes)? {
+                return validation_error!("Operação cancelada");
+            }
+            delete_user(store, COMMAND_ACTOR, &username)?;
+
+            Ok(CommandOutput {
+                data: json!({ "username": username, "deleted": true }),
+                message: format!("✅ {}", tr!("Conta '{}' excluída. Ela pode ser restaurada até ser purgada.", username)),
+            })
+        }
+        Command::List { filter, sort, desc, offset, limit } => {
+            let order = UserOrder { sort, descending: desc };
+            let users = match filter {
+                Some(pattern) => {
+                    let matches = store.search_users(&pattern, order)?.into_iter().skip(offset);
+                    matches.take(limit.unwrap_or(usize::MAX)).collect()
+                }
+                None => store.list_users_paged(offset, limit.unwrap_or(i64::MAX as usize), order)?,
+            };
+
+            let display = config::current().display.timezone;
+
+            let message = users
+                .iter()
+                .map(|user| {
+                    format!(
+                        "🆔 #{:<3} | 👤 {:<20} | 🎖️  {:<13} | ⏯️  {:<10} | 📅 {}",
+                        user.id,
+                        user.username,
+                        user.role,
+                        user.status,
+                        display.format(user.created_at)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let data = users.iter().map(user_json).collect();
+
+            Ok(CommandOutput { data: Value::Array(data), message })
+        }
+        Command::Promote { username } => change_role(service, &username, Role::Admin),
+        Command::Demote { username } => change_role(service, &username, Role::User),
+        Command::Disable { username } => change_status(service, &username, AccountStatus::Disabled),
+        Command::Enable { username } => change_status(service, &username, AccountStatus::Active),
+        Command::Permissions { action: PermissionsAction::List } => {
+            let mut lines = Vec::new();
+            let mut data = serde_json::Map::new();
+            for role in Role::ALL {
+                let granted = store.role_permissions(role)?;
+                lines.push(format!("🎖️  {}:", role));
+                for permission in Permission::ALL {
+                    let mark = if granted.contains(&permission) { "✅" } else { "⛔" };
+                    lines.push(format!("   {} {:<16} {}", mark, permission, permission.description()));
+                }
+                data.insert(
+                    role.name().to_string(),
+                    json!(granted.iter().map(Permission::name).collect::<Vec<_>>()),
+                );
+            }
+
+            Ok(CommandOutput { data: Value::Object(data), message: lines.join("\n") })
+        }
+        Command::Permissions { action: PermissionsAction::Grant { role, permission } } => {
+            change_permission(service, &role, &permission, true)
+        }
+        Command::Permissions { action: PermissionsAction::Revoke { role, permission } } => {
+            change_permission(service, &role, &permission, false)
+        }
+        Command::Group { action } => run_group(service, action, options),
+        Command::Stats => {
+            let stats = service.database()?.get_stats(service.clock())?;
+
+            let mut lines = vec![
+                format!("👥 {}", tr!("Total de usuários: {}", stats.total_users)),
+                format!("🆕 {}", tr!("Novos nas últimas 24h: {}", stats.created_last_24h)),
+                format!("📅 {}", tr!("Novos nos últimos 7 dias: {}", stats.created_last_7d)),
+                format!("🔒 {}", tr!("Contas bloqueadas: {}", stats.locked_accounts)),
+                format!("⏸️  {}", tr!("Contas desativadas: {}", stats.disabled_accounts)),
+                format!("🔐 {}", tr!("Com dois fatores: {}", stats.two_factor_users)),
+            ];
+            if let Some(days) = stats.average_account_age_days {
+                lines.push(format!("⏳ {}", tr!("Idade média das contas: {} dias", format!("{:.1}", days))));
+            }
+            let per_date = |rows: &[(chrono::NaiveDate, usize)]| {
+                rows.iter().map(|(date, count)| json!({ "date": date.to_string(), "count": count })).collect::<Vec<_>>()
+            };
+            let data = json!({
+                "total_users": stats.total_users,
+                "latest_user": stats.latest_user,
+                "created_last_24h": stats.created_last_24h,
+                "created_last_7d": stats.created_last_7d,
+                "locked_accounts": stats.locked_accounts,
+                "disabled_accounts": stats.disabled_accounts,
+                "two_factor_users": stats.two_factor_users,
+                "average_account_age_days": stats.average_account_age_days,
+                "registrations_per_day": per_date(&stats.registrations_per_day),
+                "registrations_per_week": per_date(&stats.registrations_per_week),
+            });
+
+            Ok(CommandOutput { data, message: lines.join("\n") })
+        }
+        Command::HashHealth => {
+            let health = service.database()?.scan_hash_health()?;
+
+            let mut message = format!(
+                "🧪 {}",
+                tr!(
+                    "{} de {} contas com hash fora do esquema configurado ({})",
+                    health.legacy_users.len(),
+                    health.total_users,
+                    health.scheme
+                )
+            );
+            for username in &health.legacy_users {
+                message.push_str(&format!("\n⚠️  {}", username));
+            }
+            let data = json!({
+                "total_users": health.total_users,
+                "scheme": health.scheme.name(),
+                "legacy_count": health.legacy_users.len(),
+                "legacy_users": health.legacy_users,
+            });
+
+            Ok(CommandOutput { data, message })
+        }
+        Command::Health => Ok(health::check(service).output()),
+        Command::Fsck => Ok(fsck::check(&*service.database()?.connection()?)?.output()),
+        Command::Dump { out } => {
+            let passphrase = options.read_password(&format!("🔑 {} ", tr!("Frase secreta:")))?;
+            if !options.json && !options.password_stdin {
+                let confirm = options.read_password(&format!("🔑 {} ", tr!("Confirme a frase secreta:")))?;
+                if !secrets_equal(passphrase.as_bytes(), confirm.as_bytes()) {
+                    return validation_error!("As frases secretas não coincidem");
+                }
+            }
+            let count = dump_users(&*service.database()?.connection()?, &out, &passphrase)?;
+
+            Ok(CommandOutput {
+                data: json!({ "path": out.display().to_string(), "users": count }),
+                message: format!("📦 {}", tr!("{} usuários exportados para '{}'", count, out.display())),
+            })
+        }
+        Command::Load { path } => {
+            let passphrase = options.read_password(&format!("🔑 {} ", tr!("Frase secreta:")))?;
+            let count = load_users(&*service.database()?.connection()?, &path, &passphrase)?;
+            audit::record(store, COMMAND_ACTOR, "load_users", None)?;
+
+            Ok(CommandOutput {
+                data: json!({ "path": path.display().to_string(), "users": count }),
+                message: format!("📥 {}", tr!("{} usuários importados de '{}'", count, path.display())),
+            })
+        }
+        Command::Backup { path, gzip } => {
+            let report = backup_database(&*service.database()?.connection()?, &path, gzip)?;
+            audit::record(store, COMMAND_ACTOR, "backup", None)?;
+
+            let size = format!("{:.1}", report.bytes as f64 / 1024.0);
+            Ok(CommandOutput {
+                data: json!({
+                    "path": report.path.display().to_string(),
+                    "bytes": report.bytes,
+                    "compressed": report.compressed,
+                }),
+                message: format!("💾 {}", tr!("Cópia de segurança gravada em '{}' ({} KiB)", path.display(), size)),
+            })
+        }
+        Command::Restore { path, force, yes } => {
+            let question = tr!("Substituir todo o banco atual pelo conteúdo de '{}'?", path.display());
+            if !options.confirm(&question, yes)? {
+                return Ok(CommandOutput {
+                    data: json!({ "restored": false }),
+                    message: format!("❌ {}", tr!("Restauração cancelada.")),
+                });
+            }
+            // A conexão volta ao pool antes da auditoria (o banco em memória só tem uma)
+            let report = restore_database(&mut *service.database()?.connection()?, &path, force)?;
+            audit::record(store, COMMAND_ACTOR, "restore", None)?;
+
+            let mut message = format!(
+                "♻️  {}",
+                tr!("Banco restaurado a partir de '{}' ({} usuários)", path.display(), report.users)
+            );
+            if let Some(last) = report.migrated.last() {
+                message.push_str(&format!(
+                    "\n🗂️  {}",
+                    tr!("A cópia foi migrada da versão {} para a {} do esquema", report.schema_version, last)
+                ));
+            }
+            let data = json!({
+                "restored": true,
+                "path": path.display().to_string(),
+                "users": report.users,
+                "schema_version": report.schema_version,
+                "migrated": report.migrated,
+            });
+
+            Ok(CommandOutput { data, message })
+        }
+        Command::Import { path } => {
+            let report = import_users(service.database()?, &path)?;
+            audit::record(store, COMMAND_ACTOR, "import_users", None)?;
+
+            let mut message = format!(
+                "📥 {}",
+                tr!("{} contas criadas a partir de '{}'", report.created.len(), path.display())
+            );
+            if !report.errors.is_empty() {
+                message.push_str(&format!("\n⚠️  {}", tr!("{} linhas recusadas:", report.errors.len())));
+            }
+            for error in &report.errors {
+                let username = error.username.as_deref().unwrap_or("?");
+                message.push_str(&format!("\n   {} {} ({}): {}", tr!("linha"), error.line, username, error.message));
+            }
+            let data = json!({
+                "path": path.display().to_string(),
+                "created": report.created,
+                "errors": report
+                    .errors
+                    .iter()
+                    .map(|error| json!({ "line": error.line, "username": error.username, "message": error.message }))
+                    .collect::<Vec<_>>(),
+            });
+
+            Ok(CommandOutput { data, message })
+        }
+        Command::Migrate { status } => {
+            let conn = service.database()?.connection()?;
+            let applied = if status { Vec::new() } else { migrate(&conn)? };
+            let migrations = migrations::status(&conn)?;
+            let current = migrations::current_version(&conn)?;
+
+            let display = config::current().display.timezone;
+            let mut message = format!("🗂️  {}", tr!("Versão do esquema: {} (mais recente: {})", current, latest_version()));
+            for migration in &migrations {
+                let state = match migration.applied_at {
+                    Some(applied_at) => format!("✅ {}", tr!("aplicada em {}", display.format(applied_at))),
+                    None => format!("⏳ {}", tr!("pendente")),
+                };
+                message.push_str(&format!("\n   {:>3} | {:<28} | {}", migration.version, migration.description, state));
+            }
+            if !status {
+                message.push_str(&format!("\n🔧 {}", tr!("Migrações aplicadas agora: {}", applied.len())));
+            }
+
+            let data = json!({
+                "current_version": current,
+                "latest_version": latest_version(),
+                "applied": applied,
+                "migrations": migrations
+                    .iter()
+                    .map(|migration| {
+                        json!({
+                            "version": migration.version,
+                            "description": migration.description,
+                            "applied_at": migration.applied_at.map(|at| TimestampDisplay::Utc.format(at)),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            });
+
+            Ok(CommandOutput { data, message })
+        }
+        Command::Calibrate { target_ms } => {
+            let config = Argon2Config::calibrate(Duration::from_millis(target_ms));
+            config.save(store)?;
+            audit::record(store, COMMAND_ACTOR, "calibrate", None)?;
+
+            let message = format!(
+                "⚙️  {}",
+                tr!(
+                    "Argon2 calibrado para ~{} ms: memória {} KiB, {} iterações, paralelismo {}",
+                    target_ms,
+                    config.m_cost,
+                    config.t_cost,
+                    config.p_cost
+                )
+            );
+            let data = json!({
+                "target_ms": target_ms,
+                "m_cost": config.m_cost,
+                "t_cost": config.t_cost,
+                "p_cost": config.p_cost,
+            });
+
+            Ok(CommandOutput { data, message })
+        }
+        Command::Genpass { length, no_lowercase, no_uppercase, no_digits, no_symbols } => {
+            let options = GeneratorOptions {
+                length,
+                lowercase: !no_lowercase,
+                uppercase: !no_uppercase,
+                digits: !no_digits,
+                symbols: !no_symbols,
+            };
+            let password = generate_password(&options, &config::current().password)?;
+
+            // Só a senha, para que `siri genpass` possa ser usado em scripts
+            Ok(CommandOutput {
+                data: json!({ "password": password.as_str(), "length": length }),
+                message: password.to_string(),
+            })
+        }
+        Command::Pepper { action: PepperAction::Rotate } => {
+            let previous = rotate_pepper(&config::current().hashing)?;
+            audit::record(store, COMMAND_ACTOR, "rotate_pepper", None)?;
+
+            Ok(CommandOutput {
+                data: json!({ "previous_file": previous.display().to_string() }),
+                message: format!(
+                    "🌶️  Pepper trocado. As senhas serão refeitas com o novo pepper no próximo login de cada usuário;\n\
+                     mantenha '{}' até que todos tenham entrado.",
+                    previous.display()
+                ),
+            })
+        }
+        Command::ResetToken { username, ttl_minutes } => {
+            let ttl = chrono::Duration::minutes(i64::from(ttl_minutes));
+            let token = create_reset_token(store, service.clock(), COMMAND_ACTOR, &username, ttl)?;
+            let expires_at = service.clock().now() + ttl;
+
+            Ok(CommandOutput {
+                data: json!({
+                    "username": username,
+                    "token": token,
+                    "expires_at": TimestampDisplay::Utc.format(expires_at),
+                }),
+                message: format!(
+                    "🎟️  {}\n{}",
+                    tr!(
+                        "Token de '{}' (vale uma vez, até {}):",
+                        username,
+                        config::current().display.timezone.format(expires_at)
+                    ),
+                    token
+                ),
+            })
+        }
+        Command::ResetPassword { token } => {
+            let password = options.read_password(&format!("🔒 {} ", tr!("Nova senha:")))?;
+            let username = service.redeem_reset_token(&token, &password)?;
+
+            Ok(CommandOutput {
+                data: json!({ "username": username }),
+                message: format!("✅ {}", tr!("Senha de '{}' redefinida com sucesso!", username)),
+            })
+        }
+        Command::RequestVerification { username } => {
+            let address = service.send_verification_email(&username)?;
+
+            Ok(CommandOutput {
+                data: json!({ "username": username, "sent_to": address }),
+                message: format!("📨 {}", tr!("Token de verificação de '{}' enviado para {}", username, address)),
+            })
+        }
+        Command::RequestReset { email } => {
+            service.request_password_reset(&email)?;
+
+            Ok(CommandOutput {
+                data: json!({ "email": email }),
+                message: format!(
+                    "📨 {}",
+                    tr!("Se o email estiver verificado em uma conta, um token de redefinição foi enviado para ele.")
+                ),
+            })
+        }
+        Command::VerifyEmail { token } => {
+            let username = service.verify_email(&token)?;
+
+            Ok(CommandOutput {
+                data: json!({ "username": username, "email_verified": true }),
+                message: format!("✅ {}", tr!("Email de '{}' verificado!", username)),
+            })
+        }
+        Command::Audit { actor, action, target, limit } => {
+            let filter = AuditFilter { actor, action, target, limit };
+            let entries = audit::search(store, &filter)?;
+
+            let display = config::current().display.timezone;
+            let message = if entries.is_empty() {
+                format!("📭 {}", tr!("Nenhuma entrada encontrada."))
+            } else {
+                entries
+                    .iter()
+                    .map(|entry| {
+                        format!(
+                            "📅 {} | 👤 {:<16} | ⚙️  {:<28} | 🎯 {}",
+                            display.format(entry.created_at),
+                            entry.actor,
+                            entry.action,
+                            entry.target.as_deref().unwrap_or("-")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let data = entries
+                .iter()
+                .map(|entry| {
+                    json!({
+                        "id": entry.id,
+                        "actor": entry.actor,
+                        "action": entry.action,
+                        "target": entry.target,
+                        "created_at": TimestampDisplay::Utc.format(entry.created_at),
+                    })
+                })
+                .collect();
+
+            Ok(CommandOutput { data, message })
+        }
+        Command::ExportUser { username, out } => {
+            let document = export_user_data(store, &username, service.clock().now())?;
+            let Some(path) = out else {
+                let message = serde_json::to_string_pretty(&document).map_err(io::Error::other)?;
+                return Ok(CommandOutput { data: document, message });
+            };
+
+            let existed = path.exists();
+            fs::write(&path, serde_json::to_vec_pretty(&document).map_err(io::Error::other)?)?;
+            secure_file_permissions(&path, existed)?;
+
+            Ok(CommandOutput {
+                data: json!({ "username": username, "path": path.display().to_string() }),
+                message: format!("📦 {}", tr!("Dados de '{}' exportados para '{}'", username, path.display())),
+            })
+        }
+        // O servidor e o daemon precisam ser donos do serviço; o binário os inicia diretamente
+        Command::Export { format, out, profile, include_hashes, admin, totp } => {
+            // Hashes permitem ataques offline às senhas: só com um administrador autenticado
+            let actor = match admin {
+                Some(admin) if include_hashes => {
+                    authenticate_admin(service, options, &admin, totp.as_deref())?;
+                    admin
+                }
+                _ => COMMAND_ACTOR.to_string(),
+            };
+            let fields = UsersExport { profile, hashes: include_hashes };
+            let count = export_users(store, &out, format, fields)?;
+            let action = if include_hashes { "export_users_with_hashes" } else { "export_users" };
+            audit::record(store, &actor, action, None)?;
+
+            Ok(CommandOutput {
+                data: json!({
+                    "path": out.display().to_string(),
+                    "users": count,
+                    "profile": profile,
+                    "hashes": include_hashes,
+                }),
+                message: format!("📦 {}", tr!("{} usuários exportados para '{}'", count, out.display())),
+            })
+        }
+        Command::Serve { .. } => validation_error!("O servidor HTTP não pode ser iniciado por commands::run"),
+        #[cfg(unix)]
+        Command::Daemon { .. } => validation_error!("O daemon não pode ser iniciado por commands::run"),
+        #[cfg(unix)]
+        Command::Checkpassword { .. } => {
+            validation_error!("O checkpassword não pode ser executado por commands::run")
+        }
+    }
+}
+
+/// Resumo de um usuário em JSON, como em `list` e na API HTTP.
+///
+/// No JSON as datas vão sempre em UTC, independentemente do fuso de exibição.
+pub fn user_json(user: &User) -> Value {
+    json!({
+        "id": user.id,
+        "username": user.username,
+        "email": user.email,
+        "email_verified": user.email_status() == EmailStatus::Verified,
+        "created_at": TimestampDisplay::Utc.format(user.created_at),
+        "last_login_at": user.last_login_at.map(|at| TimestampDisplay::Utc.format(at)),
+        "login_count": user.login_count,
+        "role": user.role.name(),
+        "status": user.status.name(),
+    })
+}
+
+/// Confere a senha (e o segundo fator) de um administrador antes de uma ação sensível
+fn authenticate_admin(service: &AuthService, options: CommandOptions, admin: &str, totp: Option<&str>) -> AuthResult<()> {
+    let password = options.read_password(&format!("🔒 {} ", tr!("Senha de '{}':", admin)))?;
+    service.login_with_second_factor(admin, &password, totp)?.into_result()?;
+
+    if !is_admin(service.store(), admin)? {
+        return Err(AuthError::PermissionDenied(message!("'{}' não é administrador", admin)));
+    }
+    Ok(())
+}
+
+/// Promove ou rebaixa um usuário
+fn change_role(service: &AuthService, username: &str, role: Role) -> AuthResult<CommandOutput> {
+    set_role(service.store(), COMMAND_ACTOR, username, role)?;
+
+    Ok(CommandOutput {
+        data: json!({ "username": username, "role": role.name() }),
+        message: format!("🎖️  {}", tr!("'{}' agora é {}.", username, role)),
+    })
+}
+
+/// Ativa ou desativa uma conta
+fn change_status(service: &AuthService, username: &str, status: AccountStatus) -> AuthResult<CommandOutput> {
+    set_status(service.store(), COMMAND_ACTOR, username, status)?;
+
+    let message = match status {
+        AccountStatus::Active => format!("▶️  {}", tr!("Conta '{}' reativada.", username)),
+        AccountStatus::Disabled => format!("⏸️  {}", tr!("Conta '{}' desativada. Os dados foram mantidos.", username)),
+    };
+    Ok(CommandOutput {
+        data: json!({ "username": username, "status": status.name() }),
+        message,
+    })
+}
+
+/// Concede ou revoga uma permissão de um papel, pelos nomes
+fn change_permission(
+    service: &AuthService,
+    role_name: &str,
+    permission_name: &str,
+    granted: bool,
+) -> AuthResult<CommandOutput> {
+    let Some(role) = Role::from_name(role_name) else {
+        let names: Vec<&str> = Role::ALL.iter().map(Role::name).collect();
+        return validation_error!("Papel desconhecido: '{}' (use {})", role_name, names.join(", "));
+    };
+    let Some(permission) = Permission::from_name(permission_name) else {
+        let names: Vec<&str> = Permission::ALL.iter().map(Permission::name).collect();
+        return validation_error!("Permissão desconhecida: '{}' (use {})", permission_name, names.join(", "));
+    };
+
+    set_permission(service.store(), COMMAND_ACTOR, role, permission, granted)?;
+    let message = if granted {
+        tr!("Permissão '{}' concedida a {}.", permission, role)
+    } else {
+        tr!("Permissão '{}' revogada de {}.", permission, role)
+    };
+    Ok(CommandOutput {
+        data: json!({ "role": role.name(), "permission": permission.name(), "granted": granted }),
+        message: format!("🔐 {}", message),
+    })
+}
+
+/// Executa uma operação sobre grupos
+fn run_group(service: &AuthService, action: GroupAction, options: CommandOptions) -> AuthResult<CommandOutput> {
+    let store = service.store();
+
+    match action {
+        GroupAction::Create { name } => {
+            groups::create_group(store, COMMAND_ACTOR, &name)?;
+            Ok(CommandOutput {
+                data: json!({ "group": name.trim() }),
+                message: format!("🏷️  {}", tr!("Grupo '{}' criado.", name.trim())),
+            })
+        }
+        GroupAction::Delete { name, yes } => {
+            if !store.group_exists(&name)? {
+                return Err(AuthError::NotFound(message!("Grupo '{}'", name)));
+            }
+            if !options.confirm(&tr!("Apagar o grupo '{}'?", name), yes)? {
+                return validation_error!("Operação cancelada");
+            }
+            groups::delete_group(store, COMMAND_ACTOR, &name)?;
+            Ok(CommandOutput {
+                data: json!({ "group": name, "deleted": true }),
+                message: format!("🗑️  {}", tr!("Grupo '{}' apagado.", name)),
+            })
+        }
+        GroupAction::Add { group, username } => {
+            groups::add_member(store, COMMAND_ACTOR, &group, &username)?;
+            Ok(CommandOutput {
+                data: json!({ "group": group, "username": username }),
+                message: format!("✅ {}", tr!("'{}' adicionado ao grupo '{}'.", username, group)),
+            })
+        }
+        GroupAction::Remove { group, username } => {
+            groups::remove_member(store, COMMAND_ACTOR, &group, &username)?;
+            Ok(CommandOutput {
+                data: json!({ "group": group, "username": username }),
+                message: format!("✅ {}", tr!("'{}' removido do grupo '{}'.", username, group)),
+            })
+        }
+        GroupAction::List { user: Some(username) } => {
+            let names = groups::user_groups(store, &username)?;
+            let message = if names.is_empty() {
+                format!("📭 {}", tr!("'{}' não pertence a nenhum grupo.", username))
+            } else {
+                format!("🏷️  {}", tr!("Grupos de '{}': {}", username, names.join(", ")))
+            };
+            Ok(CommandOutput { data: json!({ "username": username, "groups": names }), message })
+        }
+        GroupAction::List { user: None } => {
+            let list = groups::list_groups(store)?;
+            let display = config::current().display.timezone;
+            let message = if list.is_empty() {
+                format!("📭 {}", tr!("Nenhum grupo cadastrado."))
+            } else {
+                list.iter()
+                    .map(|group| {
+                        format!(
+                            "🏷️  {:<24} | 👥 {:<4} | 📅 {}",
+                            group.name,
+                            group.members,
+                            display.format(group.created_at)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let data = list
+                .iter()
+                .map(|group| {
+                    json!({
+                        "name": group.name,
+                        "members": group.members,
+                        "created_at": TimestampDisplay::Utc.format(group.created_at),
+                    })
+                })
+                .collect();
+            Ok(CommandOutput { data: Value::Array(data), message })
+        }
+        GroupAction::Members { group } => {
+            let members = groups::group_members(store, &group)?;
+            let message = if members.is_empty() {
+                format!("📭 {}", tr!("O grupo '{}' não tem membros.", group))
+            } else {
+                format!("👥 {}", tr!("Membros de '{}': {}", group, members.join(", ")))
+            };
+            Ok(CommandOutput { data: json!({ "group": group, "members": members }), message })
+        }
+    }
+}
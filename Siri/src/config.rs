@@ -0,0 +1,475 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use chrono::Duration;
+use serde::de::{value, DeserializeOwned, IntoDeserializer};
+use serde::{Deserialize, Deserializer};
+use crate::auth::{Argon2Config, LockoutPolicy, PasswordConfig};
+use crate::backup::BackupConfig;
+use crate::breach::BreachConfig;
+use crate::db::DB_FILE;
+use crate::error::{AuthError, AuthResult};
+use crate::events::WebhookConfig;
+use crate::hasher::{HashScheme, HashingConfig};
+use crate::i18n::Locale;
+use crate::jwt::JwtConfig;
+use crate::logging::LogConfig;
+use crate::mailer::SmtpConfig;
+use crate::pool::{JournalMode, Synchronous};
+use crate::user::DisplayConfig;
+use crate::{tr, validation, validation_error};
+
+/// Nome do arquivo de configuração
+pub const CONFIG_FILE: &str = "siri.toml";
+
+/// Variáveis de ambiente que sobrepõem o arquivo de configuração, uma por opção
+const LOCALE_ENV: &str = "SIRI_LOCALE";
+const DB_PATH_ENV: &str = "SIRI_DB_PATH";
+const DB_POOL_SIZE_ENV: &str = "SIRI_DB_POOL_SIZE";
+const DB_JOURNAL_MODE_ENV: &str = "SIRI_DB_JOURNAL_MODE";
+const DB_SYNCHRONOUS_ENV: &str = "SIRI_DB_SYNCHRONOUS";
+const DB_BUSY_TIMEOUT_ENV: &str = "SIRI_DB_BUSY_TIMEOUT_MS";
+const MIN_PASSWORD_LEN_ENV: &str = "SIRI_MIN_PASSWORD_LEN";
+const REQUIRE_DIGIT_ENV: &str = "SIRI_REQUIRE_DIGIT";
+const REQUIRE_UPPERCASE_ENV: &str = "SIRI_REQUIRE_UPPERCASE";
+const REQUIRE_LOWERCASE_ENV: &str = "SIRI_REQUIRE_LOWERCASE";
+const REQUIRE_SPECIAL_ENV: &str = "SIRI_REQUIRE_SPECIAL";
+const MIN_STRENGTH_ENV: &str = "SIRI_MIN_STRENGTH";
+const PASSWORD_MAX_AGE_ENV: &str = "SIRI_PASSWORD_MAX_AGE_DAYS";
+const MAX_USERS_ENV: &str = "SIRI_MAX_USERS";
+const USERNAME_GRACE_ENV: &str = "SIRI_USERNAME_GRACE_DAYS";
+const ARGON2_MEMORY_ENV: &str = "SIRI_ARGON2_MEMORY";
+const ARGON2_ITERATIONS_ENV: &str = "SIRI_ARGON2_ITERATIONS";
+const ARGON2_PARALLELISM_ENV: &str = "SIRI_ARGON2_PARALLELISM";
+const HASH_SCHEME_ENV: &str = "SIRI_HASH_SCHEME";
+const LEGACY_HASHES_ENV: &str = "SIRI_LEGACY_HASHES";
+const PEPPER_ENV: &str = "SIRI_PEPPER";
+const PEPPER_FILE_ENV: &str = "SIRI_PEPPER_FILE";
+const PREVIOUS_PEPPER_ENV: &str = "SIRI_PEPPER_PREVIOUS";
+const LOCKOUT_ATTEMPTS_ENV: &str = "SIRI_LOCKOUT_ATTEMPTS";
+const LOCKOUT_MINUTES_ENV: &str = "SIRI_LOCKOUT_MINUTES";
+const SESSION_TIMEOUT_ENV: &str = "SIRI_SESSION_TIMEOUT";
+const SESSION_MAX_AGE_ENV: &str = "SIRI_SESSION_MAX_AGE";
+const LOG_FILE_ENV: &str = "SIRI_LOG_FILE";
+const LOG_LEVEL_ENV: &str = "SIRI_LOG_LEVEL";
+const LOG_MAX_SIZE_ENV: &str = "SIRI_LOG_MAX_SIZE_MB";
+const LOG_DAILY_ENV: &str = "SIRI_LOG_DAILY";
+const LOG_KEEP_ENV: &str = "SIRI_LOG_KEEP";
+const JWT_ALGORITHM_ENV: &str = "SIRI_JWT_ALGORITHM";
+const JWT_ISSUER_ENV: &str = "SIRI_JWT_ISSUER";
+const JWT_TTL_ENV: &str = "SIRI_JWT_TTL_MINUTES";
+const JWT_SECRET_ENV: &str = "SIRI_JWT_SECRET";
+const JWT_SECRET_FILE_ENV: &str = "SIRI_JWT_SECRET_FILE";
+const JWT_PRIVATE_KEY_ENV: &str = "SIRI_JWT_PRIVATE_KEY_FILE";
+const JWT_PUBLIC_KEY_ENV: &str = "SIRI_JWT_PUBLIC_KEY_FILE";
+const WEBHOOK_URLS_ENV: &str = "SIRI_WEBHOOK_URLS";
+const WEBHOOK_SECRET_ENV: &str = "SIRI_WEBHOOK_SECRET";
+const WEBHOOK_SECRET_FILE_ENV: &str = "SIRI_WEBHOOK_SECRET_FILE";
+const WEBHOOK_FAILURE_STREAK_ENV: &str = "SIRI_WEBHOOK_FAILURE_STREAK";
+const BACKUP_DIR_ENV: &str = "SIRI_BACKUP_DIR";
+const BACKUP_INTERVAL_ENV: &str = "SIRI_BACKUP_INTERVAL_MINUTES";
+const BACKUP_KEEP_ENV: &str = "SIRI_BACKUP_KEEP";
+const BACKUP_GZIP_ENV: &str = "SIRI_BACKUP_GZIP";
+const BREACH_CHECK_ENV: &str = "SIRI_BREACH_CHECK";
+const BREACH_FILE_ENV: &str = "SIRI_BREACH_FILE";
+const BREACH_FAIL_OPEN_ENV: &str = "SIRI_BREACH_FAIL_OPEN";
+const SMTP_HOST_ENV: &str = "SIRI_SMTP_HOST";
+const SMTP_PORT_ENV: &str = "SIRI_SMTP_PORT";
+const SMTP_USER_ENV: &str = "SIRI_SMTP_USER";
+const SMTP_PASSWORD_ENV: &str = "SIRI_SMTP_PASSWORD";
+const SMTP_FROM_ENV: &str = "SIRI_SMTP_FROM";
+const SMTP_TLS_ENV: &str = "SIRI_SMTP_TLS";
+const TIMEZONE_ENV: &str = "SIRI_TIMEZONE";
+
+/// Conexões padrão do pool do banco
+pub const DEFAULT_POOL_SIZE: u32 = 8;
+
+/// Espera padrão por um banco ocupado por outra conexão ou processo
+pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+
+/// Tempo padrão de inatividade antes de exigir novo login
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::minutes(5);
+
+/// Duração máxima padrão de uma sessão, mesmo com atividade contínua
+pub const DEFAULT_MAX_LIFETIME: Duration = Duration::hours(24);
+
+/// Seção `[session]` do arquivo de configuração: política de expiração das sessões
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionPolicy {
+    /// Tempo sem atividade após o qual a sessão expira (`timeout_seconds` no arquivo)
+    #[serde(rename = "timeout_seconds", deserialize_with = "seconds")]
+    pub idle_timeout: Duration,
+    /// Tempo máximo desde o login, independentemente da atividade (`max_age_seconds` no arquivo)
+    #[serde(rename = "max_age_seconds", deserialize_with = "seconds")]
+    pub max_lifetime: Duration,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        SessionPolicy {
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            max_lifetime: DEFAULT_MAX_LIFETIME,
+        }
+    }
+}
+
+/// Onde fica o banco SQLite
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub path: PathBuf,
+    /// Máximo de conexões abertas ao mesmo tempo (relevante em `siri serve` e `siri daemon`)
+    pub pool_size: u32,
+    pub journal_mode: JournalMode,
+    pub synchronous: Synchronous,
+    /// Quanto uma conexão espera por um banco ocupado antes de desistir, em milissegundos
+    pub busy_timeout_ms: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            path: default_db_path(),
+            pool_size: DEFAULT_POOL_SIZE,
+            journal_mode: JournalMode::default(),
+            synchronous: Synchronous::default(),
+            busy_timeout_ms: DEFAULT_BUSY_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Configuração lida de `siri.toml`; o que o arquivo omite fica com o padrão
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Idioma das mensagens (None = o de `LANG`, ou português)
+    pub locale: Option<Locale>,
+    pub database: DatabaseConfig,
+    pub password: PasswordConfig,
+    pub argon2: Argon2Config,
+    pub hashing: HashingConfig,
+    pub lockout: LockoutPolicy,
+    pub session: SessionPolicy,
+    pub log: LogConfig,
+    pub jwt: JwtConfig,
+    pub webhooks: WebhookConfig,
+    pub backup: BackupConfig,
+    pub breach: BreachConfig,
+    pub smtp: SmtpConfig,
+    pub display: DisplayConfig,
+}
+
+impl Config {
+    /// Lê e valida um arquivo de configuração
+    pub fn from_file(path: &Path) -> AuthResult<Config> {
+        let text = fs::read_to_string(path)?;
+        let config: Config = toml::from_str(&text).map_err(|err| {
+            let line = err.span().map(|span| text[..span.start].matches('\n').count() + 1);
+            let place = match line {
+                Some(line) => tr!("{}, linha {}", path.display(), line),
+                None => path.display().to_string(),
+            };
+            validation!("{}: {}", place, err.message())
+        })?;
+        config.validate().map_err(|err| match err {
+            AuthError::Validation(msg) => validation!("{}: {}", path.display(), msg),
+            err => err,
+        })?;
+        Ok(config)
+    }
+
+    /// Aplica as variáveis de ambiente `SIRI_*` definidas sobre os valores atuais.
+    ///
+    /// Variáveis vazias são ignoradas; um valor que não pode ser convertido é um erro.
+    pub fn apply_env(&mut self) -> AuthResult<()> {
+        override_from_env(&mut self.locale, LOCALE_ENV, |value| Locale::from_code(value).map(Some))?;
+        override_from_env(&mut self.database.path, DB_PATH_ENV, |value| Some(PathBuf::from(value)))?;
+        override_from_env(&mut self.database.pool_size, DB_POOL_SIZE_ENV, parse_number)?;
+        override_from_env(&mut self.database.journal_mode, DB_JOURNAL_MODE_ENV, parse_name)?;
+        override_from_env(&mut self.database.synchronous, DB_SYNCHRONOUS_ENV, parse_name)?;
+        override_from_env(&mut self.database.busy_timeout_ms, DB_BUSY_TIMEOUT_ENV, parse_number)?;
+
+        let password = &mut self.password;
+        override_from_env(&mut password.min_length, MIN_PASSWORD_LEN_ENV, parse_number)?;
+        override_from_env(&mut password.require_digit, REQUIRE_DIGIT_ENV, parse_bool)?;
+        override_from_env(&mut password.require_uppercase, REQUIRE_UPPERCASE_ENV, parse_bool)?;
+        override_from_env(&mut password.require_lowercase, REQUIRE_LOWERCASE_ENV, parse_bool)?;
+        override_from_env(&mut password.require_special, REQUIRE_SPECIAL_ENV, parse_bool)?;
+        override_from_env(&mut password.min_strength, MIN_STRENGTH_ENV, parse_name)?;
+        override_from_env(&mut password.max_age_days, PASSWORD_MAX_AGE_ENV, parse_number)?;
+        override_from_env(&mut password.max_users, MAX_USERS_ENV, |value| match value {
+            "none" => Some(None),
+            value => value.parse().ok().map(Some),
+        })?;
+        override_from_env(&mut password.deleted_username_grace_days, USERNAME_GRACE_ENV, parse_number)?;
+
+        override_from_env(&mut self.argon2.m_cost, ARGON2_MEMORY_ENV, parse_number)?;
+        override_from_env(&mut self.argon2.t_cost, ARGON2_ITERATIONS_ENV, parse_number)?;
+        override_from_env(&mut self.argon2.p_cost, ARGON2_PARALLELISM_ENV, parse_number)?;
+
+        let hashing = &mut self.hashing;
+        override_from_env(&mut hashing.scheme, HASH_SCHEME_ENV, HashScheme::from_name)?;
+        override_from_env(&mut hashing.legacy_hashes, LEGACY_HASHES_ENV, |value| parse_name(&value.to_lowercase()))?;
+        override_from_env(&mut hashing.pepper, PEPPER_ENV, |value| Some(Some(value.to_string())))?;
+        override_from_env(&mut hashing.pepper_file, PEPPER_FILE_ENV, |value| Some(Some(PathBuf::from(value))))?;
+        override_from_env(&mut hashing.previous_pepper, PREVIOUS_PEPPER_ENV, |value| Some(Some(value.to_string())))?;
+
+        override_from_env(&mut self.lockout.max_failed_attempts, LOCKOUT_ATTEMPTS_ENV, parse_number)?;
+        override_from_env(&mut self.lockout.lock_duration, LOCKOUT_MINUTES_ENV, |value| {
+            value.parse::<u32>().ok().map(|minutes| Duration::minutes(minutes.into()))
+        })?;
+
+        override_from_env(&mut self.session.idle_timeout, SESSION_TIMEOUT_ENV, |value| {
+            value.parse::<u32>().ok().map(|secs| Duration::seconds(secs.into()))
+        })?;
+        override_from_env(&mut self.session.max_lifetime, SESSION_MAX_AGE_ENV, |value| {
+            value.parse::<u32>().ok().map(|secs| Duration::seconds(secs.into()))
+        })?;
+
+        let log = &mut self.log;
+        override_from_env(&mut log.file, LOG_FILE_ENV, |value| Some(Some(PathBuf::from(value))))?;
+        override_from_env(&mut log.level, LOG_LEVEL_ENV, parse_name)?;
+        override_from_env(&mut log.max_size_mb, LOG_MAX_SIZE_ENV, parse_number)?;
+        override_from_env(&mut log.daily, LOG_DAILY_ENV, parse_bool)?;
+        override_from_env(&mut log.keep, LOG_KEEP_ENV, parse_number)?;
+
+        let jwt = &mut self.jwt;
+        override_from_env(&mut jwt.algorithm, JWT_ALGORITHM_ENV, parse_name)?;
+        override_from_env(&mut jwt.issuer, JWT_ISSUER_ENV, |value| Some(value.to_string()))?;
+        override_from_env(&mut jwt.ttl, JWT_TTL_ENV, |value| {
+            value.parse::<u32>().ok().map(|minutes| Duration::minutes(minutes.into()))
+        })?;
+        override_from_env(&mut jwt.secret, JWT_SECRET_ENV, |value| Some(Some(value.to_string())))?;
+        override_from_env(&mut jwt.secret_file, JWT_SECRET_FILE_ENV, |value| Some(Some(PathBuf::from(value))))?;
+        override_from_env(&mut jwt.private_key_file, JWT_PRIVATE_KEY_ENV, |value| Some(Some(PathBuf::from(value))))?;
+        override_from_env(&mut jwt.public_key_file, JWT_PUBLIC_KEY_ENV, |value| Some(Some(PathBuf::from(value))))?;
+
+        let webhooks = &mut self.webhooks;
+        // Vários endereços vão separados por vírgula
+        override_from_env(&mut webhooks.urls, WEBHOOK_URLS_ENV, |value| {
+            Some(value.split(',').map(str::trim).filter(|url| !url.is_empty()).map(String::from).collect())
+        })?;
+        override_from_env(&mut webhooks.secret, WEBHOOK_SECRET_ENV, |value| Some(Some(value.to_string())))?;
+        override_from_env(&mut webhooks.secret_file, WEBHOOK_SECRET_FILE_ENV, |value| {
+            Some(Some(PathBuf::from(value)))
+        })?;
+        override_from_env(&mut webhooks.failure_streak, WEBHOOK_FAILURE_STREAK_ENV, parse_number)?;
+
+        let backup = &mut self.backup;
+        override_from_env(&mut backup.directory, BACKUP_DIR_ENV, |value| Some(Some(PathBuf::from(value))))?;
+        override_from_env(&mut backup.interval_minutes, BACKUP_INTERVAL_ENV, parse_number)?;
+        override_from_env(&mut backup.keep, BACKUP_KEEP_ENV, parse_number)?;
+        override_from_env(&mut backup.gzip, BACKUP_GZIP_ENV, parse_bool)?;
+
+        override_from_env(&mut self.breach.check, BREACH_CHECK_ENV, |value| parse_name(&value.to_lowercase()))?;
+        override_from_env(&mut self.breach.file, BREACH_FILE_ENV, |value| Some(Some(PathBuf::from(value))))?;
+        override_from_env(&mut self.breach.fail_open, BREACH_FAIL_OPEN_ENV, parse_bool)?;
+
+        let smtp = &mut self.smtp;
+        override_from_env(&mut smtp.host, SMTP_HOST_ENV, |value| Some(Some(value.to_string())))?;
+        override_from_env(&mut smtp.port, SMTP_PORT_ENV, |value| parse_number(value).map(Some))?;
+        override_from_env(&mut smtp.user, SMTP_USER_ENV, |value| Some(Some(value.to_string())))?;
+        override_from_env(&mut smtp.password, SMTP_PASSWORD_ENV, |value| Some(Some(value.to_string())))?;
+        override_from_env(&mut smtp.from, SMTP_FROM_ENV, |value| Some(value.to_string()))?;
+        // "none" desliga o TLS, como nas versões anteriores
+        override_from_env(&mut smtp.tls, SMTP_TLS_ENV, |value| match value.to_lowercase().as_str() {
+            "none" => Some(false),
+            value => parse_bool(value),
+        })?;
+
+        override_from_env(&mut self.display.timezone, TIMEZONE_ENV, |value| parse_name(&value.to_lowercase()))?;
+        Ok(())
+    }
+
+    fn validate(&self) -> AuthResult<()> {
+        if self.database.path.as_os_str().is_empty() {
+            return validation_error!("O caminho do banco não pode ser vazio");
+        }
+        if self.database.pool_size == 0 {
+            return validation_error!("O pool de conexões precisa de ao menos uma conexão");
+        }
+        if self.log.file.as_ref().is_some_and(|path| path.as_os_str().is_empty()) {
+            return validation_error!("O caminho do log não pode ser vazio");
+        }
+        if self.password.min_length == 0 {
+            return validation_error!("O tamanho mínimo da senha deve ser pelo menos 1");
+        }
+        if self.lockout.max_failed_attempts > 0 && self.lockout.lock_duration <= Duration::zero() {
+            return validation_error!("O bloqueio deve durar pelo menos 1 minuto");
+        }
+        if self.session.idle_timeout <= Duration::zero() || self.session.max_lifetime <= Duration::zero() {
+            return validation_error!("As sessões devem durar pelo menos 1 segundo");
+        }
+        if self.jwt.ttl <= Duration::zero() {
+            return validation_error!("Os tokens JWT devem valer pelo menos 1 minuto");
+        }
+        if self.jwt.issuer.trim().is_empty() {
+            return validation_error!("O emissor dos tokens JWT não pode ser vazio");
+        }
+        self.hashing.validate()?;
+        self.webhooks.validate()?;
+        self.backup.validate()?;
+        self.breach.validate()?;
+        self.smtp.validate()?;
+        self.argon2.params().map_err(|err| match err {
+            AuthError::PasswordHashing(msg) => AuthError::Validation(msg),
+            err => err,
+        })?;
+        Ok(())
+    }
+}
+
+/// Substitui `target` pelo valor da variável de ambiente, se ela estiver definida e não vazia
+fn override_from_env<T>(target: &mut T, name: &str, parse: impl Fn(&str) -> Option<T>) -> AuthResult<()> {
+    let Ok(value) = env::var(name) else {
+        return Ok(());
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(());
+    }
+    match parse(value) {
+        Some(parsed) => {
+            *target = parsed;
+            Ok(())
+        }
+        None => validation_error!("Valor inválido em {}: '{}'", name, value),
+    }
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+}
+
+/// Booleano de uma variável de ambiente: true/false, 1/0, yes/no ou on/off
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Valor pelo mesmo nome usado no arquivo, como a força mínima (weak, fair...) ou o nível do log
+fn parse_name<T: DeserializeOwned>(value: &str) -> Option<T> {
+    let deserializer: value::StrDeserializer<'_, value::Error> = value.into_deserializer();
+    T::deserialize(deserializer).ok()
+}
+
+/// Diretório base do XDG: o da variável, se for absoluto, ou `~/<fallback>`
+fn xdg_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+}
+
+/// Onde `siri.toml` é procurado, em ordem: o diretório atual e `<XDG_CONFIG_HOME>/siri`
+pub fn config_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(CONFIG_FILE)];
+    if let Some(dir) = xdg_dir("XDG_CONFIG_HOME", ".config") {
+        paths.push(dir.join("siri").join(CONFIG_FILE));
+    }
+    paths
+}
+
+/// Caminho padrão do banco: `<XDG_DATA_HOME>/siri/users.db` (`~/.local/share` se a variável
+/// não estiver definida), ou `users.db` no diretório atual se nem `HOME` existir
+pub fn default_db_path() -> PathBuf {
+    match xdg_dir("XDG_DATA_HOME", ".local/share") {
+        Some(dir) => dir.join("siri").join(DB_FILE),
+        None => PathBuf::from(DB_FILE),
+    }
+}
+
+/// Configuração do processo, preenchida por `load`
+static CURRENT: OnceLock<Config> = OnceLock::new();
+
+/// Carrega o primeiro `siri.toml` encontrado, aplica as variáveis de ambiente por
+/// cima e torna o resultado a configuração do processo.
+///
+/// Sem nenhum arquivo, valem os padrões. Um arquivo ou variável inválida é um erro,
+/// em vez de ser ignorado em silêncio. Chamadas seguintes devolvem a mesma configuração.
+pub fn load() -> AuthResult<&'static Config> {
+    if let Some(config) = CURRENT.get() {
+        return Ok(config);
+    }
+    let mut config = match config_paths().into_iter().find(|path| path.is_file()) {
+        Some(path) => Config::from_file(&path)?,
+        None => Config::default(),
+    };
+    config.apply_env()?;
+    config.validate()?;
+    Ok(CURRENT.get_or_init(|| config))
+}
+
+/// Configuração em vigor: a carregada por `load` ou, se ele ainda não foi chamado, os padrões
+pub fn current() -> &'static Config {
+    // Os padrões ficam à parte, para que um uso antes de `load` não impeça a leitura do arquivo
+    static DEFAULTS: OnceLock<Config> = OnceLock::new();
+    CURRENT.get().unwrap_or_else(|| DEFAULTS.get_or_init(Config::default))
+}
+
+/// Lê um número de minutos como `Duration` (usado em `lock_minutes`)
+pub(crate) fn minutes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u32::deserialize(deserializer).map(|minutes| Duration::minutes(minutes.into()))
+}
+
+/// Lê um número de segundos como `Duration` (usado na seção `[session]`)
+fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u32::deserialize(deserializer).map(|secs| Duration::seconds(secs.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::breach::BreachService;
+    use crate::user::TimestampDisplay;
+
+    #[test]
+    fn session_section_is_read_in_seconds() {
+        let config: Config = toml::from_str("[session]\ntimeout_seconds = 600\nmax_age_seconds = 3600\n").unwrap();
+        assert_eq!(
+            config.session,
+            SessionPolicy { idle_timeout: Duration::minutes(10), max_lifetime: Duration::hours(1) }
+        );
+        assert_eq!(Config::default().session, SessionPolicy::default());
+
+        let zero: Config = toml::from_str("[session]\ntimeout_seconds = 0\n").unwrap();
+        assert!(matches!(zero.validate(), Err(AuthError::Validation(_))));
+    }
+
+    #[test]
+    fn breach_and_smtp_sections_are_read_and_validated() {
+        let config: Config = toml::from_str(
+            "[breach]\ncheck = \"hibp\"\n\n[smtp]\nhost = \"mail.example.com\"\nport = 2525\ntls = false\n",
+        )
+        .unwrap();
+        assert_eq!(config.breach.check, BreachService::Hibp);
+        assert_eq!(config.smtp.host.as_deref(), Some("mail.example.com"));
+        assert_eq!(config.smtp.port, Some(2525));
+        assert!(!config.smtp.tls);
+        config.validate().unwrap();
+
+        assert!(toml::from_str::<Config>("[breach]\ncheck = \"sim\"\n").is_err());
+        assert!(toml::from_str::<Config>("[smtp]\nport = 70000\n").is_err());
+        let sender: Config = toml::from_str("[smtp]\nfrom = \"não é um endereço\"\n").unwrap();
+        assert!(matches!(sender.validate(), Err(AuthError::Validation(_))));
+        let half: Config = toml::from_str("[smtp]\nuser = \"siri\"\n").unwrap();
+        assert!(matches!(half.validate(), Err(AuthError::Validation(_))));
+    }
+
+    #[test]
+    fn display_timezone_rejects_unknown_names() {
+        let config: Config = toml::from_str("[display]\ntimezone = \"utc\"\n").unwrap();
+        assert_eq!(config.display.timezone, TimestampDisplay::Utc);
+        assert_eq!(Config::default().display.timezone, TimestampDisplay::Local);
+
+        assert!(toml::from_str::<Config>("[display]\ntimezone = \"America/Sao_Paulo\"\n").is_err());
+        assert_eq!(parse_name::<TimestampDisplay>("local"), Some(TimestampDisplay::Local));
+        assert_eq!(parse_name::<TimestampDisplay>("gmt"), None);
+    }
+}
@@ -0,0 +1,32 @@
+use subtle::ConstantTimeEq;
+
+/// Compara dois segredos em tempo constante em relação ao conteúdo.
+///
+/// Tamanhos diferentes retornam `false` imediatamente; o tamanho não é tratado como segredo.
+pub fn secrets_equal(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_secrets_match() {
+        assert!(secrets_equal(b"", b""));
+        assert!(secrets_equal(b"a3f1c09e", b"a3f1c09e"));
+    }
+
+    #[test]
+    fn secrets_of_the_same_length_differ_anywhere() {
+        assert!(!secrets_equal(b"a3f1c09e", b"b3f1c09e"));
+        assert!(!secrets_equal(b"a3f1c09e", b"a3f1c09f"));
+    }
+
+    #[test]
+    fn secrets_of_different_lengths_never_match() {
+        assert!(!secrets_equal(b"a3f1c09e", b"a3f1c09"));
+        assert!(!secrets_equal(b"a3f1c09e", b"a3f1c09e0"));
+        assert!(!secrets_equal(b"", b"a"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Daemon em um socket Unix (`siri daemon`), para que serviços locais (ex.: um
+//! servidor de email) confiram credenciais sem abrir o SQLite por conta própria.
+//!
+//! O protocolo é uma linha JSON por pedido e uma por resposta, no mesmo envelope
+//! do `--json`:
+//!
+//! ```text
+//! → {"op":"check","username":"alice","password":"...","totp":"123456"}
+//! ← {"status":"ok","data":{"username":"alice","outcome":"success"}}
+//! → {"op":"ping"}
+//! ← {"status":"ok","data":{"pong":true}}
+//! ```
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use zeroize::Zeroizing;
+use crate::error::{AuthError, AuthResult};
+use crate::output::{error_json, success_json};
+use crate::server::metrics_router;
+use crate::service::AuthService;
+use crate::session::generate_token;
+use crate::{backup, config, outln, tr, validation, validation_error};
+
+/// Caminho padrão do socket
+pub const DEFAULT_SOCKET: &str = "/run/siri.sock";
+
+/// Tamanho máximo de uma linha de pedido, para que um cliente não esgote a memória
+const MAX_LINE: usize = 64 * 1024;
+
+/// Um pedido do protocolo
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+enum Request {
+    /// Confere usuário e senha (e o segundo fator, se a conta exigir)
+    Check {
+        username: String,
+        password: Zeroizing<String>,
+        #[serde(default)]
+        totp: Option<Zeroizing<String>>,
+    },
+    /// Responde sem tocar no banco, para monitorar o daemon
+    Ping,
+}
+
+/// Atende um pedido já interpretado
+fn handle(service: &AuthService, request: Request) -> AuthResult<Value> {
+    match request {
+        Request::Check { username, password, totp } => {
+            let totp = totp.as_ref().map(|code| code.as_str());
+            let outcome = service.login_with_second_factor(&username, &password, totp)?;
+            outcome.into_result()?;
+            Ok(json!({ "username": username, "outcome": outcome.name() }))
+        }
+        Request::Ping => Ok(json!({ "pong": true })),
+    }
+}
+
+/// Interpreta e atende uma linha, devolvendo a resposta no envelope JSON
+async fn respond(service: Arc<AuthService>, line: String) -> Value {
+    let request = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => request,
+        Err(err) => return error_json(&validation!("Pedido inválido: {}", err)),
+    };
+    let result = tokio::task::spawn_blocking(move || handle(&service, request))
+        .await
+        .unwrap_or_else(|err| Err(AuthError::Input(std::io::Error::other(err))));
+
+    match result {
+        Ok(data) => success_json(&data),
+        Err(err) => error_json(&err),
+    }
+}
+
+/// Atende uma conexão até o cliente fechá-la
+async fn serve_connection(service: Arc<AuthService>, stream: UnixStream) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = (&mut reader).take(MAX_LINE as u64 + 1).read_line(&mut line).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        if line.len() > MAX_LINE {
+            let response = error_json(&validation!("Pedido maior que {} bytes", MAX_LINE));
+            writer.write_all(format!("{}\n", response).as_bytes()).await?;
+            return Ok(());
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let response = respond(Arc::clone(&service), trimmed.to_string()).await;
+        writer.write_all(format!("{}\n", response).as_bytes()).await?;
+    }
+}
+
+/// Cria o socket, acessível só pelo dono (0600); um socket antigo sem ninguém ouvindo é substituído.
+///
+/// O socket nasce num diretório temporário 0700 ao lado do destino e só é movido para
+/// o lugar depois de restrito, para que ninguém se conecte entre a criação e o `chmod`.
+fn bind(path: &Path) -> AuthResult<UnixListener> {
+    use std::fs::{self, DirBuilder, Permissions};
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return validation_error!("Já existe um daemon ouvindo em '{}'", path.display());
+        }
+        fs::remove_file(path)?;
+    }
+
+    // Nome curto: o caminho de um socket Unix tem pouco mais de 100 bytes
+    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let staging = parent.join(format!(".siri-{}", &generate_token()[..12]));
+    DirBuilder::new().mode(0o700).create(&staging)?;
+    let result = (|| -> AuthResult<UnixListener> {
+        let temporary = staging.join("s");
+        let listener = UnixListener::bind(&temporary)?;
+        fs::set_permissions(&temporary, Permissions::from_mode(0o600))?;
+        fs::rename(&temporary, path)?;
+        Ok(listener)
+    })();
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+/// Atende o protocolo no socket informado até o processo receber Ctrl+C, removendo o socket ao sair.
+///
+/// Com `metrics_listen`, as métricas também são expostas em `http://<endereço>/metrics`.
+pub fn run(service: AuthService, socket: &Path, metrics_listen: Option<SocketAddr>) -> AuthResult<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    runtime.block_on(async move {
+        let listener = bind(socket)?;
+        log::info!("daemon ouvindo em {}", socket.display());
+        outln!("🔌 {}", tr!("Daemon ouvindo em '{}' (Ctrl+C para encerrar)", socket.display()));
+
+        if let Some(address) = metrics_listen {
+            let metrics_listener = tokio::net::TcpListener::bind(address).await?;
+            let address = metrics_listener.local_addr()?;
+            log::info!("métricas do daemon em http://{}/metrics", address);
+            outln!("📈 {}", tr!("Métricas em http://{}/metrics", address));
+            tokio::spawn(async move {
+                if let Err(err) = axum::serve(metrics_listener, metrics_router()).await {
+                    log::warn!("servidor de métricas encerrado com erro: {}", err);
+                }
+            });
+        }
+
+        let service = Arc::new(service);
+        backup::schedule(Arc::clone(&service), &config::current().backup)?;
+
+        let shutdown = tokio::signal::ctrl_c();
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted?;
+                    let service = Arc::clone(&service);
+                    tokio::spawn(async move {
+                        if let Err(err) = serve_connection(service, stream).await {
+                            log::warn!("conexão do daemon encerrada com erro: {}", err);
+                        }
+                    });
+                }
+                _ = &mut shutdown => break,
+            }
+        }
+
+        std::fs::remove_file(socket)?;
+        log::info!("daemon encerrado");
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn socket_is_private_and_staging_directory_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("siri.sock");
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+        let _listener = runtime.block_on(async { bind(&path) }).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(std::os::unix::net::UnixStream::connect(&path).is_ok());
+        let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(entries, ["siri.sock"]);
+    }
+}
@@ -1,57 +1,245 @@
-use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::auth::{format_sqlite_timestamp, is_scheme_hash, parse_sqlite_timestamp};
+use crate::clock::Clock;
+use crate::config;
 use crate::error::{AuthError, AuthResult};
+use crate::groups::Group;
+use crate::profile::Profile;
+use crate::hasher::HashScheme;
+use crate::migrations::migrate;
+use crate::pool::{configure, DatabasePool, PooledConn};
+use crate::rbac::{Permission, Role};
+use crate::settings;
+use crate::store::{like_pattern, LoginAttempt, StoredCredentials, StoredSession, UserOrder, UserStore};
+use crate::user::{AccountStatus, User};
+use crate::{errln, message, tr, validation};
 
-const DB_FILE: &str = "users.db";
+/// Nome do arquivo do banco de dados (o diretório vem de `config::default_db_path`)
+pub const DB_FILE: &str = "users.db";
 
-/// Estrutura para gerenciar a conexão com o banco de dados
-pub struct Database {
-    conn: Connection,
+/// Cria os diretórios que faltam até o arquivo do banco, acessíveis só pelo dono (0700)
+#[cfg(unix)]
+pub(crate) fn create_parent_dirs(path: &Path) -> AuthResult<()> {
+    use std::fs::DirBuilder;
+    use std::os::unix::fs::DirBuilderExt;
+
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            DirBuilder::new().recursive(true).mode(0o700).create(parent)?;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
 }
 
-impl Database {
-    /// Cria uma nova instância do banco de dados
-    pub fn new() -> AuthResult<Self> {
-        let conn = Connection::open(DB_FILE)?;
-        let db = Database { conn };
-        db.init_tables()?;
-        Ok(db)
-    }
-
-    /// Inicializa as tabelas necessárias
-    fn init_tables(&self) -> AuthResult<()> {
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS users (
-                id INTEGER PRIMARY KEY,
-                username TEXT NOT NULL UNIQUE,
-                password_hash TEXT NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
+/// Cria os diretórios que faltam até o arquivo do banco
+#[cfg(not(unix))]
+pub(crate) fn create_parent_dirs(path: &Path) -> AuthResult<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => Ok(std::fs::create_dir_all(parent)?),
+        _ => Ok(()),
+    }
+}
+
+/// Cria um arquivo vazio que só o dono lê (0600), já com essa permissão na criação.
+///
+/// Falha se o caminho já existir, inclusive como link simbólico, para que ninguém
+/// possa preparar o arquivo antes de nós.
+pub(crate) fn create_private_file(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Arquivos que o SQLite mantém ao lado do banco, com os mesmos dados (e hashes)
+fn sidecar_files(path: &Path) -> Vec<PathBuf> {
+    ["-wal", "-shm", "-journal"]
+        .iter()
+        .map(|suffix| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(suffix);
+            PathBuf::from(name)
+        })
+        .collect()
+}
+
+/// Restringe as permissões do arquivo do banco ao dono (0600).
+///
+/// Arquivos recém-criados são ajustados; arquivos já existentes com permissões mais
+/// abertas apenas geram um aviso, para não alterar uma escolha do administrador.
+#[cfg(unix)]
+pub(crate) fn secure_file_permissions(path: &Path, existed: bool) -> AuthResult<()> {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)?.permissions().mode() & 0o777;
+
+    if !existed {
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    } else if mode & 0o077 != 0 {
+        errln!(
+            "⚠️  {}",
+            tr!(
+                "O arquivo '{}' tem permissões {}; recomenda-se 600 (chmod 600 {})",
+                path.display(),
+                format!("{:o}", mode),
+                path.display()
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Em plataformas não-Unix as permissões ficam a cargo do sistema
+#[cfg(not(unix))]
+pub(crate) fn secure_file_permissions(_path: &Path, _existed: bool) -> AuthResult<()> {
+    Ok(())
+}
+
+/// Verifica se uma coluna existe em uma tabela
+pub(crate) fn column_exists(conn: &Connection, table: &str, column: &str) -> AuthResult<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    for name in columns {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Lê uma coluna de timestamp do SQLite (UTC) como DateTime
+fn timestamp_column(row: &rusqlite::Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: Option<String> = row.get(index)?;
+    value
+        .as_deref()
+        .and_then(parse_sqlite_timestamp)
+        .ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Text,
+                format!("timestamp inválido: {:?}", value).into(),
+            )
+        })
+}
+
+/// Monta um `User` a partir de uma linha com id, username, email, email_verified_at, created_at e role
+fn user_from_row(row: &rusqlite::Row) -> rusqlite::Result<User> {
+    Ok(User {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        email: row.get(2)?,
+        email_verified_at: optional_timestamp_column(row, 3)?,
+        created_at: timestamp_column(row, 4)?,
+        role: role_from_name(&row.get::<_, String>(5)?),
+        status: AccountStatus::from_name(&row.get::<_, String>(6)?),
+        display_name: row.get(7)?,
+        phone: row.get(8)?,
+        bio: row.get(9)?,
+        last_login_at: optional_timestamp_column(row, 10)?,
+        login_count: row.get(11)?,
+    })
+}
+
+/// Converte o valor da coluna `role`; um papel desconhecido não concede privilégios
+fn role_from_name(name: &str) -> Role {
+    Role::from_name(name).unwrap_or_default()
+}
+
+/// Lê uma coluna de timestamp opcional do SQLite (UTC)
+fn optional_timestamp_column(row: &rusqlite::Row, index: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    let value: Option<String> = row.get(index)?;
+    Ok(value.as_deref().and_then(parse_sqlite_timestamp))
+}
+
+/// Executa `operation` em uma transação, ou em um savepoint se a conexão já estiver em uma.
+///
+/// A transação é IMMEDIATE: o lock de escrita vem antes da primeira leitura, então
+/// uma verificação seguida de escrita não é atropelada por outra conexão.
+fn in_transaction<T>(conn: &Connection, operation: impl FnOnce(&Connection) -> AuthResult<T>) -> AuthResult<T> {
+    if conn.is_autocommit() {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        let value = operation(&tx)?;
+        tx.commit()?;
+        return Ok(value);
+    }
+
+    conn.execute_batch("SAVEPOINT siri_nested")?;
+    match operation(conn) {
+        Ok(value) => {
+            conn.execute_batch("RELEASE siri_nested")?;
+            Ok(value)
+        }
+        Err(err) => {
+            conn.execute_batch("ROLLBACK TO siri_nested; RELEASE siri_nested")?;
+            Err(err)
+        }
+    }
+}
+
+/// Implementação SQLite do armazenamento, usada diretamente dentro de transações
+impl UserStore for Connection {
+    fn user_exists(&self, username: &str) -> AuthResult<bool> {
+        let mut stmt = self.prepare_cached("SELECT COUNT(*) FROM users WHERE username = ?1 AND deleted_at IS NULL")?;
+        let count: i64 = stmt.query_row([username], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    fn count_users(&self) -> AuthResult<u32> {
+        let count = self.query_row(
+            "SELECT COUNT(*) FROM users WHERE deleted_at IS NULL",
             [],
+            |row| row.get(0),
         )?;
-        Ok(())
+        Ok(count)
     }
 
-    /// Retorna uma referência para a conexão
-    pub fn connection(&self) -> &Connection {
-        &self.conn
+    // As restrições UNIQUE do banco rejeitam nomes e emails duplicados sem uma consulta prévia
+    fn insert_user(&self, username: &str, password_hash: &str, email: Option<&str>) -> AuthResult<()> {
+        match self.execute(
+            "INSERT INTO users (username, password_hash, email, password_changed_at, role)
+             VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP,
+                     CASE WHEN EXISTS (SELECT 1 FROM users) THEN 'user' ELSE 'admin' END)",
+            rusqlite::params![username, password_hash, email],
+        ) {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(err, message))
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                match (email, message) {
+                    (Some(email), Some(message)) if message.contains("email") => {
+                        Err(validation!("Email '{}' já está em uso", email))
+                    }
+                    _ => Err(AuthError::UserAlreadyExists(username.to_string())),
+                }
+            }
+            Err(e) => Err(AuthError::from(e)),
+        }
     }
 
-    /// Verifica se um usuário existe
-    pub fn user_exists(&self, username: &str) -> AuthResult<bool> {
-        let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM users WHERE username = ?1",
-            [username],
-            |row| row.get(0),
+    fn insert_user_if_absent(&self, username: &str, password_hash: &str) -> AuthResult<bool> {
+        let inserted = self.execute(
+            "INSERT INTO users (username, password_hash, password_changed_at, role)
+             VALUES (?1, ?2, CURRENT_TIMESTAMP,
+                     CASE WHEN EXISTS (SELECT 1 FROM users) THEN 'user' ELSE 'admin' END)
+             ON CONFLICT(username) DO NOTHING",
+            [username, password_hash],
         )?;
-        Ok(count > 0)
+        Ok(inserted > 0)
     }
 
-    /// Obtém o hash da senha de um usuário
-    pub fn get_password_hash(&self, username: &str) -> AuthResult<Option<String>> {
-        use rusqlite::OptionalExtension;
-        
-        let hash = self.conn.query_row(
-            "SELECT password_hash FROM users WHERE username = ?1",
+    fn get_password_hash(&self, username: &str) -> AuthResult<Option<String>> {
+        let hash = self.query_row(
+            "SELECT password_hash FROM users WHERE username = ?1 AND deleted_at IS NULL",
             [username],
             |row| row.get(0),
         ).optional()?;
@@ -59,75 +247,1478 @@ impl Database {
         Ok(hash)
     }
 
-    /// Insere um novo usuário no banco
-    pub fn insert_user(&self, username: &str, password_hash: &str) -> AuthResult<()> {
-        match self.conn.execute(
-            "INSERT INTO users (username, password_hash) VALUES (?1, ?2)",
-            [username, password_hash],
-        ) {
-            Ok(_) => Ok(()),
+    fn get_credentials(&self, username: &str) -> AuthResult<Option<StoredCredentials>> {
+        // Consultada a cada login: a instrução preparada fica no cache da conexão
+        let mut stmt = self.prepare_cached(
+            "SELECT password_hash, COALESCE(password_changed_at, created_at), locked_until, status,
+                    must_change_password
+             FROM users WHERE username = ?1 AND deleted_at IS NULL",
+        )?;
+        let credentials = stmt
+            .query_row([username], |row| {
+                Ok(StoredCredentials {
+                    password_hash: row.get(0)?,
+                    changed_at: optional_timestamp_column(row, 1)?,
+                    locked_until: optional_timestamp_column(row, 2)?,
+                    status: AccountStatus::from_name(&row.get::<_, String>(3)?),
+                    must_change_password: row.get(4)?,
+                })
+            })
+            .optional()?;
+        Ok(credentials)
+    }
+
+    fn set_password_hash(&self, username: &str, password_hash: &str, rotated: bool) -> AuthResult<bool> {
+        let sql = if rotated {
+            "UPDATE users SET password_hash = ?1, password_changed_at = CURRENT_TIMESTAMP,
+                              must_change_password = 0
+             WHERE username = ?2 AND deleted_at IS NULL"
+        } else {
+            "UPDATE users SET password_hash = ?1 WHERE username = ?2 AND deleted_at IS NULL"
+        };
+        let rows_affected = self.prepare_cached(sql)?.execute([password_hash, username])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn record_failed_attempt(&self, username: &str) -> AuthResult<u32> {
+        let mut stmt = self.prepare_cached(
+            "UPDATE users SET failed_attempts = failed_attempts + 1
+             WHERE username = ?1 RETURNING failed_attempts",
+        )?;
+        let failed_attempts = stmt.query_row([username], |row| row.get(0))?;
+        Ok(failed_attempts)
+    }
+
+    fn lock_user(&self, username: &str, until: DateTime<Utc>) -> AuthResult<()> {
+        self.prepare_cached("UPDATE users SET failed_attempts = 0, locked_until = ?1 WHERE username = ?2")?
+            .execute([&format_sqlite_timestamp(until), username])?;
+        Ok(())
+    }
+
+    fn clear_failed_attempts(&self, username: &str) -> AuthResult<()> {
+        self.prepare_cached("UPDATE users SET failed_attempts = 0, locked_until = NULL WHERE username = ?1")?
+            .execute([username])?;
+        Ok(())
+    }
+
+    fn record_successful_login(&self, username: &str, at: DateTime<Utc>) -> AuthResult<()> {
+        let mut stmt = self.prepare_cached(
+            "UPDATE users SET last_login_at = ?1, login_count = login_count + 1
+             WHERE username = ?2 AND deleted_at IS NULL",
+        )?;
+        stmt.execute([&format_sqlite_timestamp(at), username])?;
+        Ok(())
+    }
+
+    fn find_by_email(&self, email: &str) -> AuthResult<Option<String>> {
+        let username = self.query_row(
+            "SELECT username FROM users
+             WHERE email = ?1 COLLATE NOCASE AND deleted_at IS NULL",
+            [email.trim()],
+            |row| row.get(0),
+        ).optional()?;
+
+        Ok(username)
+    }
+
+    fn list_users(&self) -> AuthResult<Vec<User>> {
+        let mut stmt = self.prepare(
+            "SELECT id, username, email, email_verified_at, created_at, role, status,
+                    display_name, phone, bio, last_login_at, login_count
+             FROM users WHERE deleted_at IS NULL ORDER BY username"
+        )?;
+
+        let users = stmt
+            .query_map([], user_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(users)
+    }
+
+    fn list_users_paged(&self, offset: usize, limit: usize, order: UserOrder) -> AuthResult<Vec<User>> {
+        let mut stmt = self.prepare(&format!(
+            "SELECT id, username, email, email_verified_at, created_at, role, status,
+                    display_name, phone, bio, last_login_at, login_count
+             FROM users WHERE deleted_at IS NULL {}
+             LIMIT ?1 OFFSET ?2",
+            order.sql()
+        ))?;
+
+        let users = stmt
+            .query_map(rusqlite::params![limit as i64, offset as i64], user_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(users)
+    }
+
+    fn search_users(&self, pattern: &str, order: UserOrder) -> AuthResult<Vec<User>> {
+        // O LIKE do SQLite já ignora maiúsculas/minúsculas nas letras ASCII
+        let mut stmt = self.prepare(&format!(
+            "SELECT id, username, email, email_verified_at, created_at, role, status,
+                    display_name, phone, bio, last_login_at, login_count
+             FROM users
+             WHERE deleted_at IS NULL AND (username LIKE ?1 ESCAPE '\\' OR email LIKE ?1 ESCAPE '\\')
+             {}",
+            order.sql()
+        ))?;
+
+        let users = stmt
+            .query_map([like_pattern(pattern)], user_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(users)
+    }
+
+    fn find_user(&self, username: &str) -> AuthResult<Option<User>> {
+        let user = self
+            .query_row(
+                "SELECT id, username, email, email_verified_at, created_at, role, status,
+                        display_name, phone, bio, last_login_at, login_count
+                 FROM users WHERE username = ?1 AND deleted_at IS NULL",
+                [username],
+                user_from_row,
+            )
+            .optional()?;
+        Ok(user)
+    }
+
+    fn rename_user(&self, current: &str, new_username: &str) -> AuthResult<bool> {
+        let result = self.execute(
+            "UPDATE users SET username = ?1 WHERE username = ?2 AND deleted_at IS NULL",
+            [new_username, current],
+        );
+
+        match result {
+            Ok(rows_affected) => Ok(rows_affected > 0),
             Err(rusqlite::Error::SqliteFailure(err, _))
                 if err.code == rusqlite::ErrorCode::ConstraintViolation =>
             {
-                Err(AuthError::Validation(format!("Usuário '{}' já existe", username)))
+                Err(AuthError::UserAlreadyExists(new_username.to_string()))
             }
             Err(e) => Err(AuthError::from(e)),
         }
     }
 
-    /// Lista todos os usuários com informações de criação
-    pub fn list_users(&self) -> AuthResult<Vec<(i32, String, String)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, username, datetime(created_at, 'localtime') as created 
-             FROM users ORDER BY username"
-        )?;
-        
-        let user_iter = stmt.query_map([], |row| {
-            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
-        })?;
+    fn update_profile(&self, username: &str, profile: &Profile) -> AuthResult<bool> {
+        let result = self.execute(
+            "UPDATE users SET display_name = ?1, phone = ?2, bio = ?3, email = ?4,
+                              email_verified_at = CASE WHEN email = ?4 COLLATE NOCASE
+                                                       THEN email_verified_at END
+             WHERE username = ?5 AND deleted_at IS NULL",
+            rusqlite::params![profile.display_name, profile.phone, profile.bio, profile.email, username],
+        );
 
-        let mut users = Vec::new();
-        for user in user_iter {
-            users.push(user?);
+        match result {
+            Ok(rows_affected) => Ok(rows_affected > 0),
+            Err(rusqlite::Error::SqliteFailure(err, _))
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                Err(validation!("Email '{}' já está em uso", profile.email.as_deref().unwrap_or_default()))
+            }
+            Err(e) => Err(AuthError::from(e)),
         }
-        Ok(users)
     }
 
-    /// Deleta um usuário (para fins administrativos)
-    pub fn delete_user(&self, username: &str) -> AuthResult<bool> {
-        let rows_affected = self.conn.execute(
-            "DELETE FROM users WHERE username = ?1",
+    // A conta some das listagens e do login, mas os dados ficam até `purge_deleted`
+    fn delete_user(&self, username: &str) -> AuthResult<bool> {
+        in_transaction(self, |tx| {
+            let rows_affected = tx.execute(
+                "UPDATE users SET deleted_at = CURRENT_TIMESTAMP
+                 WHERE username = ?1 AND deleted_at IS NULL",
+                [username],
+            )?;
+            if rows_affected == 0 {
+                return Ok(false);
+            }
+            for table in ["sessions", "password_resets", "email_verifications"] {
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE user_id = (SELECT id FROM users WHERE username = ?1)", table),
+                    [username],
+                )?;
+            }
+            Ok(true)
+        })
+    }
+
+    fn erase_user(&self, username: &str, actor: &str, action: &str) -> AuthResult<bool> {
+        // As tabelas ligadas ao usuário são apagadas em cascata (foreign_keys = ON)
+        in_transaction(self, |tx| {
+            let deleted = tx.execute(
+                "DELETE FROM users WHERE username = ?1 AND deleted_at IS NULL",
+                [username],
+            )?;
+            if deleted == 0 {
+                return Ok(false);
+            }
+            tx.record_audit(actor, action, Some(username))?;
+            Ok(true)
+        })
+    }
+
+    fn restore_user(&self, username: &str) -> AuthResult<bool> {
+        let rows_affected = self.execute(
+            "UPDATE users SET deleted_at = NULL
+             WHERE username = ?1 AND deleted_at IS NOT NULL",
             [username],
         )?;
         Ok(rows_affected > 0)
     }
 
-    /// Obtém estatísticas do banco
-    pub fn get_stats(&self) -> AuthResult<DatabaseStats> {
-        let user_count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM users",
+    fn list_deleted_users(&self) -> AuthResult<Vec<(String, DateTime<Utc>)>> {
+        let mut stmt = self.prepare(
+            "SELECT username, deleted_at
+             FROM users WHERE deleted_at IS NOT NULL ORDER BY deleted_at"
+        )?;
+
+        let users = stmt
+            .query_map([], |row| Ok((row.get(0)?, timestamp_column(row, 1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(users)
+    }
+
+    fn purge_deleted(&self, older_than: DateTime<Utc>) -> AuthResult<usize> {
+        let rows_affected = self.execute(
+            "DELETE FROM users WHERE deleted_at IS NOT NULL AND deleted_at <= ?1",
+            [format_sqlite_timestamp(older_than)],
+        )?;
+        Ok(rows_affected)
+    }
+
+    fn release_username(&self, username: &str, deleted_before: DateTime<Utc>) -> AuthResult<()> {
+        self.execute(
+            "DELETE FROM users
+             WHERE username = ?1 AND deleted_at IS NOT NULL AND deleted_at <= ?2",
+            [username, &format_sqlite_timestamp(deleted_before)],
+        )?;
+        Ok(())
+    }
+
+    fn set_status(&self, username: &str, status: AccountStatus) -> AuthResult<bool> {
+        let rows_affected = self.execute(
+            "UPDATE users SET status = ?1 WHERE username = ?2 AND deleted_at IS NULL",
+            [status.name(), username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn get_status(&self, username: &str) -> AuthResult<Option<AccountStatus>> {
+        let mut stmt = self.prepare_cached("SELECT status FROM users WHERE username = ?1 AND deleted_at IS NULL")?;
+        let status: Option<String> = stmt.query_row([username], |row| row.get(0)).optional()?;
+
+        Ok(status.as_deref().map(AccountStatus::from_name))
+    }
+
+    fn get_role(&self, username: &str) -> AuthResult<Option<Role>> {
+        let mut stmt = self.prepare_cached("SELECT role FROM users WHERE username = ?1 AND deleted_at IS NULL")?;
+        let role: Option<String> = stmt.query_row([username], |row| row.get(0)).optional()?;
+
+        Ok(role.as_deref().map(role_from_name))
+    }
+
+    fn set_role(&self, username: &str, role: Role) -> AuthResult<bool> {
+        let rows_affected = self.execute(
+            "UPDATE users SET role = ?1 WHERE username = ?2 AND deleted_at IS NULL",
+            [role.name(), username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn count_admins(&self) -> AuthResult<u32> {
+        let count = self.query_row(
+            "SELECT COUNT(*) FROM users WHERE role = 'admin' AND status = 'active' AND deleted_at IS NULL",
             [],
             |row| row.get(0),
         )?;
+        Ok(count)
+    }
+
+    fn role_permissions(&self, role: Role) -> AuthResult<Vec<Permission>> {
+        let mut stmt = self.prepare_cached("SELECT permission FROM role_permissions WHERE role = ?1")?;
+        let names = stmt
+            .query_map([role.name()], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        // Permissões que este binário não conhece são ignoradas
+        Ok(names.iter().filter_map(|name| Permission::from_name(name)).collect())
+    }
+
+    fn set_role_permission(&self, role: Role, permission: Permission, granted: bool) -> AuthResult<()> {
+        let sql = if granted {
+            "INSERT OR IGNORE INTO role_permissions (role, permission) VALUES (?1, ?2)"
+        } else {
+            "DELETE FROM role_permissions WHERE role = ?1 AND permission = ?2"
+        };
+        self.execute(sql, [role.name(), permission.name()])?;
+        Ok(())
+    }
 
+    fn insert_group(&self, name: &str) -> AuthResult<()> {
+        match self.execute("INSERT INTO groups (name) VALUES (?1)", [name]) {
+            Ok(_) => Ok(()),
+            Err(rusqlite::Error::SqliteFailure(err, _))
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                Err(validation!("Grupo '{}' já existe", name))
+            }
+            Err(e) => Err(AuthError::from(e)),
+        }
+    }
+
+    fn delete_group(&self, name: &str) -> AuthResult<bool> {
+        let rows_affected = self.execute("DELETE FROM groups WHERE name = ?1", [name])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn group_exists(&self, name: &str) -> AuthResult<bool> {
+        let count: i64 = self.query_row("SELECT COUNT(*) FROM groups WHERE name = ?1", [name], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    fn list_groups(&self) -> AuthResult<Vec<Group>> {
+        let mut stmt = self.prepare(
+            "SELECT g.name, COUNT(u.id), g.created_at
+             FROM groups g
+             LEFT JOIN group_members m ON m.group_id = g.id
+             LEFT JOIN users u ON u.id = m.user_id AND u.deleted_at IS NULL
+             GROUP BY g.id ORDER BY g.name",
+        )?;
+
+        let groups = stmt
+            .query_map([], |row| {
+                Ok(Group { name: row.get(0)?, members: row.get(1)?, created_at: timestamp_column(row, 2)? })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(groups)
+    }
+
+    fn add_group_member(&self, group: &str, username: &str) -> AuthResult<bool> {
+        let inserted = self.execute(
+            "INSERT OR IGNORE INTO group_members (group_id, user_id)
+             SELECT g.id, u.id FROM groups g, users u
+             WHERE g.name = ?1 AND u.username = ?2 AND u.deleted_at IS NULL",
+            [group, username],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    fn remove_group_member(&self, group: &str, username: &str) -> AuthResult<bool> {
+        let rows_affected = self.execute(
+            "DELETE FROM group_members
+             WHERE group_id = (SELECT id FROM groups WHERE name = ?1)
+               AND user_id = (SELECT id FROM users WHERE username = ?2 AND deleted_at IS NULL)",
+            [group, username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn group_members(&self, group: &str) -> AuthResult<Vec<String>> {
+        let mut stmt = self.prepare(
+            "SELECT u.username FROM group_members m
+             JOIN groups g ON g.id = m.group_id
+             JOIN users u ON u.id = m.user_id
+             WHERE g.name = ?1 AND u.deleted_at IS NULL
+             ORDER BY u.username",
+        )?;
+        let members = stmt.query_map([group], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(members)
+    }
+
+    fn user_groups(&self, username: &str) -> AuthResult<Vec<String>> {
+        let mut stmt = self.prepare(
+            "SELECT g.name FROM group_members m
+             JOIN groups g ON g.id = m.group_id
+             JOIN users u ON u.id = m.user_id
+             WHERE u.username = ?1 AND u.deleted_at IS NULL
+             ORDER BY g.name",
+        )?;
+        let groups = stmt.query_map([username], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+        Ok(groups)
+    }
+
+    fn insert_session(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        let mut stmt = self.prepare_cached(
+            "INSERT INTO sessions (token_hash, user_id, created_at, expires_at)
+             SELECT ?1, id, ?2, ?3 FROM users WHERE username = ?4 AND deleted_at IS NULL",
+        )?;
+        let inserted = stmt.execute(rusqlite::params![
+            token_hash,
+            format_sqlite_timestamp(created_at),
+            format_sqlite_timestamp(expires_at),
+            username
+        ])?;
+        if inserted == 0 {
+            return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+        }
+        Ok(())
+    }
+
+    fn find_session(&self, token_hash: &str) -> AuthResult<Option<StoredSession>> {
+        // Consultada a cada requisição autenticada do `siri serve`
+        let mut stmt = self.prepare_cached(
+            "SELECT u.username, s.created_at, s.expires_at
+             FROM sessions s JOIN users u ON u.id = s.user_id
+             WHERE s.token_hash = ?1 AND u.deleted_at IS NULL AND u.status = 'active'",
+        )?;
+        let session = stmt
+            .query_row([token_hash], |row| {
+                Ok(StoredSession {
+                    username: row.get(0)?,
+                    created_at: timestamp_column(row, 1)?,
+                    expires_at: timestamp_column(row, 2)?,
+                })
+            })
+            .optional()?;
+        Ok(session)
+    }
+
+    fn user_sessions(&self, username: &str) -> AuthResult<Vec<StoredSession>> {
+        let mut stmt = self.prepare(
+            "SELECT u.username, s.created_at, s.expires_at
+             FROM sessions s JOIN users u ON u.id = s.user_id
+             WHERE u.username = ?1 AND u.deleted_at IS NULL
+             ORDER BY s.created_at DESC, s.id DESC",
+        )?;
+        let sessions = stmt
+            .query_map([username], |row| {
+                Ok(StoredSession {
+                    username: row.get(0)?,
+                    created_at: timestamp_column(row, 1)?,
+                    expires_at: timestamp_column(row, 2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
+    fn extend_session(&self, token_hash: &str, expires_at: DateTime<Utc>) -> AuthResult<bool> {
+        let rows_affected = self
+            .prepare_cached("UPDATE sessions SET expires_at = ?1 WHERE token_hash = ?2")?
+            .execute([&format_sqlite_timestamp(expires_at), token_hash])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn delete_session(&self, token_hash: &str) -> AuthResult<bool> {
+        let rows_affected = self.prepare_cached("DELETE FROM sessions WHERE token_hash = ?1")?.execute([token_hash])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn purge_expired_sessions(&self, now: DateTime<Utc>) -> AuthResult<usize> {
+        let rows_affected = self.execute(
+            "DELETE FROM sessions WHERE expires_at <= ?1",
+            [format_sqlite_timestamp(now)],
+        )?;
+        Ok(rows_affected)
+    }
+
+    fn insert_password_reset(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_by: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        in_transaction(self, |tx| {
+            tx.execute(
+                "DELETE FROM password_resets
+                 WHERE used_at IS NULL AND user_id = (SELECT id FROM users WHERE username = ?1)",
+                [username],
+            )?;
+            let inserted = tx.execute(
+                "INSERT INTO password_resets (token_hash, user_id, created_by, expires_at)
+                 SELECT ?1, id, ?2, ?3 FROM users WHERE username = ?4 AND deleted_at IS NULL",
+                rusqlite::params![token_hash, created_by, format_sqlite_timestamp(expires_at), username],
+            )?;
+            if inserted == 0 {
+                return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+            }
+            Ok(())
+        })
+    }
+
+    fn consume_password_reset(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>> {
+        let now = format_sqlite_timestamp(now);
+        in_transaction(self, |tx| {
+            let found: Option<(i64, String)> = tx
+                .query_row(
+                    "SELECT r.id, u.username FROM password_resets r JOIN users u ON u.id = r.user_id
+                     WHERE r.token_hash = ?1 AND r.used_at IS NULL AND r.expires_at > ?2
+                       AND u.deleted_at IS NULL",
+                    [token_hash, now.as_str()],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            let Some((reset_id, username)) = found else {
+                return Ok(None);
+            };
+            tx.execute(
+                "UPDATE password_resets SET used_at = ?1 WHERE id = ?2",
+                rusqlite::params![now, reset_id],
+            )?;
+            Ok(Some(username))
+        })
+    }
+
+    fn insert_email_verification(&self, token_hash: &str, username: &str, expires_at: DateTime<Utc>) -> AuthResult<()> {
+        in_transaction(self, |tx| {
+            tx.execute(
+                "DELETE FROM email_verifications WHERE user_id = (SELECT id FROM users WHERE username = ?1)",
+                [username],
+            )?;
+            let inserted = tx.execute(
+                "INSERT INTO email_verifications (token_hash, user_id, email, expires_at)
+                 SELECT ?1, id, email, ?2 FROM users
+                 WHERE username = ?3 AND deleted_at IS NULL AND email IS NOT NULL",
+                rusqlite::params![token_hash, format_sqlite_timestamp(expires_at), username],
+            )?;
+            if inserted == 0 {
+                return Err(AuthError::NotFound(message!("Email do usuário '{}'", username)));
+            }
+            Ok(())
+        })
+    }
+
+    fn consume_email_verification(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>> {
+        let now = format_sqlite_timestamp(now);
+        in_transaction(self, |tx| {
+            let found: Option<(i64, String)> = tx
+                .query_row(
+                    "SELECT u.id, u.username FROM email_verifications v JOIN users u ON u.id = v.user_id
+                     WHERE v.token_hash = ?1 AND v.expires_at > ?2 AND u.deleted_at IS NULL
+                       AND u.email = v.email COLLATE NOCASE",
+                    [token_hash, now.as_str()],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            let Some((user_id, username)) = found else {
+                return Ok(None);
+            };
+            tx.execute("DELETE FROM email_verifications WHERE user_id = ?1", [user_id])?;
+            tx.execute(
+                "UPDATE users SET email_verified_at = ?1 WHERE id = ?2",
+                rusqlite::params![now, user_id],
+            )?;
+            Ok(Some(username))
+        })
+    }
+
+    fn get_totp_secret(&self, username: &str) -> AuthResult<Option<String>> {
+        let mut stmt = self.prepare_cached(
+            "SELECT t.secret FROM totp_secrets t JOIN users u ON u.id = t.user_id
+             WHERE u.username = ?1 AND u.deleted_at IS NULL",
+        )?;
+        let secret = stmt.query_row([username], |row| row.get(0)).optional()?;
+        Ok(secret)
+    }
+
+    fn set_totp_secret(&self, username: &str, secret: Option<&str>) -> AuthResult<()> {
+        match secret {
+            Some(secret) => self.execute(
+                "INSERT INTO totp_secrets (user_id, secret)
+                 SELECT id, ?1 FROM users WHERE username = ?2 AND deleted_at IS NULL
+                 ON CONFLICT(user_id) DO UPDATE SET secret = excluded.secret, last_used_step = NULL,
+                                                    enabled_at = CURRENT_TIMESTAMP",
+                [secret, username],
+            )?,
+            None => self.execute(
+                "DELETE FROM totp_secrets WHERE user_id = (SELECT id FROM users WHERE username = ?1)",
+                [username],
+            )?,
+        };
+        Ok(())
+    }
+
+    fn claim_totp_step(&self, username: &str, step: i64) -> AuthResult<bool> {
+        let mut stmt = self.prepare_cached(
+            "UPDATE totp_secrets SET last_used_step = ?1
+             WHERE user_id = (SELECT id FROM users WHERE username = ?2)
+               AND (last_used_step IS NULL OR last_used_step < ?1)",
+        )?;
+        let rows_affected = stmt.execute(rusqlite::params![step, username])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn replace_recovery_codes(&self, username: &str, code_hashes: &[String]) -> AuthResult<()> {
+        in_transaction(self, |tx| {
+            tx.execute(
+                "DELETE FROM recovery_codes WHERE user_id = (SELECT id FROM users WHERE username = ?1)",
+                [username],
+            )?;
+            for code_hash in code_hashes {
+                tx.execute(
+                    "INSERT INTO recovery_codes (user_id, code_hash)
+                     SELECT id, ?1 FROM users WHERE username = ?2 AND deleted_at IS NULL",
+                    [code_hash.as_str(), username],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    fn unused_recovery_codes(&self, username: &str) -> AuthResult<Vec<(i64, String)>> {
+        let mut stmt = self.prepare(
+            "SELECT c.id, c.code_hash FROM recovery_codes c JOIN users u ON u.id = c.user_id
+             WHERE u.username = ?1 AND u.deleted_at IS NULL AND c.consumed_at IS NULL
+             ORDER BY c.id",
+        )?;
+        let codes = stmt
+            .query_map([username], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(codes)
+    }
+
+    fn consume_recovery_code(&self, code_id: i64) -> AuthResult<bool> {
+        let rows_affected = self.execute(
+            "UPDATE recovery_codes SET consumed_at = CURRENT_TIMESTAMP
+             WHERE id = ?1 AND consumed_at IS NULL",
+            [code_id],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn record_login_attempt(&self, attempt: &LoginAttempt) -> AuthResult<()> {
+        let mut stmt = self.prepare_cached(
+            "INSERT INTO login_attempts (user_id, username, success, outcome, source, attempted_at)
+             VALUES ((SELECT id FROM users WHERE username = ?1 AND deleted_at IS NULL), ?1, ?2, ?3, ?4, ?5)",
+        )?;
+        stmt.execute(rusqlite::params![
+            attempt.username,
+            attempt.success,
+            attempt.outcome,
+            attempt.source,
+            format_sqlite_timestamp(attempt.attempted_at)
+        ])?;
+        Ok(())
+    }
+
+    fn login_history(&self, username: &str, limit: usize) -> AuthResult<Vec<LoginAttempt>> {
+        let mut stmt = self.prepare(
+            "SELECT a.username, a.success, a.outcome, a.source, a.attempted_at
+             FROM login_attempts a JOIN users u ON u.id = a.user_id
+             WHERE u.username = ?1 AND u.deleted_at IS NULL
+             ORDER BY a.attempted_at DESC, a.id DESC
+             LIMIT ?2",
+        )?;
+        let attempts = stmt
+            .query_map(rusqlite::params![username, limit as i64], |row| {
+                Ok(LoginAttempt {
+                    username: row.get(0)?,
+                    success: row.get(1)?,
+                    outcome: row.get(2)?,
+                    source: row.get(3)?,
+                    attempted_at: timestamp_column(row, 4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(attempts)
+    }
+
+    fn record_audit(&self, actor: &str, action: &str, target: Option<&str>) -> AuthResult<()> {
+        self.prepare_cached("INSERT INTO audit_log (actor, action, target) VALUES (?1, ?2, ?3)")?
+            .execute(rusqlite::params![actor, action, target])?;
+        Ok(())
+    }
+
+    fn audit_log(&self, filter: &AuditFilter) -> AuthResult<Vec<AuditEntry>> {
+        let mut stmt = self.prepare(
+            "SELECT id, actor, action, target, created_at FROM audit_log
+             WHERE (?1 IS NULL OR actor = ?1)
+               AND (?2 IS NULL OR action = ?2)
+               AND (?3 IS NULL OR target = ?3)
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?4",
+        )?;
+        let entries = stmt
+            .query_map(
+                rusqlite::params![filter.actor, filter.action, filter.target, filter.limit as i64],
+                |row| {
+                    Ok(AuditEntry {
+                        id: row.get(0)?,
+                        actor: row.get(1)?,
+                        action: row.get(2)?,
+                        target: row.get(3)?,
+                        created_at: timestamp_column(row, 4)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    fn get_setting(&self, key: &str) -> AuthResult<Option<String>> {
+        settings::get_setting(self, key)
+    }
+
+    fn set_setting(&self, key: &str, value: Option<&str>) -> AuthResult<()> {
+        settings::set_setting(self, key, value)
+    }
+
+    fn transaction(&self, operation: &mut dyn FnMut(&dyn UserStore) -> AuthResult<()>) -> AuthResult<()> {
+        in_transaction(self, |tx| operation(tx))
+    }
+}
+
+/// O que fazer ao abrir um banco que falha na verificação de integridade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnCorrupt {
+    /// Recusa abrir, retornando `AuthError::CorruptDatabase` (padrão)
+    #[default]
+    Refuse,
+    /// Renomeia o arquivo corrompido e cria um banco novo no lugar
+    MoveAside,
+}
+
+/// Executa `PRAGMA integrity_check`, retornando a descrição do problema se houver.
+///
+/// Um arquivo que nem chega a ser um banco SQLite falha já na consulta.
+pub(crate) fn check_integrity(conn: &Connection) -> Result<(), String> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check").map_err(|e| e.to_string())?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| e.to_string())?;
+
+    if messages.len() == 1 && messages[0] == "ok" {
+        Ok(())
+    } else {
+        Err(messages.join("; "))
+    }
+}
+
+/// Renomeia um banco corrompido para `<nome>.corrupt-<timestamp>`
+fn move_aside(path: &Path) -> AuthResult<PathBuf> {
+    let stamp = Utc::now().format("%Y%m%d%H%M%S");
+    let mut aside = path.as_os_str().to_owned();
+    aside.push(format!(".corrupt-{}", stamp));
+    let aside = PathBuf::from(aside);
+
+    std::fs::rename(path, &aside)?;
+    Ok(aside)
+}
+
+/// Estrutura para gerenciar as conexões com o banco de dados.
+///
+/// Cada operação pega uma conexão do pool, então várias threads (ex.: as requisições
+/// de `siri serve`) podem usar o mesmo `Database` ao mesmo tempo.
+pub struct Database {
+    pool: DatabasePool,
+}
+
+impl Database {
+    /// Abre o banco no caminho da configuração em vigor
+    pub fn new() -> AuthResult<Self> {
+        Self::open(&config::current().database.path, OnCorrupt::Refuse)
+    }
+
+    /// Abre o banco no caminho informado, verificando a integridade do arquivo
+    pub fn open(path: &Path, on_corrupt: OnCorrupt) -> AuthResult<Self> {
+        let mut existed = path.exists();
+        if !existed {
+            create_parent_dirs(path)?;
+            // Criado já com 0600: o SQLite aceita um arquivo vazio como banco novo
+            match create_private_file(path) {
+                Ok(_) => {}
+                // Outro processo criou o banco entre a verificação e a criação
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => existed = true,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let conn = Connection::open(path)?;
+
+        if let Err(problem) = check_integrity(&conn) {
+            match on_corrupt {
+                OnCorrupt::Refuse => {
+                    return Err(AuthError::CorruptDatabase(message!(
+                        "'{}' falhou na verificação de integridade ({}). Restaure um backup \
+                         ou execute com --recover-corrupt para movê-lo e começar um banco novo",
+                        path.display(),
+                        problem
+                    )));
+                }
+                OnCorrupt::MoveAside => {
+                    drop(conn);
+                    let aside = move_aside(path)?;
+                    log::warn!("banco corrompido '{}' movido para '{}'", path.display(), aside.display());
+                    errln!(
+                        "⚠️  {}",
+                        tr!(
+                            "'{}' estava corrompido e foi movido para '{}'. Um banco novo foi criado.",
+                            path.display(),
+                            aside.display()
+                        )
+                    );
+                    return Self::open(path, OnCorrupt::Refuse);
+                }
+            }
+        }
+
+        secure_file_permissions(path, existed)?;
+        let config = &config::current().database;
+        configure(&conn, config)?;
+        migrate(&conn)?;
+        // O WAL e a memória compartilhada guardam as mesmas páginas do banco
+        for sidecar in sidecar_files(path).iter().filter(|sidecar| sidecar.exists()) {
+            secure_file_permissions(sidecar, existed)?;
+        }
+        drop(conn);
+
+        // Esta conexão só conferiu e migrou o banco; as operações usam as do pool
+        let pool = DatabasePool::connect(path, config)?;
+        Ok(Database { pool })
+    }
+
+    /// Abre um banco em memória, descartado ao fim do processo
+    pub fn in_memory() -> AuthResult<Self> {
+        let pool = DatabasePool::in_memory()?;
+        migrate(&*pool.get_conn()?)?;
+        Ok(Database { pool })
+    }
+
+    /// Obtém uma conexão do pool, devolvida a ele quando descartada
+    pub fn connection(&self) -> AuthResult<PooledConn> {
+        self.pool.get_conn()
+    }
+
+    /// Verifica quantas contas têm o hash da senha fora do formato PHC do Argon2
+    pub fn scan_hash_health(&self) -> AuthResult<HashHealth> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT username, password_hash FROM users WHERE deleted_at IS NULL ORDER BY username"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+        let scheme = config::current().hashing.scheme;
+        let mut health = HashHealth { scheme, ..HashHealth::default() };
+        for row in rows {
+            let (username, password_hash) = row?;
+            health.total_users += 1;
+            if !is_scheme_hash(&password_hash, scheme) {
+                health.legacy_users.push(username);
+            }
+        }
+        Ok(health)
+    }
+
+    /// Obtém estatísticas do banco, usando o relógio informado para as janelas de tempo
+    pub fn get_stats(&self, clock: &dyn Clock) -> AuthResult<DatabaseStats> {
         use rusqlite::OptionalExtension;
+
+        let now = clock.now();
+        let today = now.date_naive();
+        let last_24h = format_sqlite_timestamp(now - Duration::hours(24));
+        let last_7d = format_sqlite_timestamp(now - Duration::days(7));
+        let now = format_sqlite_timestamp(now);
+
+        let conn = self.connection()?;
+        let (total_users, created_last_24h, created_last_7d, locked_accounts, disabled_accounts, average_age):
+            (i64, i64, i64, i64, i64, Option<f64>) = conn.query_row(
+                "SELECT COUNT(*),
+                        COALESCE(SUM(created_at >= ?1), 0),
+                        COALESCE(SUM(created_at >= ?2), 0),
+                        COALESCE(SUM(locked_until > ?3), 0),
+                        COALESCE(SUM(status = 'disabled'), 0),
+                        AVG(julianday(?3) - julianday(created_at))
+                 FROM users WHERE deleted_at IS NULL",
+                [&last_24h, &last_7d, &now],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+            )?;
+
+        let two_factor_users: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM totp_secrets t JOIN users u ON u.id = t.user_id
+             WHERE u.deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
         
-        let latest_user: Option<String> = self.conn.query_row(
-            "SELECT username FROM users ORDER BY created_at DESC LIMIT 1",
+        let latest_user: Option<String> = conn.query_row(
+            "SELECT username FROM users WHERE deleted_at IS NULL
+             ORDER BY created_at DESC, id DESC LIMIT 1",
             [],
             |row| row.get(0),
         ).optional()?;
 
+        // Semanas de segunda a domingo; a mais antiga define o início da consulta
+        let this_week = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+        let first_week = this_week - Duration::weeks(STATS_WEEKS as i64 - 1);
+        let first_day = today - Duration::days(STATS_DAYS as i64 - 1);
+
+        let mut stmt = conn.prepare(
+            "SELECT date(created_at), COUNT(*) FROM users
+             WHERE deleted_at IS NULL AND date(created_at) >= ?1
+             GROUP BY date(created_at)",
+        )?;
+        let per_date = stmt
+            .query_map([first_week.min(first_day).to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let per_date: Vec<(NaiveDate, usize)> = per_date
+            .into_iter()
+            .filter_map(|(date, count)| Some((date.parse().ok()?, count as usize)))
+            .collect();
+        let count_between = |start: NaiveDate, days: i64| {
+            let end = start + Duration::days(days);
+            per_date.iter().filter(|(date, _)| *date >= start && *date < end).map(|(_, count)| count).sum()
+        };
+
         Ok(DatabaseStats {
-            total_users: user_count as usize,
+            total_users: total_users as usize,
             latest_user,
+            created_last_24h: created_last_24h as usize,
+            created_last_7d: created_last_7d as usize,
+            locked_accounts: locked_accounts as usize,
+            disabled_accounts: disabled_accounts as usize,
+            two_factor_users: two_factor_users as usize,
+            registrations_per_day: first_day
+                .iter_days()
+                .take(STATS_DAYS)
+                .map(|day| (day, count_between(day, 1)))
+                .collect(),
+            registrations_per_week: first_week
+                .iter_weeks()
+                .take(STATS_WEEKS)
+                .map(|week| (week, count_between(week, 7)))
+                .collect(),
+            average_account_age_days: average_age,
         })
     }
 }
 
+
+/// O `Database` delega para uma conexão do pool a cada operação
+impl UserStore for Database {
+    fn user_exists(&self, username: &str) -> AuthResult<bool> {
+        self.connection()?.user_exists(username)
+    }
+
+    fn count_users(&self) -> AuthResult<u32> {
+        self.connection()?.count_users()
+    }
+
+    fn insert_user(&self, username: &str, password_hash: &str, email: Option<&str>) -> AuthResult<()> {
+        self.connection()?.insert_user(username, password_hash, email)
+    }
+
+    fn insert_user_if_absent(&self, username: &str, password_hash: &str) -> AuthResult<bool> {
+        self.connection()?.insert_user_if_absent(username, password_hash)
+    }
+
+    fn get_password_hash(&self, username: &str) -> AuthResult<Option<String>> {
+        self.connection()?.get_password_hash(username)
+    }
+
+    fn get_credentials(&self, username: &str) -> AuthResult<Option<StoredCredentials>> {
+        self.connection()?.get_credentials(username)
+    }
+
+    fn set_password_hash(&self, username: &str, password_hash: &str, rotated: bool) -> AuthResult<bool> {
+        self.connection()?.set_password_hash(username, password_hash, rotated)
+    }
+
+    fn record_failed_attempt(&self, username: &str) -> AuthResult<u32> {
+        self.connection()?.record_failed_attempt(username)
+    }
+
+    fn lock_user(&self, username: &str, until: DateTime<Utc>) -> AuthResult<()> {
+        self.connection()?.lock_user(username, until)
+    }
+
+    fn clear_failed_attempts(&self, username: &str) -> AuthResult<()> {
+        self.connection()?.clear_failed_attempts(username)
+    }
+
+    fn record_successful_login(&self, username: &str, at: DateTime<Utc>) -> AuthResult<()> {
+        self.connection()?.record_successful_login(username, at)
+    }
+
+    fn find_by_email(&self, email: &str) -> AuthResult<Option<String>> {
+        self.connection()?.find_by_email(email)
+    }
+
+    fn list_users(&self) -> AuthResult<Vec<User>> {
+        self.connection()?.list_users()
+    }
+
+    fn list_users_paged(&self, offset: usize, limit: usize, order: UserOrder) -> AuthResult<Vec<User>> {
+        self.connection()?.list_users_paged(offset, limit, order)
+    }
+
+    fn search_users(&self, pattern: &str, order: UserOrder) -> AuthResult<Vec<User>> {
+        self.connection()?.search_users(pattern, order)
+    }
+
+    fn find_user(&self, username: &str) -> AuthResult<Option<User>> {
+        self.connection()?.find_user(username)
+    }
+
+    fn rename_user(&self, current: &str, new_username: &str) -> AuthResult<bool> {
+        self.connection()?.rename_user(current, new_username)
+    }
+
+    fn update_profile(&self, username: &str, profile: &Profile) -> AuthResult<bool> {
+        self.connection()?.update_profile(username, profile)
+    }
+
+    fn delete_user(&self, username: &str) -> AuthResult<bool> {
+        self.connection()?.delete_user(username)
+    }
+
+    fn erase_user(&self, username: &str, actor: &str, action: &str) -> AuthResult<bool> {
+        self.connection()?.erase_user(username, actor, action)
+    }
+
+    fn restore_user(&self, username: &str) -> AuthResult<bool> {
+        self.connection()?.restore_user(username)
+    }
+
+    fn list_deleted_users(&self) -> AuthResult<Vec<(String, DateTime<Utc>)>> {
+        self.connection()?.list_deleted_users()
+    }
+
+    fn purge_deleted(&self, older_than: DateTime<Utc>) -> AuthResult<usize> {
+        self.connection()?.purge_deleted(older_than)
+    }
+
+    fn release_username(&self, username: &str, deleted_before: DateTime<Utc>) -> AuthResult<()> {
+        self.connection()?.release_username(username, deleted_before)
+    }
+
+    fn set_status(&self, username: &str, status: AccountStatus) -> AuthResult<bool> {
+        self.connection()?.set_status(username, status)
+    }
+
+    fn get_status(&self, username: &str) -> AuthResult<Option<AccountStatus>> {
+        self.connection()?.get_status(username)
+    }
+
+    fn get_role(&self, username: &str) -> AuthResult<Option<Role>> {
+        self.connection()?.get_role(username)
+    }
+
+    fn set_role(&self, username: &str, role: Role) -> AuthResult<bool> {
+        self.connection()?.set_role(username, role)
+    }
+
+    fn count_admins(&self) -> AuthResult<u32> {
+        self.connection()?.count_admins()
+    }
+
+    fn role_permissions(&self, role: Role) -> AuthResult<Vec<Permission>> {
+        self.connection()?.role_permissions(role)
+    }
+
+    fn set_role_permission(&self, role: Role, permission: Permission, granted: bool) -> AuthResult<()> {
+        self.connection()?.set_role_permission(role, permission, granted)
+    }
+
+    fn insert_group(&self, name: &str) -> AuthResult<()> {
+        self.connection()?.insert_group(name)
+    }
+
+    fn delete_group(&self, name: &str) -> AuthResult<bool> {
+        self.connection()?.delete_group(name)
+    }
+
+    fn group_exists(&self, name: &str) -> AuthResult<bool> {
+        self.connection()?.group_exists(name)
+    }
+
+    fn list_groups(&self) -> AuthResult<Vec<Group>> {
+        self.connection()?.list_groups()
+    }
+
+    fn add_group_member(&self, group: &str, username: &str) -> AuthResult<bool> {
+        self.connection()?.add_group_member(group, username)
+    }
+
+    fn remove_group_member(&self, group: &str, username: &str) -> AuthResult<bool> {
+        self.connection()?.remove_group_member(group, username)
+    }
+
+    fn group_members(&self, group: &str) -> AuthResult<Vec<String>> {
+        self.connection()?.group_members(group)
+    }
+
+    fn user_groups(&self, username: &str) -> AuthResult<Vec<String>> {
+        self.connection()?.user_groups(username)
+    }
+
+    fn insert_session(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        self.connection()?.insert_session(token_hash, username, created_at, expires_at)
+    }
+
+    fn find_session(&self, token_hash: &str) -> AuthResult<Option<StoredSession>> {
+        self.connection()?.find_session(token_hash)
+    }
+
+    fn user_sessions(&self, username: &str) -> AuthResult<Vec<StoredSession>> {
+        self.connection()?.user_sessions(username)
+    }
+
+    fn extend_session(&self, token_hash: &str, expires_at: DateTime<Utc>) -> AuthResult<bool> {
+        self.connection()?.extend_session(token_hash, expires_at)
+    }
+
+    fn delete_session(&self, token_hash: &str) -> AuthResult<bool> {
+        self.connection()?.delete_session(token_hash)
+    }
+
+    fn purge_expired_sessions(&self, now: DateTime<Utc>) -> AuthResult<usize> {
+        self.connection()?.purge_expired_sessions(now)
+    }
+
+    fn insert_password_reset(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_by: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        self.connection()?.insert_password_reset(token_hash, username, created_by, expires_at)
+    }
+
+    fn consume_password_reset(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>> {
+        self.connection()?.consume_password_reset(token_hash, now)
+    }
+
+    fn insert_email_verification(&self, token_hash: &str, username: &str, expires_at: DateTime<Utc>) -> AuthResult<()> {
+        self.connection()?.insert_email_verification(token_hash, username, expires_at)
+    }
+
+    fn consume_email_verification(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>> {
+        self.connection()?.consume_email_verification(token_hash, now)
+    }
+
+    fn get_totp_secret(&self, username: &str) -> AuthResult<Option<String>> {
+        self.connection()?.get_totp_secret(username)
+    }
+
+    fn set_totp_secret(&self, username: &str, secret: Option<&str>) -> AuthResult<()> {
+        self.connection()?.set_totp_secret(username, secret)
+    }
+
+    fn claim_totp_step(&self, username: &str, step: i64) -> AuthResult<bool> {
+        self.connection()?.claim_totp_step(username, step)
+    }
+
+    fn replace_recovery_codes(&self, username: &str, code_hashes: &[String]) -> AuthResult<()> {
+        self.connection()?.replace_recovery_codes(username, code_hashes)
+    }
+
+    fn unused_recovery_codes(&self, username: &str) -> AuthResult<Vec<(i64, String)>> {
+        self.connection()?.unused_recovery_codes(username)
+    }
+
+    fn consume_recovery_code(&self, code_id: i64) -> AuthResult<bool> {
+        self.connection()?.consume_recovery_code(code_id)
+    }
+
+    fn record_login_attempt(&self, attempt: &LoginAttempt) -> AuthResult<()> {
+        self.connection()?.record_login_attempt(attempt)
+    }
+
+    fn login_history(&self, username: &str, limit: usize) -> AuthResult<Vec<LoginAttempt>> {
+        self.connection()?.login_history(username, limit)
+    }
+
+    fn record_audit(&self, actor: &str, action: &str, target: Option<&str>) -> AuthResult<()> {
+        self.connection()?.record_audit(actor, action, target)
+    }
+
+    fn audit_log(&self, filter: &AuditFilter) -> AuthResult<Vec<AuditEntry>> {
+        self.connection()?.audit_log(filter)
+    }
+
+    fn get_setting(&self, key: &str) -> AuthResult<Option<String>> {
+        self.connection()?.get_setting(key)
+    }
+
+    fn set_setting(&self, key: &str, value: Option<&str>) -> AuthResult<()> {
+        self.connection()?.set_setting(key, value)
+    }
+
+    fn transaction(&self, operation: &mut dyn FnMut(&dyn UserStore) -> AuthResult<()>) -> AuthResult<()> {
+        // Toda a operação usa a mesma conexão do pool
+        self.connection()?.transaction(operation)
+    }
+}
+
+/// Dias com cadastros contados em `DatabaseStats::registrations_per_day`
+pub const STATS_DAYS: usize = 7;
+
+/// Semanas com cadastros contadas em `DatabaseStats::registrations_per_week`
+pub const STATS_WEEKS: usize = 4;
+
 /// Estrutura para estatísticas do banco
 #[derive(Debug)]
 pub struct DatabaseStats {
     pub total_users: usize,
     pub latest_user: Option<String>,
-}
\ No newline at end of file
+    pub created_last_24h: usize,
+    pub created_last_7d: usize,
+    pub locked_accounts: usize,
+    /// Contas desativadas por um administrador
+    pub disabled_accounts: usize,
+    /// Contas com o segundo fator ativado
+    pub two_factor_users: usize,
+    /// Cadastros em cada um dos últimos `STATS_DAYS` dias, do mais antigo até hoje
+    pub registrations_per_day: Vec<(NaiveDate, usize)>,
+    /// Cadastros em cada uma das últimas `STATS_WEEKS` semanas, identificadas pela segunda-feira
+    pub registrations_per_week: Vec<(NaiveDate, usize)>,
+    /// Idade média das contas, em dias; `None` sem usuários
+    pub average_account_age_days: Option<f64>,
+}
+
+/// Resultado da verificação dos hashes de senha
+#[derive(Debug, Default)]
+pub struct HashHealth {
+    pub total_users: usize,
+    /// Esquema configurado para as senhas novas
+    pub scheme: HashScheme,
+    /// Contas cujo hash não está no esquema configurado (texto puro, outro esquema ou corrompido)
+    pub legacy_users: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::auth::{hash_argon2, match_argon2_peppers, register_user, verify_and_upgrade, LegacyHashPolicy, PepperMatch};
+    use crate::clock::MockClock;
+    use crate::hasher::HashingConfig;
+    use crate::pepper::Peppers;
+    use crate::testing::{database, register, CHEAP_ARGON2, PASSWORD};
+
+    /// Banco em arquivo, com o pool de conexões completo (o banco em memória usa uma só)
+    fn pooled_database(dir: &tempfile::TempDir) -> Database {
+        let db = Database::open(&dir.path().join("users.db"), OnCorrupt::Refuse).unwrap();
+        CHEAP_ARGON2.save(&db).unwrap();
+        db
+    }
+
+    #[test]
+    fn threads_register_distinct_users_concurrently() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = pooled_database(&dir);
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|i| {
+                    let db = &db;
+                    scope.spawn(move || register_user(db, &format!("usuario{}", i), PASSWORD))
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap().unwrap();
+            }
+        });
+
+        let conn = db.connection().unwrap();
+        let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal_mode, "wal");
+        assert_eq!(db.list_users().unwrap().len(), 8);
+        for i in 0..8 {
+            assert!(db.user_exists(&format!("usuario{}", i)).unwrap());
+        }
+    }
+
+    #[test]
+    fn racing_registrations_of_one_username_admit_exactly_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = pooled_database(&dir);
+        let barrier = std::sync::Barrier::new(2);
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    let (db, barrier) = (&db, &barrier);
+                    scope.spawn(move || {
+                        barrier.wait();
+                        register_user(db, "alice", PASSWORD)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1, "{:?}", results);
+        let rejected = results.iter().filter_map(|result| result.as_ref().err()).collect::<Vec<_>>();
+        assert!(matches!(rejected[..], [AuthError::UserAlreadyExists(name)] if name == "alice"), "{:?}", rejected);
+        assert_eq!(db.list_users().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn garbage_file_is_reported_as_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.db");
+        std::fs::write(&path, b"isto definitivamente nao e um banco sqlite".repeat(200)).unwrap();
+
+        let err = Database::open(&path, OnCorrupt::Refuse).err().unwrap();
+        assert!(matches!(err, AuthError::CorruptDatabase(_)), "{:?}", err);
+        // O arquivo fica onde estava, para ser restaurado ou examinado
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn corrupt_file_can_be_moved_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.db");
+        std::fs::write(&path, b"lixo".repeat(1000)).unwrap();
+
+        let db = Database::open(&path, OnCorrupt::MoveAside).unwrap();
+        assert!(db.list_users().unwrap().is_empty());
+        let aside = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("users.db.corrupt-"))
+            .count();
+        assert_eq!(aside, 1);
+    }
+
+    #[cfg(unix)]
+    fn mode(path: &Path) -> u32 {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn new_database_file_is_private_to_the_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dados").join("users.db");
+
+        let db = Database::open(&path, OnCorrupt::Refuse).unwrap();
+        assert_eq!(mode(&path), 0o600);
+
+        // Com o banco aberto em WAL, os arquivos auxiliares também ficam só com o dono
+        CHEAP_ARGON2.save(&db).unwrap();
+        register_user(&db, "alice", PASSWORD).unwrap();
+        let sidecars: Vec<_> = sidecar_files(&path).into_iter().filter(|sidecar| sidecar.exists()).collect();
+        assert!(!sidecars.is_empty());
+        for sidecar in sidecars {
+            assert_eq!(mode(&sidecar), 0o600, "{}", sidecar.display());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn database_is_not_created_through_a_planted_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("alvo");
+        let path = dir.path().join("users.db");
+        std::os::unix::fs::symlink(&target, &path).unwrap();
+
+        assert!(create_private_file(&path).is_err());
+        assert!(!target.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn existing_database_keeps_its_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.db");
+        drop(Database::open(&path, OnCorrupt::Refuse).unwrap());
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        Database::open(&path, OnCorrupt::Refuse).unwrap();
+        assert_eq!(mode(&path), 0o640);
+    }
+
+    #[test]
+    fn stats_count_live_users_inside_each_window() {
+        // Quarta-feira, ao meio-dia
+        let now = Utc.with_ymd_and_hms(2026, 3, 11, 12, 0, 0).unwrap();
+        let db = database();
+        let ages = [
+            ("alice", Duration::hours(2)),
+            ("bob", Duration::days(3)),
+            ("carol", Duration::days(10)),
+            ("dave", Duration::hours(1)),
+        ];
+        for (username, _) in ages {
+            register(&db, username);
+        }
+        // O banco em memória tem uma só conexão: os cadastros vêm antes de tomá-la
+        let conn = db.connection().unwrap();
+        for (username, age) in ages {
+            conn.execute(
+                "UPDATE users SET created_at = ?1 WHERE username = ?2",
+                [format_sqlite_timestamp(now - age), username.to_string()],
+            )
+            .unwrap();
+        }
+        let locked_until = format_sqlite_timestamp(now + Duration::hours(1));
+        conn.execute("UPDATE users SET locked_until = ?1 WHERE username = 'bob'", [locked_until]).unwrap();
+        conn.execute("UPDATE users SET status = 'disabled' WHERE username = 'carol'", []).unwrap();
+        drop(conn);
+        assert!(db.delete_user("dave").unwrap());
+
+        let stats = db.get_stats(&MockClock::new(now)).unwrap();
+        assert_eq!(stats.total_users, 3);
+        assert_eq!(stats.latest_user.as_deref(), Some("alice"));
+        assert_eq!(stats.created_last_24h, 1);
+        assert_eq!(stats.created_last_7d, 2);
+        assert_eq!(stats.locked_accounts, 1);
+        assert_eq!(stats.disabled_accounts, 1);
+
+        let today = now.date_naive();
+        assert_eq!(stats.registrations_per_day.last(), Some(&(today, 1)));
+        // carol fica fora dos últimos `STATS_DAYS` dias
+        assert_eq!(stats.registrations_per_day.iter().map(|(_, count)| count).sum::<usize>(), 2);
+        // bob entrou no domingo, carol no domingo anterior
+        let monday = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        assert_eq!(
+            stats.registrations_per_week[stats.registrations_per_week.len() - 3..],
+            [(monday - Duration::weeks(2), 1), (monday - Duration::weeks(1), 1), (monday, 1)]
+        );
+        let average = stats.average_account_age_days.unwrap();
+        assert!((average - (2.0 / 24.0 + 3.0 + 10.0) / 3.0).abs() < 1e-6, "{}", average);
+    }
+
+    #[test]
+    fn hash_health_flags_legacy_rows_and_login_repairs_them() {
+        let db = database();
+        register(&db, "alice");
+        db.insert_user("bob", PASSWORD, None).unwrap();
+        db.insert_user("carol", "md5$5f4dcc3b5aa765d61d8327deb882cf99", None).unwrap();
+        let stale = hash_argon2(PASSWORD, &CHEAP_ARGON2, Some(b"pepper-antigo")).unwrap();
+        db.insert_user("dave", &stale, None).unwrap();
+
+        let health = db.scan_hash_health().unwrap();
+        assert_eq!(health.total_users, 4);
+        assert_eq!(health.scheme, HashScheme::Argon2);
+        // O pepper não fica no hash: a conta de dave parece saudável até o login
+        assert_eq!(health.legacy_users, ["bob", "carol"]);
+
+        let hashing = HashingConfig {
+            legacy_hashes: LegacyHashPolicy::Plaintext,
+            pepper: Some("pepper-novo".to_string()),
+            previous_pepper: Some("pepper-antigo".to_string()),
+            ..HashingConfig::default()
+        };
+        let stored_hash = |username: &str| db.get_credentials(username).unwrap().unwrap().password_hash;
+        for username in ["bob", "carol", "dave"] {
+            let matches = verify_and_upgrade(&db, username, PASSWORD, &stored_hash(username), &hashing).unwrap();
+            assert_eq!(matches, username != "carol", "{}", username);
+        }
+
+        // Formato desconhecido não confere nem é tocado
+        assert_eq!(stored_hash("carol"), "md5$5f4dcc3b5aa765d61d8327deb882cf99");
+        assert_eq!(db.scan_hash_health().unwrap().legacy_users, ["carol"]);
+        let current = Peppers { current: Some(b"pepper-novo".to_vec()), previous: Vec::new() };
+        for username in ["bob", "dave"] {
+            let upgraded = stored_hash(username);
+            assert_ne!(upgraded, stale);
+            assert_eq!(match_argon2_peppers(PASSWORD, &upgraded, &current).unwrap(), Some(PepperMatch::Current));
+        }
+    }
+}
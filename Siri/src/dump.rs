@@ -0,0 +1,242 @@
+use std::fs;
+use std::path::Path;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand_core::RngCore;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+use crate::db::{column_exists, secure_file_permissions};
+use crate::error::{AuthError, AuthResult};
+use crate::rbac::Role;
+use crate::user::AccountStatus;
+use crate::{message, validation, validation_error};
+
+/// Identifica um arquivo de exportação do Siri
+const MAGIC: &[u8; 8] = b"SIRIDUMP";
+
+/// Versão atual do formato; versões desconhecidas são recusadas na importação
+const FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Parâmetros do Argon2 usados para derivar a chave a partir da frase secreta
+const KDF_M_COST: u32 = 64 * 1024;
+const KDF_T_COST: u32 = 3;
+const KDF_P_COST: u32 = 1;
+
+/// Tamanho do cabeçalho: magic, versão, parâmetros do KDF, salt e nonce
+const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+/// Deriva a chave de cifragem a partir da frase secreta
+fn derive_key(passphrase: &str, salt: &[u8], m_cost: u32, t_cost: u32, p_cost: u32) -> AuthResult<Key> {
+    let params = Params::new(m_cost, t_cost, p_cost, Some(32))
+        .map_err(|e| AuthError::PasswordHashing(message!("Parâmetros do Argon2 inválidos: {}", e)))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = Key::default();
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| AuthError::PasswordHashing(message!("Erro ao derivar chave: {}", e)))?;
+    Ok(key)
+}
+
+/// Converte um valor do SQLite para JSON
+fn sql_to_json(value: ValueRef) -> AuthResult<Value> {
+    Ok(match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(_) => {
+            return validation_error!("Coluna binária não suportada na exportação");
+        }
+    })
+}
+
+/// Converte um valor JSON de volta para o SQLite
+fn json_to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(i64::from(*b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+/// Lê todas as linhas da tabela de usuários, com todas as colunas
+fn read_users(conn: &Connection) -> AuthResult<Vec<Map<String, Value>>> {
+    let mut stmt = conn.prepare("SELECT * FROM users ORDER BY id")?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+
+    let mut rows = stmt.query([])?;
+    let mut users = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut user = Map::new();
+        for (index, column) in columns.iter().enumerate() {
+            user.insert(column.clone(), sql_to_json(row.get_ref(index)?)?);
+        }
+        users.push(user);
+    }
+    Ok(users)
+}
+
+/// Exporta a tabela de usuários inteira (incluindo hashes) para um arquivo cifrado.
+///
+/// Os hashes continuam dependendo do mesmo pepper na máquina de destino.
+/// Retorna quantos usuários foram exportados.
+pub fn dump_users(conn: &Connection, path: &Path, passphrase: &str) -> AuthResult<usize> {
+    if passphrase.is_empty() {
+        return validation_error!("A frase secreta não pode estar vazia");
+    }
+
+    let users = read_users(conn)?;
+    let count = users.len();
+    let plaintext = serde_json::to_vec(&users)
+        .map_err(|e| validation!("Erro ao serializar usuários: {}", e))?;
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt, KDF_M_COST, KDF_T_COST, KDF_P_COST)?;
+
+    let cipher = ChaCha20Poly1305::new(&key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    for cost in [KDF_M_COST, KDF_T_COST, KDF_P_COST] {
+        header.extend_from_slice(&cost.to_le_bytes());
+    }
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    // O cabeçalho entra como dado associado, então alterá-lo invalida o arquivo
+    let ciphertext = cipher
+        .encrypt(&nonce, chacha20poly1305::aead::Payload { msg: &plaintext, aad: &header })
+        .map_err(|_| AuthError::PasswordHashing(message!("Erro ao cifrar a exportação")))?;
+
+    let existed = path.exists();
+    let mut contents = header;
+    contents.extend_from_slice(&ciphertext);
+    fs::write(path, contents)?;
+    secure_file_permissions(path, existed)?;
+
+    Ok(count)
+}
+
+/// Importa um arquivo gerado por `dump_users` para um banco sem usuários.
+///
+/// Retorna quantos usuários foram importados.
+pub fn load_users(conn: &Connection, path: &Path, passphrase: &str) -> AuthResult<usize> {
+    let contents = fs::read(path)?;
+    if contents.len() < HEADER_LEN || &contents[..MAGIC.len()] != MAGIC {
+        return validation_error!("Arquivo não é uma exportação do Siri");
+    }
+
+    let (header, ciphertext) = contents.split_at(HEADER_LEN);
+    let version = header[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return validation_error!("Versão de exportação {} não suportada (esperada {})", version, FORMAT_VERSION);
+    }
+
+    let mut offset = MAGIC.len() + 1;
+    let mut costs = [0u32; 3];
+    for cost in &mut costs {
+        *cost = u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap_or_default());
+        offset += 4;
+    }
+    let salt = &header[offset..offset + SALT_LEN];
+    let nonce = Nonce::from_slice(&header[offset + SALT_LEN..]);
+
+    let key = derive_key(passphrase, salt, costs[0], costs[1], costs[2])?;
+    let plaintext = ChaCha20Poly1305::new(&key)
+        .decrypt(nonce, chacha20poly1305::aead::Payload { msg: ciphertext, aad: header })
+        .map_err(|_| validation!("Frase secreta incorreta ou arquivo corrompido"))?;
+
+    let mut users: Vec<Map<String, Value>> = serde_json::from_slice(&plaintext)
+        .map_err(|_| validation!("Conteúdo da exportação inválido"))?;
+
+    let existing: i64 = conn.query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))?;
+    if existing > 0 {
+        return validation_error!("A importação exige um banco sem usuários");
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    for user in &mut users {
+        // Exportações anteriores aos papéis marcavam os administradores com is_admin
+        if let Some(is_admin) = user.remove("is_admin") {
+            let role = if is_admin.as_i64() == Some(1) { Role::Admin } else { Role::User };
+            user.entry("role").or_insert_with(|| Value::String(role.name().to_string()));
+        }
+        // E as anteriores à coluna status marcavam as contas desativadas com enabled
+        if let Some(enabled) = user.remove("enabled") {
+            let status = if enabled.as_i64() == Some(0) { AccountStatus::Disabled } else { AccountStatus::Active };
+            user.entry("status").or_insert_with(|| Value::String(status.name().to_string()));
+        }
+        let columns: Vec<&str> = user.keys().map(String::as_str).collect();
+        for column in &columns {
+            if !column_exists(&tx, "users", column)? {
+                return validation_error!("Coluna desconhecida na exportação: {}", column);
+            }
+        }
+        let placeholders = (1..=columns.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>();
+        let sql = format!(
+            "INSERT INTO users ({}) VALUES ({})",
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        let values: Vec<SqlValue> = user.values().map(json_to_sql).collect();
+        tx.execute(&sql, rusqlite::params_from_iter(values))?;
+    }
+    tx.commit()?;
+
+    Ok(users.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{login_user, LoginOutcome};
+    use crate::store::UserStore;
+    use crate::testing::{database, register, PASSWORD};
+
+    const PASSPHRASE: &str = "frase secreta da exportação";
+
+    #[test]
+    fn dump_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.siri");
+        let source = database();
+        register(&source, "alice");
+        register(&source, "bruno");
+
+        assert_eq!(dump_users(&source.connection().unwrap(), &path, PASSPHRASE).unwrap(), 2);
+
+        let target = database();
+        assert_eq!(load_users(&target.connection().unwrap(), &path, PASSPHRASE).unwrap(), 2);
+        let names = |db: &dyn UserStore| db.list_users().unwrap().into_iter().map(|u| u.username).collect::<Vec<_>>();
+        assert_eq!(names(&target), names(&source));
+        assert_eq!(login_user(&target, "bruno", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_without_importing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.siri");
+        let source = database();
+        register(&source, "alice");
+        dump_users(&source.connection().unwrap(), &path, PASSPHRASE).unwrap();
+
+        let target = database();
+        let err = load_users(&target.connection().unwrap(), &path, "outra frase").unwrap_err();
+        assert!(matches!(err, AuthError::Validation(_)), "{:?}", err);
+        assert!(target.list_users().unwrap().is_empty());
+    }
+}
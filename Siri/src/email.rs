@@ -0,0 +1,110 @@
+use chrono::Duration;
+use crate::audit;
+use crate::auth::{create_reset_token, RESET_TOKEN_TTL};
+use crate::clock::Clock;
+use crate::error::{AuthError, AuthResult};
+use crate::mailer::{Email, Mailer};
+use crate::session::{generate_token, token_hash};
+use crate::store::UserStore;
+use crate::user::EmailStatus;
+use crate::{message, tr, validation_error};
+
+/// Validade de um token de verificação de email
+pub const VERIFICATION_TOKEN_TTL: Duration = Duration::hours(24);
+
+/// Gera um token para confirmar o email pendente de `username`.
+///
+/// O token em texto puro é retornado apenas aqui; no banco fica só o seu hash,
+/// junto com o endereço a confirmar. Pedir um novo token invalida o anterior.
+pub fn request_email_verification(store: &dyn UserStore, clock: &dyn Clock, username: &str) -> AuthResult<String> {
+    let user = store
+        .find_user(username)?
+        .ok_or_else(|| AuthError::NotFound(message!("Usuário '{}'", username)))?;
+
+    match user.email_status() {
+        EmailStatus::Missing => return validation_error!("A conta não tem email cadastrado"),
+        EmailStatus::Verified => return validation_error!("O email da conta já foi verificado"),
+        EmailStatus::Pending => {}
+    }
+
+    let token = generate_token();
+    store.insert_email_verification(&token_hash(&token), username, clock.now() + VERIFICATION_TOKEN_TTL)?;
+    audit::record(store, username, "request_email_verification", Some(username))?;
+    Ok(token)
+}
+
+/// Gera um token de verificação e o envia para o email pendente de `username`,
+/// retornando o endereço para onde ele foi enviado
+pub fn send_verification_email(
+    store: &dyn UserStore,
+    clock: &dyn Clock,
+    mailer: &dyn Mailer,
+    username: &str,
+) -> AuthResult<String> {
+    let token = request_email_verification(store, clock, username)?;
+    let address = store
+        .find_user(username)?
+        .and_then(|user| user.email)
+        .ok_or_else(|| AuthError::NotFound(message!("Email do usuário '{}'", username)))?;
+
+    mailer.send(&Email {
+        to: address.clone(),
+        subject: tr!("Confirme seu email").to_string(),
+        body: tr!(
+            "Olá, {}!\n\nUse o token abaixo para confirmar seu email no Siri Ferrugem:\n\n    {}\n\n\
+             Ele vale por {} horas. Se você não pediu esta confirmação, ignore este email.",
+            username,
+            token,
+            VERIFICATION_TOKEN_TTL.num_hours(),
+        ),
+    })?;
+    Ok(address)
+}
+
+/// Envia um token de redefinição de senha para o dono de um email verificado.
+///
+/// Retorna `Ok` mesmo quando nenhuma conta usa o endereço (ou ele não foi
+/// verificado), para que a resposta não revele quais emails estão cadastrados.
+pub fn request_password_reset(
+    store: &dyn UserStore,
+    clock: &dyn Clock,
+    mailer: &dyn Mailer,
+    email: &str,
+) -> AuthResult<()> {
+    let Some(username) = store.find_by_email(email.trim())? else {
+        return Ok(());
+    };
+    let Some(user) = store.find_user(&username)? else {
+        return Ok(());
+    };
+    // Só um endereço confirmado comprova que quem o lê é o dono da conta
+    let (EmailStatus::Verified, Some(address)) = (user.email_status(), user.email) else {
+        return Ok(());
+    };
+
+    let token = create_reset_token(store, clock, &username, &username, RESET_TOKEN_TTL)?;
+    mailer.send(&Email {
+        to: address,
+        subject: tr!("Redefinição de senha").to_string(),
+        body: tr!(
+            "Olá, {}!\n\nUse o token abaixo para definir uma nova senha no Siri Ferrugem:\n\n    {}\n\n\
+             Ele vale por {} minutos e pode ser usado uma única vez. Se você não pediu a\n\
+             redefinição, ignore este email: sua senha continua a mesma.",
+            username,
+            token,
+            RESET_TOKEN_TTL.num_minutes(),
+        ),
+    })
+}
+
+/// Confirma o email com um token de verificação, retornando o dono da conta.
+///
+/// O token vale uma única vez e só para o endereço em que foi emitido: se o
+/// email da conta mudou desde então, ele é rejeitado.
+pub fn verify_email(store: &dyn UserStore, clock: &dyn Clock, token: &str) -> AuthResult<String> {
+    let Some(username) = store.consume_email_verification(&token_hash(token.trim()), clock.now())? else {
+        return validation_error!("Token de verificação inválido ou expirado");
+    };
+    audit::record(store, &username, "verify_email", Some(&username))?;
+    Ok(username)
+}
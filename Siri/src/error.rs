@@ -1,53 +1,336 @@
-use std::fmt;
+use chrono::{DateTime, SecondsFormat, Utc};
+use thiserror::Error;
+use crate::i18n::Message;
+use crate::strength::StrengthScore;
+use crate::tr;
 
-/// Enum para diferentes tipos de erros do sistema
-#[derive(Debug)]
+/// Enum para diferentes tipos de erros do sistema.
+///
+/// Erros de bibliotecas ficam guardados como `source()`; as falhas mais comuns têm
+/// variante própria, para que quem chama possa reagir a elas sem ler a mensagem.
+/// As variantes guardam dados ou uma `Message`, não texto pronto: a mensagem é
+/// traduzida ao ser exibida.
+#[derive(Debug, Error)]
 pub enum AuthError {
-    Database(rusqlite::Error),
-    PasswordHashing(String),
-    Validation(String),
-    Input(std::io::Error),
-    NotFound(String),
-    PermissionDenied(String),
+    #[error("{}", tr!("Erro de banco de dados: {}", .0))]
+    Database(#[from] rusqlite::Error),
+    #[error("{}", tr!("Erro no PostgreSQL: {}", .0))]
+    Postgres(#[from] postgres::Error),
+    #[error("{}", tr!("Erro no pool de conexões: {}", .0))]
+    ConnectionPool(#[from] r2d2::Error),
+    #[error("{}", tr!("Erro ao processar senha: {}", .0))]
+    PasswordHashing(Message),
+    #[error("{}", tr!("Erro de validação: {}", .0))]
+    Validation(Message),
+    /// Nome de usuário já usado por outra conta
+    #[error("{}", tr!("Erro de validação: {}", tr!("Usuário '{}' já existe", .0)))]
+    UserAlreadyExists(String),
+    /// Senha recusada pela política: regras, força estimada ou vazamentos conhecidos
+    #[error("{}", tr!("Erro de validação: {}", .reason))]
+    WeakPassword { reason: PasswordIssue },
+    #[error("{}", tr!("Erro de entrada: {}", .0))]
+    Input(#[from] std::io::Error),
+    #[error("{}", tr!("Não encontrado: {}", .0))]
+    NotFound(Message),
+    #[error("{}", tr!("Permissão negada: {}", .0))]
+    PermissionDenied(Message),
+    #[error("{}", tr!("Banco de dados corrompido: {}", .0))]
+    CorruptDatabase(Message),
+    /// Falha ao entregar um email ao servidor
+    #[error("{}", tr!("Erro ao enviar email: {}", .0))]
+    Mail(String),
+    /// Usuário inexistente ou senha (ou código do segundo fator) incorreta
+    #[error("{}", tr!("Credenciais inválidas"))]
+    InvalidCredentials,
+    /// Conta bloqueada (por excesso de tentativas ou por um administrador) até o instante informado
+    #[error("{}", tr!("Conta bloqueada temporariamente até {}", .0.to_rfc3339_opts(SecondsFormat::Secs, true)))]
+    AccountLocked(DateTime<Utc>),
+    /// Conta desativada por um administrador
+    #[error("{}", tr!("Conta desativada. Procure um administrador"))]
+    AccountDisabled,
+    /// Senha correta, mas falta o código do segundo fator
+    #[error("{}", tr!("Esta conta exige um segundo fator de autenticação"))]
+    SecondFactorRequired,
+    /// Senha correta, mas ela precisa ser trocada antes do acesso
+    #[error("{}", tr!("A senha expirou ou precisa ser trocada"))]
+    PasswordExpired,
+    /// Token de sessão que expirou, foi encerrado ou não existe mais
+    #[error("{}", tr!("Sessão expirada ou encerrada. Faça login novamente."))]
+    SessionExpired,
 }
 
-impl fmt::Display for AuthError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// Motivo pelo qual a política de senhas recusou uma senha
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PasswordIssue {
+    #[error("{}", tr!("A senha deve ter pelo menos {} caracteres", .min_length))]
+    TooShort { min_length: usize },
+    #[error("{}", tr!("A senha deve conter pelo menos um número"))]
+    MissingDigit,
+    #[error("{}", tr!("A senha deve conter pelo menos uma letra maiúscula"))]
+    MissingUppercase,
+    #[error("{}", tr!("A senha deve conter pelo menos uma letra minúscula"))]
+    MissingLowercase,
+    #[error("{}", tr!("A senha deve conter pelo menos um caractere especial"))]
+    MissingSpecial,
+    /// Cumpre as regras, mas a força estimada fica abaixo da mínima
+    #[error("{}", tr!("Senha fraca demais ({}). {}", .score, .hints.join(" ")))]
+    TooGuessable { score: StrengthScore, hints: Vec<&'static str> },
+    /// Aparece em vazamentos conhecidos, o número de vezes informado
+    #[error("{}", tr!("Esta senha já apareceu {} vez(es) em vazamentos de dados conhecidos. Escolha outra", .count))]
+    Breached { count: u64 },
+}
+
+impl From<PasswordIssue> for AuthError {
+    fn from(reason: PasswordIssue) -> Self {
+        AuthError::WeakPassword { reason }
+    }
+}
+
+/// Classe estável de um erro, independente da mensagem exibida
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Validation,
+    Database,
+    PermissionDenied,
+    Input,
+    NotFound,
+    PasswordHashing,
+    InvalidCredentials,
+    AccountLocked,
+    AccountDisabled,
+    SecondFactorRequired,
+    PasswordExpired,
+    SessionExpired,
+}
+
+impl ErrorKind {
+    /// Nome estável, para consumo por programas (ex.: saída JSON)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Validation => "Validation",
+            ErrorKind::Database => "Database",
+            ErrorKind::PermissionDenied => "PermissionDenied",
+            ErrorKind::Input => "Input",
+            ErrorKind::NotFound => "NotFound",
+            ErrorKind::PasswordHashing => "PasswordHashing",
+            ErrorKind::InvalidCredentials => "InvalidCredentials",
+            ErrorKind::AccountLocked => "AccountLocked",
+            ErrorKind::AccountDisabled => "AccountDisabled",
+            ErrorKind::SecondFactorRequired => "SecondFactorRequired",
+            ErrorKind::PasswordExpired => "PasswordExpired",
+            ErrorKind::SessionExpired => "SessionExpired",
+        }
+    }
+
+    /// Código de saída do processo documentado para cada classe de erro.
+    ///
+    /// Os códigos são estáveis: scripts podem testar, por exemplo, se `siri login`
+    /// terminou com 1 (credenciais inválidas) ou 8 (conta bloqueada).
+    pub fn exit_code(&self) -> i32 {
         match self {
-            AuthError::Database(err) => write!(f, "Erro de banco de dados: {}", err),
-            AuthError::PasswordHashing(msg) => write!(f, "Erro ao processar senha: {}", msg),
-            AuthError::Validation(msg) => write!(f, "Erro de validação: {}", msg),
-            AuthError::Input(err) => write!(f, "Erro de entrada: {}", err),
-            AuthError::NotFound(msg) => write!(f, "Não encontrado: {}", msg),
-            AuthError::PermissionDenied(msg) => write!(f, "Permissão negada: {}", msg),
+            ErrorKind::InvalidCredentials => 1,
+            ErrorKind::Validation => 2,
+            ErrorKind::Database => 3,
+            ErrorKind::PermissionDenied => 4,
+            ErrorKind::Input => 5,
+            ErrorKind::NotFound => 6,
+            ErrorKind::PasswordHashing => 7,
+            ErrorKind::AccountLocked => 8,
+            ErrorKind::AccountDisabled => 9,
+            ErrorKind::SecondFactorRequired => 10,
+            ErrorKind::PasswordExpired => 11,
+            ErrorKind::SessionExpired => 12,
         }
     }
 }
 
-impl std::error::Error for AuthError {}
+impl AuthError {
+    /// Classe estável do erro
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            AuthError::Database(_)
+            | AuthError::Postgres(_)
+            | AuthError::ConnectionPool(_)
+            | AuthError::CorruptDatabase(_) => ErrorKind::Database,
+            AuthError::PasswordHashing(_) => ErrorKind::PasswordHashing,
+            AuthError::Validation(_) | AuthError::UserAlreadyExists(_) | AuthError::WeakPassword { .. } => {
+                ErrorKind::Validation
+            }
+            AuthError::Input(_) | AuthError::Mail(_) => ErrorKind::Input,
+            AuthError::NotFound(_) => ErrorKind::NotFound,
+            AuthError::PermissionDenied(_) => ErrorKind::PermissionDenied,
+            AuthError::InvalidCredentials => ErrorKind::InvalidCredentials,
+            AuthError::AccountLocked(_) => ErrorKind::AccountLocked,
+            AuthError::AccountDisabled => ErrorKind::AccountDisabled,
+            AuthError::SecondFactorRequired => ErrorKind::SecondFactorRequired,
+            AuthError::PasswordExpired => ErrorKind::PasswordExpired,
+            AuthError::SessionExpired => ErrorKind::SessionExpired,
+        }
+    }
 
-impl From<rusqlite::Error> for AuthError {
-    fn from(err: rusqlite::Error) -> Self {
-        AuthError::Database(err)
+    /// Código estável do erro, para consumo por programas (saída JSON e API HTTP).
+    ///
+    /// Mais específico que a classe (`kind`) e, ao contrário da mensagem, independe
+    /// do idioma: integrações devem decidir por ele, nunca pelo texto.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthError::Database(_) => "E_DATABASE",
+            AuthError::Postgres(_) => "E_POSTGRES",
+            AuthError::ConnectionPool(_) => "E_CONNECTION_POOL",
+            AuthError::PasswordHashing(_) => "E_PASSWORD_HASHING",
+            AuthError::Validation(_) => "E_VALIDATION",
+            AuthError::UserAlreadyExists(_) => "E_USER_EXISTS",
+            AuthError::WeakPassword { .. } => "E_WEAK_PASSWORD",
+            AuthError::Input(_) => "E_INPUT",
+            AuthError::NotFound(_) => "E_NOT_FOUND",
+            AuthError::PermissionDenied(_) => "E_PERMISSION_DENIED",
+            AuthError::CorruptDatabase(_) => "E_CORRUPT_DATABASE",
+            AuthError::Mail(_) => "E_MAIL",
+            AuthError::InvalidCredentials => "E_INVALID_CREDENTIALS",
+            AuthError::AccountLocked(_) => "E_ACCOUNT_LOCKED",
+            AuthError::AccountDisabled => "E_ACCOUNT_DISABLED",
+            AuthError::SecondFactorRequired => "E_SECOND_FACTOR_REQUIRED",
+            AuthError::PasswordExpired => "E_PASSWORD_EXPIRED",
+            AuthError::SessionExpired => "E_SESSION_EXPIRED",
+        }
     }
-}
 
-impl From<std::io::Error> for AuthError {
-    fn from(err: std::io::Error) -> Self {
-        AuthError::Input(err)
+    /// Mensagem sem o prefixo da classe (ex.: sem "Erro de validação: "), para quem já
+    /// indica a classe de outra forma, como os avisos dos menus
+    pub fn detail(&self) -> String {
+        match self {
+            AuthError::Validation(msg) => msg.to_string(),
+            AuthError::PermissionDenied(msg) => msg.to_string(),
+            AuthError::UserAlreadyExists(username) => tr!("Usuário '{}' já existe", username),
+            AuthError::WeakPassword { reason } => reason.to_string(),
+            err => err.to_string(),
+        }
     }
 }
 
 /// Tipo Result personalizado para o sistema
 pub type AuthResult<T> = Result<T, AuthError>;
 
-/// Macro para criar erros de validação rapidamente
+/// Macro para criar um `AuthError::Validation` como valor (para `map_err`, closures e braços de match).
+///
+/// A mensagem é guardada com `message!` e traduzida só ao ser exibida; vale a mesma
+/// regra de `tr!`: só `{}` como marcador.
+///
+/// ```
+/// use siri_ferrugem::{validation, AuthError};
+///
+/// let err = validation!("Limite de {} usuários atingido", 10);
+/// let AuthError::Validation(msg) = &err else { panic!("esperava um erro de validação") };
+/// assert_eq!(msg.source(), "Limite de {} usuários atingido");
+/// assert_eq!(msg.args(), ["10"]);
+/// assert_eq!(err.code(), "E_VALIDATION");
+/// ```
 #[macro_export]
-macro_rules! validation_error {
-    ($msg:expr) => {
-        Err(crate::error::AuthError::Validation($msg.to_string()))
+macro_rules! validation {
+    ($($arg:tt)+) => {
+        $crate::error::AuthError::Validation($crate::message!($($arg)+))
     };
-    ($fmt:expr, $($arg:tt)*) => {
-        Err(crate::error::AuthError::Validation(format!($fmt, $($arg)*)))
+}
+
+/// Macro para criar erros de validação rapidamente, já embrulhados em `Err`
+///
+/// ```
+/// use siri_ferrugem::{validation_error, AuthResult, ErrorKind};
+///
+/// fn check_age(age: u32) -> AuthResult<u32> {
+///     if age < 18 {
+///         return validation_error!("Idade mínima: {} anos", 18);
+///     }
+///     Ok(age)
+/// }
+///
+/// assert_eq!(check_age(30).unwrap(), 30);
+/// let err = check_age(12).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Validation);
+/// assert_eq!(err.kind().exit_code(), 2);
+/// ```
+#[macro_export]
+macro_rules! validation_error {
+    ($($arg:tt)+) => {
+        Err($crate::validation!($($arg)+))
     };
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use chrono::TimeZone;
+    use super::*;
+    use crate::message;
+
+    /// Erro do PostgreSQL sem rede: o socket Unix não existe
+    fn postgres_error() -> postgres::Error {
+        postgres::Client::connect("host=/nao/existe user=siri", postgres::NoTls).err().unwrap()
+    }
+
+    /// Erro do pool: nenhuma conexão pode ser aberta dentro do prazo
+    fn pool_error() -> r2d2::Error {
+        let manager = r2d2_sqlite::SqliteConnectionManager::file("/nao/existe/users.db");
+        r2d2::Pool::builder()
+            .connection_timeout(std::time::Duration::from_millis(10))
+            .build(manager)
+            .err()
+            .unwrap()
+    }
+
+    fn every_variant() -> Vec<(AuthError, ErrorKind, &'static str, i32)> {
+        let locked_until = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        vec![
+            (rusqlite::Error::QueryReturnedNoRows.into(), ErrorKind::Database, "E_DATABASE", 3),
+            (postgres_error().into(), ErrorKind::Database, "E_POSTGRES", 3),
+            (pool_error().into(), ErrorKind::Database, "E_CONNECTION_POOL", 3),
+            (AuthError::PasswordHashing(message!("Erro ao analisar hash: {}", "salt")), ErrorKind::PasswordHashing, "E_PASSWORD_HASHING", 7),
+            (AuthError::Validation(message!("Código inválido")), ErrorKind::Validation, "E_VALIDATION", 2),
+            (AuthError::UserAlreadyExists("alice".into()), ErrorKind::Validation, "E_USER_EXISTS", 2),
+            (PasswordIssue::MissingDigit.into(), ErrorKind::Validation, "E_WEAK_PASSWORD", 2),
+            (std::io::Error::other("eof").into(), ErrorKind::Input, "E_INPUT", 5),
+            (AuthError::NotFound(message!("Usuário '{}'", "alice")), ErrorKind::NotFound, "E_NOT_FOUND", 6),
+            (AuthError::PermissionDenied(message!("'{}' não é administrador", "bob")), ErrorKind::PermissionDenied, "E_PERMISSION_DENIED", 4),
+            (AuthError::CorruptDatabase(Message::verbatim("users.db")), ErrorKind::Database, "E_CORRUPT_DATABASE", 3),
+            (AuthError::Mail("smtp".into()), ErrorKind::Input, "E_MAIL", 5),
+            (AuthError::InvalidCredentials, ErrorKind::InvalidCredentials, "E_INVALID_CREDENTIALS", 1),
+            (AuthError::AccountLocked(locked_until), ErrorKind::AccountLocked, "E_ACCOUNT_LOCKED", 8),
+            (AuthError::AccountDisabled, ErrorKind::AccountDisabled, "E_ACCOUNT_DISABLED", 9),
+            (AuthError::SecondFactorRequired, ErrorKind::SecondFactorRequired, "E_SECOND_FACTOR_REQUIRED", 10),
+            (AuthError::PasswordExpired, ErrorKind::PasswordExpired, "E_PASSWORD_EXPIRED", 11),
+            (AuthError::SessionExpired, ErrorKind::SessionExpired, "E_SESSION_EXPIRED", 12),
+        ]
+    }
+
+    #[test]
+    fn each_variant_has_its_documented_kind_code_and_exit_code() {
+        for (err, kind, code, exit_code) in every_variant() {
+            assert_eq!(err.kind(), kind, "{:?}", err);
+            assert_eq!(err.code(), code, "{:?}", err);
+            assert_eq!(err.kind().exit_code(), exit_code, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        let variants = every_variant();
+        let codes: HashSet<_> = variants.iter().map(|(err, ..)| err.code()).collect();
+        assert_eq!(codes.len(), variants.len());
+    }
+
+    #[test]
+    fn detail_drops_the_class_prefix() {
+        let err = AuthError::UserAlreadyExists("alice".into());
+        assert_eq!(err.to_string(), tr!("Erro de validação: {}", err.detail()));
+        assert_eq!(err.detail(), tr!("Usuário '{}' já existe", "alice"));
+    }
+
+    #[test]
+    fn not_found_keeps_the_untranslated_source() {
+        let err = AuthError::NotFound(message!("Usuário '{}'", "alice"));
+        let AuthError::NotFound(what) = &err else { unreachable!() };
+        assert_eq!(what.source(), "Usuário '{}'");
+        assert_eq!(what.args(), ["alice"]);
+        assert_eq!(err.to_string(), tr!("Não encontrado: {}", tr!("Usuário '{}'", "alice")));
+    }
+}
@@ -0,0 +1,164 @@
+//! Eventos de autenticação, entregues por webhook aos endereços configurados.
+//!
+//! Cada evento vira um POST com um corpo JSON assinado com HMAC-SHA256: o cabeçalho
+//! `X-Siri-Signature: sha256=<hex>` permite ao receptor conferir que o aviso veio
+//! do Siri. A entrega é feita na hora, com um prazo curto; uma falha só gera um
+//! aviso no log, para que um receptor fora do ar não impeça logins e cadastros.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use crate::{config, metrics};
+use crate::error::AuthResult;
+use crate::user::TimestampDisplay;
+use crate::{validation, validation_error};
+
+/// Prazo de cada entrega
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Seção `[webhooks]` do arquivo de configuração
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Endereços que recebem todos os eventos (vazio = sem webhooks)
+    pub urls: Vec<String>,
+    /// Segredo das assinaturas; tem prioridade sobre `secret_file`
+    pub secret: Option<String>,
+    /// Arquivo com o segredo das assinaturas
+    pub secret_file: Option<PathBuf>,
+    /// Falhas de login seguidas de uma conta que disparam `login_failure_streak` (0 = nunca)
+    pub failure_streak: u32,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig { urls: Vec::new(), secret: None, secret_file: None, failure_streak: 3 }
+    }
+}
+
+impl WebhookConfig {
+    /// Confere que há um segredo para assinar, se houver algum endereço
+    pub(crate) fn validate(&self) -> AuthResult<()> {
+        let has_secret = self.secret.is_some() || self.secret_file.is_some();
+        if !self.urls.is_empty() && !has_secret {
+            return validation_error!("webhooks.urls requer um segredo (webhooks.secret ou webhooks.secret_file)");
+        }
+        Ok(())
+    }
+}
+
+/// Acontecimento avisado aos webhooks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Conta cadastrada
+    UserRegistered { username: String },
+    /// Uma conta chegou a `failure_streak` falhas de login seguidas
+    LoginFailureStreak { username: String, failures: u32 },
+    /// Conta bloqueada, por excesso de tentativas ou por um administrador
+    AccountLocked { username: String, until: DateTime<Utc> },
+    /// Senha trocada pelo dono, redefinida com um token ou definida por um administrador
+    PasswordChanged { username: String },
+}
+
+impl Event {
+    /// Nome estável do evento, enviado no corpo e no cabeçalho `X-Siri-Event`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::UserRegistered { .. } => "user_registered",
+            Event::LoginFailureStreak { .. } => "login_failure_streak",
+            Event::AccountLocked { .. } => "account_locked",
+            Event::PasswordChanged { .. } => "password_changed",
+        }
+    }
+
+    /// Corpo do webhook
+    pub fn payload(&self, occurred_at: DateTime<Utc>) -> Value {
+        let (username, details) = match self {
+            Event::UserRegistered { username } | Event::PasswordChanged { username } => (username, json!({})),
+            Event::LoginFailureStreak { username, failures } => (username, json!({ "failures": failures })),
+            Event::AccountLocked { username, until } => {
+                (username, json!({ "locked_until": TimestampDisplay::Utc.format(*until) }))
+            }
+        };
+        json!({
+            "event": self.name(),
+            "username": username,
+            "occurred_at": TimestampDisplay::Utc.format(occurred_at),
+            "details": details,
+        })
+    }
+}
+
+/// Assinatura do corpo, no formato do cabeçalho `X-Siri-Signature`
+pub fn sign(secret: &[u8], body: &[u8]) -> AuthResult<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|e| validation!("Segredo dos webhooks inválido: {}", e))?;
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    Ok(format!("sha256={}", digest.iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+}
+
+/// Segredo das assinaturas: `secret` ou o conteúdo de `secret_file`
+fn load_secret(config: &WebhookConfig) -> AuthResult<Option<Vec<u8>>> {
+    if let Some(secret) = &config.secret {
+        return Ok(Some(secret.clone().into_bytes()));
+    }
+    match &config.secret_file {
+        Some(path) => Ok(Some(fs::read(path)?.trim_ascii_end().to_vec())),
+        None => Ok(None),
+    }
+}
+
+/// Envia o evento a todos os webhooks configurados.
+///
+/// Nunca falha: problemas de entrega (ou de leitura do segredo) ficam só no log.
+pub fn emit(event: Event, occurred_at: DateTime<Utc>) {
+    metrics::record_event(&event);
+    let config = &config::current().webhooks;
+    if config.urls.is_empty() {
+        return;
+    }
+    let secret = match load_secret(config) {
+        Ok(Some(secret)) => secret,
+        Ok(None) => {
+            log::warn!("evento {} não enviado: webhooks sem segredo configurado", event.name());
+            return;
+        }
+        Err(err) => {
+            log::warn!("evento {} não enviado: segredo dos webhooks ilegível: {}", event.name(), err);
+            return;
+        }
+    };
+
+    let body = event.payload(occurred_at).to_string();
+    let signature = match sign(&secret, body.as_bytes()) {
+        Ok(signature) => signature,
+        Err(err) => {
+            log::warn!("evento {} não enviado: {}", event.name(), err);
+            return;
+        }
+    };
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(DELIVERY_TIMEOUT))
+        .build()
+        .new_agent();
+
+    for url in &config.urls {
+        let result = agent
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", concat!("siri-ferrugem/", env!("CARGO_PKG_VERSION")))
+            .header("X-Siri-Event", event.name())
+            .header("X-Siri-Signature", &signature)
+            .send(body.as_str());
+        match result {
+            Ok(_) => log::info!("evento {} entregue a {}", event.name(), url),
+            Err(err) => log::warn!("falha ao entregar o evento {} a {}: {}", event.name(), url, err),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+use crate::audit::{self, AuditEntry, AuditFilter};
+use crate::db::secure_file_permissions;
+use crate::error::{AuthError, AuthResult};
+use crate::rbac::user_permissions;
+use crate::store::UserStore;
+use crate::totp::is_totp_enabled;
+use crate::user::{EmailStatus, TimestampDisplay, User};
+use crate::{message, validation};
+
+/// Limite usado para trazer todas as linhas (o maior que os dois bancos aceitam)
+const NO_LIMIT: usize = i64::MAX as usize;
+
+/// Reúne tudo o que o sistema guarda sobre um usuário em um único documento JSON,
+/// para pedidos de portabilidade de dados (LGPD/GDPR).
+///
+/// Hashes de senha, segredos do segundo fator e tokens ficam de fora: servem só
+/// para autenticar e não dizem nada sobre a pessoa. As datas vão sempre em UTC.
+pub fn export_user_data(store: &dyn UserStore, username: &str, now: DateTime<Utc>) -> AuthResult<Value> {
+    let Some(user) = store.find_user(username)? else {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    };
+    let utc = |value: DateTime<Utc>| TimestampDisplay::Utc.format(value);
+
+    let permissions: Vec<&str> = user_permissions(store, username)?.iter().map(|p| p.name()).collect();
+    let profile = json!({
+        "id": user.id,
+        "username": user.username,
+        "display_name": user.display_name,
+        "email": user.email,
+        "email_verified": user.email_status() == EmailStatus::Verified,
+        "email_verified_at": user.email_verified_at.map(utc),
+        "phone": user.phone,
+        "bio": user.bio,
+        "created_at": utc(user.created_at),
+        "last_login_at": user.last_login_at.map(utc),
+        "login_count": user.login_count,
+        "role": user.role.name(),
+        "status": user.status.name(),
+        "permissions": permissions,
+        "groups": store.user_groups(username)?,
+        "two_factor": is_totp_enabled(store, username)?,
+    });
+
+    let sessions: Vec<Value> = store
+        .user_sessions(username)?
+        .into_iter()
+        .map(|session| {
+            json!({
+                "created_at": utc(session.created_at),
+                "expires_at": utc(session.expires_at),
+                "active": session.expires_at > now,
+            })
+        })
+        .collect();
+
+    let login_history: Vec<Value> = store
+        .login_history(username, NO_LIMIT)?
+        .into_iter()
+        .map(|attempt| {
+            json!({
+                "attempted_at": utc(attempt.attempted_at),
+                "success": attempt.success,
+                "outcome": attempt.outcome,
+                "source": attempt.source,
+            })
+        })
+        .collect();
+
+    let audit_entries: Vec<Value> = user_audit_entries(store, username)?
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "created_at": utc(entry.created_at),
+                "actor": entry.actor,
+                "action": entry.action,
+                "target": entry.target,
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "exported_at": utc(now),
+        "profile": profile,
+        "sessions": sessions,
+        "login_history": login_history,
+        "audit_log": audit_entries,
+    }))
+}
+
+/// Entradas do log em que o usuário é o autor ou o alvo, das mais recentes para as mais antigas
+fn user_audit_entries(store: &dyn UserStore, username: &str) -> AuthResult<Vec<AuditEntry>> {
+    let as_actor = AuditFilter { actor: Some(username.to_string()), limit: NO_LIMIT, ..AuditFilter::default() };
+    let as_target = AuditFilter { target: Some(username.to_string()), limit: NO_LIMIT, ..AuditFilter::default() };
+
+    let mut entries = audit::search(store, &as_actor)?;
+    entries.extend(audit::search(store, &as_target)?);
+    entries.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    entries.dedup_by_key(|entry| entry.id);
+    Ok(entries)
+}
+
+/// Formato de `siri export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Uma linha por usuário, com cabeçalho
+    Csv,
+    /// Uma lista de objetos
+    Json,
+}
+
+/// O que entra em `export_users` além das colunas básicas
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsersExport {
+    /// Nome de exibição, telefone e bio
+    pub profile: bool,
+    /// Hash da senha de cada conta; quem chama deve ter autorizado a exportação
+    pub hashes: bool,
+}
+
+/// Colunas da exportação, na ordem do CSV
+fn user_columns(fields: UsersExport) -> Vec<&'static str> {
+    let mut columns = vec![
+        "id", "username", "email", "email_verified", "role", "status", "created_at", "last_login_at", "login_count",
+    ];
+    if fields.profile {
+        columns.extend(["display_name", "phone", "bio"]);
+    }
+    if fields.hashes {
+        columns.push("password_hash");
+    }
+    columns
+}
+
+/// Valores de um usuário, na ordem de `user_columns`
+fn user_values(store: &dyn UserStore, user: &User, fields: UsersExport) -> AuthResult<Vec<Value>> {
+    let utc = |value: DateTime<Utc>| TimestampDisplay::Utc.format(value);
+    let mut values = vec![
+        json!(user.id),
+        json!(user.username),
+        json!(user.email),
+        json!(user.email_status() == EmailStatus::Verified),
+        json!(user.role.name()),
+        json!(user.status.name()),
+        json!(utc(user.created_at)),
+        json!(user.last_login_at.map(utc)),
+        json!(user.login_count),
+    ];
+    if fields.profile {
+        values.extend([json!(user.display_name), json!(user.phone), json!(user.bio)]);
+    }
+    if fields.hashes {
+        values.push(json!(store.get_password_hash(&user.username)?));
+    }
+    Ok(values)
+}
+
+/// Valor de uma célula do CSV: ausente vira campo vazio
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Grava todos os usuários no arquivo, no formato escolhido, retornando quantos foram exportados.
+///
+/// O arquivo é criado com permissão 600, já que pode conter emails e hashes.
+pub fn export_users(store: &dyn UserStore, path: &Path, format: ExportFormat, fields: UsersExport) -> AuthResult<usize> {
+    let columns = user_columns(fields);
+    let rows = store
+        .list_users()?
+        .iter()
+        .map(|user| user_values(store, user, fields))
+        .collect::<AuthResult<Vec<_>>>()?;
+
+    let existed = path.exists();
+    let mut writer = BufWriter::new(File::create(path)?);
+    secure_file_permissions(path, existed)?;
+    match format {
+        ExportFormat::Json => {
+            let records: Vec<Map<String, Value>> = rows
+                .iter()
+                .map(|row| columns.iter().map(|column| column.to_string()).zip(row.iter().cloned()).collect())
+                .collect();
+            serde_json::to_writer_pretty(&mut writer, &records).map_err(io::Error::other)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(&mut writer);
+            let write_error = |e: csv::Error| validation!("Falha ao gravar o CSV: {}", e);
+            csv.write_record(&columns).map_err(write_error)?;
+            for row in &rows {
+                csv.write_record(row.iter().map(csv_cell)).map_err(write_error)?;
+            }
+            csv.flush()?;
+        }
+    }
+    writer.flush()?;
+    Ok(rows.len())
+}
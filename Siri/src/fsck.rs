@@ -0,0 +1,174 @@
+//! Verificação completa do banco SQLite (`siri fsck`), para monitoramento.
+//!
+//! Vai além do `siri health`: roda `PRAGMA integrity_check`, confere se cada hash de
+//! senha gravado é legível pelo esquema que ele anuncia e procura linhas órfãs, cuja
+//! conta (ou grupo, ou permissão) não existe mais. Lê o banco inteiro, então não é
+//! para ser chamada a cada poucos segundos.
+
+use std::str::FromStr;
+use argon2::PasswordHash;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use crate::auth::LegacyHashPolicy;
+use crate::commands::CommandOutput;
+use crate::config;
+use crate::db::check_integrity;
+use crate::error::AuthResult;
+use crate::hasher::HashScheme;
+use crate::tr;
+
+/// Código de saída de `siri fsck` quando algum problema é encontrado
+pub const PROBLEMS_FOUND: i32 = 1;
+
+/// Hash de senha que não pode ser usado
+#[derive(Debug, Clone)]
+pub struct InvalidHash {
+    pub username: String,
+    pub reason: String,
+}
+
+/// Linhas de uma tabela que apontam para um registro inexistente
+#[derive(Debug, Clone)]
+pub struct Orphans {
+    pub table: String,
+    /// Tabela do registro que falta
+    pub parent: String,
+    pub rows: usize,
+}
+
+/// Resultado de `check`
+#[derive(Debug, Clone, Default)]
+pub struct FsckReport {
+    /// Problema apontado pelo `PRAGMA integrity_check` (None = íntegro)
+    pub integrity: Option<String>,
+    /// Hashes conferidos, de todas as contas (inclusive as excluídas)
+    pub hashes_checked: usize,
+    pub invalid_hashes: Vec<InvalidHash>,
+    /// Contas com hash legado aceito pela política (`hashing.legacy_hashes`)
+    pub legacy_hashes: Vec<String>,
+    pub orphans: Vec<Orphans>,
+}
+
+impl FsckReport {
+    /// Se nenhum problema foi encontrado; hashes legados aceitos não contam como problema
+    pub fn clean(&self) -> bool {
+        self.integrity.is_none() && self.invalid_hashes.is_empty() && self.orphans.is_empty()
+    }
+
+    /// Relatório para o `--json`
+    pub fn to_json(&self) -> Value {
+        json!({
+            "clean": self.clean(),
+            "integrity": { "ok": self.integrity.is_none(), "detail": self.integrity },
+            "hashes": {
+                "checked": self.hashes_checked,
+                "invalid": self
+                    .invalid_hashes
+                    .iter()
+                    .map(|hash| json!({ "username": hash.username, "reason": hash.reason }))
+                    .collect::<Vec<_>>(),
+                "legacy": self.legacy_hashes,
+            },
+            "orphans": self
+                .orphans
+                .iter()
+                .map(|orphans| json!({ "table": orphans.table, "parent": orphans.parent, "rows": orphans.rows }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Relatório como saída de comando
+    pub fn output(&self) -> CommandOutput {
+        let mut message = if self.clean() {
+            format!("💚 {}", tr!("Nenhum problema encontrado"))
+        } else {
+            format!("💔 {}", tr!("Foram encontrados problemas no banco"))
+        };
+
+        match &self.integrity {
+            None => message.push_str(&format!("\n   ✅ {}", tr!("Integridade do SQLite: ok"))),
+            Some(problem) => message.push_str(&format!("\n   ❌ {}", tr!("Integridade do SQLite: {}", problem))),
+        }
+
+        let mark = if self.invalid_hashes.is_empty() { "✅" } else { "❌" };
+        message.push_str(&format!(
+            "\n   {} {}",
+            mark,
+            tr!("Hashes: {} conferidos, {} inválidos", self.hashes_checked, self.invalid_hashes.len())
+        ));
+        for hash in &self.invalid_hashes {
+            message.push_str(&format!("\n      {}: {}", hash.username, hash.reason));
+        }
+        if !self.legacy_hashes.is_empty() {
+            message.push_str(&format!(
+                "\n   ⚠️  {}",
+                tr!("{} hashes legados, aceitos por hashing.legacy_hashes", self.legacy_hashes.len())
+            ));
+        }
+
+        if self.orphans.is_empty() {
+            message.push_str(&format!("\n   ✅ {}", tr!("Nenhuma linha órfã")));
+        }
+        for orphans in &self.orphans {
+            message.push_str(&format!(
+                "\n   ❌ {}",
+                tr!("{} linhas órfãs em {} (sem o registro em {})", orphans.rows, orphans.table, orphans.parent)
+            ));
+        }
+
+        CommandOutput { data: self.to_json(), message }
+    }
+}
+
+/// Confere o banco inteiro
+pub fn check(conn: &Connection) -> AuthResult<FsckReport> {
+    let mut report = FsckReport { integrity: check_integrity(conn).err(), ..FsckReport::default() };
+
+    let policy = config::current().hashing.legacy_hashes;
+    let mut stmt = conn.prepare("SELECT username, password_hash FROM users ORDER BY username")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (username, password_hash) = row?;
+        report.hashes_checked += 1;
+        match parse_hash(&password_hash) {
+            Ok(Some(_)) => {}
+            // Fora de qualquer esquema: só entra se a política aceitar hashes legados
+            Ok(None) if policy != LegacyHashPolicy::Reject => report.legacy_hashes.push(username),
+            Ok(None) => report.invalid_hashes.push(InvalidHash {
+                username,
+                reason: tr!("formato desconhecido; o login sempre falha").to_string(),
+            }),
+            Err(reason) => report.invalid_hashes.push(InvalidHash { username, reason }),
+        }
+    }
+
+    // Todas as tabelas filhas declaram as chaves estrangeiras, então o próprio SQLite as confere
+    let mut stmt = conn.prepare(
+        "SELECT \"table\", parent, COUNT(*) FROM pragma_foreign_key_check GROUP BY \"table\", parent ORDER BY \"table\"",
+    )?;
+    report.orphans = stmt
+        .query_map([], |row| Ok(Orphans { table: row.get(0)?, parent: row.get(1)?, rows: row.get(2)? }))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(report)
+}
+
+/// Esquema de um hash gravado, se ele for legível pelo esquema que anuncia.
+///
+/// `Ok(None)` é um valor que não anuncia esquema nenhum (um hash legado).
+fn parse_hash(stored: &str) -> Result<Option<HashScheme>, String> {
+    let Some(scheme) = HashScheme::detect(stored) else {
+        return Ok(None);
+    };
+    let parsed = match scheme {
+        // O bcrypt tem o formato próprio ($2b$<custo>$<salt e hash>), anterior ao PHC
+        HashScheme::Bcrypt => bcrypt::HashParts::from_str(stored).map(|_| ()).map_err(|err| err.to_string()),
+        // O PHC aceita strings só com os parâmetros, que não servem para conferir senha nenhuma
+        _ => match PasswordHash::new(stored) {
+            Ok(hash) if hash.salt.is_some() && hash.hash.is_some() => Ok(()),
+            Ok(_) => Err(tr!("sem salt ou sem hash").to_string()),
+            Err(err) => Err(err.to_string()),
+        },
+    };
+    parsed.map(|()| Some(scheme)).map_err(|err| tr!("hash {} ilegível: {}", scheme, err))
+}
@@ -0,0 +1,139 @@
+use rand_core::{OsRng, RngCore};
+use zeroize::Zeroizing;
+use crate::auth::{validate_password_strength, PasswordConfig, SPECIAL_CHARACTERS};
+use crate::error::AuthResult;
+use crate::strength::estimate_strength;
+use crate::{tr, validation_error};
+
+/// Tamanho padrão de uma senha gerada
+pub const DEFAULT_GENERATED_LENGTH: usize = 20;
+
+/// Tamanho máximo aceito, para não gerar senhas que nenhum campo comporta
+pub const MAX_GENERATED_LENGTH: usize = 128;
+
+/// Sorteios antes de desistir de uma senha que passe na estimativa de força
+const MAX_GENERATION_ATTEMPTS: usize = 100;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+
+/// Tamanho e conjuntos de caracteres de uma senha gerada
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneratorOptions {
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    /// Caracteres especiais aceitos por `require_special`
+    pub symbols: bool,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        GeneratorOptions {
+            length: DEFAULT_GENERATED_LENGTH,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+        }
+    }
+}
+
+impl GeneratorOptions {
+    /// Conjuntos de caracteres ligados
+    fn charsets(&self) -> Vec<&'static str> {
+        [
+            (self.lowercase, LOWERCASE),
+            (self.uppercase, UPPERCASE),
+            (self.digits, DIGITS),
+            (self.symbols, SPECIAL_CHARACTERS),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, charset)| enabled.then_some(charset))
+        .collect()
+    }
+
+    /// Confere se as opções conseguem produzir uma senha aceita pela configuração
+    fn validate(&self, config: &PasswordConfig) -> AuthResult<()> {
+        let charsets = self.charsets();
+        if charsets.is_empty() {
+            return validation_error!("Escolha pelo menos um conjunto de caracteres");
+        }
+        if self.length > MAX_GENERATED_LENGTH {
+            return validation_error!("O tamanho máximo de uma senha gerada é {}", MAX_GENERATED_LENGTH);
+        }
+        if self.length < config.min_length {
+            return validation_error!("A senha deve ter pelo menos {} caracteres", config.min_length);
+        }
+        if self.length < charsets.len() {
+            return validation_error!("O tamanho deve comportar um caractere de cada conjunto escolhido");
+        }
+
+        let required = [
+            (config.require_lowercase && !self.lowercase, tr!("letras minúsculas")),
+            (config.require_uppercase && !self.uppercase, tr!("letras maiúsculas")),
+            (config.require_digit && !self.digits, tr!("números")),
+            (config.require_special && !self.symbols, tr!("caracteres especiais")),
+        ];
+        if let Some((_, name)) = required.iter().find(|(missing, _)| *missing) {
+            return validation_error!("A política de senhas exige {}", name);
+        }
+        Ok(())
+    }
+}
+
+/// Gera uma senha aleatória que cumpre `config`.
+///
+/// Cada conjunto escolhido aparece pelo menos uma vez, e as posições são
+/// embaralhadas depois. Sorteios que por acaso formem um padrão fraco
+/// (uma palavra comum, uma sequência) são descartados.
+pub fn generate_password(options: &GeneratorOptions, config: &PasswordConfig) -> AuthResult<Zeroizing<String>> {
+    options.validate(config)?;
+    let charsets = options.charsets();
+    let alphabet: Vec<char> = charsets.concat().chars().collect();
+
+    for _ in 0..MAX_GENERATION_ATTEMPTS {
+        let mut chars = Zeroizing::new(Vec::with_capacity(options.length));
+        for charset in &charsets {
+            chars.push(pick(&charset.chars().collect::<Vec<_>>()));
+        }
+        while chars.len() < options.length {
+            chars.push(pick(&alphabet));
+        }
+        shuffle(&mut chars);
+
+        let password = Zeroizing::new(chars.iter().collect::<String>());
+        if estimate_strength(&password, &[]).score >= config.min_strength {
+            // Confere também as regras e, se ligada, a base de senhas vazadas
+            validate_password_strength(&password, config, &[])?;
+            return Ok(password);
+        }
+    }
+
+    validation_error!("Não foi possível gerar uma senha forte o suficiente; aumente o tamanho ou os conjuntos")
+}
+
+/// Índice uniforme em `0..bound`, descartando os valores que causariam viés do módulo
+fn random_index(bound: usize) -> usize {
+    let bound = bound as u32;
+    let zone = u32::MAX - u32::MAX % bound;
+    loop {
+        let value = OsRng.next_u32();
+        if value < zone {
+            return (value % bound) as usize;
+        }
+    }
+}
+
+fn pick(chars: &[char]) -> char {
+    chars[random_index(chars.len())]
+}
+
+/// Embaralhamento de Fisher-Yates
+fn shuffle(chars: &mut [char]) {
+    for i in (1..chars.len()).rev() {
+        chars.swap(i, random_index(i + 1));
+    }
+}
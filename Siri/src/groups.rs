@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use crate::audit;
+use crate::error::{AuthError, AuthResult};
+use crate::store::UserStore;
+use crate::{message, validation_error};
+
+/// Tamanho máximo do nome de um grupo
+const GROUP_NAME_MAX_LENGTH: usize = 64;
+
+/// Grupo de usuários, para autorização por grupo nas aplicações que usam o Siri
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub name: String,
+    /// Membros com conta ativa (não excluída)
+    pub members: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Valida o nome de um grupo: letras, números, '.', '_' e '-'
+fn validate_group_name(name: &str) -> AuthResult<()> {
+    if name.is_empty() || name.chars().count() > GROUP_NAME_MAX_LENGTH {
+        return validation_error!("O nome do grupo deve ter entre 1 e {} caracteres", GROUP_NAME_MAX_LENGTH);
+    }
+    if !name.chars().all(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '-')) {
+        return validation_error!("O nome do grupo só pode conter letras, números, '.', '_' e '-'");
+    }
+    Ok(())
+}
+
+/// Falha com `NotFound` se o grupo não existe
+fn require_group(store: &dyn UserStore, group: &str) -> AuthResult<()> {
+    if !store.group_exists(group)? {
+        return Err(AuthError::NotFound(message!("Grupo '{}'", group)));
+    }
+    Ok(())
+}
+
+/// Falha com `NotFound` se o usuário não existe
+fn require_user(store: &dyn UserStore, username: &str) -> AuthResult<()> {
+    if !store.user_exists(username)? {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    }
+    Ok(())
+}
+
+/// Cria um grupo vazio
+pub fn create_group(store: &dyn UserStore, actor: &str, name: &str) -> AuthResult<()> {
+    let name = name.trim();
+    validate_group_name(name)?;
+    store.insert_group(name)?;
+    audit::record(store, actor, "create_group", Some(name))
+}
+
+/// Apaga um grupo; as contas dos membros não são afetadas
+pub fn delete_group(store: &dyn UserStore, actor: &str, name: &str) -> AuthResult<()> {
+    if !store.delete_group(name)? {
+        return Err(AuthError::NotFound(message!("Grupo '{}'", name)));
+    }
+    audit::record(store, actor, "delete_group", Some(name))
+}
+
+/// Lista os grupos em ordem alfabética, com a quantidade de membros
+pub fn list_groups(store: &dyn UserStore) -> AuthResult<Vec<Group>> {
+    store.list_groups()
+}
+
+/// Adiciona um usuário a um grupo
+pub fn add_member(store: &dyn UserStore, actor: &str, group: &str, username: &str) -> AuthResult<()> {
+    require_group(store, group)?;
+    require_user(store, username)?;
+    if !store.add_group_member(group, username)? {
+        return validation_error!("'{}' já pertence ao grupo '{}'", username, group);
+    }
+    audit::record(store, actor, "add_group_member", Some(username))
+}
+
+/// Remove um usuário de um grupo
+pub fn remove_member(store: &dyn UserStore, actor: &str, group: &str, username: &str) -> AuthResult<()> {
+    require_group(store, group)?;
+    require_user(store, username)?;
+    if !store.remove_group_member(group, username)? {
+        return validation_error!("'{}' não pertence ao grupo '{}'", username, group);
+    }
+    audit::record(store, actor, "remove_group_member", Some(username))
+}
+
+/// Membros de um grupo, em ordem alfabética
+pub fn group_members(store: &dyn UserStore, group: &str) -> AuthResult<Vec<String>> {
+    require_group(store, group)?;
+    store.group_members(group)
+}
+
+/// Grupos de um usuário, em ordem alfabética
+pub fn user_groups(store: &dyn UserStore, username: &str) -> AuthResult<Vec<String>> {
+    require_user(store, username)?;
+    store.user_groups(username)
+}
+
+/// Verifica se um usuário pertence a um grupo (false se algum dos dois não existe)
+pub fn is_member(store: &dyn UserStore, username: &str, group: &str) -> AuthResult<bool> {
+    Ok(store.user_groups(username)?.iter().any(|name| name == group))
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use std::path::PathBuf;
+use pbkdf2::Pbkdf2;
+use scrypt::password_hash::{PasswordHash, PasswordVerifier};
+use scrypt::Scrypt;
+use serde::Deserialize;
+use crate::auth::{hash_password_with_config, verify_argon2, Argon2Config, LegacyHashPolicy};
+use crate::error::{AuthError, AuthResult};
+use crate::{message, validation_error};
+
+/// Esquema capaz de gerar e verificar hashes de senha
+pub trait PasswordHasher {
+    /// Gera o hash da senha
+    fn hash(&self, password: &str) -> AuthResult<String>;
+    /// Verifica se a senha corresponde ao hash armazenado
+    fn verify(&self, password: &str, stored: &str) -> AuthResult<bool>;
+}
+
+/// Argon2, o esquema usado para todas as senhas novas
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Argon2Hasher {
+    pub config: Argon2Config,
+}
+
+impl PasswordHasher for Argon2Hasher {
+    fn hash(&self, password: &str) -> AuthResult<String> {
+        hash_password_with_config(password, &self.config)
+    }
+
+    fn verify(&self, password: &str, stored: &str) -> AuthResult<bool> {
+        verify_argon2(password, stored)
+    }
+}
+
+/// Custo usado ao gerar hashes bcrypt
+const BCRYPT_COST: u32 = 12;
+
+/// bcrypt, aceito para importar usuários de outros sistemas
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BcryptHasher;
+
+impl PasswordHasher for BcryptHasher {
+    fn hash(&self, password: &str) -> AuthResult<String> {
+        bcrypt::hash(password, BCRYPT_COST)
+            .map_err(|e| AuthError::PasswordHashing(message!("Erro ao hashear senha: {}", e)))
+    }
+
+    fn verify(&self, password: &str, stored: &str) -> AuthResult<bool> {
+        bcrypt::verify(password, stored)
+            .map_err(|e| AuthError::PasswordHashing(message!("Erro ao analisar hash: {}", e)))
+    }
+}
+
+/// scrypt (formato PHC), aceito para importar usuários de outros sistemas
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScryptHasher;
+
+impl PasswordHasher for ScryptHasher {
+    fn hash(&self, password: &str) -> AuthResult<String> {
+        use scrypt::password_hash::{rand_core::OsRng, PasswordHasher as _, SaltString};
+
+        let salt = SaltString::generate(&mut OsRng);
+        Scrypt
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| AuthError::PasswordHashing(message!("Erro ao hashear senha: {}", e)))
+    }
+
+    fn verify(&self, password: &str, stored: &str) -> AuthResult<bool> {
+        let parsed = PasswordHash::new(stored)
+            .map_err(|e| AuthError::PasswordHashing(message!("Erro ao analisar hash: {}", e)))?;
+        Ok(Scrypt.verify_password(password.as_bytes(), &parsed).is_ok())
+    }
+}
+
+/// PBKDF2-SHA256 (formato PHC, 600 mil iterações), para ambientes que exigem um
+/// algoritmo aprovado pelo FIPS
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Pbkdf2Hasher;
+
+impl PasswordHasher for Pbkdf2Hasher {
+    fn hash(&self, password: &str) -> AuthResult<String> {
+        use pbkdf2::password_hash::{rand_core::OsRng, PasswordHasher as _, SaltString};
+
+        let salt = SaltString::generate(&mut OsRng);
+        Pbkdf2
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| AuthError::PasswordHashing(message!("Erro ao hashear senha: {}", e)))
+    }
+
+    fn verify(&self, password: &str, stored: &str) -> AuthResult<bool> {
+        let parsed = PasswordHash::new(stored)
+            .map_err(|e| AuthError::PasswordHashing(message!("Erro ao analisar hash: {}", e)))?;
+        Ok(Pbkdf2.verify_password(password.as_bytes(), &parsed).is_ok())
+    }
+}
+
+/// Esquema de um hash armazenado, identificado pelo prefixo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashScheme {
+    #[default]
+    Argon2,
+    Bcrypt,
+    Scrypt,
+    Pbkdf2,
+}
+
+impl HashScheme {
+    /// Todos os esquemas suportados
+    pub const ALL: [HashScheme; 4] = [HashScheme::Argon2, HashScheme::Bcrypt, HashScheme::Scrypt, HashScheme::Pbkdf2];
+
+    /// Nome do esquema, como aceito em `hashing.scheme`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashScheme::Argon2 => "argon2",
+            HashScheme::Bcrypt => "bcrypt",
+            HashScheme::Scrypt => "scrypt",
+            HashScheme::Pbkdf2 => "pbkdf2",
+        }
+    }
+
+    /// Procura um esquema pelo nome, sem diferenciar maiúsculas/minúsculas
+    pub fn from_name(name: &str) -> Option<HashScheme> {
+        let name = name.trim();
+        HashScheme::ALL.into_iter().find(|scheme| scheme.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Identifica o esquema de um hash armazenado; `None` para formatos desconhecidos
+    pub fn detect(stored: &str) -> Option<HashScheme> {
+        if stored.starts_with("$argon2") {
+            Some(HashScheme::Argon2)
+        } else if ["$2a$", "$2b$", "$2x$", "$2y$"].iter().any(|prefix| stored.starts_with(prefix)) {
+            Some(HashScheme::Bcrypt)
+        } else if stored.starts_with("$scrypt$") {
+            Some(HashScheme::Scrypt)
+        } else if stored.starts_with("$pbkdf2") {
+            Some(HashScheme::Pbkdf2)
+        } else {
+            None
+        }
+    }
+
+    /// Verificador do esquema (o Argon2 usa os parâmetros gravados no próprio hash)
+    pub fn hasher(&self) -> Box<dyn PasswordHasher> {
+        match self {
+            HashScheme::Argon2 => Box::new(Argon2Hasher::default()),
+            HashScheme::Bcrypt => Box::new(BcryptHasher),
+            HashScheme::Scrypt => Box::new(ScryptHasher),
+            HashScheme::Pbkdf2 => Box::new(Pbkdf2Hasher),
+        }
+    }
+}
+
+impl fmt::Display for HashScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Seção `[hashing]` do arquivo de configuração
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HashingConfig {
+    /// Esquema das senhas novas; as existentes são refeitas no próximo login
+    pub scheme: HashScheme,
+    /// Como tratar hashes fora de qualquer esquema conhecido
+    pub legacy_hashes: LegacyHashPolicy,
+    /// Pepper (segredo global) usado como chave do Argon2; tem prioridade sobre `pepper_file`
+    pub pepper: Option<String>,
+    /// Arquivo com o pepper; os anteriores ficam em `<arquivo>.previous`
+    pub pepper_file: Option<PathBuf>,
+    /// Pepper anterior a uma rotação, ainda aceito ao verificar senhas
+    pub previous_pepper: Option<String>,
+}
+
+impl HashingConfig {
+    pub(crate) fn validate(&self) -> AuthResult<()> {
+        if self.pepper_file.as_ref().is_some_and(|path| path.as_os_str().is_empty()) {
+            return validation_error!("O caminho do arquivo do pepper não pode ser vazio");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{is_scheme_hash, login_user, LoginOutcome};
+    use crate::config::Config;
+    use crate::store::UserStore;
+    use crate::testing::{database, BCRYPT_HASH, PASSWORD};
+
+    #[test]
+    fn known_bcrypt_hash_verifies() {
+        assert_eq!(HashScheme::detect(BCRYPT_HASH), Some(HashScheme::Bcrypt));
+        assert!(BcryptHasher.verify(PASSWORD, BCRYPT_HASH).unwrap());
+        assert!(!BcryptHasher.verify("Senha-Errada-123", BCRYPT_HASH).unwrap());
+    }
+
+    #[test]
+    fn bcrypt_hash_is_rehashed_to_the_configured_scheme_on_login() {
+        let db = database();
+        db.insert_user("alice", BCRYPT_HASH, None).unwrap();
+        let stored_hash = |db: &dyn UserStore| db.get_credentials("alice").unwrap().unwrap().password_hash;
+
+        // Senha errada não toca no hash
+        assert_eq!(login_user(&db, "alice", "Senha-Errada-123").unwrap(), LoginOutcome::InvalidCredentials);
+        assert_eq!(stored_hash(&db), BCRYPT_HASH);
+
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+        let rehashed = stored_hash(&db);
+        // Argon2 é o esquema padrão, com os parâmetros persistidos no banco
+        assert!(is_scheme_hash(&rehashed, HashScheme::Argon2), "{}", rehashed);
+        assert!(rehashed.contains("m=8,t=1,p=1"), "{}", rehashed);
+        assert_eq!(login_user(&db, "alice", PASSWORD).unwrap(), LoginOutcome::Success);
+    }
+
+    #[test]
+    fn hashing_section_is_read_from_the_config_file() {
+        let config: Config = toml::from_str(
+            "[hashing]\nscheme = \"bcrypt\"\nlegacy_hashes = \"sha256\"\npepper_file = \"/etc/siri/pepper\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.hashing.scheme, HashScheme::Bcrypt);
+        assert_eq!(config.hashing.legacy_hashes, LegacyHashPolicy::Sha256);
+        assert_eq!(config.hashing.pepper_file, Some(PathBuf::from("/etc/siri/pepper")));
+
+        assert!(toml::from_str::<Config>("[hashing]\nscheme = \"md5\"\n").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Verificação de saúde (`siri health` e `GET /healthz`), para orquestradores.
+//!
+//! Confere se o banco responde, se o esquema está na versão deste binário e se um
+//! hash de teste é gerado e conferido com a configuração em vigor (esquema, custo
+//! e pepper). O teste de hash custa o mesmo que um login.
+
+use serde_json::{json, Value};
+use crate::auth::{hash_password, verify_password};
+use crate::commands::CommandOutput;
+use crate::error::{AuthError, AuthResult};
+use crate::migrations::{current_version, latest_version};
+use crate::service::AuthService;
+use crate::tr;
+
+/// Código de saída de `siri health` quando alguma verificação falha (o mesmo do HEALTHCHECK do Docker)
+pub const UNHEALTHY: i32 = 1;
+
+/// Senha usada no teste de hash
+const PROBE_PASSWORD: &str = "siri-health-probe";
+
+/// Resultado de uma verificação
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// Nome estável da verificação: `database`, `schema` ou `hash`
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn from_result(name: &'static str, result: AuthResult<(bool, String)>) -> Self {
+        match result {
+            Ok((ok, detail)) => Check { name, ok, detail },
+            Err(err) => Check { name, ok: false, detail: err.to_string() },
+        }
+    }
+}
+
+/// Resultado de todas as verificações
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub checks: Vec<Check>,
+}
+
+impl HealthReport {
+    /// Se todas as verificações passaram
+    pub fn healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.ok)
+    }
+
+    /// Relatório para o `--json` e para o `/healthz`
+    pub fn to_json(&self) -> Value {
+        json!({
+            "healthy": self.healthy(),
+            "checks": self
+                .checks
+                .iter()
+                .map(|check| json!({ "name": check.name, "ok": check.ok, "detail": check.detail }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Relatório como saída de comando
+    pub fn output(&self) -> CommandOutput {
+        let mut message = if self.healthy() {
+            format!("💚 {}", tr!("Tudo certo"))
+        } else {
+            format!("💔 {}", tr!("Há verificações falhando"))
+        };
+        for check in &self.checks {
+            let mark = if check.ok { "✅" } else { "❌" };
+            message.push_str(&format!("\n   {} {:<8} | {}", mark, check.name, check.detail));
+        }
+        CommandOutput { data: self.to_json(), message }
+    }
+}
+
+/// Confere o banco e o hash de um serviço já aberto
+pub fn check(service: &AuthService) -> HealthReport {
+    let store = service.store();
+
+    let database = Check::from_result(
+        "database",
+        store.count_users().map(|count| (true, tr!("{} usuários", count))),
+    );
+    let schema = Check::from_result(
+        "schema",
+        match service.database() {
+            Ok(db) => db.connection().and_then(|conn| current_version(&conn)).map(|current| {
+                let detail = tr!("versão {} (esperada: {})", current, latest_version());
+                (current == latest_version(), detail)
+            }),
+            // O esquema do PostgreSQL é criado na conexão, sem versões
+            Err(_) => Ok((true, tr!("sem versões no PostgreSQL").to_string())),
+        },
+    );
+    let hash = Check::from_result(
+        "hash",
+        hash_password(store, PROBE_PASSWORD)
+            .and_then(|hash| verify_password(PROBE_PASSWORD, &hash))
+            .map(|ok| {
+                let detail = if ok { tr!("hash de teste conferido") } else { tr!("o hash de teste não confere") };
+                (ok, detail.to_string())
+            }),
+    );
+    HealthReport { checks: vec![database, schema, hash] }
+}
+
+/// Relatório de um serviço que nem chegou a abrir: o banco falha e o resto não é conferido
+pub fn unavailable(err: &AuthError) -> HealthReport {
+    let skipped = |name| Check { name, ok: false, detail: tr!("não conferido: o banco não abriu").to_string() };
+    HealthReport {
+        checks: vec![Check { name: "database", ok: false, detail: err.to_string() }, skipped("schema"), skipped("hash")],
+    }
+}
@@ -0,0 +1,181 @@
+//! Tradução das mensagens exibidas ao usuário.
+//!
+//! O texto-fonte fica em português no próprio código, dentro de `tr!`, e serve de
+//! chave para o catálogo dos outros idiomas (como no gettext). Uma mensagem que
+//! ainda não tem tradução aparece em português, em vez de sumir.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::sync::OnceLock;
+use serde::{Deserialize, Deserializer};
+use crate::{config, tr};
+
+mod en;
+
+/// Idioma da interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    /// Português do Brasil, o idioma original das mensagens
+    #[default]
+    PtBr,
+    En,
+}
+
+impl Locale {
+    /// Todos os idiomas disponíveis
+    pub const ALL: [Locale; 2] = [Locale::PtBr, Locale::En];
+
+    /// Código do idioma, como aceito em `locale` e `SIRI_LOCALE`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::PtBr => "pt-BR",
+            Locale::En => "en",
+        }
+    }
+
+    /// Interpreta um código de idioma ("pt-BR", "en") ou um locale POSIX ("en_US.UTF-8").
+    ///
+    /// Só a língua conta: qualquer variante de português ou de inglês é aceita.
+    pub fn from_code(code: &str) -> Option<Locale> {
+        let code = code.trim().split(['.', '@']).next().unwrap_or_default();
+        let language = code.split(['_', '-']).next().unwrap_or_default().to_lowercase();
+        match language.as_str() {
+            "pt" => Some(Locale::PtBr),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    /// Idioma pedido pelo ambiente, na ordem do POSIX: `LC_ALL`, `LC_MESSAGES` e `LANG`.
+    ///
+    /// A primeira variável definida decide; um idioma sem tradução (ou "C") dá `None`.
+    pub fn from_env() -> Option<Locale> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.trim().is_empty())
+            .and_then(|value| Locale::from_code(&value))
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Locale {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Locale::from_code(&code).ok_or_else(|| {
+            let codes: Vec<&str> = Locale::ALL.iter().map(Locale::code).collect();
+            serde::de::Error::custom(tr!("idioma '{}' não suportado (use {})", code, codes.join(", ")))
+        })
+    }
+}
+
+/// Idioma em vigor: o da configuração (`locale` ou `SIRI_LOCALE`), senão o do ambiente,
+/// senão português
+pub fn locale() -> Locale {
+    config::current().locale.or_else(Locale::from_env).unwrap_or_default()
+}
+
+/// Catálogo inglês, indexado pelo texto em português
+fn english() -> &'static HashMap<&'static str, &'static str> {
+    static CATALOG: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    CATALOG.get_or_init(|| en::MESSAGES.iter().copied().collect())
+}
+
+/// Traduz um texto-fonte para o idioma em vigor (o próprio texto se não houver tradução)
+pub fn translate(source: &str) -> &str {
+    match locale() {
+        Locale::PtBr => source,
+        Locale::En => english().get(source).copied().unwrap_or(source),
+    }
+}
+
+/// Substitui cada `{}` do modelo pelo próximo argumento (`{{` e `}}` viram chaves literais)
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('}')) => {
+                chars.next();
+                if let Some(arg) = args.next() {
+                    out.push_str(&arg.to_string());
+                }
+            }
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Mensagem guardada ainda sem tradução: o texto-fonte e os argumentos já formatados.
+///
+/// É traduzida só ao ser exibida, no idioma em vigor nesse momento. Os erros a guardam
+/// no lugar do texto pronto; crie com `message!`, que aceita o mesmo formato de `tr!`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    source: &'static str,
+    args: Vec<String>,
+}
+
+impl Message {
+    pub fn new(source: &'static str, args: Vec<String>) -> Self {
+        Message { source, args }
+    }
+
+    /// Texto que não passa pelo catálogo, como a mensagem de uma biblioteca
+    pub fn verbatim(text: impl Into<String>) -> Self {
+        Message { source: "{}", args: vec![text.into()] }
+    }
+
+    /// Texto-fonte, em português, que serve de chave do catálogo
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<&dyn fmt::Display> = self.args.iter().map(|arg| arg as &dyn fmt::Display).collect();
+        f.write_str(&fill(translate(self.source), &args))
+    }
+}
+
+/// Cria uma `Message`, com o mesmo formato de `tr!`, para ser traduzida só ao ser exibida
+#[macro_export]
+macro_rules! message {
+    ($msg:literal) => {
+        $crate::i18n::Message::new($msg, ::std::vec::Vec::new())
+    };
+    ($msg:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::Message::new($msg, ::std::vec![$(::std::string::ToString::to_string(&$arg)),+])
+    };
+}
+
+/// Traduz uma mensagem e, se houver argumentos, preenche seus `{}` em ordem.
+///
+/// Sem argumentos devolve `&str`; com argumentos, `String`. Só `{}` é aceito como
+/// marcador: alinhamentos e precisões devem ser aplicados antes, no argumento.
+#[macro_export]
+macro_rules! tr {
+    ($msg:literal) => {
+        $crate::i18n::translate($msg)
+    };
+    ($msg:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::translate($msg), &[$(&$arg as &dyn ::std::fmt::Display),+])
+    };
+}
@@ -0,0 +1,762 @@
+//! Catálogo inglês: pares (texto em português, tradução)
+
+pub(super) const MESSAGES: &[(&str, &str)] = &[
+    // Modo interativo
+    ("Código do autenticador (ou de recuperação):", "Authenticator code (or recovery code):"),
+    ("Recurso disponível apenas com o backend SQLite.", "Feature available only with the SQLite backend."),
+    ("Encerrando o sistema. Até logo!", "Shutting down. Goodbye!"),
+    ("Opção inválida. Tente novamente.", "Invalid option. Try again."),
+    ("Escolha uma opção:", "Choose an option:"),
+    ("Registrar novo usuário", "Register a new user"),
+    ("Fazer login", "Log in"),
+    ("Esqueci minha senha", "Forgot my password"),
+    ("Sair", "Quit"),
+    ("Opção:", "Option:"),
+    ("REGISTRO DE NOVO USUÁRIO", "NEW USER REGISTRATION"),
+    ("Senha (oculta):", "Password (hidden):"),
+    ("Confirme a senha (oculta):", "Confirm the password (hidden):"),
+    ("(oculta)", "(hidden)"),
+    ("Email (opcional, Enter para pular):", "Email (optional, Enter to skip):"),
+    ("Usuário '{}' registrado com sucesso!", "User '{}' registered successfully!"),
+    (
+        "Confirme o email em \"Ver informações da conta\", depois do login.",
+        "Confirm the email under \"View account information\" after logging in.",
+    ),
+    ("LOGIN", "LOGIN"),
+    ("Muitas tentativas malsucedidas. Aguarde {}s...", "Too many failed attempts. Wait {}s..."),
+    ("Nome de usuário não pode estar vazio.", "Username cannot be empty."),
+    ("Senha não pode estar vazia.", "Password cannot be empty."),
+    ("Defina uma nova senha para continuar.", "Set a new password to continue."),
+    ("Acesso negado até que a senha seja alterada.", "Access denied until the password is changed."),
+    ("Tente novamente após {}.", "Try again after {}."),
+    ("Último login: {}", "Last login: {}"),
+    ("Este é o seu primeiro login.", "This is your first login."),
+    ("ESQUECI MINHA SENHA", "FORGOT MY PASSWORD"),
+    ("Token de redefinição (Enter para pedir um por email):", "Reset token (Enter to request one by email):"),
+    (
+        "Email verificado da conta (Enter para usar um código de recuperação):",
+        "Verified account email (Enter to use a recovery code):",
+    ),
+    (
+        "Se o email estiver verificado em uma conta, um token foi enviado para ele.",
+        "If the email is verified on an account, a token has been sent to it.",
+    ),
+    ("Digite o token recebido (Enter para deixar para depois):", "Enter the token you received (Enter to do it later):"),
+    (
+        "O token vale por 1 hora; use-o em \"Esqueci minha senha\".",
+        "The token is valid for 1 hour; use it under \"Forgot my password\".",
+    ),
+    ("Nome de usuário ou email:", "Username or email:"),
+    ("Código de recuperação:", "Recovery code:"),
+    ("Nova senha (oculta):", "New password (hidden):"),
+    ("Confirme a nova senha (oculta):", "Confirm the new password (hidden):"),
+    ("As senhas não coincidem.", "The passwords do not match."),
+    (
+        "Senha redefinida com sucesso! O código usado não vale mais.",
+        "Password reset successfully! The code you used is no longer valid.",
+    ),
+    (
+        "Senha de '{}' redefinida com sucesso! O token não vale mais.",
+        "Password for '{}' reset successfully! The token is no longer valid.",
+    ),
+    ("USUÁRIOS CADASTRADOS", "REGISTERED USERS"),
+    ("Nenhum usuário cadastrado.", "No registered users."),
+    ("Total de usuários: {}", "Total users: {}"),
+    ("ADMINISTRAÇÃO ({})", "ADMINISTRATION ({})"),
+    ("Painel de estatísticas", "Statistics dashboard"),
+    ("Limite de cadastros", "Registration limit"),
+    ("Ativar/desativar conta", "Enable/disable account"),
+    ("Excluir usuário", "Delete user"),
+    ("Restaurar usuário excluído", "Restore deleted user"),
+    ("Purgar usuários excluídos", "Purge deleted users"),
+    ("Bloquear/desbloquear usuário", "Lock/unlock user"),
+    ("Forçar troca de senha no próximo login", "Force a password change at next login"),
+    ("Redefinir senha", "Reset password"),
+    ("Log de auditoria", "Audit log"),
+    ("Token de redefinição de senha", "Password reset token"),
+    ("Validade das senhas", "Password expiry"),
+    ("Listar usuários", "List users"),
+    ("Promover/rebaixar usuário", "Promote/demote user"),
+    ("Voltar ao menu do usuário", "Back to the user menu"),
+    ("{} não encontrado.", "{} not found."),
+    ("BLOQUEAR/DESBLOQUEAR USUÁRIO", "LOCK/UNLOCK USER"),
+    ("Usuário '{}' não encontrado.", "User '{}' not found."),
+    ("Conta bloqueada até {}.", "Account locked until {}."),
+    ("Desbloquear agora?", "Unlock now?"),
+    ("Operação cancelada.", "Operation cancelled."),
+    ("Conta '{}' desbloqueada.", "Account '{}' unlocked."),
+    ("Bloquear por quantos minutos? (Enter cancela):", "Lock for how many minutes? (Enter cancels):"),
+    ("Valor inválido.", "Invalid value."),
+    ("Conta '{}' bloqueada até {}.", "Account '{}' locked until {}."),
+    ("FORÇAR TROCA DE SENHA", "FORCE PASSWORD CHANGE"),
+    ("'{}' precisará trocar a senha no próximo login.", "'{}' will have to change the password at next login."),
+    ("REDEFINIR SENHA", "RESET PASSWORD"),
+    ("Senha temporária (oculta):", "Temporary password (hidden):"),
+    ("Confirme a senha temporária (oculta):", "Confirm the temporary password (hidden):"),
+    (
+        "Senha de '{}' redefinida. A troca será exigida no próximo login.",
+        "Password for '{}' reset. A change will be required at next login.",
+    ),
+    ("LIMITE DE CADASTROS", "REGISTRATION LIMIT"),
+    ("Limite atual: {} usuários", "Current limit: {} users"),
+    ("Limite atual: sem limite", "Current limit: none"),
+    (
+        "Novo limite (número, 0 para remover, Enter para manter):",
+        "New limit (number, 0 to remove, Enter to keep):",
+    ),
+    ("Limite removido.", "Limit removed."),
+    ("Limite definido para {} usuários.", "Limit set to {} users."),
+    ("VALIDADE DAS SENHAS", "PASSWORD EXPIRY"),
+    ("Validade atual: as senhas não expiram", "Current expiry: passwords do not expire"),
+    ("Validade atual: {} dias", "Current expiry: {} days"),
+    (
+        "Nova validade em dias (0 para nunca expirar, Enter para manter):",
+        "New expiry in days (0 to never expire, Enter to keep):",
+    ),
+    ("As senhas não expiram mais.", "Passwords no longer expire."),
+    (
+        "Senhas com mais de {} dias terão de ser trocadas no próximo login.",
+        "Passwords older than {} days will have to be changed at next login.",
+    ),
+    ("ATIVAR/DESATIVAR CONTA", "ENABLE/DISABLE ACCOUNT"),
+    ("Conta '{}' desativada.", "Account '{}' disabled."),
+    ("Conta '{}' reativada.", "Account '{}' re-enabled."),
+    ("PROMOVER/REBAIXAR USUÁRIO", "PROMOTE/DEMOTE USER"),
+    ("'{}' é {}. Tornar {}?", "'{}' is {}. Make {}?"),
+    ("'{}' agora é {}.", "'{}' is now {}."),
+    ("(s/N):", "(y/N):"),
+    ("EXCLUIR USUÁRIO", "DELETE USER"),
+    ("Excluir a conta '{}'?", "Delete the account '{}'?"),
+    (
+        "Conta '{}' excluída. Ela pode ser restaurada até ser purgada.",
+        "Account '{}' deleted. It can be restored until it is purged.",
+    ),
+    ("RESTAURAR USUÁRIO", "RESTORE USER"),
+    ("Nenhum usuário excluído.", "No deleted users."),
+    ("Conta '{}' restaurada.", "Account '{}' restored."),
+    ("Nenhuma conta excluída com o nome '{}'.", "No deleted account named '{}'."),
+    ("PURGAR USUÁRIOS EXCLUÍDOS", "PURGE DELETED USERS"),
+    (
+        "Purgar exclusões com mais de quantos dias? (0 = todas):",
+        "Purge deletions older than how many days? (0 = all):",
+    ),
+    ("Esta ação não pode ser desfeita. Continuar?", "This action cannot be undone. Continue?"),
+    ("{} conta(s) removida(s) definitivamente.", "{} account(s) permanently removed."),
+    ("TOKEN DE REDEFINIÇÃO DE SENHA", "PASSWORD RESET TOKEN"),
+    (
+        "Token gerado para '{}'. Ele vale por {} min e uma única vez:",
+        "Token generated for '{}'. It is valid for {} min and a single use:",
+    ),
+    (
+        "Entregue-o ao usuário, que deve usar \"Esqueci minha senha\" no menu principal.",
+        "Give it to the user, who should use \"Forgot my password\" in the main menu.",
+    ),
+    ("LOG DE AUDITORIA", "AUDIT LOG"),
+    ("Deixe um filtro em branco para não filtrar por ele.", "Leave a filter blank to not filter by it."),
+    ("Autor:", "Actor:"),
+    ("Ação (ex.: register, delete_user):", "Action (e.g. register, delete_user):"),
+    ("Usuário afetado:", "Affected user:"),
+    ("Nenhuma entrada encontrada.", "No entries found."),
+    ("{} entrada(s), das mais recentes para as mais antigas:", "{} entry(ies), newest first:"),
+    ("PAINEL DE ESTATÍSTICAS", "STATISTICS DASHBOARD"),
+    ("Novos nas últimas 24h: {}", "New in the last 24h: {}"),
+    ("Novos nos últimos 7 dias: {}", "New in the last 7 days: {}"),
+    ("Contas bloqueadas: {}", "Locked accounts: {}"),
+    ("Hashes fora do esquema configurado ({}): {}", "Hashes outside the configured scheme ({}): {}"),
+    ("Último cadastro: {}", "Latest registration: {}"),
+    ("Último cadastro: -", "Latest registration: -"),
+    ("Código inválido. Tente novamente.", "Invalid code. Try again."),
+    (
+        "Nome de usuário não pode estar vazio (digite '{}' para cancelar).",
+        "Username cannot be empty (type '{}' to cancel).",
+    ),
+    ("Tentativas esgotadas. Voltando ao menu.", "No attempts left. Back to the menu."),
+    (
+        "Digite '{}' para gerar uma senha forte (senha vazia cancela).",
+        "Type '{}' to generate a strong password (an empty password cancels).",
+    ),
+    ("Força da senha: {}", "Password strength: {}"),
+    (
+        "Senha fraca demais. Tente outra (senha vazia cancela).",
+        "Password too weak. Try another one (an empty password cancels).",
+    ),
+    (
+        "As senhas não coincidem. Tente novamente (senha vazia cancela).",
+        "The passwords do not match. Try again (an empty password cancels).",
+    ),
+    ("Senha gerada: {}", "Generated password: {}"),
+    (
+        "Guarde-a em um gerenciador de senhas: ela não será exibida de novo.",
+        "Keep it in a password manager: it will not be shown again.",
+    ),
+    ("Usar esta senha?", "Use this password?"),
+    (
+        "Senha descartada. Digite outra ou '{}' para gerar de novo.",
+        "Password discarded. Enter another one or '{}' to generate again.",
+    ),
+    ("Nome de usuário:", "Username:"),
+    ("MENU DO USUÁRIO - {}", "USER MENU - {}"),
+    ("Alterar senha", "Change password"),
+    ("Ver informações da conta", "View account information"),
+    ("Gerar códigos de recuperação", "Generate recovery codes"),
+    ("Alterar nome de usuário", "Change username"),
+    ("Autenticação em dois fatores", "Two-factor authentication"),
+    ("Histórico de acessos", "Login history"),
+    ("Editar perfil", "Edit profile"),
+    ("Excluir minha conta", "Delete my account"),
+    ("Sair da conta", "Log out"),
+    ("Administração", "Administration"),
+    ("Saindo da conta de '{}'...", "Logging out of '{}'..."),
+    ("ALTERAR SENHA", "CHANGE PASSWORD"),
+    ("Senha atual (oculta):", "Current password (hidden):"),
+    ("Senha alterada com sucesso!", "Password changed successfully!"),
+    ("EDITAR PERFIL", "EDIT PROFILE"),
+    ("Enter mantém o valor atual; '{}' apaga o campo.", "Enter keeps the current value; '{}' clears the field."),
+    ("Nome de exibição", "Display name"),
+    ("Email", "Email"),
+    ("Telefone", "Phone"),
+    ("Biografia", "Bio"),
+    ("Nada foi alterado.", "Nothing was changed."),
+    ("Perfil atualizado.", "Profile updated."),
+    (
+        "Confirme o novo email em \"Ver informações da conta\".",
+        "Confirm the new email under \"View account information\".",
+    ),
+    ("EXCLUIR MINHA CONTA", "DELETE MY ACCOUNT"),
+    (
+        "A exclusão é definitiva: sessões, dois fatores e histórico de acessos serão apagados.",
+        "Deletion is permanent: sessions, two-factor settings and login history will be erased.",
+    ),
+    ("Conta '{}' excluída. Até logo!", "Account '{}' deleted. Goodbye!"),
+    ("ALTERAR NOME DE USUÁRIO", "CHANGE USERNAME"),
+    ("Novo nome de usuário:", "New username:"),
+    ("Senha incorreta.", "Incorrect password."),
+    ("Nome de usuário alterado de '{}' para '{}'.", "Username changed from '{}' to '{}'."),
+    ("CÓDIGOS DE RECUPERAÇÃO", "RECOVERY CODES"),
+    ("Os códigos anteriores foram invalidados.", "The previous codes have been invalidated."),
+    ("AUTENTICAÇÃO EM DOIS FATORES", "TWO-FACTOR AUTHENTICATION"),
+    ("Escaneie o QR code no aplicativo autenticador:", "Scan the QR code with your authenticator app:"),
+    ("Ou digite o segredo manualmente: {}", "Or enter the secret manually: {}"),
+    ("Código do autenticador:", "Authenticator code:"),
+    (
+        "Autenticação em dois fatores ativada! O código será pedido nos próximos logins.",
+        "Two-factor authentication enabled! The code will be requested at future logins.",
+    ),
+    (
+        "Sem o aparelho, use um dos códigos de recuperação abaixo no lugar do código.",
+        "Without the device, use one of the recovery codes below instead of the code.",
+    ),
+    ("{} (código vazio cancela).", "{} (an empty code cancels)."),
+    ("A autenticação em dois fatores está ativada.", "Two-factor authentication is enabled."),
+    ("Códigos de recuperação restantes: {} de {}", "Recovery codes left: {} of {}"),
+    ("Gerar novos códigos de recuperação", "Generate new recovery codes"),
+    ("Desativar a autenticação em dois fatores", "Disable two-factor authentication"),
+    ("Voltar", "Back"),
+    ("Código inválido.", "Invalid code."),
+    ("Autenticação em dois fatores desativada.", "Two-factor authentication disabled."),
+    ("HISTÓRICO DE ACESSOS (últimos {})", "LOGIN HISTORY (last {})"),
+    ("Nenhuma tentativa registrada.", "No attempts recorded."),
+    ("Sessão expirada ou encerrada. Faça login novamente.", "Session expired or ended. Please log in again."),
+    ("INFORMAÇÕES DA CONTA", "ACCOUNT INFORMATION"),
+    ("Nome de usuário: {}", "Username: {}"),
+    ("Nome de exibição: {}", "Display name: {}"),
+    ("verificado", "verified"),
+    ("aguardando verificação", "awaiting verification"),
+    ("Email: {} ({})", "Email: {} ({})"),
+    ("Telefone: {}", "Phone: {}"),
+    ("Biografia: {}", "Bio: {}"),
+    ("Papel: {}", "Role: {}"),
+    ("Situação: conta {}", "Status: account {}"),
+    ("Conta criada em: {}", "Account created on: {}"),
+    ("Login anterior a esta sessão: {}", "Login before this session: {}"),
+    ("Login anterior a esta sessão: nenhum", "Login before this session: none"),
+    ("Logins bem-sucedidos: {}", "Successful logins: {}"),
+    ("Grupos: {}", "Groups: {}"),
+    ("Sessão iniciada há {} min", "Session started {} min ago"),
+    ("Verificar o email agora?", "Verify the email now?"),
+    ("Token de verificação enviado para {}", "Verification token sent to {}"),
+    ("O token continua valendo por 24 horas.", "The token remains valid for 24 hours."),
+    ("Email verificado!", "Email verified!"),
+    (
+        "Guarde estes códigos em local seguro. Cada um pode ser usado apenas uma vez\n    \
+         e eles não serão exibidos novamente.",
+        "Keep these codes somewhere safe. Each one can be used only once\n    \
+         and they will not be shown again.",
+    ),
+    // Comandos
+    (
+        "Senha ausente: ela deve ser enviada na primeira linha da entrada padrão",
+        "Missing password: it must be sent on the first line of standard input",
+    ),
+    ("Confirmação necessária: repita o comando com --yes", "Confirmation required: repeat the command with --yes"),
+    ("Senha:", "Password:"),
+    ("Operação cancelada", "Operation cancelled"),
+    (
+        "{} de {} contas com hash fora do esquema configurado ({})",
+        "{} of {} accounts with a hash outside the configured scheme ({})",
+    ),
+    ("Frase secreta:", "Passphrase:"),
+    ("Confirme a frase secreta:", "Confirm the passphrase:"),
+    ("As frases secretas não coincidem", "The passphrases do not match"),
+    ("{} usuários exportados para '{}'", "{} users exported to '{}'"),
+    ("{} usuários importados de '{}'", "{} users imported from '{}'"),
+    ("Versão do esquema: {} (mais recente: {})", "Schema version: {} (latest: {})"),
+    ("aplicada em {}", "applied on {}"),
+    ("pendente", "pending"),
+    ("Migrações aplicadas agora: {}", "Migrations applied now: {}"),
+    (
+        "Argon2 calibrado para ~{} ms: memória {} KiB, {} iterações, paralelismo {}",
+        "Argon2 calibrated for ~{} ms: memory {} KiB, {} iterations, parallelism {}",
+    ),
+    ("Token de '{}' (vale uma vez, até {}):", "Token for '{}' (single use, until {}):"),
+    ("Nova senha:", "New password:"),
+    ("Senha de '{}' redefinida com sucesso!", "Password for '{}' reset successfully!"),
+    ("Token de verificação de '{}' enviado para {}", "Verification token for '{}' sent to {}"),
+    (
+        "Se o email estiver verificado em uma conta, um token de redefinição foi enviado para ele.",
+        "If the email is verified on an account, a reset token has been sent to it.",
+    ),
+    ("Email de '{}' verificado!", "Email of '{}' verified!"),
+    ("Dados de '{}' exportados para '{}'", "Data of '{}' exported to '{}'"),
+    ("Conta '{}' desativada. Os dados foram mantidos.", "Account '{}' disabled. Its data was kept."),
+    ("Papel desconhecido: '{}' (use {})", "Unknown role: '{}' (use {})"),
+    ("Permissão desconhecida: '{}' (use {})", "Unknown permission: '{}' (use {})"),
+    ("Permissão '{}' concedida a {}.", "Permission '{}' granted to {}."),
+    ("Permissão '{}' revogada de {}.", "Permission '{}' revoked from {}."),
+    ("Grupo '{}' criado.", "Group '{}' created."),
+    ("Grupo '{}'", "Group '{}'"),
+    ("Apagar o grupo '{}'?", "Delete the group '{}'?"),
+    ("Grupo '{}' apagado.", "Group '{}' deleted."),
+    ("'{}' adicionado ao grupo '{}'.", "'{}' added to group '{}'."),
+    ("'{}' removido do grupo '{}'.", "'{}' removed from group '{}'."),
+    ("'{}' não pertence a nenhum grupo.", "'{}' does not belong to any group."),
+    ("Grupos de '{}': {}", "Groups of '{}': {}"),
+    ("Nenhum grupo cadastrado.", "No groups registered."),
+    ("O grupo '{}' não tem membros.", "The group '{}' has no members."),
+    ("Membros de '{}': {}", "Members of '{}': {}"),
+    // Configuração e inicialização
+    ("{}, linha {}", "{}, line {}"),
+    ("O caminho do banco não pode ser vazio", "The database path cannot be empty"),
+    ("O pool de conexões precisa de ao menos uma conexão", "The connection pool needs at least one connection"),
+    ("O caminho do log não pode ser vazio", "The log path cannot be empty"),
+    ("As sessões devem durar pelo menos 1 segundo", "Sessions must last at least 1 second"),
+    ("O caminho do arquivo do pepper não pode ser vazio", "The pepper file path cannot be empty"),
+    (
+        "O caminho do arquivo de senhas vazadas não pode ser vazio",
+        "The breached passwords file path cannot be empty",
+    ),
+    ("O tamanho mínimo da senha deve ser pelo menos 1", "The minimum password length must be at least 1"),
+    ("O bloqueio deve durar pelo menos 1 minuto", "The lockout must last at least 1 minute"),
+    ("Valor inválido em {}: '{}'", "Invalid value in {}: '{}'"),
+    ("idioma '{}' não suportado (use {})", "unsupported language '{}' (use {})"),
+    ("--ephemeral só pode ser usado com o backend sqlite", "--ephemeral can only be used with the sqlite backend"),
+    ("--db só pode ser usado com o backend sqlite", "--db can only be used with the sqlite backend"),
+    ("O backend postgres requer --dsn (ou SIRI_DSN)", "The postgres backend requires --dsn (or SIRI_DSN)"),
+    ("--tui não pode ser usado com um comando", "--tui cannot be used with a command"),
+    ("O modo --tui requer um terminal", "--tui mode requires a terminal"),
+    (
+        "O modo --json requer um comando (register, login, list, stats)",
+        "--json mode requires a command (register, login, list, stats)",
+    ),
+    ("entrada interrompida", "input interrupted"),
+    // Rótulos do modo texto puro (--plain)
+    ("[ok]", "[ok]"),
+    ("[erro]", "[error]"),
+    ("[aviso]", "[warning]"),
+    ("[negado]", "[denied]"),
+    ("[não]", "[no]"),
+    ("[dica]", "[tip]"),
+    ("[info]", "[info]"),
+    // Erros
+    ("Erro de banco de dados: {}", "Database error: {}"),
+    ("Erro no PostgreSQL: {}", "PostgreSQL error: {}"),
+    ("Erro no pool de conexões: {}", "Connection pool error: {}"),
+    ("Erro ao processar senha: {}", "Password processing error: {}"),
+    ("Erro de validação: {}", "Validation error: {}"),
+    ("Erro de entrada: {}", "Input error: {}"),
+    ("Não encontrado: {}", "Not found: {}"),
+    ("Permissão negada: {}", "Permission denied: {}"),
+    ("Banco de dados corrompido: {}", "Corrupt database: {}"),
+    ("Erro ao enviar email: {}", "Error sending email: {}"),
+    ("Credenciais inválidas", "Invalid credentials"),
+    ("Conta bloqueada temporariamente até {}", "Account temporarily locked until {}"),
+    ("Conta desativada. Procure um administrador", "Account disabled. Contact an administrator"),
+    ("Esta conta exige um segundo fator de autenticação", "This account requires a second authentication factor"),
+    ("A senha expirou ou precisa ser trocada", "The password has expired or must be changed"),
+    // Autenticação e contas
+    ("Login bem-sucedido!", "Login successful!"),
+    ("Credenciais inválidas.", "Invalid credentials."),
+    (
+        "Conta bloqueada temporariamente por excesso de tentativas.",
+        "Account temporarily locked after too many attempts.",
+    ),
+    ("Conta desativada. Procure um administrador.", "Account disabled. Contact an administrator."),
+    ("Esta conta exige um segundo fator de autenticação.", "This account requires a second authentication factor."),
+    ("Sua senha expirou ou precisa ser trocada.", "Your password has expired or must be changed."),
+    ("Nome de usuário não pode estar vazio", "Username cannot be empty"),
+    ("Senha não pode estar vazia", "Password cannot be empty"),
+    ("O nome de usuário deve ter entre {} e {} caracteres", "The username must be between {} and {} characters long"),
+    (
+        "O nome de usuário deve conter apenas letras, números, '_', '-' ou '.'",
+        "The username may only contain letters, digits, '_', '-' or '.'",
+    ),
+    ("Email '{}' inválido", "Invalid email '{}'"),
+    ("A senha deve ter pelo menos {} caracteres", "The password must be at least {} characters long"),
+    ("A senha deve conter pelo menos um número", "The password must contain at least one digit"),
+    ("A senha deve conter pelo menos uma letra maiúscula", "The password must contain at least one uppercase letter"),
+    ("A senha deve conter pelo menos uma letra minúscula", "The password must contain at least one lowercase letter"),
+    ("A senha deve conter pelo menos um caractere especial", "The password must contain at least one special character"),
+    ("Senha fraca demais ({}). {}", "Password too weak ({}). {}"),
+    ("Parâmetros do Argon2 inválidos: {}", "Invalid Argon2 parameters: {}"),
+    ("Pepper inválido: {}", "Invalid pepper: {}"),
+    ("Erro ao hashear senha: {}", "Error hashing password: {}"),
+    ("Erro ao analisar hash: esquema desconhecido", "Error parsing hash: unknown scheme"),
+    ("Erro ao analisar hash: {}", "Error parsing hash: {}"),
+    ("registro fechado: limite atingido", "registration closed: limit reached"),
+    (
+        "Usuário '{}' foi excluído e o nome ainda está reservado",
+        "User '{}' was deleted and the name is still reserved",
+    ),
+    (
+        "Você é o único administrador ativo. Promova outra conta antes de excluir a sua",
+        "You are the only active administrator. Promote another account before deleting yours",
+    ),
+    ("Conta desativada", "Account disabled"),
+    ("Senha atual incorreta", "Incorrect current password"),
+    ("A validade do token deve ser positiva", "The token lifetime must be positive"),
+    ("Token de redefinição inválido ou expirado", "Invalid or expired reset token"),
+    ("O novo nome é igual ao atual", "The new name is the same as the current one"),
+    ("Usuário '{}'", "User '{}'"),
+    ("Email do usuário '{}'", "Email of user '{}'"),
+    ("Email '{}' já está em uso", "Email '{}' is already in use"),
+    ("Usuário '{}' já existe", "User '{}' already exists"),
+    ("Grupo '{}' já existe", "Group '{}' already exists"),
+    ("Código de recuperação inválido", "Invalid recovery code"),
+    ("Recurso disponível apenas com o backend SQLite", "Feature available only with the SQLite backend"),
+    ("A conta não tem email cadastrado", "The account has no email address"),
+    ("O email da conta já foi verificado", "The account email has already been verified"),
+    ("Token de verificação inválido ou expirado", "Invalid or expired verification token"),
+    (
+        "Esta senha já apareceu {} vez(es) em vazamentos de dados conhecidos. Escolha outra",
+        "This password has appeared {} time(s) in known data breaches. Choose another one",
+    ),
+    // Papéis, permissões e grupos
+    ("usuário", "user"),
+    ("administrador", "administrator"),
+    ("ativa", "active"),
+    ("desativada", "disabled"),
+    ("Listar todos os usuários", "List all users"),
+    ("Excluir, restaurar e purgar usuários", "Delete, restore and purge users"),
+    ("Consultar o log de auditoria", "View the audit log"),
+    ("Promover/rebaixar usuários e alterar permissões", "Promote/demote users and change permissions"),
+    ("Ativar, desbloquear e redefinir senhas de contas", "Enable, unlock and reset passwords of accounts"),
+    ("Alterar limites e validade das senhas", "Change limits and password expiry"),
+    ("Ver estatísticas do sistema", "View system statistics"),
+    ("'{}' não tem a permissão '{}'", "'{}' does not have the '{}' permission"),
+    ("O papel {} não pode perder a permissão '{}'", "The {} role cannot lose the '{}' permission"),
+    ("O papel {} já tem a permissão '{}'", "The {} role already has the '{}' permission"),
+    ("O papel {} não tem a permissão '{}'", "The {} role does not have the '{}' permission"),
+    ("'{}' já é {}", "'{}' is already {}"),
+    (
+        "'{}' é o único administrador ativo e não pode ser rebaixado",
+        "'{}' is the only active administrator and cannot be demoted",
+    ),
+    ("A conta '{}' já está {}", "The account '{}' is already {}"),
+    (
+        "'{}' é o único administrador ativo e não pode ser desativado",
+        "'{}' is the only active administrator and cannot be disabled",
+    ),
+    ("O nome do grupo deve ter entre 1 e {} caracteres", "The group name must be between 1 and {} characters long"),
+    (
+        "O nome do grupo só pode conter letras, números, '.', '_' e '-'",
+        "The group name may only contain letters, digits, '.', '_' and '-'",
+    ),
+    ("'{}' já pertence ao grupo '{}'", "'{}' already belongs to group '{}'"),
+    ("'{}' não pertence ao grupo '{}'", "'{}' does not belong to group '{}'"),
+    // Perfil
+    ("O nome de exibição", "The display name"),
+    ("A biografia", "The bio"),
+    ("{} deve ter no máximo {} caracteres", "{} must be at most {} characters long"),
+    ("{} não pode conter caracteres de controle", "{} cannot contain control characters"),
+    (
+        "Telefone '{}' inválido: use de {} a {} dígitos, com '+' e separadores opcionais",
+        "Invalid phone '{}': use {} to {} digits, with optional '+' and separators",
+    ),
+    // Força e geração de senhas
+    ("Fraca", "Weak"),
+    ("Razoável", "Fair"),
+    ("Forte", "Strong"),
+    ("Muito forte", "Very strong"),
+    (
+        "Evite senhas e palavras comuns, como 'senha' ou 'admin'.",
+        "Avoid common passwords and words, such as 'password' or 'admin'.",
+    ),
+    (
+        "Trocar letras por símbolos parecidos ('@' por 'a', '0' por 'o') não engana um atacante.",
+        "Swapping letters for look-alike symbols ('@' for 'a', '0' for 'o') does not fool an attacker.",
+    ),
+    ("Não use o nome de usuário ou o email na senha.", "Do not use the username or the email in the password."),
+    ("Evite sequências como 'abc', '123' ou 'qwerty'.", "Avoid sequences such as 'abc', '123' or 'qwerty'."),
+    ("Evite repetições como 'aaa' ou 'abcabc'.", "Avoid repetitions such as 'aaa' or 'abcabc'."),
+    ("Evite anos e datas, que são fáceis de adivinhar.", "Avoid years and dates, which are easy to guess."),
+    (
+        "Use uma senha mais longa: algumas palavras incomuns juntas são fortes e fáceis de lembrar.",
+        "Use a longer password: a few uncommon words together are strong and easy to remember.",
+    ),
+    ("Escolha pelo menos um conjunto de caracteres", "Choose at least one character set"),
+    ("O tamanho máximo de uma senha gerada é {}", "The maximum length of a generated password is {}"),
+    (
+        "O tamanho deve comportar um caractere de cada conjunto escolhido",
+        "The length must fit one character from each chosen set",
+    ),
+    ("letras minúsculas", "lowercase letters"),
+    ("letras maiúsculas", "uppercase letters"),
+    ("números", "digits"),
+    ("caracteres especiais", "special characters"),
+    ("A política de senhas exige {}", "The password policy requires {}"),
+    (
+        "Não foi possível gerar uma senha forte o suficiente; aumente o tamanho ou os conjuntos",
+        "Could not generate a strong enough password; increase the length or the character sets",
+    ),
+    // Segundo fator e pepper
+    ("segredo TOTP inválido: {}", "invalid TOTP secret: {}"),
+    ("segredo TOTP corrompido", "corrupted TOTP secret"),
+    ("Não foi possível gerar o QR code: {}", "Could not generate the QR code: {}"),
+    (
+        "Código inválido. Confira o relógio do aparelho e tente novamente",
+        "Invalid code. Check the device clock and try again",
+    ),
+    ("Código inválido", "Invalid code"),
+    (
+        "O pepper vem de hashing.pepper: mova o valor atual para hashing.previous_pepper e defina o novo em hashing.pepper",
+        "The pepper comes from hashing.pepper: move the current value to hashing.previous_pepper and set the new one in hashing.pepper",
+    ),
+    ("A rotação requer o pepper em um arquivo (hashing.pepper_file)", "Rotation requires the pepper in a file (hashing.pepper_file)"),
+    ("O arquivo '{}' não contém um pepper para rotacionar", "The file '{}' does not contain a pepper to rotate"),
+    // Banco de dados e exportação
+    (
+        "O arquivo '{}' tem permissões {}; recomenda-se 600 (chmod 600 {})",
+        "The file '{}' has permissions {}; 600 is recommended (chmod 600 {})",
+    ),
+    (
+        "'{}' falhou na verificação de integridade ({}). Restaure um backup \
+         ou execute com --recover-corrupt para movê-lo e começar um banco novo",
+        "'{}' failed the integrity check ({}). Restore a backup \
+         or run with --recover-corrupt to move it aside and start a new database",
+    ),
+    (
+        "'{}' estava corrompido e foi movido para '{}'. Um banco novo foi criado.",
+        "'{}' was corrupted and has been moved to '{}'. A new database was created.",
+    ),
+    ("Erro ao derivar chave: {}", "Error deriving key: {}"),
+    ("Coluna binária não suportada na exportação", "Binary column not supported in the export"),
+    ("A frase secreta não pode estar vazia", "The passphrase cannot be empty"),
+    ("Erro ao serializar usuários: {}", "Error serializing users: {}"),
+    ("Erro ao cifrar a exportação", "Error encrypting the export"),
+    ("Arquivo não é uma exportação do Siri", "File is not a Siri export"),
+    ("Versão de exportação {} não suportada (esperada {})", "Unsupported export version {} (expected {})"),
+    ("Frase secreta incorreta ou arquivo corrompido", "Wrong passphrase or corrupted file"),
+    ("Conteúdo da exportação inválido", "Invalid export contents"),
+    ("A importação exige um banco sem usuários", "Importing requires a database with no users"),
+    ("Coluna desconhecida na exportação: {}", "Unknown column in the export: {}"),
+    // Emails
+    ("Email (não enviado: SMTP não configurado)", "Email (not sent: SMTP not configured)"),
+    ("Para: {}", "To: {}"),
+    ("Assunto: {}", "Subject: {}"),
+    ("Remetente inválido '{}': {}", "Invalid sender '{}': {}"),
+    ("O servidor SMTP não pode ser vazio", "The SMTP server cannot be empty"),
+    ("smtp.user e smtp.password devem ser definidos juntos", "smtp.user and smtp.password must be set together"),
+    ("Destinatário inválido '{}': {}", "Invalid recipient '{}': {}"),
+    ("Confirme seu email", "Confirm your email"),
+    (
+        "Olá, {}!\n\nUse o token abaixo para confirmar seu email no Siri Ferrugem:\n\n    {}\n\n\
+         Ele vale por {} horas. Se você não pediu esta confirmação, ignore este email.",
+        "Hello, {}!\n\nUse the token below to confirm your email on Siri Ferrugem:\n\n    {}\n\n\
+         It is valid for {} hours. If you did not request this confirmation, ignore this email.",
+    ),
+    ("Redefinição de senha", "Password reset"),
+    (
+        "Olá, {}!\n\nUse o token abaixo para definir uma nova senha no Siri Ferrugem:\n\n    {}\n\n\
+         Ele vale por {} minutos e pode ser usado uma única vez. Se você não pediu a\n\
+         redefinição, ignore este email: sua senha continua a mesma.",
+        "Hello, {}!\n\nUse the token below to set a new password on Siri Ferrugem:\n\n    {}\n\n\
+         It is valid for {} minutes and can be used only once. If you did not request the\n\
+         reset, ignore this email: your password stays the same.",
+    ),
+    // Interface de tela cheia (--tui)
+    ("Usuários", "Users"),
+    ("Detalhes da conta", "Account details"),
+    ("Auditoria", "Audit log"),
+    ("ID", "ID"),
+    ("Usuário", "User"),
+    ("Papel", "Role"),
+    ("Situação", "Status"),
+    ("Nome", "Name"),
+    ("Grupos", "Groups"),
+    ("Dois fatores", "Two-factor"),
+    ("Criada em", "Created at"),
+    ("Último login", "Last login"),
+    ("Logins", "Logins"),
+    ("{} (confirmado)", "{} (verified)"),
+    ("{} (pendente)", "{} (pending)"),
+    ("sim", "yes"),
+    ("não", "no"),
+    ("Quando", "When"),
+    ("Autor", "Actor"),
+    ("Ação", "Action"),
+    ("Alvo", "Target"),
+    ("Novo usuário", "New user"),
+    ("Trocar a senha de '{}'", "Change the password of '{}'"),
+    ("Senha", "Password"),
+    ("Confirme a senha", "Confirm the password"),
+    ("Email (opcional)", "Email (optional)"),
+    ("Senha atual", "Current password"),
+    ("Nova senha", "New password"),
+    ("Confirme a nova senha", "Confirm the new password"),
+    (
+        "Setas: navegar | Tab: trocar painel | n: novo usuário | p: trocar senha | r: recarregar | q: sair",
+        "Arrows: move | Tab: switch pane | n: new user | p: change password | r: reload | q: quit",
+    ),
+    ("Tab: próximo campo | Enter: confirmar | Esc: cancelar", "Tab: next field | Enter: confirm | Esc: cancel"),
+    // Servidor HTTP (siri serve)
+    (
+        "Servidor HTTP ouvindo em http://{} (Ctrl+C para encerrar)",
+        "HTTP server listening on http://{} (Ctrl+C to stop)",
+    ),
+    ("Corpo da requisição inválido: {}", "Invalid request body: {}"),
+    ("O servidor HTTP não pode ser iniciado por commands::run", "The HTTP server cannot be started through commands::run"),
+    ("Chave privada RSA inválida: {}", "Invalid RSA private key: {}"),
+    ("Chave pública RSA inválida: {}", "Invalid RSA public key: {}"),
+    ("O segredo do JWT não pode ser vazio", "The JWT secret cannot be empty"),
+    ("O algoritmo rs256 requer jwt.public_key_file", "The rs256 algorithm requires jwt.public_key_file"),
+    (
+        "Sem chave privada para assinar tokens (jwt.private_key_file)",
+        "No private key to sign tokens (jwt.private_key_file)",
+    ),
+    ("Falha ao assinar o token: {}", "Failed to sign the token: {}"),
+    ("Tokens JWT não configurados neste serviço", "JWT tokens are not configured for this service"),
+    ("Os tokens JWT devem valer pelo menos 1 minuto", "JWT tokens must be valid for at least 1 minute"),
+    ("O emissor dos tokens JWT não pode ser vazio", "The JWT issuer cannot be empty"),
+    // Daemon (siri daemon)
+    ("Daemon ouvindo em '{}' (Ctrl+C para encerrar)", "Daemon listening on '{}' (Ctrl+C to stop)"),
+    ("Já existe um daemon ouvindo em '{}'", "A daemon is already listening on '{}'"),
+    ("Pedido inválido: {}", "Invalid request: {}"),
+    ("Pedido maior que {} bytes", "Request larger than {} bytes"),
+    ("O daemon não pode ser iniciado por commands::run", "The daemon cannot be started through commands::run"),
+    // checkpassword
+    ("Entrada do checkpassword malformada", "Malformed checkpassword input"),
+    (
+        "O checkpassword não pode ser executado por commands::run",
+        "checkpassword cannot be run through commands::run",
+    ),
+    // Webhooks
+    (
+        "webhooks.urls requer um segredo (webhooks.secret ou webhooks.secret_file)",
+        "webhooks.urls requires a secret (webhooks.secret or webhooks.secret_file)",
+    ),
+    ("Segredo dos webhooks inválido: {}", "Invalid webhook secret: {}"),
+    // Métricas
+    ("Métricas em http://{}/metrics", "Metrics at http://{}/metrics"),
+    // Verificação de saúde (siri health)
+    ("Tudo certo", "All good"),
+    ("Há verificações falhando", "Some checks are failing"),
+    ("{} usuários", "{} users"),
+    ("versão {} (esperada: {})", "version {} (expected: {})"),
+    ("sem versões no PostgreSQL", "no versions on PostgreSQL"),
+    ("hash de teste conferido", "test hash verified"),
+    ("o hash de teste não confere", "the test hash does not match"),
+    ("não conferido: o banco não abriu", "not checked: the database did not open"),
+    // Cadastro em lote (siri import)
+    ("Papel desconhecido: {}", "Unknown role: {}"),
+    ("password_hash não está em um esquema suportado", "password_hash is not in a supported scheme"),
+    ("Informe password ou password_hash, não os dois", "Provide password or password_hash, not both"),
+    ("Informe password ou password_hash", "Provide password or password_hash"),
+    ("Não foi possível ler '{}': {}", "Could not read '{}': {}"),
+    ("Cabeçalho do CSV inválido: {}", "Invalid CSV header: {}"),
+    ("O CSV precisa de uma coluna username", "The CSV needs a username column"),
+    ("Coluna desconhecida no CSV: {}", "Unknown CSV column: {}"),
+    ("{} contas criadas a partir de '{}'", "{} accounts created from '{}'"),
+    ("{} linhas recusadas:", "{} rows rejected:"),
+    ("linha", "line"),
+    // Exportação de usuários (siri export)
+    ("Falha ao gravar o CSV: {}", "Failed to write the CSV: {}"),
+    ("Senha de '{}':", "Password for '{}':"),
+    ("'{}' não é administrador", "'{}' is not an administrator"),
+    // Listagem paginada
+    ("Página {} de {}", "Page {} of {}"),
+    ("Não há mais páginas nessa direção.", "There are no more pages in that direction."),
+    // Busca de usuários
+    ("Buscar usuários", "Search users"),
+    ("BUSCAR USUÁRIOS", "SEARCH USERS"),
+    ("Nome ou email (* e ? como curingas):", "Name or email (* and ? as wildcards):"),
+    ("Nenhum usuário encontrado para '{}'.", "No users found for '{}'."),
+    ("{} usuários encontrados", "{} users found"),
+    // Ordenação da listagem
+    ("id", "id"),
+    ("nome", "name"),
+    ("data de cadastro", "creation date"),
+    ("último login", "last login"),
+    ("crescente", "ascending"),
+    ("decrescente", "descending"),
+    ("ordem: {} ({})", "order: {} ({})"),
+    ("[n] próxima, [p] anterior, [s] trocar ordem, [d] inverter, [q] voltar:", "[n] next, [p] previous, [s] change order, [d] reverse, [q] back:"),
+    // Painel de estatísticas
+    ("Contas", "Accounts"),
+    ("Segurança", "Security"),
+    ("Cadastros por dia", "Registrations per day"),
+    ("Cadastros por semana", "Registrations per week"),
+    ("semana de {}", "week of {}"),
+    ("Contas desativadas: {}", "Disabled accounts: {}"),
+    ("Com dois fatores: {}", "With two factors: {}"),
+    ("Com dois fatores: {} ({}%)", "With two factors: {} ({}%)"),
+    ("Idade média das contas: {} dias", "Average account age: {} days"),
+    ("Idade média das contas: -", "Average account age: -"),
+    // Cópia de segurança
+    ("O arquivo '{}' já existe", "The file '{}' already exists"),
+    ("Caminho inválido para a cópia de segurança: '{}'", "Invalid backup path: '{}'"),
+    ("Cópia de segurança gravada em '{}' ({} KiB)", "Backup written to '{}' ({} KiB)"),
+    // Restauração de cópia de segurança
+    ("Arquivo '{}'", "File '{}'"),
+    ("A cópia '{}' está corrompida: {}", "The backup '{}' is corrupted: {}"),
+    ("'{}' não é uma cópia de segurança do Siri", "'{}' is not a Siri backup"),
+    (
+        "A cópia '{}' está na versão {} do esquema, mais nova que a suportada ({}). Atualize o programa",
+        "The backup '{}' is at schema version {}, newer than the supported one ({}). Update the program",
+    ),
+    (
+        "O banco atual foi alterado em {}, depois da cópia; use --force para substituí-lo mesmo assim",
+        "The current database was changed at {}, after the backup; use --force to replace it anyway",
+    ),
+    ("O banco está ocupado; tente restaurar de novo em instantes", "The database is busy; try restoring again in a moment"),
+    ("Não foi possível descompactar '{}': {}", "Could not decompress '{}': {}"),
+    ("'{}' não é um banco SQLite", "'{}' is not an SQLite database"),
+    ("Substituir todo o banco atual pelo conteúdo de '{}'?", "Replace the whole current database with the contents of '{}'?"),
+    ("Restauração cancelada.", "Restore cancelled."),
+    ("Banco restaurado a partir de '{}' ({} usuários)", "Database restored from '{}' ({} users)"),
+    ("A cópia foi migrada da versão {} para a {} do esquema", "The backup was migrated from schema version {} to {}"),
+    // Cópias de segurança automáticas
+    ("O diretório das cópias de segurança não pode ser vazio", "The backup directory cannot be empty"),
+    (
+        "O intervalo entre as cópias de segurança deve ser de pelo menos 1 minuto",
+        "The interval between backups must be at least 1 minute",
+    ),
+    ("É preciso manter pelo menos uma cópia de segurança", "At least one backup must be kept"),
+    ("Cópias de segurança em '{}' a cada {} minutos", "Backups to '{}' every {} minutes"),
+    // Verificação do banco (fsck)
+    ("Nenhum problema encontrado", "No problems found"),
+    ("Foram encontrados problemas no banco", "Problems were found in the database"),
+    ("Integridade do SQLite: ok", "SQLite integrity: ok"),
+    ("Integridade do SQLite: {}", "SQLite integrity: {}"),
+    ("Hashes: {} conferidos, {} inválidos", "Hashes: {} checked, {} invalid"),
+    ("{} hashes legados, aceitos por hashing.legacy_hashes", "{} legacy hashes, accepted by hashing.legacy_hashes"),
+    ("Nenhuma linha órfã", "No orphaned rows"),
+    ("{} linhas órfãs em {} (sem o registro em {})", "{} orphaned rows in {} (missing the record in {})"),
+    ("formato desconhecido; o login sempre falha", "unknown format; login always fails"),
+    ("hash {} ilegível: {}", "unreadable {} hash: {}"),
+    ("sem salt ou sem hash", "missing the salt or the hash"),
+    // Migrações
+    (
+        "O banco está na versão {} do esquema, mais nova que a suportada ({}). Atualize o programa",
+        "The database is at schema version {}, newer than the supported one ({}). Update the program",
+    ),
+];
@@ -0,0 +1,171 @@
+//! Cadastro em lote a partir de um CSV (`siri import`).
+//!
+//! A primeira linha do arquivo nomeia as colunas: `username` é obrigatória, e cada
+//! conta traz `password` (em texto, validada e convertida em hash na importação) ou
+//! `password_hash` (um hash já pronto, em um esquema suportado). `email` e `role`
+//! são opcionais. Todas as contas entram em uma única transação, mas uma linha com
+//! problema não interrompe as outras: ela é só anotada no relatório.
+
+use std::path::Path;
+use chrono::Utc;
+use serde::Deserialize;
+use zeroize::Zeroizing;
+use crate::auth::{
+    check_registration_open, hash_password, is_scheme_hash, release_deleted_username, validate_email,
+    validate_password_strength, validate_username,
+};
+use crate::config;
+use crate::db::Database;
+use crate::error::AuthResult;
+use crate::events::{self, Event};
+use crate::hasher::HashScheme;
+use crate::rbac::Role;
+use crate::store::UserStore;
+use crate::{validation, validation_error};
+
+/// Colunas aceitas no cabeçalho
+const COLUMNS: [&str; 5] = ["username", "password", "password_hash", "email", "role"];
+
+/// Uma linha do CSV; campos vazios contam como ausentes
+#[derive(Deserialize)]
+struct CsvUser {
+    username: String,
+    #[serde(default)]
+    password: Option<Zeroizing<String>>,
+    #[serde(default)]
+    password_hash: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    role: Option<String>,
+}
+
+/// Linha recusada na importação
+#[derive(Debug, Clone)]
+pub struct RowError {
+    /// Linha do arquivo, contando o cabeçalho como 1
+    pub line: u64,
+    /// Usuário da linha, se ela pôde ser lida
+    pub username: Option<String>,
+    pub message: String,
+}
+
+/// Resultado de uma importação
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Contas criadas, na ordem do arquivo
+    pub created: Vec<String>,
+    pub errors: Vec<RowError>,
+}
+
+/// Valida uma linha e retorna o hash a gravar e o papel da conta
+fn prepare(store: &dyn UserStore, user: &CsvUser) -> AuthResult<(String, Role)> {
+    validate_username(&user.username)?;
+    if let Some(email) = &user.email {
+        validate_email(email)?;
+    }
+    let role = match &user.role {
+        Some(name) => Role::from_name(name).ok_or_else(|| validation!("Papel desconhecido: {}", name))?,
+        None => Role::User,
+    };
+
+    let password_hash = match (&user.password, &user.password_hash) {
+        (Some(password), None) => {
+            let config = &config::current().password;
+            let inputs = [user.username.as_str(), user.email.as_deref().unwrap_or_default()];
+            validate_password_strength(password, config, &inputs)?;
+            hash_password(store, password)?
+        }
+        (None, Some(hash)) => {
+            let valid = HashScheme::detect(hash).is_some_and(|scheme| is_scheme_hash(hash, scheme));
+            if !valid {
+                return validation_error!("password_hash não está em um esquema suportado");
+            }
+            hash.clone()
+        }
+        (Some(_), Some(_)) => return validation_error!("Informe password ou password_hash, não os dois"),
+        (None, None) => return validation_error!("Informe password ou password_hash"),
+    };
+    Ok((password_hash, role))
+}
+
+/// Grava uma conta já validada
+fn insert(store: &dyn UserStore, user: &CsvUser, password_hash: &str, role: Role) -> AuthResult<()> {
+    let config = &config::current().password;
+    check_registration_open(store, config)?;
+    release_deleted_username(store, &user.username, config)?;
+    store.insert_user(&user.username, password_hash, user.email.as_deref())?;
+    if role != Role::User {
+        store.set_role(&user.username, role)?;
+    }
+    Ok(())
+}
+
+/// Importa as contas do CSV em uma transação, anotando as linhas recusadas.
+///
+/// Só um cabeçalho inválido (ou um arquivo ilegível) interrompe a importação.
+pub fn import_users(db: &Database, path: &Path) -> AuthResult<ImportReport> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| validation!("Não foi possível ler '{}': {}", path.display(), e))?;
+
+    let headers = reader.headers().map_err(|e| validation!("Cabeçalho do CSV inválido: {}", e))?.clone();
+    if !headers.iter().any(|column| column == "username") {
+        return validation_error!("O CSV precisa de uma coluna username");
+    }
+    if let Some(column) = headers.iter().find(|column| !COLUMNS.contains(column)) {
+        return validation_error!("Coluna desconhecida no CSV: {}", column);
+    }
+
+    // As linhas passam pela mesma conexão da transação, e não por outra do pool
+    let conn = db.connection()?;
+    let store: &dyn UserStore = &*conn;
+    let tx = conn.unchecked_transaction()?;
+    let mut report = ImportReport::default();
+    for record in reader.records() {
+        let (line, result) = match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, |position| position.line());
+                (line, record.deserialize::<CsvUser>(Some(&headers)))
+            }
+            Err(err) => (err.position().map_or(0, |position| position.line()), Err(err)),
+        };
+        let user = match result {
+            Ok(user) => user,
+            Err(err) => {
+                report.errors.push(RowError { line, username: None, message: err.to_string() });
+                continue;
+            }
+        };
+
+        // Cada linha em um savepoint: uma falha no meio desfaz só a própria linha
+        let result = prepare(store, &user).and_then(|(password_hash, role)| {
+            tx.execute_batch("SAVEPOINT import_row")?;
+            match insert(store, &user, &password_hash, role) {
+                Ok(()) => Ok(tx.execute_batch("RELEASE import_row")?),
+                Err(err) => {
+                    tx.execute_batch("ROLLBACK TO import_row; RELEASE import_row")?;
+                    Err(err)
+                }
+            }
+        });
+        match result {
+            Ok(()) => report.created.push(user.username),
+            Err(err) => report.errors.push(RowError { line, username: Some(user.username), message: err.to_string() }),
+        }
+    }
+    tx.commit()?;
+
+    log::info!(
+        "importação de '{}': {} contas criadas, {} linhas recusadas",
+        path.display(),
+        report.created.len(),
+        report.errors.len()
+    );
+    let now = Utc::now();
+    for username in &report.created {
+        events::emit(Event::UserRegistered { username: username.clone() }, now);
+    }
+    Ok(report)
+}
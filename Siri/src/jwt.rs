@@ -0,0 +1,202 @@
+//! Tokens JWT assinados, emitidos no login do servidor HTTP.
+//!
+//! Diferente do token de sessão, que só vale consultando o banco, um JWT pode ser
+//! conferido por qualquer serviço que tenha a chave de verificação: o segredo
+//! compartilhado (HS256) ou a chave pública (RS256). Por isso ele não pode ser
+//! revogado antes de vencer, e a validade padrão é curta.
+
+use std::fs;
+use std::path::PathBuf;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use crate::error::{AuthError, AuthResult};
+use crate::rbac::Role;
+use crate::session::generate_token;
+use crate::{validation, validation_error};
+
+/// Algoritmo de assinatura dos tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JwtAlgorithm {
+    /// HMAC-SHA256 com um segredo compartilhado
+    #[default]
+    Hs256,
+    /// RSA-SHA256: assina com a chave privada, verifica com a pública
+    Rs256,
+}
+
+impl JwtAlgorithm {
+    fn algorithm(self) -> Algorithm {
+        match self {
+            JwtAlgorithm::Hs256 => Algorithm::HS256,
+            JwtAlgorithm::Rs256 => Algorithm::RS256,
+        }
+    }
+}
+
+/// Seção `[jwt]` do arquivo de configuração
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    pub algorithm: JwtAlgorithm,
+    /// Valor da reivindicação `iss`, conferido na verificação
+    pub issuer: String,
+    /// Validade de cada token (`ttl_minutes` no arquivo de configuração)
+    #[serde(rename = "ttl_minutes", deserialize_with = "crate::config::minutes")]
+    pub ttl: Duration,
+    /// Segredo do HS256; tem prioridade sobre `secret_file`
+    pub secret: Option<String>,
+    /// Arquivo com o segredo do HS256
+    pub secret_file: Option<PathBuf>,
+    /// Chave privada RSA em PEM, para assinar com RS256
+    pub private_key_file: Option<PathBuf>,
+    /// Chave pública RSA em PEM, para verificar com RS256
+    pub public_key_file: Option<PathBuf>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        JwtConfig {
+            algorithm: JwtAlgorithm::Hs256,
+            issuer: "siri".to_string(),
+            ttl: Duration::minutes(15),
+            secret: None,
+            secret_file: None,
+            private_key_file: None,
+            public_key_file: None,
+        }
+    }
+}
+
+/// Reivindicações de um token emitido pelo Siri
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenClaims {
+    /// Nome do usuário autenticado
+    pub sub: String,
+    /// Papel do usuário no momento do login
+    pub role: String,
+    pub iss: String,
+    /// Emissão, em segundos desde a época Unix
+    pub iat: i64,
+    /// Expiração, em segundos desde a época Unix
+    pub exp: i64,
+}
+
+impl TokenClaims {
+    /// Instante da expiração
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.exp, 0).unwrap_or_default()
+    }
+
+    /// Instante da emissão
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.iat, 0).unwrap_or_default()
+    }
+}
+
+/// Chaves para emitir e verificar tokens
+#[derive(Clone)]
+pub struct JwtKeys {
+    algorithm: JwtAlgorithm,
+    /// Ausente quando só a chave pública do RS256 está disponível
+    encoding: Option<EncodingKey>,
+    decoding: DecodingKey,
+    issuer: String,
+    ttl: Duration,
+}
+
+impl JwtKeys {
+    /// Chaves HS256 a partir de um segredo compartilhado
+    pub fn hs256(secret: &[u8], issuer: &str, ttl: Duration) -> Self {
+        JwtKeys {
+            algorithm: JwtAlgorithm::Hs256,
+            encoding: Some(EncodingKey::from_secret(secret)),
+            decoding: DecodingKey::from_secret(secret),
+            issuer: issuer.to_string(),
+            ttl,
+        }
+    }
+
+    /// Chaves RS256 a partir de PEMs; sem a chave privada, os tokens só podem ser verificados
+    pub fn rs256(private_pem: Option<&[u8]>, public_pem: &[u8], issuer: &str, ttl: Duration) -> AuthResult<Self> {
+        let encoding = private_pem
+            .map(EncodingKey::from_rsa_pem)
+            .transpose()
+            .map_err(|err| validation!("Chave privada RSA inválida: {}", err))?;
+        let decoding =
+            DecodingKey::from_rsa_pem(public_pem).map_err(|err| validation!("Chave pública RSA inválida: {}", err))?;
+        Ok(JwtKeys { algorithm: JwtAlgorithm::Rs256, encoding, decoding, issuer: issuer.to_string(), ttl })
+    }
+
+    /// Carrega as chaves da configuração.
+    ///
+    /// Com HS256 e sem segredo (`secret` ou `secret_file`), um segredo
+    /// aleatório é gerado: os tokens deixam de valer quando o processo termina.
+    pub fn from_config(config: &JwtConfig) -> AuthResult<Self> {
+        match config.algorithm {
+            JwtAlgorithm::Hs256 => {
+                let secret = match (&config.secret, &config.secret_file) {
+                    (Some(secret), _) => secret.clone().into_bytes(),
+                    (_, Some(path)) => fs::read(path)?.trim_ascii_end().to_vec(),
+                    _ => {
+                        log::warn!("jwt sem segredo configurado; usando um segredo aleatório até o fim do processo");
+                        generate_token().into_bytes()
+                    }
+                };
+                if secret.is_empty() {
+                    return validation_error!("O segredo do JWT não pode ser vazio");
+                }
+                Ok(JwtKeys::hs256(&secret, &config.issuer, config.ttl))
+            }
+            JwtAlgorithm::Rs256 => {
+                let Some(public_path) = &config.public_key_file else {
+                    return validation_error!("O algoritmo rs256 requer jwt.public_key_file");
+                };
+                let public_pem = fs::read(public_path)?;
+                let private_pem = config.private_key_file.as_ref().map(fs::read).transpose()?;
+                JwtKeys::rs256(private_pem.as_deref(), &public_pem, &config.issuer, config.ttl)
+            }
+        }
+    }
+
+    /// Emite um token para o usuário, válido a partir de `now`
+    pub fn issue(&self, username: &str, role: Role, now: DateTime<Utc>) -> AuthResult<String> {
+        let Some(encoding) = &self.encoding else {
+            return validation_error!("Sem chave privada para assinar tokens (jwt.private_key_file)");
+        };
+        let claims = TokenClaims {
+            sub: username.to_string(),
+            role: role.name().to_string(),
+            iss: self.issuer.clone(),
+            iat: now.timestamp(),
+            exp: (now + self.ttl).timestamp(),
+        };
+        jsonwebtoken::encode(&Header::new(self.algorithm.algorithm()), &claims, encoding)
+            .map_err(|err| validation!("Falha ao assinar o token: {}", err))
+    }
+
+    /// Confere a assinatura, o emissor e a validade de um token no instante `now`.
+    ///
+    /// Token malformado, adulterado, de outro emissor ou vencido resulta em
+    /// `InvalidCredentials`, sem distinguir o motivo.
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> AuthResult<TokenClaims> {
+        let mut validation = Validation::new(self.algorithm.algorithm());
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+        // A expiração é conferida abaixo, com o relógio do serviço
+        validation.validate_exp = false;
+
+        let claims = match jsonwebtoken::decode::<TokenClaims>(token, &self.decoding, &validation) {
+            Ok(data) => data.claims,
+            Err(err) => {
+                log::debug!("jwt recusado: {}", err);
+                return Err(AuthError::InvalidCredentials);
+            }
+        };
+        if now.timestamp() >= claims.exp {
+            return Err(AuthError::InvalidCredentials);
+        }
+        Ok(claims)
+    }
+}
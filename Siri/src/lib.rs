@@ -0,0 +1,90 @@
+//! Siri Ferrugem: sistema de autenticação com Argon2 e SQLite.
+//!
+//! A biblioteca expõe a lógica de registro, login e administração de usuários para
+//! ser reutilizada por outros programas; o binário `auth-system` é apenas uma
+//! interface de linha de comando sobre ela.
+
+pub mod admin;
+pub mod args;
+pub mod audit;
+pub mod auth;
+pub mod backup;
+pub mod breach;
+#[cfg(unix)]
+pub mod checkpassword;
+pub mod cli;
+pub mod clock;
+pub mod commands;
+pub mod config;
+pub mod crypto;
+#[cfg(unix)]
+pub mod daemon;
+pub mod db;
+pub mod email;
+pub mod dump;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod fsck;
+pub mod genpass;
+pub mod groups;
+pub mod hasher;
+pub mod health;
+pub mod i18n;
+pub mod import;
+pub mod jwt;
+pub mod logging;
+pub mod mailer;
+pub mod metrics;
+pub mod migrations;
+pub mod output;
+pub mod pepper;
+pub mod pool;
+pub mod postgres_store;
+pub mod profile;
+pub mod prompt;
+pub mod rbac;
+pub mod rate_limit;
+pub mod recovery;
+pub mod server;
+pub mod service;
+pub mod session;
+pub mod settings;
+pub mod store;
+pub mod strength;
+#[cfg(test)]
+mod testing;
+pub mod totp;
+pub mod tui;
+pub mod user;
+
+pub use auth::{
+    change_password, change_username, create_reset_token, delete_user, describe_login_outcome, ensure_user,
+    login_user, login_user_with_clock, redeem_reset_token, register_user, register_user_with_email,
+    validate_email,
+    Argon2Config, EnsureOutcome, IfExists, LegacyHashPolicy, LockoutPolicy, LoginOutcome, PasswordConfig,
+};
+pub use backup::{backup_database, restore_database, BackupReport, RestoreReport};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use config::{Config, DatabaseConfig, SessionPolicy};
+pub use crypto::secrets_equal;
+pub use db::{Database, DatabaseStats, HashHealth};
+pub use error::{AuthError, AuthResult, ErrorKind, PasswordIssue};
+pub use export::export_user_data;
+pub use genpass::{generate_password, GeneratorOptions};
+pub use groups::Group;
+pub use mailer::{ConsoleMailer, Email, Mailer, SmtpMailer};
+pub use i18n::{Locale, Message};
+pub use jwt::{JwtAlgorithm, JwtConfig, JwtKeys, TokenClaims};
+pub use hasher::{Argon2Hasher, BcryptHasher, HashScheme, PasswordHasher, Pbkdf2Hasher, ScryptHasher};
+pub use pool::{DatabasePool, JournalMode, Synchronous};
+pub use postgres_store::PostgresStore;
+pub use profile::Profile;
+pub use rate_limit::RateLimiter;
+pub use rbac::{authorize, Permission, Role};
+pub use recovery::{generate_recovery_codes, reset_password_with_recovery_code};
+pub use service::AuthService;
+pub use session::Session;
+pub use store::{LoginAttempt, StoredCredentials, UserStore};
+pub use strength::{estimate_strength, password_strength_score, StrengthReport, StrengthScore};
+pub use user::{AccountStatus, EmailStatus, TimestampDisplay, User};
@@ -0,0 +1,206 @@
+//! Log de diagnóstico em arquivo, com rotação por tamanho e por dia.
+//!
+//! O log fica separado da saída do terminal: o menu interativo continua falando com
+//! o usuário, e o arquivo guarda o que aconteceu para quem opera o sistema. As
+//! mensagens do log não são traduzidas, para que o arquivo seja o mesmo em qualquer
+//! idioma e possa ser filtrado com `grep`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::Deserialize;
+use crate::error::AuthResult;
+
+/// Alvos deste pacote (a biblioteca e o binário); as dependências só registram avisos e erros
+const OWN_TARGETS: [&str; 2] = ["siri_ferrugem", "auth_system"];
+
+/// Nível mínimo das mensagens gravadas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Seção `[log]` do arquivo de configuração
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// Arquivo do log (None = sem log)
+    pub file: Option<PathBuf>,
+    pub level: LogLevel,
+    /// Tamanho a partir do qual o arquivo é rotacionado, em MiB (0 = sem limite)
+    pub max_size_mb: u64,
+    /// Rotaciona também na virada do dia
+    pub daily: bool,
+    /// Quantos arquivos antigos (`.1`, `.2`...) são mantidos
+    pub keep: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig { file: None, level: LogLevel::Info, max_size_mb: 10, daily: false, keep: 5 }
+    }
+}
+
+/// Arquivo de log aberto para acréscimo, que se rotaciona sozinho antes de cada linha
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// Dia da última escrita, para a rotação diária
+    day: NaiveDate,
+    max_size: u64,
+    daily: bool,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(config: &LogConfig, path: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = open_append(path)?;
+        let metadata = file.metadata()?;
+        // Um arquivo de ontem é rotacionado na primeira linha de hoje
+        let day = metadata.modified().map(|at| DateTime::<Local>::from(at).date_naive()).unwrap_or_else(|_| today());
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            file,
+            size: metadata.len(),
+            day,
+            max_size: config.max_size_mb.saturating_mul(1024 * 1024),
+            daily: config.daily,
+            keep: config.keep,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let today = today();
+        let too_big = self.max_size > 0 && self.size + line.len() as u64 > self.max_size;
+        let new_day = self.daily && today != self.day;
+        if self.size > 0 && (too_big || new_day) {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        self.day = today;
+        Ok(())
+    }
+
+    /// `siri.log` vira `siri.log.1`, `siri.log.1` vira `siri.log.2` e assim por diante;
+    /// o que passaria de `keep` é apagado
+    fn rotate(&mut self) -> io::Result<()> {
+        ignore_missing(fs::remove_file(self.numbered(self.keep)))?;
+        for n in (1..self.keep).rev() {
+            ignore_missing(fs::rename(self.numbered(n), self.numbered(n + 1)))?;
+        }
+        if self.keep > 0 {
+            fs::rename(&self.path, self.numbered(1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn numbered(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn ignore_missing(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Abre para acréscimo; o log pode conter nomes de usuário, então só o dono o lê
+fn open_append(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+struct FileLogger {
+    level: LevelFilter,
+    file: Mutex<RotatingFile>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let own = OWN_TARGETS.iter().any(|target| metadata.target().starts_with(target));
+        metadata.level() <= self.level && (own || metadata.level() <= LevelFilter::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        // Uma falha ao gravar o log não deve derrubar a operação que ele descreve
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_line(&line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+/// Passa a gravar o log no arquivo configurado; sem arquivo, não faz nada.
+///
+/// Deve ser chamada uma vez, no início do programa.
+pub fn init(config: &LogConfig) -> AuthResult<()> {
+    let Some(path) = &config.file else {
+        return Ok(());
+    };
+    let file = RotatingFile::open(config, path)?;
+    let level = config.level.filter();
+    // Só falha se já houver um logger instalado, e então o primeiro continua valendo
+    if log::set_boxed_logger(Box::new(FileLogger { level, file: Mutex::new(file) })).is_ok() {
+        log::set_max_level(level);
+    }
+    Ok(())
+}
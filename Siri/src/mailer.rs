@@ -0,0 +1,149 @@
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::Deserialize;
+use crate::error::{AuthError, AuthResult};
+use crate::{errln, tr, validation, validation_error};
+
+/// Remetente padrão
+const DEFAULT_FROM: &str = "Siri Ferrugem <no-reply@localhost>";
+
+/// Seção `[smtp]` do arquivo de configuração
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfig {
+    /// Servidor SMTP; sem ele, os emails são exibidos no console
+    pub host: Option<String>,
+    /// Porta do servidor (padrão: 587 com STARTTLS, 25 sem TLS)
+    pub port: Option<u16>,
+    /// Usuário e senha do servidor, se ele exigir autenticação
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// Remetente dos emails
+    pub from: String,
+    /// false desliga o TLS, para servidores de teste locais (ex.: MailHog)
+    pub tls: bool,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        SmtpConfig { host: None, port: None, user: None, password: None, from: DEFAULT_FROM.to_string(), tls: true }
+    }
+}
+
+impl SmtpConfig {
+    pub(crate) fn validate(&self) -> AuthResult<()> {
+        if self.host.as_ref().is_some_and(|host| host.trim().is_empty()) {
+            return validation_error!("O servidor SMTP não pode ser vazio");
+        }
+        if self.user.is_some() != self.password.is_some() {
+            return validation_error!("smtp.user e smtp.password devem ser definidos juntos");
+        }
+        self.from
+            .parse::<Mailbox>()
+            .map_err(|e| validation!("Remetente inválido '{}': {}", self.from, e))?;
+        Ok(())
+    }
+}
+
+/// Um email a enviar, em texto puro
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Forma de entregar os emails de verificação e de redefinição de senha
+pub trait Mailer: Send + Sync {
+    /// Envia o email, retornando erro se ele não puder ser entregue ao servidor
+    fn send(&self, email: &Email) -> AuthResult<()>;
+}
+
+/// Mailer de desenvolvimento: exibe o email no console em vez de enviá-lo.
+///
+/// Escreve na saída de erro, para não se misturar à saída JSON dos comandos.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleMailer;
+
+impl Mailer for ConsoleMailer {
+    fn send(&self, email: &Email) -> AuthResult<()> {
+        errln!("📨 ───── {} ─────", tr!("Email (não enviado: SMTP não configurado)"));
+        errln!("{}", tr!("Para: {}", email.to));
+        errln!("{}", tr!("Assunto: {}", email.subject));
+        errln!();
+        errln!("{}", email.body);
+        errln!("────────────────────────────────────────────────────");
+        Ok(())
+    }
+}
+
+/// Envia os emails por um servidor SMTP
+pub struct SmtpMailer {
+    transport: SmtpTransport,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Conecta ao servidor com STARTTLS (ou sem TLS, com `tls = false`)
+    pub fn new(
+        host: &str,
+        port: Option<u16>,
+        credentials: Option<(String, String)>,
+        from: &str,
+        tls: bool,
+    ) -> AuthResult<Self> {
+        let mut builder = if tls {
+            SmtpTransport::starttls_relay(host).map_err(|e| AuthError::Mail(e.to_string()))?
+        } else {
+            SmtpTransport::builder_dangerous(host)
+        };
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+        if let Some((user, password)) = credentials {
+            builder = builder.credentials(Credentials::new(user, password));
+        }
+
+        let from = from
+            .parse()
+            .map_err(|e| validation!("Remetente inválido '{}': {}", from, e))?;
+        Ok(SmtpMailer { transport: builder.build(), from })
+    }
+
+    /// Conecta ao servidor da seção `[smtp]` (None se `smtp.host` não estiver definido)
+    pub fn from_config(config: &SmtpConfig) -> AuthResult<Option<Self>> {
+        let Some(host) = &config.host else {
+            return Ok(None);
+        };
+        let credentials = config.user.clone().zip(config.password.clone());
+        Self::new(host, config.port, credentials, &config.from, config.tls).map(Some)
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, email: &Email) -> AuthResult<()> {
+        let to: Mailbox = email
+            .to
+            .parse()
+            .map_err(|e| validation!("Destinatário inválido '{}': {}", email.to, e))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&email.subject)
+            .body(email.body.clone())
+            .map_err(|e| AuthError::Mail(e.to_string()))?;
+
+        self.transport.send(&message).map_err(|e| AuthError::Mail(e.to_string()))?;
+        log::info!("email '{}' enviado para {}", email.subject, email.to);
+        Ok(())
+    }
+}
+
+/// Mailer da seção `[smtp]`: SMTP se `smtp.host` estiver definido, senão o console
+pub fn mailer_from_config(config: &SmtpConfig) -> AuthResult<Box<dyn Mailer>> {
+    Ok(match SmtpMailer::from_config(config)? {
+        Some(smtp) => Box::new(smtp),
+        None => Box::new(ConsoleMailer),
+    })
+}
@@ -1,13 +1,139 @@
-mod auth;
-mod cli;
-mod db;
-mod error;
+use std::process;
+use clap::Parser;
+use siri_ferrugem::args::{Args, Backend, Command};
+#[cfg(unix)]
+use siri_ferrugem::checkpassword;
+use siri_ferrugem::commands::CommandOptions;
+use siri_ferrugem::cli::CLI;
+use siri_ferrugem::prompt::PasswordEcho;
+use siri_ferrugem::db::OnCorrupt;
+use siri_ferrugem::jwt::JwtKeys;
+use siri_ferrugem::mailer::mailer_from_config;
+use siri_ferrugem::{commands, config, fsck, health, logging, output, server, tui, AuthError, AuthResult, AuthService, Database, ErrorKind, Message, PostgresStore};
+use siri_ferrugem::{validation, validation_error};
 
-use cli::CLI;
-use error::AuthResult;
+/// Se a linha de comando pede saída JSON, mesmo quando ela não pôde ser interpretada
+fn json_requested() -> bool {
+    std::env::args_os().skip(1).take_while(|arg| arg != "--").any(|arg| arg == "--json")
+}
 
-fn main() -> AuthResult<()> {
-    let cli = CLI::new()?;
-    cli.run()?;
+fn run(args: Args) -> AuthResult<()> {
+    let config = config::load()?;
+    let mut log_config = config.log.clone();
+    if let Some(path) = args.log_file {
+        log_config.file = Some(path);
+    }
+    logging::init(&log_config)?;
+    log::info!("siri {} iniciado (backend {:?})", env!("CARGO_PKG_VERSION"), args.backend);
+
+    let echo = if args.mask { PasswordEcho::Masked } else { PasswordEcho::Hidden };
+    let on_corrupt = if args.recover_corrupt { OnCorrupt::MoveAside } else { OnCorrupt::Refuse };
+    let backend = args.backend;
+    let dsn = args.dsn;
+    let ephemeral = args.ephemeral;
+    let db_flag = args.db.is_some();
+    let db_path = args.db.unwrap_or_else(|| config.database.path.clone());
+    let open_service = || -> AuthResult<AuthService> {
+        let service = match backend {
+            Backend::Sqlite if ephemeral => Ok(AuthService::new(Database::in_memory()?)),
+            Backend::Sqlite => Ok(AuthService::new(Database::open(&db_path, on_corrupt)?)),
+            Backend::Postgres if ephemeral => Err(validation!("--ephemeral só pode ser usado com o backend sqlite")),
+            Backend::Postgres if db_flag => Err(validation!("--db só pode ser usado com o backend sqlite")),
+            Backend::Postgres => {
+                let dsn = dsn.as_deref().ok_or_else(|| {
+                    validation!("O backend postgres requer --dsn (ou SIRI_DSN)")
+                })?;
+                Ok(AuthService::with_postgres(PostgresStore::connect(dsn)?))
+            }
+        };
+        Ok(service?.with_mailer(mailer_from_config(&config.smtp)?))
+    };
+
+    match args.command {
+        Some(_) if args.tui => {
+            return validation_error!("--tui não pode ser usado com um comando");
+        }
+        Some(Command::Serve { listen }) => {
+            let service = open_service()?.with_source("http").with_jwt(JwtKeys::from_config(&config.jwt)?);
+            server::serve(service, listen)?;
+        }
+        #[cfg(unix)]
+        Some(Command::Daemon { socket, metrics_listen }) => {
+            let service = open_service()?.with_source("daemon");
+            siri_ferrugem::daemon::run(service, &socket, metrics_listen)?;
+        }
+        #[cfg(unix)]
+        Some(Command::Checkpassword { program }) => {
+            // O protocolo tem os próprios códigos de saída: 1 para recusa e 111 para falha temporária
+            let code = match open_service() {
+                Ok(service) => checkpassword::run(&service.with_source("checkpassword"), &program),
+                Err(err) => {
+                    log::error!("checkpassword: {}", err);
+                    checkpassword::TEMPORARY_FAILURE
+                }
+            };
+            process::exit(code);
+        }
+        Some(Command::Health) => {
+            // Um banco que não abre também é um resultado, com o relatório completo
+            let report = match open_service() {
+                Ok(service) => health::check(&service.with_source("health")),
+                Err(err) => health::unavailable(&err),
+            };
+            output::print_success(&report.output(), args.json);
+            if !report.healthy() {
+                process::exit(health::UNHEALTHY);
+            }
+        }
+        Some(Command::Fsck) => {
+            let report = fsck::check(&*open_service()?.database()?.connection()?)?;
+            output::print_success(&report.output(), args.json);
+            if !report.clean() {
+                process::exit(fsck::PROBLEMS_FOUND);
+            }
+        }
+        Some(command) => {
+            let service = open_service()?.with_source("comando");
+            let options = CommandOptions { json: args.json, password_stdin: args.password_stdin, echo };
+            let output = commands::run(&service, command, options)?;
+            output::print_success(&output, args.json);
+        }
+        None if args.json => {
+            return validation_error!("O modo --json requer um comando (register, login, list, stats)");
+        }
+        None if args.tui => {
+            let service = open_service()?.with_source("tui");
+            tui::run(&service)?;
+        }
+        None => {
+            let cli = CLI::with_service(open_service()?).with_password_echo(echo);
+            cli.run()?;
+        }
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn main() {
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        // Com --json, argumentos inválidos também viram um objeto JSON (ajuda e versão continuam em texto)
+        Err(err) if json_requested() && err.use_stderr() => {
+            // Só o primeiro parágrafo, sem as dicas e o resumo de uso
+            let rendered = err.render().to_string();
+            let summary = rendered.trim().trim_start_matches("error: ").split("\n\n").next().unwrap_or_default();
+            let message = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+            output::print_error(&AuthError::Validation(Message::verbatim(message)), true);
+            process::exit(ErrorKind::Validation.exit_code());
+        }
+        Err(err) => err.exit(),
+    };
+    let json = args.json;
+    let dumb = output::dumb_terminal();
+    output::configure(args.plain || dumb, !(args.no_color || output::no_color_requested() || dumb));
+
+    if let Err(err) = run(args) {
+        log::error!("{}", err);
+        output::print_error(&err, json);
+        process::exit(err.kind().exit_code());
+    }
+}
@@ -0,0 +1,81 @@
+//! Métricas do processo no formato de texto do Prometheus.
+//!
+//! Os contadores são globais e começam em zero a cada execução; só fazem sentido
+//! nos modos de longa duração (`siri serve` e `siri daemon`), que os expõem em
+//! `/metrics` para alertas de picos de força bruta.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use crate::events::Event;
+
+/// Tipo de conteúdo da exposição de texto do Prometheus
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Limites superiores dos baldes do histograma de verificação, em segundos
+const VERIFY_BUCKETS: [f64; 9] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+static LOGIN_SUCCESS: AtomicU64 = AtomicU64::new(0);
+static LOGIN_FAILURE: AtomicU64 = AtomicU64::new(0);
+static REGISTRATIONS: AtomicU64 = AtomicU64::new(0);
+static LOCKOUTS: AtomicU64 = AtomicU64::new(0);
+
+/// Contagem acumulada de cada balde (o último é o `+Inf`)
+static VERIFY_BUCKET_COUNTS: [AtomicU64; VERIFY_BUCKETS.len() + 1] = [const { AtomicU64::new(0) }; VERIFY_BUCKETS.len() + 1];
+/// Soma das durações, em microssegundos
+static VERIFY_SUM_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// Conta uma tentativa de login, aceita ou recusada
+pub fn record_login(success: bool) {
+    let counter = if success { &LOGIN_SUCCESS } else { &LOGIN_FAILURE };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Conta os eventos que têm métrica própria (cadastros e bloqueios)
+pub fn record_event(event: &Event) {
+    match event {
+        Event::UserRegistered { .. } => REGISTRATIONS.fetch_add(1, Ordering::Relaxed),
+        Event::AccountLocked { .. } => LOCKOUTS.fetch_add(1, Ordering::Relaxed),
+        Event::LoginFailureStreak { .. } | Event::PasswordChanged { .. } => return,
+    };
+}
+
+/// Registra quanto tempo levou uma verificação de senha com o Argon2
+pub fn observe_argon2_verify(elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+    let bucket = VERIFY_BUCKETS.iter().position(|limit| seconds <= *limit).unwrap_or(VERIFY_BUCKETS.len());
+    VERIFY_BUCKET_COUNTS[bucket].fetch_add(1, Ordering::Relaxed);
+    VERIFY_SUM_MICROS.fetch_add(elapsed.as_micros().try_into().unwrap_or(u64::MAX), Ordering::Relaxed);
+}
+
+/// Todas as métricas, no formato de exposição de texto do Prometheus (versão 0.0.4)
+pub fn render() -> String {
+    let mut out = String::new();
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+    let _ = writeln!(out, "# HELP siri_logins_total Tentativas de login, por resultado.");
+    let _ = writeln!(out, "# TYPE siri_logins_total counter");
+    let _ = writeln!(out, "siri_logins_total{{result=\"success\"}} {}", load(&LOGIN_SUCCESS));
+    let _ = writeln!(out, "siri_logins_total{{result=\"failure\"}} {}", load(&LOGIN_FAILURE));
+
+    let _ = writeln!(out, "# HELP siri_registrations_total Contas cadastradas.");
+    let _ = writeln!(out, "# TYPE siri_registrations_total counter");
+    let _ = writeln!(out, "siri_registrations_total {}", load(&REGISTRATIONS));
+
+    let _ = writeln!(out, "# HELP siri_lockouts_total Contas bloqueadas, por tentativas ou por um administrador.");
+    let _ = writeln!(out, "# TYPE siri_lockouts_total counter");
+    let _ = writeln!(out, "siri_lockouts_total {}", load(&LOCKOUTS));
+
+    let _ = writeln!(out, "# HELP siri_argon2_verify_seconds Duração de cada verificação de senha com o Argon2.");
+    let _ = writeln!(out, "# TYPE siri_argon2_verify_seconds histogram");
+    let mut cumulative = 0;
+    for (limit, count) in VERIFY_BUCKETS.iter().zip(&VERIFY_BUCKET_COUNTS) {
+        cumulative += load(count);
+        let _ = writeln!(out, "siri_argon2_verify_seconds_bucket{{le=\"{}\"}} {}", limit, cumulative);
+    }
+    cumulative += load(&VERIFY_BUCKET_COUNTS[VERIFY_BUCKETS.len()]);
+    let _ = writeln!(out, "siri_argon2_verify_seconds_bucket{{le=\"+Inf\"}} {}", cumulative);
+    let _ = writeln!(out, "siri_argon2_verify_seconds_sum {}", load(&VERIFY_SUM_MICROS) as f64 / 1_000_000.0);
+    let _ = writeln!(out, "siri_argon2_verify_seconds_count {}", cumulative);
+    out
+}
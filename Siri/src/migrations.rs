@@ -0,0 +1,466 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use crate::auth::parse_sqlite_timestamp;
+use crate::db::column_exists;
+use crate::error::AuthResult;
+use crate::validation_error;
+
+/// Uma alteração versionada do esquema
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    apply: fn(&Connection) -> AuthResult<()>,
+}
+
+/// Migrações em ordem de versão. Novas alterações de esquema entram sempre no fim,
+/// com a próxima versão; migrações já publicadas nunca devem ser editadas.
+///
+/// Bancos criados antes do controle de versão já têm parte dessas alterações, por
+/// isso cada passo verifica o que existe antes de alterar.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "tabelas iniciais", apply: create_initial_tables },
+    Migration { version: 2, description: "email dos usuários", apply: add_email },
+    Migration { version: 3, description: "data da troca de senha", apply: add_password_changed_at },
+    Migration { version: 4, description: "bloqueio por tentativas", apply: add_lockout },
+    Migration { version: 5, description: "ativação de contas", apply: add_enabled },
+    Migration { version: 6, description: "exclusão reversível", apply: add_deleted_at },
+    Migration { version: 7, description: "troca de senha obrigatória", apply: add_must_change_password },
+    Migration { version: 8, description: "administradores", apply: add_is_admin },
+    Migration { version: 9, description: "sessões", apply: create_sessions },
+    Migration { version: 10, description: "autenticação em dois fatores", apply: create_totp_secrets },
+    Migration { version: 11, description: "códigos de recuperação do segundo fator", apply: create_totp_recovery_codes },
+    Migration { version: 12, description: "histórico de logins", apply: create_login_attempts },
+    Migration { version: 13, description: "tokens de redefinição de senha", apply: create_password_resets },
+    Migration { version: 14, description: "verificação de email", apply: add_email_verification },
+    Migration { version: 15, description: "papéis dos usuários", apply: add_role },
+    Migration { version: 16, description: "permissões dos papéis", apply: create_permissions },
+    Migration { version: 17, description: "grupos de usuários", apply: create_groups },
+    Migration { version: 18, description: "situação das contas", apply: add_status },
+    Migration { version: 19, description: "perfil dos usuários", apply: add_profile },
+    Migration { version: 20, description: "último login", apply: add_last_login },
+    Migration { version: 21, description: "códigos de recuperação unificados", apply: merge_recovery_codes },
+];
+
+/// Estado de uma migração no banco
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub description: &'static str,
+    /// Quando foi aplicada (None se ainda pendente)
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// Versão mais recente do esquema conhecida por este binário
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Versão atual do esquema do banco (0 se nenhuma migração foi aplicada)
+pub fn current_version(conn: &Connection) -> AuthResult<u32> {
+    create_version_table(conn)?;
+    let version = conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?;
+    Ok(version)
+}
+
+/// Aplica as migrações pendentes, cada uma em sua própria transação.
+///
+/// Recusa bancos com uma versão mais nova que a deste binário, para não operar
+/// sobre um esquema que ele não conhece.
+pub fn migrate(conn: &Connection) -> AuthResult<Vec<u32>> {
+    let current = current_version(conn)?;
+    if current > latest_version() {
+        return validation_error!(
+            "O banco está na versão {} do esquema, mais nova que a suportada ({}). Atualize o programa",
+            current,
+            latest_version()
+        );
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.apply)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description) VALUES (?1, ?2)",
+            rusqlite::params![migration.version, migration.description],
+        )?;
+        tx.commit()?;
+        applied.push(migration.version);
+    }
+    Ok(applied)
+}
+
+/// Lista todas as migrações conhecidas e se já foram aplicadas
+pub fn status(conn: &Connection) -> AuthResult<Vec<MigrationStatus>> {
+    create_version_table(conn)?;
+    let mut stmt = conn.prepare("SELECT applied_at FROM schema_version WHERE version = ?1")?;
+
+    MIGRATIONS
+        .iter()
+        .map(|migration| {
+            let applied_at: Option<String> = stmt
+                .query_row([migration.version], |row| row.get(0))
+                .optional()?;
+            Ok(MigrationStatus {
+                version: migration.version,
+                description: migration.description,
+                applied_at: applied_at.as_deref().and_then(parse_sqlite_timestamp),
+            })
+        })
+        .collect()
+}
+
+fn create_version_table(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Adiciona uma coluna a uma tabela existente, caso ainda não exista
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> AuthResult<()> {
+    if !column_exists(conn, table, column)? {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn create_initial_tables(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS users (
+            id INTEGER PRIMARY KEY,
+            username TEXT NOT NULL UNIQUE,
+            password_hash TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS recovery_codes (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            code_hash TEXT NOT NULL,
+            consumed_at DATETIME,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY,
+            actor TEXT NOT NULL,
+            action TEXT NOT NULL,
+            target TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_email(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "email", "TEXT")?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email
+         ON users (email COLLATE NOCASE)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_password_changed_at(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "password_changed_at", "DATETIME")
+}
+
+fn add_lockout(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "failed_attempts", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "users", "locked_until", "DATETIME")
+}
+
+fn add_enabled(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "enabled", "INTEGER NOT NULL DEFAULT 1")
+}
+
+fn add_deleted_at(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "deleted_at", "DATETIME")
+}
+
+fn add_must_change_password(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "must_change_password", "INTEGER NOT NULL DEFAULT 0")
+}
+
+fn add_is_admin(conn: &Connection) -> AuthResult<()> {
+    // Bancos anteriores aos administradores: o usuário mais antigo assume o papel
+    if !column_exists(conn, "users", "is_admin")? {
+        add_column_if_missing(conn, "users", "is_admin", "INTEGER NOT NULL DEFAULT 0")?;
+        conn.execute(
+            "UPDATE users SET is_admin = 1
+             WHERE id = (SELECT MIN(id) FROM users WHERE deleted_at IS NULL)",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn create_sessions(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at DATETIME NOT NULL,
+            expires_at DATETIME NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn create_totp_secrets(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS totp_secrets (
+            user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+            secret TEXT NOT NULL,
+            last_used_step INTEGER,
+            enabled_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn create_login_attempts(conn: &Connection) -> AuthResult<()> {
+    // Tentativas com nomes inexistentes também entram, sem usuário associado
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS login_attempts (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+            username TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            outcome TEXT NOT NULL,
+            source TEXT NOT NULL,
+            attempted_at DATETIME NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_login_attempts_user
+         ON login_attempts (user_id, attempted_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn create_password_resets(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS password_resets (
+            id INTEGER PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_by TEXT NOT NULL,
+            expires_at DATETIME NOT NULL,
+            used_at DATETIME,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_email_verification(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "email_verified_at", "DATETIME")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS email_verifications (
+            id INTEGER PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            email TEXT NOT NULL,
+            expires_at DATETIME NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn create_totp_recovery_codes(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS totp_recovery_codes (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            code_hash TEXT NOT NULL,
+            consumed_at DATETIME,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_role(conn: &Connection) -> AuthResult<()> {
+    // O papel substitui a coluna is_admin, que deixa de existir
+    add_column_if_missing(conn, "users", "role", "TEXT NOT NULL DEFAULT 'user'")?;
+    if column_exists(conn, "users", "is_admin")? {
+        conn.execute("UPDATE users SET role = 'admin' WHERE is_admin = 1", [])?;
+        conn.execute("ALTER TABLE users DROP COLUMN is_admin", [])?;
+    }
+    Ok(())
+}
+
+fn create_permissions(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS permissions (
+            name TEXT PRIMARY KEY,
+            description TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS role_permissions (
+            role TEXT NOT NULL,
+            permission TEXT NOT NULL REFERENCES permissions(name) ON DELETE CASCADE,
+            PRIMARY KEY (role, permission)
+        )",
+        [],
+    )?;
+
+    // Lista fixa desta versão: permissões futuras entram com novas migrações
+    let permissions = [
+        ("list_users", "Listar todos os usuários"),
+        ("delete_user", "Excluir, restaurar e purgar usuários"),
+        ("view_audit", "Consultar o log de auditoria"),
+        ("manage_roles", "Promover/rebaixar usuários e alterar permissões"),
+        ("manage_accounts", "Ativar, desbloquear e redefinir senhas de contas"),
+        ("manage_settings", "Alterar limites e validade das senhas"),
+        ("view_stats", "Ver estatísticas do sistema"),
+    ];
+    for (name, description) in permissions {
+        conn.execute(
+            "INSERT OR IGNORE INTO permissions (name, description) VALUES (?1, ?2)",
+            [name, description],
+        )?;
+        // Os administradores mantêm tudo o que já podiam fazer
+        conn.execute(
+            "INSERT OR IGNORE INTO role_permissions (role, permission) VALUES ('admin', ?1)",
+            [name],
+        )?;
+    }
+    Ok(())
+}
+
+fn create_groups(conn: &Connection) -> AuthResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS groups (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS group_members (
+            group_id INTEGER NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            added_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (group_id, user_id)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_group_members_user ON group_members (user_id)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn add_status(conn: &Connection) -> AuthResult<()> {
+    // A situação substitui a coluna enabled, que deixa de existir
+    add_column_if_missing(conn, "users", "status", "TEXT NOT NULL DEFAULT 'active'")?;
+    if column_exists(conn, "users", "enabled")? {
+        conn.execute("UPDATE users SET status = 'disabled' WHERE enabled = 0", [])?;
+        conn.execute("ALTER TABLE users DROP COLUMN enabled", [])?;
+    }
+    Ok(())
+}
+
+fn add_profile(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "display_name", "TEXT")?;
+    add_column_if_missing(conn, "users", "phone", "TEXT")?;
+    add_column_if_missing(conn, "users", "bio", "TEXT")
+}
+
+fn add_last_login(conn: &Connection) -> AuthResult<()> {
+    add_column_if_missing(conn, "users", "last_login_at", "DATETIME")?;
+    add_column_if_missing(conn, "users", "login_count", "INTEGER NOT NULL DEFAULT 0")
+}
+
+/// Os códigos do segundo fator passam para `recovery_codes`, o único conjunto por conta,
+/// que serve tanto para o login com dois fatores quanto para "Esqueci minha senha".
+/// Os códigos já entregues continuam valendo, agora para os dois usos.
+fn merge_recovery_codes(conn: &Connection) -> AuthResult<()> {
+    conn.execute_batch(
+        "INSERT INTO recovery_codes (user_id, code_hash, consumed_at, created_at)
+            SELECT user_id, code_hash, consumed_at, created_at FROM totp_recovery_codes;
+         DROP TABLE totp_recovery_codes;",
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_factor_codes_move_to_the_single_recovery_table() {
+        // Um banco parado na versão 20, ainda com a tabela própria do segundo fator
+        let conn = Connection::open_in_memory().unwrap();
+        create_version_table(&conn).unwrap();
+        for migration in MIGRATIONS.iter().filter(|migration| migration.version <= 20) {
+            (migration.apply)(&conn).unwrap();
+            conn.execute(
+                "INSERT INTO schema_version (version, description) VALUES (?1, ?2)",
+                rusqlite::params![migration.version, migration.description],
+            )
+            .unwrap();
+        }
+        conn.execute("INSERT INTO users (id, username, password_hash) VALUES (1, 'alice', 'x')", []).unwrap();
+        conn.execute(
+            "INSERT INTO totp_recovery_codes (user_id, code_hash, consumed_at)
+             VALUES (1, 'livre', NULL), (1, 'usado', CURRENT_TIMESTAMP)",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(migrate(&conn).unwrap(), vec![21]);
+        let unused: Vec<String> = conn
+            .prepare("SELECT code_hash FROM recovery_codes WHERE user_id = 1 AND consumed_at IS NULL")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(unused, vec!["livre".to_string()]);
+        let old_tables: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'totp_recovery_codes'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(old_tables, 0);
+    }
+}
@@ -0,0 +1,259 @@
+use std::borrow::Cow;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crossterm::style::{Color, Stylize};
+use serde_json::{json, Value};
+use crate::commands::CommandOutput;
+use crate::error::AuthError;
+use crate::tr;
+
+/// Modo texto puro: sem emoji nem molduras, para terminais simples, logs e leitores de tela
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Se as mensagens de sucesso, aviso e erro saem coloridas na saída padrão e na de erro
+static COLOR_STDOUT: AtomicBool = AtomicBool::new(false);
+static COLOR_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Para onde vai um texto (cada saída só é colorida se for um terminal)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Tipo de uma linha, deduzido do ícone que a abre
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tone {
+    Success,
+    Warning,
+    Error,
+    Hint,
+}
+
+impl Tone {
+    fn of(line: &str) -> Option<Tone> {
+        let line = line.trim_start();
+        let tone = match line.chars().next()? {
+            '✅' => Tone::Success,
+            '⚠' => Tone::Warning,
+            '❌' | '🚫' | '⛔' => Tone::Error,
+            '💡' | 'ℹ' => Tone::Hint,
+            _ => return None,
+        };
+        Some(tone)
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Tone::Success => Color::Green,
+            Tone::Warning => Color::Yellow,
+            Tone::Error => Color::Red,
+            Tone::Hint => Color::Cyan,
+        }
+    }
+}
+
+/// Sufixo que transforma um dígito em tecla numérica ("1️⃣")
+const KEYCAP: &str = "\u{fe0f}\u{20e3}";
+
+/// Seletor que pede a apresentação colorida de um símbolo ("⚠" → "⚠️")
+const EMOJI_PRESENTATION: char = '\u{fe0f}';
+
+/// Define o modo texto puro e se há cores (que só valem nas saídas ligadas a um terminal)
+pub fn configure(plain: bool, color: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+    COLOR_STDOUT.store(color && io::stdout().is_terminal(), Ordering::Relaxed);
+    COLOR_STDERR.store(color && io::stderr().is_terminal(), Ordering::Relaxed);
+}
+
+/// Se a saída está em modo texto puro
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Se o terminal provavelmente não desenha emoji: `TERM=dumb`, ou `TERM` ausente fora do Windows
+pub fn dumb_terminal() -> bool {
+    match env::var("TERM") {
+        Ok(term) => term.is_empty() || term == "dumb",
+        Err(_) => !cfg!(windows),
+    }
+}
+
+/// Se a saída indicada está colorida
+pub fn color_enabled(stream: Stream) -> bool {
+    match stream {
+        Stream::Stdout => &COLOR_STDOUT,
+        Stream::Stderr => &COLOR_STDERR,
+    }
+    .load(Ordering::Relaxed)
+}
+
+/// Se o ambiente pede saída sem cores (`NO_COLOR` definido e não vazio, como em no-color.org)
+pub fn no_color_requested() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Prepara um texto para a saída: no modo texto puro, troca emoji e molduras por ASCII, e
+/// com cores, pinta cada linha de sucesso, aviso, erro ou dica
+pub fn render(text: &str, stream: Stream) -> Cow<'_, str> {
+    if !color_enabled(stream) {
+        return if is_plain() { Cow::Owned(to_plain(text)) } else { Cow::Borrowed(text) };
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches('\n');
+        let newline = &line[content.len()..];
+        let rendered = if is_plain() { Cow::Owned(to_plain(content)) } else { Cow::Borrowed(content) };
+        match Tone::of(content) {
+            Some(tone) => out.push_str(&rendered.with(tone.color()).to_string()),
+            None => out.push_str(&rendered),
+        }
+        out.push_str(newline);
+    }
+    Cow::Owned(out)
+}
+
+/// Troca os emoji com significado por rótulos ("✅" → "[ok]"), as teclas numéricas por
+/// "1)", as linhas de moldura por traços e remove os emoji puramente decorativos
+pub fn to_plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        let (label, after) = match after.strip_prefix(KEYCAP) {
+            Some(after) if c.is_ascii_digit() => {
+                out.push(c);
+                // "1️⃣4️⃣" é uma opção só: o parêntese vem depois do último dígito
+                if starts_with_keycap(after) {
+                    rest = after;
+                    continue;
+                }
+                (Some(")"), after)
+            }
+            _ if c == '🔟' => (Some("10)"), after),
+            _ if is_emoji(c) => {
+                let after = after.strip_prefix(EMOJI_PRESENTATION).unwrap_or(after);
+                (label(&rest[..rest.len() - after.len()]), after)
+            }
+            _ => {
+                out.push(box_drawing(c).unwrap_or(c));
+                rest = after;
+                continue;
+            }
+        };
+
+        // O espaçamento depois do ícone vira um espaço só (ou nenhum, se o ícone sumiu)
+        let trimmed = after.trim_start_matches(' ');
+        if let Some(label) = label {
+            out.push_str(label);
+            if trimmed.len() < after.len() {
+                out.push(' ');
+            }
+        }
+        rest = trimmed;
+    }
+    out
+}
+
+/// Rótulo dos emoji que carregam informação; os demais são decoração
+fn label(icon: &str) -> Option<&'static str> {
+    let label = match icon {
+        "✅" => tr!("[ok]"),
+        "❌" => tr!("[erro]"),
+        "⚠️" => tr!("[aviso]"),
+        "🚫" => tr!("[negado]"),
+        "⛔" => tr!("[não]"),
+        "💡" => tr!("[dica]"),
+        "ℹ️" => tr!("[info]"),
+        "👉" => ">",
+        _ => return None,
+    };
+    Some(label)
+}
+
+fn starts_with_keycap(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_digit()) && chars.as_str().starts_with(KEYCAP)
+}
+
+/// Símbolos e pictogramas (os blocos Unicode em que ficam os emoji usados nas mensagens)
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2300..=0x23FF | 0x2190..=0x21FF | 0x2139 | 0x203C
+    )
+}
+
+/// Equivalente ASCII de um caractere de moldura
+fn box_drawing(c: char) -> Option<char> {
+    match c {
+        '─' | '━' | '┄' | '┈' => Some('-'),
+        '═' => Some('='),
+        '│' | '┃' | '║' => Some('|'),
+        '\u{2500}'..='\u{257f}' => Some('+'),
+        _ => None,
+    }
+}
+
+/// Como `print!`, passando o texto por `render`
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)+) => {
+        ::std::print!("{}", $crate::output::render(&::std::format!($($arg)+), $crate::output::Stream::Stdout))
+    };
+}
+
+/// Como `println!`, passando o texto por `render`
+#[macro_export]
+macro_rules! outln {
+    () => {
+        ::std::println!()
+    };
+    ($($arg:tt)+) => {
+        ::std::println!("{}", $crate::output::render(&::std::format!($($arg)+), $crate::output::Stream::Stdout))
+    };
+}
+
+/// Como `eprintln!`, passando o texto por `render`
+#[macro_export]
+macro_rules! errln {
+    () => {
+        ::std::eprintln!()
+    };
+    ($($arg:tt)+) => {
+        ::std::eprintln!("{}", $crate::output::render(&::std::format!($($arg)+), $crate::output::Stream::Stderr))
+    };
+}
+
+/// Envelope JSON de um resultado bem-sucedido (o mesmo de `--json` e da API HTTP)
+pub fn success_json(data: &Value) -> Value {
+    json!({ "status": "ok", "data": data })
+}
+
+/// Envelope JSON de um erro, com o código e a classe estáveis e a mensagem
+pub fn error_json(err: &AuthError) -> Value {
+    json!({
+        "status": "error",
+        "error": { "code": err.code(), "kind": err.kind().as_str(), "message": err.to_string() },
+    })
+}
+
+/// Imprime o resultado de um comando bem-sucedido
+pub fn print_success(output: &CommandOutput, json: bool) {
+    if json {
+        println!("{}", success_json(&output.data));
+    } else if !output.message.is_empty() {
+        outln!("{}", output.message);
+    }
+}
+
+/// Imprime um erro: como JSON na saída padrão, ou como texto na saída de erro
+pub fn print_error(err: &AuthError, json: bool) {
+    if json {
+        println!("{}", error_json(err));
+    } else {
+        errln!("❌ {}", err);
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::db::secure_file_permissions;
+use crate::error::AuthResult;
+use crate::hasher::HashingConfig;
+use crate::session::generate_token;
+use crate::validation_error;
+
+/// Sufixo do arquivo, ao lado do arquivo do pepper, com os peppers anteriores
+const PREVIOUS_FILE_SUFFIX: &str = ".previous";
+
+/// Pepper atual e anteriores, na ordem em que são tentados ao verificar um hash
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Peppers {
+    pub current: Option<Vec<u8>>,
+    /// Do mais recente para o mais antigo
+    pub previous: Vec<Vec<u8>>,
+}
+
+impl Peppers {
+    /// Carrega o pepper atual e os anteriores da configuração
+    pub(crate) fn load(config: &HashingConfig) -> AuthResult<Self> {
+        Ok(Peppers { current: load_pepper(config)?, previous: load_previous_peppers(config)? })
+    }
+}
+
+/// Carrega o pepper configurado, se houver.
+///
+/// `pepper` tem prioridade sobre `pepper_file`. Trocar o pepper sem manter o
+/// anterior em `load_previous_peppers` invalida todos os hashes existentes.
+pub(crate) fn load_pepper(config: &HashingConfig) -> AuthResult<Option<Vec<u8>>> {
+    if let Some(pepper) = config.pepper.as_ref().filter(|pepper| !pepper.is_empty()) {
+        return Ok(Some(pepper.clone().into_bytes()));
+    }
+
+    if let Some(path) = &config.pepper_file {
+        let contents = fs::read(path)?;
+        let pepper = contents.trim_ascii_end().to_vec();
+        if !pepper.is_empty() {
+            return Ok(Some(pepper));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Peppers anteriores, do mais recente para o mais antigo.
+///
+/// Vêm de `previous_pepper` e do arquivo `<pepper_file>.previous` (um por linha).
+/// Hashes feitos com eles ainda são aceitos e refeitos com o pepper atual no
+/// próximo login.
+pub(crate) fn load_previous_peppers(config: &HashingConfig) -> AuthResult<Vec<Vec<u8>>> {
+    let mut peppers = Vec::new();
+
+    if let Some(pepper) = config.previous_pepper.as_ref().filter(|pepper| !pepper.is_empty()) {
+        peppers.push(pepper.clone().into_bytes());
+    }
+
+    if let Some(path) = &config.pepper_file {
+        let previous = previous_file(path);
+        if previous.exists() {
+            let contents = fs::read(&previous)?;
+            peppers.extend(
+                contents
+                    .split(|&b| b == b'\n')
+                    .map(|line| line.trim_ascii().to_vec())
+                    .filter(|line| !line.is_empty()),
+            );
+        }
+    }
+
+    Ok(peppers)
+}
+
+/// Arquivo com os peppers anteriores de um arquivo de pepper
+fn previous_file(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(PREVIOUS_FILE_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Troca o pepper de `pepper_file` por um novo, aleatório.
+///
+/// O pepper atual passa para o topo do arquivo `.previous`, de modo que as senhas
+/// continuam sendo aceitas e são refeitas com o novo pepper conforme os usuários
+/// entram. Retorna o caminho do arquivo `.previous`, que deve ser mantido até que
+/// todos tenham migrado.
+pub fn rotate_pepper(config: &HashingConfig) -> AuthResult<PathBuf> {
+    if config.pepper.as_ref().is_some_and(|pepper| !pepper.is_empty()) {
+        return validation_error!(
+            "O pepper vem de hashing.pepper: mova o valor atual para hashing.previous_pepper e defina o novo em hashing.pepper"
+        );
+    }
+    let Some(path) = &config.pepper_file else {
+        return validation_error!("A rotação requer o pepper em um arquivo (hashing.pepper_file)");
+    };
+    let Some(current) = load_pepper(config)? else {
+        return validation_error!("O arquivo '{}' não contém um pepper para rotacionar", path.display());
+    };
+
+    // O pepper atual é guardado antes de ser substituído, para nunca ficar sem ele
+    let previous = previous_file(path);
+    let mut contents = current;
+    contents.push(b'\n');
+    if previous.exists() {
+        contents.extend(fs::read(&previous)?);
+    }
+    fs::write(&previous, contents)?;
+    secure_file_permissions(&previous, false)?;
+
+    fs::write(path, format!("{}\n", generate_token()))?;
+    secure_file_permissions(path, true)?;
+
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{hash_argon2, match_argon2_peppers, PepperMatch};
+    use crate::error::AuthError;
+    use crate::testing::{CHEAP_ARGON2, PASSWORD};
+
+    fn peppers(current: Option<&str>, previous: &[&str]) -> Peppers {
+        Peppers {
+            current: current.map(|p| p.as_bytes().to_vec()),
+            previous: previous.iter().map(|p| p.as_bytes().to_vec()).collect(),
+        }
+    }
+
+    #[test]
+    fn peppered_hash_fails_without_the_pepper() {
+        let hash = hash_argon2(PASSWORD, &CHEAP_ARGON2, Some(b"pepper-atual")).unwrap();
+
+        let matched = |peppers: Peppers| match_argon2_peppers(PASSWORD, &hash, &peppers).unwrap();
+        assert_eq!(matched(peppers(Some("pepper-atual"), &[])), Some(PepperMatch::Current));
+        assert_eq!(matched(peppers(None, &[])), None);
+        assert_eq!(matched(peppers(Some("outro-pepper"), &[])), None);
+    }
+
+    #[test]
+    fn previous_pepper_still_verifies_after_rotation() {
+        let hash = hash_argon2(PASSWORD, &CHEAP_ARGON2, Some(b"pepper-antigo")).unwrap();
+        let rotated = peppers(Some("pepper-novo"), &["pepper-intermediario", "pepper-antigo"]);
+
+        assert_eq!(match_argon2_peppers(PASSWORD, &hash, &rotated).unwrap(), Some(PepperMatch::Previous));
+        assert_eq!(match_argon2_peppers("Senha-Errada-123", &hash, &rotated).unwrap(), None);
+    }
+
+    #[test]
+    fn unpeppered_hash_fails_once_a_pepper_is_set() {
+        let hash = hash_argon2(PASSWORD, &CHEAP_ARGON2, None).unwrap();
+
+        assert_eq!(match_argon2_peppers(PASSWORD, &hash, &peppers(None, &[])).unwrap(), Some(PepperMatch::Current));
+        assert_eq!(match_argon2_peppers(PASSWORD, &hash, &peppers(Some("pepper-novo"), &[])).unwrap(), None);
+    }
+
+    #[test]
+    fn rotation_moves_the_file_pepper_to_previous() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pepper");
+        fs::write(&path, "pepper-antigo\n").unwrap();
+        let config = HashingConfig { pepper_file: Some(path.clone()), ..HashingConfig::default() };
+
+        rotate_pepper(&config).unwrap();
+        let loaded = Peppers::load(&config).unwrap();
+        assert!(loaded.current.is_some_and(|pepper| pepper != b"pepper-antigo"));
+        assert_eq!(loaded.previous, vec![b"pepper-antigo".to_vec()]);
+
+        let inline = HashingConfig { pepper: Some("pepper-no-arquivo".into()), ..config };
+        assert!(matches!(rotate_pepper(&inline), Err(AuthError::Validation(_))));
+    }
+}
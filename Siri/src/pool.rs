@@ -0,0 +1,139 @@
+use std::path::Path;
+use std::time::Duration;
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::Connection;
+use serde::Deserialize;
+use crate::config::DatabaseConfig;
+use crate::db::secure_file_permissions;
+use crate::error::AuthResult;
+use crate::migrations::migrate;
+
+/// Modo do diário do SQLite (`PRAGMA journal_mode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalMode {
+    /// Write-ahead log: leitores não esperam pelo processo que escreve (padrão)
+    #[default]
+    Wal,
+    /// Diário de reversão apagado a cada transação (o padrão do próprio SQLite)
+    Delete,
+    Truncate,
+    Persist,
+}
+
+impl JournalMode {
+    /// Nome aceito pelo pragma
+    pub fn name(self) -> &'static str {
+        match self {
+            JournalMode::Wal => "wal",
+            JournalMode::Delete => "delete",
+            JournalMode::Truncate => "truncate",
+            JournalMode::Persist => "persist",
+        }
+    }
+}
+
+/// Quando o SQLite força a gravação em disco (`PRAGMA synchronous`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Synchronous {
+    Off,
+    /// Seguro com WAL: uma queda de energia pode perder só as últimas transações (padrão)
+    #[default]
+    Normal,
+    Full,
+    Extra,
+}
+
+impl Synchronous {
+    /// Nome aceito pelo pragma
+    pub fn name(self) -> &'static str {
+        match self {
+            Synchronous::Off => "off",
+            Synchronous::Normal => "normal",
+            Synchronous::Full => "full",
+            Synchronous::Extra => "extra",
+        }
+    }
+}
+
+/// Instruções preparadas guardadas por conexão (o padrão do rusqlite, 16, não
+/// comporta todas as consultas de um login com sessão e dois fatores)
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Aplica os pragmas da configuração a uma conexão com um banco em arquivo.
+///
+/// O modo do diário fica gravado no arquivo; os demais valem só para a conexão.
+pub(crate) fn configure(conn: &Connection, config: &DatabaseConfig) -> rusqlite::Result<()> {
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    // Quem encontra o banco ocupado espera até o tempo limite antes de falhar com SQLITE_BUSY
+    conn.busy_timeout(Duration::from_millis(config.busy_timeout_ms))?;
+    let mode: String =
+        conn.pragma_update_and_check(None, "journal_mode", config.journal_mode.name(), |row| row.get(0))?;
+    if !mode.eq_ignore_ascii_case(config.journal_mode.name()) {
+        log::warn!("o SQLite manteve o diário em '{}' em vez de '{}'", mode, config.journal_mode.name());
+    }
+    conn.pragma_update(None, "synchronous", config.synchronous.name())?;
+    conn.pragma_update(None, "foreign_keys", true)
+}
+
+/// Conexão emprestada do pool; volta para ele quando é descartada
+pub type PooledConn = PooledConnection<SqliteConnectionManager>;
+
+/// Pool de conexões SQLite para acesso concorrente (ex.: `siri serve` e `siri daemon`).
+///
+/// As funções de `auth` continuam recebendo `&Connection`: basta obter uma conexão
+/// do pool com `get_conn` e passá-la adiante.
+#[derive(Clone)]
+pub struct DatabasePool {
+    pool: Pool<SqliteConnectionManager>,
+}
+
+impl DatabasePool {
+    /// Abre (ou cria) o banco no caminho informado com um pool de conexões
+    pub fn open<P: AsRef<Path>>(path: P, config: &DatabaseConfig) -> AuthResult<Self> {
+        let path = path.as_ref();
+        let existed = path.exists();
+
+        let pool = Self::connect(path, config)?;
+
+        let conn = pool.get_conn()?;
+        secure_file_permissions(path, existed)?;
+        migrate(&conn)?;
+
+        Ok(pool)
+    }
+
+    /// Cria o pool sobre um banco que já existe e está migrado.
+    ///
+    /// As conexões são abertas sob demanda, até `pool_size`, e ficam no pool depois de usadas.
+    pub(crate) fn connect(path: &Path, config: &DatabaseConfig) -> AuthResult<Self> {
+        let pragmas = config.clone();
+        let manager = SqliteConnectionManager::file(path).with_init(move |conn| configure(conn, &pragmas));
+        let pool = Pool::builder().max_size(config.pool_size).min_idle(Some(0)).build_unchecked(manager);
+        Ok(DatabasePool { pool })
+    }
+
+    /// Pool de uma única conexão com um banco em memória.
+    ///
+    /// Cada conexão `:memory:` é um banco separado, então a conexão nunca é fechada.
+    pub(crate) fn in_memory() -> AuthResult<Self> {
+        let manager = SqliteConnectionManager::memory().with_init(|conn| {
+            conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+            conn.execute_batch("PRAGMA foreign_keys = ON;")
+        });
+        let pool = Pool::builder()
+            .max_size(1)
+            .min_idle(Some(1))
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .build(manager)?;
+        Ok(DatabasePool { pool })
+    }
+
+    /// Obtém uma conexão do pool
+    pub fn get_conn(&self) -> AuthResult<PooledConn> {
+        Ok(self.pool.get()?)
+    }
+}
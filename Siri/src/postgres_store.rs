@@ -0,0 +1,1009 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread::{self, ThreadId};
+use chrono::{DateTime, Utc};
+use postgres::error::SqlState;
+use postgres::{Client, NoTls, Row};
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::error::{AuthError, AuthResult};
+use crate::groups::Group;
+use crate::profile::Profile;
+use crate::rbac::{Permission, Role};
+use crate::store::{like_pattern, LoginAttempt, StoredCredentials, StoredSession, UserOrder, UserStore};
+use crate::user::{AccountStatus, User};
+use crate::{message, validation};
+
+/// Armazenamento de usuários em um servidor PostgreSQL, para várias máquinas
+/// compartilharem o mesmo cadastro em vez de um `users.db` local
+pub struct PostgresStore {
+    client: Mutex<Client>,
+    /// Thread que abriu a transação em andamento com `transaction`, se houver
+    owner: Mutex<Option<ThreadId>>,
+    /// Presa durante toda a transação: as outras threads esperam o fim dela antes de
+    /// usar o cliente, para que seus comandos não entrem na transação alheia
+    gate: Mutex<()>,
+}
+
+/// Marca a thread atual como dona da transação enquanto existir
+struct TransactionOwner<'a>(&'a Mutex<Option<ThreadId>>);
+
+impl<'a> TransactionOwner<'a> {
+    fn claim(owner: &'a Mutex<Option<ThreadId>>) -> Self {
+        *lock(owner) = Some(thread::current().id());
+        TransactionOwner(owner)
+    }
+}
+
+impl Drop for TransactionOwner<'_> {
+    fn drop(&mut self) {
+        *lock(self.0) = None;
+    }
+}
+
+/// Trava um mutex; um pânico no meio de uma consulta não deixa estado inconsistente
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Comandos que abrem, confirmam e desfazem uma transação, ou um savepoint se já houver uma
+fn boundaries(nested: bool) -> (&'static str, &'static str, &'static str) {
+    if nested {
+        ("SAVEPOINT siri_nested", "RELEASE siri_nested", "ROLLBACK TO siri_nested; RELEASE siri_nested")
+    } else {
+        ("BEGIN", "COMMIT", "ROLLBACK")
+    }
+}
+
+impl PostgresStore {
+    /// Conecta ao servidor descrito pela DSN (ex.: `postgres://siri@db.local/siri`)
+    /// e cria as tabelas que ainda não existirem
+    pub fn connect(dsn: &str) -> AuthResult<Self> {
+        let mut client = Client::connect(dsn, NoTls)?;
+        init_tables(&mut client)?;
+        Ok(PostgresStore { client: Mutex::new(client), owner: Mutex::new(None), gate: Mutex::new(()) })
+    }
+
+    fn client(&self) -> MutexGuard<'_, Client> {
+        if self.owns_transaction() {
+            return lock(&self.client);
+        }
+        // O cliente é obtido com a trava em mãos, então nenhuma transação começa no meio do comando
+        let _gate = lock(&self.gate);
+        lock(&self.client)
+    }
+
+    /// Se a thread atual está dentro de uma transação aberta com `transaction`
+    fn owns_transaction(&self) -> bool {
+        *lock(&self.owner) == Some(thread::current().id())
+    }
+
+    /// Executa `operation` com o cliente preso, em uma transação (ou em um savepoint
+    /// da transação desta thread)
+    fn in_transaction<T>(&self, operation: impl FnOnce(&mut Client) -> AuthResult<T>) -> AuthResult<T> {
+        let (begin, commit, rollback) = boundaries(self.owns_transaction());
+        let mut client = self.client();
+        client.batch_execute(begin)?;
+        match operation(&mut client) {
+            Ok(value) => {
+                client.batch_execute(commit)?;
+                Ok(value)
+            }
+            Err(err) => {
+                client.batch_execute(rollback)?;
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Inicializa as tabelas necessárias, com o mesmo esquema do SQLite
+fn init_tables(client: &mut Client) -> AuthResult<()> {
+    client.batch_execute(
+        "CREATE TABLE IF NOT EXISTS users (
+            id BIGSERIAL PRIMARY KEY,
+            username TEXT NOT NULL UNIQUE,
+            password_hash TEXT NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            email TEXT,
+            password_changed_at TIMESTAMPTZ,
+            failed_attempts INTEGER NOT NULL DEFAULT 0,
+            locked_until TIMESTAMPTZ,
+            deleted_at TIMESTAMPTZ,
+            must_change_password BOOLEAN NOT NULL DEFAULT FALSE,
+            role TEXT NOT NULL DEFAULT 'user',
+            status TEXT NOT NULL DEFAULT 'active',
+            display_name TEXT,
+            phone TEXT,
+            bio TEXT,
+            last_login_at TIMESTAMPTZ,
+            login_count INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email ON users (lower(email));
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'user';
+        DO $$
+        BEGIN
+            -- Bancos anteriores aos papéis marcavam os administradores com is_admin
+            IF EXISTS (SELECT 1 FROM information_schema.columns
+                       WHERE table_name = 'users' AND column_name = 'is_admin') THEN
+                UPDATE users SET role = 'admin' WHERE is_admin;
+                ALTER TABLE users DROP COLUMN is_admin;
+            END IF;
+        END $$;
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'active';
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS display_name TEXT;
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS phone TEXT;
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS bio TEXT;
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS last_login_at TIMESTAMPTZ;
+        ALTER TABLE users ADD COLUMN IF NOT EXISTS login_count INTEGER NOT NULL DEFAULT 0;
+        DO $$
+        BEGIN
+            -- Bancos anteriores à coluna status marcavam as contas desativadas com enabled
+            IF EXISTS (SELECT 1 FROM information_schema.columns
+                       WHERE table_name = 'users' AND column_name = 'enabled') THEN
+                UPDATE users SET status = 'disabled' WHERE NOT enabled;
+                ALTER TABLE users DROP COLUMN enabled;
+            END IF;
+        END $$;
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sessions (
+            id BIGSERIAL PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMPTZ NOT NULL,
+            expires_at TIMESTAMPTZ NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS totp_secrets (
+            user_id BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+            secret TEXT NOT NULL,
+            last_used_step BIGINT,
+            enabled_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS email_verifications (
+            id BIGSERIAL PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            email TEXT NOT NULL,
+            expires_at TIMESTAMPTZ NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS password_resets (
+            id BIGSERIAL PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_by TEXT NOT NULL,
+            expires_at TIMESTAMPTZ NOT NULL,
+            used_at TIMESTAMPTZ,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id BIGSERIAL PRIMARY KEY,
+            actor TEXT NOT NULL,
+            action TEXT NOT NULL,
+            target TEXT,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS login_attempts (
+            id BIGSERIAL PRIMARY KEY,
+            user_id BIGINT REFERENCES users(id) ON DELETE CASCADE,
+            username TEXT NOT NULL,
+            success BOOLEAN NOT NULL,
+            outcome TEXT NOT NULL,
+            source TEXT NOT NULL,
+            attempted_at TIMESTAMPTZ NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_login_attempts_user ON login_attempts (user_id, attempted_at);
+        CREATE TABLE IF NOT EXISTS recovery_codes (
+            id BIGSERIAL PRIMARY KEY,
+            user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            code_hash TEXT NOT NULL,
+            consumed_at TIMESTAMPTZ,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS permissions (
+            name TEXT PRIMARY KEY,
+            description TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS role_permissions (
+            role TEXT NOT NULL,
+            permission TEXT NOT NULL REFERENCES permissions(name) ON DELETE CASCADE,
+            PRIMARY KEY (role, permission)
+        );
+        CREATE TABLE IF NOT EXISTS groups (
+            id BIGSERIAL PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS group_members (
+            group_id BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+            user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            added_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (group_id, user_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_group_members_user ON group_members (user_id);",
+    )?;
+    seed_permissions(client)
+}
+
+/// Cadastra as permissões que o banco ainda não conhece, concedendo-as aos administradores.
+///
+/// Só as recém-cadastradas são concedidas, para não desfazer uma revogação feita depois.
+fn seed_permissions(client: &mut Client) -> AuthResult<()> {
+    for permission in Permission::ALL {
+        let inserted = client.execute(
+            "INSERT INTO permissions (name, description) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            &[&permission.name(), &permission.description()],
+        )?;
+        if inserted > 0 {
+            client.execute(
+                "INSERT INTO role_permissions (role, permission) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                &[&Role::Admin.name(), &permission.name()],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Monta um `User` a partir de uma linha com id, username, email, email_verified_at, created_at, role,
+/// status, display_name, phone, bio, last_login_at e login_count
+fn user_from_row(row: &Row) -> User {
+    User {
+        id: row.get(0),
+        username: row.get(1),
+        email: row.get(2),
+        email_verified_at: row.get(3),
+        created_at: row.get(4),
+        // Um papel desconhecido não concede privilégios
+        role: Role::from_name(row.get(5)).unwrap_or_default(),
+        status: AccountStatus::from_name(row.get(6)),
+        display_name: row.get(7),
+        phone: row.get(8),
+        bio: row.get(9),
+        last_login_at: row.get(10),
+        login_count: row.get::<_, i32>(11).max(0) as u32,
+    }
+}
+
+impl UserStore for PostgresStore {
+    fn user_exists(&self, username: &str) -> AuthResult<bool> {
+        let row = self.client().query_one(
+            "SELECT COUNT(*) > 0 FROM users WHERE username = $1 AND deleted_at IS NULL",
+            &[&username],
+        )?;
+        Ok(row.get(0))
+    }
+
+    fn count_users(&self) -> AuthResult<u32> {
+        let row = self.client().query_one("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL", &[])?;
+        let count: i64 = row.get(0);
+        Ok(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+
+    fn insert_user(&self, username: &str, password_hash: &str, email: Option<&str>) -> AuthResult<()> {
+        let result = self.client().execute(
+            "INSERT INTO users (username, password_hash, email, password_changed_at, role)
+             VALUES ($1, $2, $3, CURRENT_TIMESTAMP,
+                     CASE WHEN EXISTS (SELECT 1 FROM users) THEN 'user' ELSE 'admin' END)",
+            &[&username, &password_hash, &email],
+        );
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) if err.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+                let on_email = err
+                    .as_db_error()
+                    .and_then(|db_err| db_err.constraint())
+                    .is_some_and(|constraint| constraint.contains("email"));
+                match email {
+                    Some(email) if on_email => {
+                        Err(validation!("Email '{}' já está em uso", email))
+                    }
+                    _ => Err(AuthError::UserAlreadyExists(username.to_string())),
+                }
+            }
+            Err(err) => Err(AuthError::from(err)),
+        }
+    }
+
+    fn insert_user_if_absent(&self, username: &str, password_hash: &str) -> AuthResult<bool> {
+        let inserted = self.client().execute(
+            "INSERT INTO users (username, password_hash, password_changed_at, role)
+             VALUES ($1, $2, CURRENT_TIMESTAMP,
+                     CASE WHEN EXISTS (SELECT 1 FROM users) THEN 'user' ELSE 'admin' END)
+             ON CONFLICT (username) DO NOTHING",
+            &[&username, &password_hash],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    fn get_password_hash(&self, username: &str) -> AuthResult<Option<String>> {
+        let row = self.client().query_opt(
+            "SELECT password_hash FROM users WHERE username = $1 AND deleted_at IS NULL",
+            &[&username],
+        )?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn get_credentials(&self, username: &str) -> AuthResult<Option<StoredCredentials>> {
+        let row = self.client().query_opt(
+            "SELECT password_hash, COALESCE(password_changed_at, created_at), locked_until, status,
+                    must_change_password
+             FROM users WHERE username = $1 AND deleted_at IS NULL",
+            &[&username],
+        )?;
+        Ok(row.map(|row| StoredCredentials {
+            password_hash: row.get(0),
+            changed_at: row.get(1),
+            locked_until: row.get(2),
+            status: AccountStatus::from_name(row.get(3)),
+            must_change_password: row.get(4),
+        }))
+    }
+
+    fn set_password_hash(&self, username: &str, password_hash: &str, rotated: bool) -> AuthResult<bool> {
+        let sql = if rotated {
+            "UPDATE users SET password_hash = $1, password_changed_at = CURRENT_TIMESTAMP,
+                              must_change_password = FALSE
+             WHERE username = $2 AND deleted_at IS NULL"
+        } else {
+            "UPDATE users SET password_hash = $1 WHERE username = $2 AND deleted_at IS NULL"
+        };
+        let rows_affected = self.client().execute(sql, &[&password_hash, &username])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn record_failed_attempt(&self, username: &str) -> AuthResult<u32> {
+        let row = self.client().query_one(
+            "UPDATE users SET failed_attempts = failed_attempts + 1
+             WHERE username = $1 RETURNING failed_attempts",
+            &[&username],
+        )?;
+        let failed_attempts: i32 = row.get(0);
+        Ok(failed_attempts.max(0) as u32)
+    }
+
+    fn lock_user(&self, username: &str, until: DateTime<Utc>) -> AuthResult<()> {
+        self.client().execute(
+            "UPDATE users SET failed_attempts = 0, locked_until = $1 WHERE username = $2",
+            &[&until, &username],
+        )?;
+        Ok(())
+    }
+
+    fn clear_failed_attempts(&self, username: &str) -> AuthResult<()> {
+        self.client().execute(
+            "UPDATE users SET failed_attempts = 0, locked_until = NULL WHERE username = $1",
+            &[&username],
+        )?;
+        Ok(())
+    }
+
+    fn record_successful_login(&self, username: &str, at: DateTime<Utc>) -> AuthResult<()> {
+        self.client().execute(
+            "UPDATE users SET last_login_at = $1, login_count = login_count + 1
+             WHERE username = $2 AND deleted_at IS NULL",
+            &[&at, &username],
+        )?;
+        Ok(())
+    }
+
+    fn find_by_email(&self, email: &str) -> AuthResult<Option<String>> {
+        let row = self.client().query_opt(
+            "SELECT username FROM users
+             WHERE lower(email) = lower($1) AND deleted_at IS NULL",
+            &[&email.trim()],
+        )?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn list_users(&self) -> AuthResult<Vec<User>> {
+        let rows = self.client().query(
+            "SELECT id, username, email, email_verified_at, created_at, role, status,
+                    display_name, phone, bio, last_login_at, login_count
+             FROM users WHERE deleted_at IS NULL ORDER BY username",
+            &[],
+        )?;
+        Ok(rows.iter().map(user_from_row).collect())
+    }
+
+    fn list_users_paged(&self, offset: usize, limit: usize, order: UserOrder) -> AuthResult<Vec<User>> {
+        let rows = self.client().query(
+            &format!(
+                "SELECT id, username, email, email_verified_at, created_at, role, status,
+                        display_name, phone, bio, last_login_at, login_count
+                 FROM users WHERE deleted_at IS NULL {}
+                 LIMIT $1 OFFSET $2",
+                order.sql()
+            ),
+            &[&(limit as i64), &(offset as i64)],
+        )?;
+        Ok(rows.iter().map(user_from_row).collect())
+    }
+
+    fn search_users(&self, pattern: &str, order: UserOrder) -> AuthResult<Vec<User>> {
+        let rows = self.client().query(
+            &format!(
+                "SELECT id, username, email, email_verified_at, created_at, role, status,
+                        display_name, phone, bio, last_login_at, login_count
+                 FROM users
+                 WHERE deleted_at IS NULL AND (username ILIKE $1 ESCAPE '\\' OR email ILIKE $1 ESCAPE '\\')
+                 {}",
+                order.sql()
+            ),
+            &[&like_pattern(pattern)],
+        )?;
+        Ok(rows.iter().map(user_from_row).collect())
+    }
+
+    fn find_user(&self, username: &str) -> AuthResult<Option<User>> {
+        let row = self.client().query_opt(
+            "SELECT id, username, email, email_verified_at, created_at, role, status,
+                    display_name, phone, bio, last_login_at, login_count
+             FROM users WHERE username = $1 AND deleted_at IS NULL",
+            &[&username],
+        )?;
+        Ok(row.as_ref().map(user_from_row))
+    }
+
+    fn rename_user(&self, current: &str, new_username: &str) -> AuthResult<bool> {
+        let result = self.client().execute(
+            "UPDATE users SET username = $1 WHERE username = $2 AND deleted_at IS NULL",
+            &[&new_username, &current],
+        );
+
+        match result {
+            Ok(rows_affected) => Ok(rows_affected > 0),
+            Err(err) if err.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+                Err(AuthError::UserAlreadyExists(new_username.to_string()))
+            }
+            Err(err) => Err(AuthError::from(err)),
+        }
+    }
+
+    fn update_profile(&self, username: &str, profile: &Profile) -> AuthResult<bool> {
+        let result = self.client().execute(
+            "UPDATE users SET display_name = $1, phone = $2, bio = $3, email = $4,
+                              email_verified_at = CASE WHEN lower(email) = lower($4)
+                                                       THEN email_verified_at END
+             WHERE username = $5 AND deleted_at IS NULL",
+            &[&profile.display_name, &profile.phone, &profile.bio, &profile.email, &username],
+        );
+
+        match result {
+            Ok(rows_affected) => Ok(rows_affected > 0),
+            Err(err) if err.code() == Some(&SqlState::UNIQUE_VIOLATION) => Err(validation!(
+                "Email '{}' já está em uso",
+                profile.email.as_deref().unwrap_or_default()
+            )),
+            Err(err) => Err(AuthError::from(err)),
+        }
+    }
+
+    fn delete_user(&self, username: &str) -> AuthResult<bool> {
+        self.in_transaction(|tx| {
+            let rows_affected = tx.execute(
+                "UPDATE users SET deleted_at = CURRENT_TIMESTAMP
+                 WHERE username = $1 AND deleted_at IS NULL",
+                &[&username],
+            )?;
+            if rows_affected == 0 {
+                return Ok(false);
+            }
+            for table in ["sessions", "password_resets", "email_verifications"] {
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE user_id = (SELECT id FROM users WHERE username = $1)", table),
+                    &[&username],
+                )?;
+            }
+            Ok(true)
+        })
+    }
+
+    fn erase_user(&self, username: &str, actor: &str, action: &str) -> AuthResult<bool> {
+        // As tabelas ligadas ao usuário são apagadas em cascata
+        self.in_transaction(|tx| {
+            let deleted = tx.execute(
+                "DELETE FROM users WHERE username = $1 AND deleted_at IS NULL",
+                &[&username],
+            )?;
+            if deleted == 0 {
+                return Ok(false);
+            }
+            tx.execute(
+                "INSERT INTO audit_log (actor, action, target) VALUES ($1, $2, $3)",
+                &[&actor, &action, &username],
+            )?;
+            Ok(true)
+        })
+    }
+
+    fn restore_user(&self, username: &str) -> AuthResult<bool> {
+        let rows_affected = self.client().execute(
+            "UPDATE users SET deleted_at = NULL
+             WHERE username = $1 AND deleted_at IS NOT NULL",
+            &[&username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn list_deleted_users(&self) -> AuthResult<Vec<(String, DateTime<Utc>)>> {
+        let rows = self.client().query(
+            "SELECT username, deleted_at
+             FROM users WHERE deleted_at IS NOT NULL ORDER BY deleted_at",
+            &[],
+        )?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    fn purge_deleted(&self, older_than: DateTime<Utc>) -> AuthResult<usize> {
+        let rows_affected = self.client().execute(
+            "DELETE FROM users WHERE deleted_at IS NOT NULL AND deleted_at <= $1",
+            &[&older_than],
+        )?;
+        Ok(rows_affected as usize)
+    }
+
+    fn release_username(&self, username: &str, deleted_before: DateTime<Utc>) -> AuthResult<()> {
+        self.client().execute(
+            "DELETE FROM users
+             WHERE username = $1 AND deleted_at IS NOT NULL AND deleted_at <= $2",
+            &[&username, &deleted_before],
+        )?;
+        Ok(())
+    }
+
+    fn set_status(&self, username: &str, status: AccountStatus) -> AuthResult<bool> {
+        let rows_affected = self.client().execute(
+            "UPDATE users SET status = $1 WHERE username = $2 AND deleted_at IS NULL",
+            &[&status.name(), &username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn get_status(&self, username: &str) -> AuthResult<Option<AccountStatus>> {
+        let row = self.client().query_opt(
+            "SELECT status FROM users WHERE username = $1 AND deleted_at IS NULL",
+            &[&username],
+        )?;
+        Ok(row.map(|row| AccountStatus::from_name(row.get(0))))
+    }
+
+    fn get_role(&self, username: &str) -> AuthResult<Option<Role>> {
+        let row = self.client().query_opt(
+            "SELECT role FROM users WHERE username = $1 AND deleted_at IS NULL",
+            &[&username],
+        )?;
+        Ok(row.map(|row| Role::from_name(row.get(0)).unwrap_or_default()))
+    }
+
+    fn set_role(&self, username: &str, role: Role) -> AuthResult<bool> {
+        let rows_affected = self.client().execute(
+            "UPDATE users SET role = $1 WHERE username = $2 AND deleted_at IS NULL",
+            &[&role.name(), &username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn count_admins(&self) -> AuthResult<u32> {
+        let row = self.client().query_one(
+            "SELECT COUNT(*) FROM users WHERE role = 'admin' AND status = 'active' AND deleted_at IS NULL",
+            &[],
+        )?;
+        let count: i64 = row.get(0);
+        Ok(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+
+    fn role_permissions(&self, role: Role) -> AuthResult<Vec<Permission>> {
+        let rows = self.client().query(
+            "SELECT permission FROM role_permissions WHERE role = $1",
+            &[&role.name()],
+        )?;
+        Ok(rows.iter().filter_map(|row| Permission::from_name(row.get(0))).collect())
+    }
+
+    fn set_role_permission(&self, role: Role, permission: Permission, granted: bool) -> AuthResult<()> {
+        let sql = if granted {
+            "INSERT INTO role_permissions (role, permission) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+        } else {
+            "DELETE FROM role_permissions WHERE role = $1 AND permission = $2"
+        };
+        self.client().execute(sql, &[&role.name(), &permission.name()])?;
+        Ok(())
+    }
+
+    fn insert_group(&self, name: &str) -> AuthResult<()> {
+        match self.client().execute("INSERT INTO groups (name) VALUES ($1)", &[&name]) {
+            Ok(_) => Ok(()),
+            Err(err) if err.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+                Err(validation!("Grupo '{}' já existe", name))
+            }
+            Err(err) => Err(AuthError::from(err)),
+        }
+    }
+
+    fn delete_group(&self, name: &str) -> AuthResult<bool> {
+        let rows_affected = self.client().execute("DELETE FROM groups WHERE name = $1", &[&name])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn group_exists(&self, name: &str) -> AuthResult<bool> {
+        let row = self.client().query_one("SELECT COUNT(*) > 0 FROM groups WHERE name = $1", &[&name])?;
+        Ok(row.get(0))
+    }
+
+    fn list_groups(&self) -> AuthResult<Vec<Group>> {
+        let rows = self.client().query(
+            "SELECT g.name, COUNT(u.id), g.created_at
+             FROM groups g
+             LEFT JOIN group_members m ON m.group_id = g.id
+             LEFT JOIN users u ON u.id = m.user_id AND u.deleted_at IS NULL
+             GROUP BY g.id ORDER BY g.name",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| Group {
+                name: row.get(0),
+                members: u32::try_from(row.get::<_, i64>(1)).unwrap_or(u32::MAX),
+                created_at: row.get(2),
+            })
+            .collect())
+    }
+
+    fn add_group_member(&self, group: &str, username: &str) -> AuthResult<bool> {
+        let inserted = self.client().execute(
+            "INSERT INTO group_members (group_id, user_id)
+             SELECT g.id, u.id FROM groups g, users u
+             WHERE g.name = $1 AND u.username = $2 AND u.deleted_at IS NULL
+             ON CONFLICT DO NOTHING",
+            &[&group, &username],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    fn remove_group_member(&self, group: &str, username: &str) -> AuthResult<bool> {
+        let rows_affected = self.client().execute(
+            "DELETE FROM group_members
+             WHERE group_id = (SELECT id FROM groups WHERE name = $1)
+               AND user_id = (SELECT id FROM users WHERE username = $2 AND deleted_at IS NULL)",
+            &[&group, &username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn group_members(&self, group: &str) -> AuthResult<Vec<String>> {
+        let rows = self.client().query(
+            "SELECT u.username FROM group_members m
+             JOIN groups g ON g.id = m.group_id
+             JOIN users u ON u.id = m.user_id
+             WHERE g.name = $1 AND u.deleted_at IS NULL
+             ORDER BY u.username",
+            &[&group],
+        )?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    fn user_groups(&self, username: &str) -> AuthResult<Vec<String>> {
+        let rows = self.client().query(
+            "SELECT g.name FROM group_members m
+             JOIN groups g ON g.id = m.group_id
+             JOIN users u ON u.id = m.user_id
+             WHERE u.username = $1 AND u.deleted_at IS NULL
+             ORDER BY g.name",
+            &[&username],
+        )?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    fn insert_session(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        let inserted = self.client().execute(
+            "INSERT INTO sessions (token_hash, user_id, created_at, expires_at)
+             SELECT $1, id, $2, $3 FROM users WHERE username = $4 AND deleted_at IS NULL",
+            &[&token_hash, &created_at, &expires_at, &username],
+        )?;
+        if inserted == 0 {
+            return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+        }
+        Ok(())
+    }
+
+    fn find_session(&self, token_hash: &str) -> AuthResult<Option<StoredSession>> {
+        let row = self.client().query_opt(
+            "SELECT u.username, s.created_at, s.expires_at
+             FROM sessions s JOIN users u ON u.id = s.user_id
+             WHERE s.token_hash = $1 AND u.deleted_at IS NULL AND u.status = 'active'",
+            &[&token_hash],
+        )?;
+        Ok(row.map(|row| StoredSession {
+            username: row.get(0),
+            created_at: row.get(1),
+            expires_at: row.get(2),
+        }))
+    }
+
+    fn user_sessions(&self, username: &str) -> AuthResult<Vec<StoredSession>> {
+        let rows = self.client().query(
+            "SELECT u.username, s.created_at, s.expires_at
+             FROM sessions s JOIN users u ON u.id = s.user_id
+             WHERE u.username = $1 AND u.deleted_at IS NULL
+             ORDER BY s.created_at DESC, s.id DESC",
+            &[&username],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| StoredSession {
+                username: row.get(0),
+                created_at: row.get(1),
+                expires_at: row.get(2),
+            })
+            .collect())
+    }
+
+    fn extend_session(&self, token_hash: &str, expires_at: DateTime<Utc>) -> AuthResult<bool> {
+        let rows_affected = self.client().execute(
+            "UPDATE sessions SET expires_at = $1 WHERE token_hash = $2",
+            &[&expires_at, &token_hash],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn delete_session(&self, token_hash: &str) -> AuthResult<bool> {
+        let rows_affected = self.client().execute("DELETE FROM sessions WHERE token_hash = $1", &[&token_hash])?;
+        Ok(rows_affected > 0)
+    }
+
+    fn purge_expired_sessions(&self, now: DateTime<Utc>) -> AuthResult<usize> {
+        let rows_affected = self.client().execute("DELETE FROM sessions WHERE expires_at <= $1", &[&now])?;
+        Ok(rows_affected as usize)
+    }
+
+    fn insert_password_reset(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_by: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        self.in_transaction(|tx| {
+            tx.execute(
+                "DELETE FROM password_resets
+                 WHERE used_at IS NULL AND user_id = (SELECT id FROM users WHERE username = $1)",
+                &[&username],
+            )?;
+            let inserted = tx.execute(
+                "INSERT INTO password_resets (token_hash, user_id, created_by, expires_at)
+                 SELECT $1, id, $2, $3 FROM users WHERE username = $4 AND deleted_at IS NULL",
+                &[&token_hash, &created_by, &expires_at, &username],
+            )?;
+            if inserted == 0 {
+                return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+            }
+            Ok(())
+        })
+    }
+
+    fn consume_password_reset(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>> {
+        let row = self.client().query_opt(
+            "UPDATE password_resets r SET used_at = $2
+             FROM users u
+             WHERE u.id = r.user_id AND r.token_hash = $1 AND r.used_at IS NULL
+               AND r.expires_at > $2 AND u.deleted_at IS NULL
+             RETURNING u.username",
+            &[&token_hash, &now],
+        )?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn insert_email_verification(&self, token_hash: &str, username: &str, expires_at: DateTime<Utc>) -> AuthResult<()> {
+        self.in_transaction(|tx| {
+            tx.execute(
+                "DELETE FROM email_verifications WHERE user_id = (SELECT id FROM users WHERE username = $1)",
+                &[&username],
+            )?;
+            let inserted = tx.execute(
+                "INSERT INTO email_verifications (token_hash, user_id, email, expires_at)
+                 SELECT $1, id, email, $2 FROM users
+                 WHERE username = $3 AND deleted_at IS NULL AND email IS NOT NULL",
+                &[&token_hash, &expires_at, &username],
+            )?;
+            if inserted == 0 {
+                return Err(AuthError::NotFound(message!("Email do usuário '{}'", username)));
+            }
+            Ok(())
+        })
+    }
+
+    fn consume_email_verification(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>> {
+        self.in_transaction(|tx| {
+            let found = tx.query_opt(
+                "SELECT u.id, u.username FROM email_verifications v JOIN users u ON u.id = v.user_id
+                 WHERE v.token_hash = $1 AND v.expires_at > $2 AND u.deleted_at IS NULL
+                   AND lower(u.email) = lower(v.email)",
+                &[&token_hash, &now],
+            )?;
+            let Some(row) = found else {
+                return Ok(None);
+            };
+            let user_id: i64 = row.get(0);
+            tx.execute("DELETE FROM email_verifications WHERE user_id = $1", &[&user_id])?;
+            tx.execute("UPDATE users SET email_verified_at = $1 WHERE id = $2", &[&now, &user_id])?;
+            Ok(Some(row.get(1)))
+        })
+    }
+
+    fn get_totp_secret(&self, username: &str) -> AuthResult<Option<String>> {
+        let row = self.client().query_opt(
+            "SELECT t.secret FROM totp_secrets t JOIN users u ON u.id = t.user_id
+             WHERE u.username = $1 AND u.deleted_at IS NULL",
+            &[&username],
+        )?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn set_totp_secret(&self, username: &str, secret: Option<&str>) -> AuthResult<()> {
+        match secret {
+            Some(secret) => self.client().execute(
+                "INSERT INTO totp_secrets (user_id, secret)
+                 SELECT id, $1 FROM users WHERE username = $2 AND deleted_at IS NULL
+                 ON CONFLICT (user_id) DO UPDATE SET secret = excluded.secret, last_used_step = NULL,
+                                                     enabled_at = CURRENT_TIMESTAMP",
+                &[&secret, &username],
+            )?,
+            None => self.client().execute(
+                "DELETE FROM totp_secrets WHERE user_id = (SELECT id FROM users WHERE username = $1)",
+                &[&username],
+            )?,
+        };
+        Ok(())
+    }
+
+    fn claim_totp_step(&self, username: &str, step: i64) -> AuthResult<bool> {
+        let rows_affected = self.client().execute(
+            "UPDATE totp_secrets SET last_used_step = $1
+             WHERE user_id = (SELECT id FROM users WHERE username = $2)
+               AND (last_used_step IS NULL OR last_used_step < $1)",
+            &[&step, &username],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn replace_recovery_codes(&self, username: &str, code_hashes: &[String]) -> AuthResult<()> {
+        self.in_transaction(|tx| {
+            tx.execute(
+                "DELETE FROM recovery_codes WHERE user_id = (SELECT id FROM users WHERE username = $1)",
+                &[&username],
+            )?;
+            for code_hash in code_hashes {
+                tx.execute(
+                    "INSERT INTO recovery_codes (user_id, code_hash)
+                     SELECT id, $1 FROM users WHERE username = $2 AND deleted_at IS NULL",
+                    &[code_hash, &username],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    fn unused_recovery_codes(&self, username: &str) -> AuthResult<Vec<(i64, String)>> {
+        let rows = self.client().query(
+            "SELECT c.id, c.code_hash FROM recovery_codes c JOIN users u ON u.id = c.user_id
+             WHERE u.username = $1 AND u.deleted_at IS NULL AND c.consumed_at IS NULL
+             ORDER BY c.id",
+            &[&username],
+        )?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    fn consume_recovery_code(&self, code_id: i64) -> AuthResult<bool> {
+        let rows_affected = self.client().execute(
+            "UPDATE recovery_codes SET consumed_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND consumed_at IS NULL",
+            &[&code_id],
+        )?;
+        Ok(rows_affected > 0)
+    }
+
+    fn record_login_attempt(&self, attempt: &LoginAttempt) -> AuthResult<()> {
+        self.client().execute(
+            "INSERT INTO login_attempts (user_id, username, success, outcome, source, attempted_at)
+             VALUES ((SELECT id FROM users WHERE username = $1 AND deleted_at IS NULL), $1, $2, $3, $4, $5)",
+            &[
+                &attempt.username,
+                &attempt.success,
+                &attempt.outcome,
+                &attempt.source,
+                &attempt.attempted_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn login_history(&self, username: &str, limit: usize) -> AuthResult<Vec<LoginAttempt>> {
+        let rows = self.client().query(
+            "SELECT a.username, a.success, a.outcome, a.source, a.attempted_at
+             FROM login_attempts a JOIN users u ON u.id = a.user_id
+             WHERE u.username = $1 AND u.deleted_at IS NULL
+             ORDER BY a.attempted_at DESC, a.id DESC
+             LIMIT $2",
+            &[&username, &(limit as i64)],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| LoginAttempt {
+                username: row.get(0),
+                success: row.get(1),
+                outcome: row.get(2),
+                source: row.get(3),
+                attempted_at: row.get(4),
+            })
+            .collect())
+    }
+
+    fn record_audit(&self, actor: &str, action: &str, target: Option<&str>) -> AuthResult<()> {
+        self.client().execute(
+            "INSERT INTO audit_log (actor, action, target) VALUES ($1, $2, $3)",
+            &[&actor, &action, &target],
+        )?;
+        Ok(())
+    }
+
+    fn audit_log(&self, filter: &AuditFilter) -> AuthResult<Vec<AuditEntry>> {
+        let rows = self.client().query(
+            "SELECT id, actor, action, target, created_at FROM audit_log
+             WHERE ($1::TEXT IS NULL OR actor = $1)
+               AND ($2::TEXT IS NULL OR action = $2)
+               AND ($3::TEXT IS NULL OR target = $3)
+             ORDER BY created_at DESC, id DESC
+             LIMIT $4",
+            &[&filter.actor, &filter.action, &filter.target, &(filter.limit as i64)],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| AuditEntry {
+                id: row.get(0),
+                actor: row.get(1),
+                action: row.get(2),
+                target: row.get(3),
+                created_at: row.get(4),
+            })
+            .collect())
+    }
+
+    fn get_setting(&self, key: &str) -> AuthResult<Option<String>> {
+        let row = self.client().query_opt("SELECT value FROM settings WHERE key = $1", &[&key])?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn set_setting(&self, key: &str, value: Option<&str>) -> AuthResult<()> {
+        match value {
+            Some(value) => self.client().execute(
+                "INSERT INTO settings (key, value) VALUES ($1, $2)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                &[&key, &value],
+            )?,
+            None => self.client().execute("DELETE FROM settings WHERE key = $1", &[&key])?,
+        };
+        Ok(())
+    }
+
+    fn transaction(&self, operation: &mut dyn FnMut(&dyn UserStore) -> AuthResult<()>) -> AuthResult<()> {
+        let nested = self.owns_transaction();
+        let (begin, commit, rollback) = boundaries(nested);
+        let _gate = (!nested).then(|| lock(&self.gate));
+        let _owner = (!nested).then(|| TransactionOwner::claim(&self.owner));
+
+        // Os métodos chamados pela operação pegam o cliente um de cada vez, sem a trava
+        self.client().batch_execute(begin)?;
+        let result = operation(self);
+        let end = self.client().batch_execute(if result.is_ok() { commit } else { rollback });
+        result.and(end.map_err(AuthError::from))
+    }
+}
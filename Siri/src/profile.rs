@@ -0,0 +1,107 @@
+use crate::audit;
+use crate::auth::validate_email;
+use crate::error::{AuthError, AuthResult};
+use crate::store::UserStore;
+use crate::user::User;
+use crate::{message, tr, validation_error};
+
+/// Tamanho máximo do nome de exibição
+const DISPLAY_NAME_MAX_LENGTH: usize = 64;
+
+/// Tamanho máximo da biografia
+const BIO_MAX_LENGTH: usize = 280;
+
+/// Quantidade de dígitos aceita em um telefone (o E.164 permite até 15)
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 8..=15;
+
+/// Dados opcionais de perfil que o próprio usuário pode editar
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub bio: Option<String>,
+}
+
+impl Profile {
+    /// Perfil atual de um usuário
+    pub fn of(user: &User) -> Profile {
+        Profile {
+            display_name: user.display_name.clone(),
+            email: user.email.clone(),
+            phone: user.phone.clone(),
+            bio: user.bio.clone(),
+        }
+    }
+
+    /// Remove espaços das pontas e troca campos vazios por `None`
+    fn normalized(&self) -> Profile {
+        let clean = |value: &Option<String>| {
+            value.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)
+        };
+        Profile {
+            display_name: clean(&self.display_name),
+            email: clean(&self.email),
+            phone: clean(&self.phone),
+            bio: clean(&self.bio),
+        }
+    }
+
+    fn validate(&self) -> AuthResult<()> {
+        if let Some(name) = &self.display_name {
+            validate_text(tr!("O nome de exibição"), name, DISPLAY_NAME_MAX_LENGTH)?;
+        }
+        if let Some(email) = &self.email {
+            validate_email(email)?;
+        }
+        if let Some(phone) = &self.phone {
+            validate_phone(phone)?;
+        }
+        if let Some(bio) = &self.bio {
+            validate_text(tr!("A biografia"), bio, BIO_MAX_LENGTH)?;
+        }
+        Ok(())
+    }
+}
+
+/// Texto livre de uma linha, com tamanho máximo
+fn validate_text(field: &str, value: &str, max_length: usize) -> AuthResult<()> {
+    if value.chars().count() > max_length {
+        return validation_error!("{} deve ter no máximo {} caracteres", field, max_length);
+    }
+    if value.chars().any(char::is_control) {
+        return validation_error!("{} não pode conter caracteres de controle", field);
+    }
+    Ok(())
+}
+
+/// Telefone com dígitos e os separadores usuais, opcionalmente com '+' e o código do país
+fn validate_phone(phone: &str) -> AuthResult<()> {
+    let body = phone.strip_prefix('+').unwrap_or(phone);
+    let digits = body.chars().filter(char::is_ascii_digit).count();
+    if !body.chars().all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        || !PHONE_DIGITS.contains(&digits)
+    {
+        return validation_error!(
+            "Telefone '{}' inválido: use de {} a {} dígitos, com '+' e separadores opcionais",
+            phone,
+            PHONE_DIGITS.start(),
+            PHONE_DIGITS.end()
+        );
+    }
+    Ok(())
+}
+
+/// Substitui o perfil de um usuário, registrando a alteração.
+///
+/// Campos vazios apagam o valor. Trocar o email desfaz a verificação, e o novo
+/// endereço precisa ser confirmado de novo.
+pub fn update_profile(store: &dyn UserStore, username: &str, profile: &Profile) -> AuthResult<()> {
+    let profile = profile.normalized();
+    profile.validate()?;
+
+    if !store.update_profile(username, &profile)? {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    }
+    audit::record(store, username, "update_profile", Some(username))
+}
@@ -0,0 +1,319 @@
+use std::io::{self, BufRead, StdinLock, Write};
+use crossterm::event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{execute, terminal};
+use zeroize::Zeroizing;
+use crate::config;
+use crate::crypto::secrets_equal;
+use crate::error::{AuthResult, ErrorKind};
+use crate::genpass::{generate_password, GeneratorOptions};
+use crate::strength::estimate_strength;
+use crate::{out, outln, tr};
+
+/// Tentativas de um prompt antes de voltar ao menu
+pub const MAX_PROMPT_ATTEMPTS: usize = 3;
+
+/// Entrada que cancela o prompt de nome de usuário
+pub const CANCEL_INPUT: &str = "q";
+
+/// Entrada que, no prompt de nova senha, pede uma senha gerada
+pub const GENERATE_INPUT: &str = "g";
+
+/// Como a senha aparece enquanto é digitada
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PasswordEcho {
+    /// Nada é exibido (padrão)
+    #[default]
+    Hidden,
+    /// Um `*` por caractere digitado
+    Masked,
+}
+
+/// Capacidade reservada para a senha digitada no modo mascarado, para que ela não
+/// seja realocada (deixando cópias para trás) enquanto cresce
+const MASKED_CAPACITY: usize = 256;
+
+/// Lê uma senha do terminal no modo de eco escolhido.
+///
+/// A senha é apagada da memória quando o valor retornado sai de escopo.
+pub fn read_password(prompt: &str, echo: PasswordEcho) -> AuthResult<Zeroizing<String>> {
+    out!("{}", prompt);
+    io::stdout().flush()?;
+
+    let password = match echo {
+        PasswordEcho::Hidden => Zeroizing::new(rpassword::read_password()?),
+        PasswordEcho::Masked => read_masked()?,
+    };
+    Ok(password)
+}
+
+/// Respostas dos prompts interativos: linhas de um `BufRead` e senhas do terminal.
+///
+/// Com `scripted`, as senhas também vêm do `BufRead`, uma por linha; é assim que os
+/// testes alimentam as repetições com uma entrada roteirizada.
+pub struct Prompter<R> {
+    input: R,
+    echo: PasswordEcho,
+    passwords_from_input: bool,
+}
+
+impl Prompter<StdinLock<'static>> {
+    /// Prompts na entrada padrão, com as senhas lidas do terminal no modo de eco escolhido.
+    ///
+    /// A entrada padrão fica travada enquanto o `Prompter` existir (a trava não é
+    /// reentrante): as leituras da mesma thread devem passar todas por ele.
+    pub fn stdin(echo: PasswordEcho) -> Self {
+        Prompter { input: io::stdin().lock(), echo, passwords_from_input: false }
+    }
+}
+
+impl<R: BufRead> Prompter<R> {
+    /// Prompts que leem tudo, inclusive as senhas, da entrada informada
+    pub fn scripted(input: R) -> Self {
+        Prompter { input, echo: PasswordEcho::Hidden, passwords_from_input: true }
+    }
+
+    /// Define como as senhas aparecem enquanto são digitadas
+    pub fn set_echo(&mut self, echo: PasswordEcho) {
+        self.echo = echo;
+    }
+
+    /// Exibe o prompt e lê uma linha, sem os espaços das pontas
+    pub fn read_line(&mut self, prompt: &str) -> AuthResult<String> {
+        out!("{}", prompt);
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        self.input.read_line(&mut line)?;
+        Ok(line.trim().to_string())
+    }
+
+    /// Lê uma senha: do terminal, no modo de eco escolhido, ou da entrada roteirizada
+    pub fn read_password(&mut self, prompt: &str) -> AuthResult<Zeroizing<String>> {
+        if !self.passwords_from_input {
+            // No modo mascarado a senha aparece como asteriscos, então "(oculta)" não se aplica
+            let prompt = match self.echo {
+                PasswordEcho::Hidden => prompt.to_string(),
+                PasswordEcho::Masked => prompt.replace(&format!(" {}", tr!("(oculta)")), ""),
+            };
+            return read_password(&prompt, self.echo);
+        }
+
+        out!("{}", prompt);
+        let mut line = Zeroizing::new(String::new());
+        self.input.read_line(&mut line)?;
+        outln!();
+        Ok(Zeroizing::new(line.trim_end_matches(['\r', '\n']).to_string()))
+    }
+
+    /// Faz uma pergunta de sim ou não; só uma resposta afirmativa conta como sim
+    pub fn confirm(&mut self, question: &str) -> AuthResult<bool> {
+        let answer = self.read_line(&format!("❓ {} {} ", question, tr!("(s/N):")))?;
+        Ok(matches!(answer.to_lowercase().as_str(), "s" | "sim" | "y" | "yes"))
+    }
+
+    /// Pede um nome de usuário não vazio, com até `MAX_PROMPT_ATTEMPTS` tentativas.
+    ///
+    /// Retorna `None` se o usuário cancelar (digitando `q`) ou esgotar as tentativas.
+    pub fn username(&mut self) -> AuthResult<Option<String>> {
+        for _ in 0..MAX_PROMPT_ATTEMPTS {
+            let username = self.read_line(&format!("👤 {} ", tr!("Nome de usuário:")))?;
+
+            if username == CANCEL_INPUT {
+                outln!("↩️  {}", tr!("Operação cancelada."));
+                return Ok(None);
+            }
+            if !username.is_empty() {
+                return Ok(Some(username));
+            }
+            outln!("⚠️  {}", tr!("Nome de usuário não pode estar vazio (digite '{}' para cancelar).", CANCEL_INPUT));
+        }
+
+        outln!("🚫 {}", tr!("Tentativas esgotadas. Voltando ao menu."));
+        Ok(None)
+    }
+
+    /// Pede uma nova senha e sua confirmação, repetindo se não coincidirem ou se a
+    /// senha for fraca demais. A força estimada é exibida junto com dicas de melhoria.
+    ///
+    /// Retorna `None` se o usuário cancelar (senha vazia) ou esgotar as tentativas.
+    pub fn new_password(
+        &mut self,
+        prompt: &str,
+        confirm_prompt: &str,
+        user_inputs: &[&str],
+    ) -> AuthResult<Option<Zeroizing<String>>> {
+        outln!("💡 {}", tr!("Digite '{}' para gerar uma senha forte (senha vazia cancela).", GENERATE_INPUT));
+        for _ in 0..MAX_PROMPT_ATTEMPTS {
+            let password = self.read_password(prompt)?;
+            if password.is_empty() {
+                outln!("↩️  {}", tr!("Operação cancelada."));
+                return Ok(None);
+            }
+            if password.as_str() == GENERATE_INPUT {
+                match self.offer_generated_password()? {
+                    Some(password) => return Ok(Some(password)),
+                    None => continue,
+                }
+            }
+
+            let report = estimate_strength(&password, user_inputs);
+            outln!("💪 {}", tr!("Força da senha: {}", report.score));
+            for hint in &report.hints {
+                outln!("   💡 {}", hint);
+            }
+            if report.score < config::current().password.min_strength {
+                outln!("⚠️  {}", tr!("Senha fraca demais. Tente outra (senha vazia cancela)."));
+                continue;
+            }
+
+            let confirm_password = self.read_password(confirm_prompt)?;
+            if secrets_equal(password.as_bytes(), confirm_password.as_bytes()) {
+                return Ok(Some(password));
+            }
+            outln!("⚠️  {}", tr!("As senhas não coincidem. Tente novamente (senha vazia cancela)."));
+        }
+
+        outln!("🚫 {}", tr!("Tentativas esgotadas. Voltando ao menu."));
+        Ok(None)
+    }
+
+    /// Gera uma senha e a mostra uma única vez, retornando-a se o usuário aceitar
+    fn offer_generated_password(&mut self) -> AuthResult<Option<Zeroizing<String>>> {
+        let password = match generate_password(&GeneratorOptions::default(), &config::current().password) {
+            Ok(password) => password,
+            Err(err) if err.kind() == ErrorKind::Validation => {
+                outln!("⚠️  {}", err.detail());
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+
+        outln!("🎲 {}", tr!("Senha gerada: {}", password.as_str()));
+        outln!("   {}", tr!("Guarde-a em um gerenciador de senhas: ela não será exibida de novo."));
+        if self.confirm(tr!("Usar esta senha?"))? {
+            return Ok(Some(password));
+        }
+        outln!("↩️  {}", tr!("Senha descartada. Digite outra ou '{}' para gerar de novo.", GENERATE_INPUT));
+        Ok(None)
+    }
+}
+
+/// Restaura o terminal ao sair de escopo, inclusive em caso de erro ou interrupção
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        // Com "bracketed paste" um texto colado chega como um único evento
+        let _ = execute!(io::stdout(), EnableBracketedPaste);
+        Ok(RawModeGuard)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), DisableBracketedPaste);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Lê a senha exibindo `*` por caractere, com suporte a backspace e colagem
+fn read_masked() -> io::Result<Zeroizing<String>> {
+    let mut password = Zeroizing::new(String::with_capacity(MASKED_CAPACITY));
+    let mut stdout = io::stdout();
+
+    {
+        let _guard = RawModeGuard::enable()?;
+
+        loop {
+            match event::read()? {
+                Event::Paste(text) => {
+                    let text = Zeroizing::new(text);
+                    let text: Zeroizing<String> = Zeroizing::new(text.chars().filter(|c| !c.is_control()).collect());
+                    write!(stdout, "{}", "*".repeat(text.chars().count()))?;
+                    password.push_str(&text);
+                }
+                Event::Key(key) if key.kind != KeyEventKind::Release => match key.code {
+                    KeyCode::Enter => break,
+                    // Alguns terminais enviam LF (Ctrl+J) em vez de CR no Enter
+                    KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        // Em modo raw o Ctrl+C não gera sinal: tratamos aqui
+                        write!(stdout, "\r\n")?;
+                        return Err(io::Error::new(io::ErrorKind::Interrupted, tr!("entrada interrompida")));
+                    }
+                    KeyCode::Backspace if !password.is_empty() => {
+                        password.pop();
+                        write!(stdout, "\u{8} \u{8}")?;
+                    }
+                    KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        password.push(c);
+                        write!(stdout, "*")?;
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+            stdout.flush()?;
+        }
+    }
+
+    outln!();
+    Ok(password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{NEW_PASSWORD, PASSWORD};
+
+    fn scripted(lines: &[&str]) -> Prompter<io::Cursor<String>> {
+        Prompter::scripted(io::Cursor::new(lines.iter().map(|line| format!("{}\n", line)).collect()))
+    }
+
+    #[test]
+    fn empty_username_is_asked_again() {
+        let mut prompter = scripted(&["", "  ", "alice"]);
+        assert_eq!(prompter.username().unwrap().as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn username_gives_up_after_the_attempt_limit() {
+        let mut prompter = scripted(&["", "", "", "alice"]);
+        assert_eq!(prompter.username().unwrap(), None);
+        // A linha seguinte não foi consumida
+        assert_eq!(prompter.read_line("").unwrap(), "alice");
+    }
+
+    #[test]
+    fn username_can_be_cancelled() {
+        let mut prompter = scripted(&["", CANCEL_INPUT, "alice"]);
+        assert_eq!(prompter.username().unwrap(), None);
+    }
+
+    #[test]
+    fn mismatched_confirmation_is_asked_again() {
+        let mut prompter = scripted(&[PASSWORD, NEW_PASSWORD, PASSWORD, PASSWORD]);
+        let password = prompter.new_password("senha: ", "confirme: ", &[]).unwrap();
+        assert_eq!(password.as_deref().map(String::as_str), Some(PASSWORD));
+    }
+
+    #[test]
+    fn weak_password_is_asked_again_without_confirmation() {
+        let mut prompter = scripted(&["abc", PASSWORD, PASSWORD]);
+        let password = prompter.new_password("senha: ", "confirme: ", &[]).unwrap();
+        assert_eq!(password.as_deref().map(String::as_str), Some(PASSWORD));
+    }
+
+    #[test]
+    fn empty_password_cancels() {
+        let mut prompter = scripted(&[PASSWORD, NEW_PASSWORD, "", PASSWORD, PASSWORD]);
+        assert!(prompter.new_password("senha: ", "confirme: ", &[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn new_password_gives_up_after_the_attempt_limit() {
+        let mut prompter = scripted(&[PASSWORD, "x1", PASSWORD, "x2", PASSWORD, "x3", PASSWORD, PASSWORD]);
+        assert!(prompter.new_password("senha: ", "confirme: ", &[]).unwrap().is_none());
+    }
+}
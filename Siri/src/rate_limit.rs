@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use chrono::{DateTime, Duration, Utc};
+
+/// Limitador de tentativas de login por processo, com janela deslizante.
+///
+/// Não diferencia usuários: o atraso vale para qualquer nome digitado, o que
+/// também desacelera a enumeração de contas.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    failures: VecDeque<DateTime<Utc>>,
+}
+
+impl RateLimiter {
+    /// Falhas permitidas dentro da janela antes de começar a atrasar
+    pub const MAX_FAILURES: usize = 3;
+    /// Tamanho da janela deslizante
+    pub const WINDOW: Duration = Duration::minutes(5);
+    /// Atraso aplicado na primeira tentativa acima do limite
+    pub const BASE_DELAY: Duration = Duration::seconds(1);
+    /// Atraso máximo entre tentativas
+    pub const MAX_DELAY: Duration = Duration::seconds(30);
+
+    /// Cria um limitador vazio
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra uma tentativa de login que falhou
+    pub fn record_failure(&mut self, now: DateTime<Utc>) {
+        self.prune(now);
+        self.failures.push_back(now);
+    }
+
+    /// Calcula quanto tempo esperar antes de aceitar a próxima tentativa
+    pub fn delay(&mut self, now: DateTime<Utc>) -> Duration {
+        self.prune(now);
+
+        if self.failures.len() < Self::MAX_FAILURES {
+            return Duration::zero();
+        }
+
+        // Dobra o atraso a cada falha acima do limite, até o teto
+        let excess = (self.failures.len() - Self::MAX_FAILURES).min(16) as u32;
+        let delay = Self::BASE_DELAY * 2i32.pow(excess);
+        delay.min(Self::MAX_DELAY)
+    }
+
+    /// Descarta falhas que já saíram da janela
+    fn prune(&mut self, now: DateTime<Utc>) {
+        while let Some(oldest) = self.failures.front() {
+            if now - *oldest >= Self::WINDOW {
+                self.failures.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+
+    fn clock() -> MockClock {
+        MockClock::new(DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap().to_utc())
+    }
+
+    #[test]
+    fn delay_doubles_with_each_failure_above_the_limit() {
+        let clock = clock();
+        let mut limiter = RateLimiter::new();
+
+        let mut delays = Vec::new();
+        for _ in 0..RateLimiter::MAX_FAILURES + 4 {
+            delays.push(limiter.delay(clock.now()).num_seconds());
+            limiter.record_failure(clock.now());
+            clock.advance(Duration::seconds(1));
+        }
+        assert_eq!(delays, [0, 0, 0, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn delay_stops_at_the_ceiling() {
+        let clock = clock();
+        let mut limiter = RateLimiter::new();
+
+        for _ in 0..RateLimiter::MAX_FAILURES + 10 {
+            limiter.record_failure(clock.now());
+            clock.advance(Duration::seconds(1));
+        }
+        assert_eq!(limiter.delay(clock.now()), RateLimiter::MAX_DELAY);
+    }
+
+    #[test]
+    fn failures_leave_the_window_as_the_clock_advances() {
+        let clock = clock();
+        let mut limiter = RateLimiter::new();
+
+        for _ in 0..RateLimiter::MAX_FAILURES + 1 {
+            limiter.record_failure(clock.now());
+        }
+        assert_eq!(limiter.delay(clock.now()), RateLimiter::BASE_DELAY * 2);
+
+        clock.advance(RateLimiter::WINDOW - Duration::seconds(1));
+        assert_eq!(limiter.delay(clock.now()), RateLimiter::BASE_DELAY * 2);
+
+        clock.advance(Duration::seconds(1));
+        assert_eq!(limiter.delay(clock.now()), Duration::zero());
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt;
+use crate::audit;
+use crate::error::{AuthError, AuthResult};
+use crate::session::Session;
+use crate::store::UserStore;
+use crate::user::AccountStatus;
+use crate::{message, tr, validation_error};
+
+/// Papel de um usuário, que define o que ele pode fazer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Role {
+    /// Apenas o autoatendimento da própria conta
+    #[default]
+    User,
+    /// Também lista, exclui e administra outras contas e consulta a auditoria
+    Admin,
+}
+
+impl Role {
+    /// Todos os papéis
+    pub const ALL: [Role; 2] = [Role::User, Role::Admin];
+
+    /// Nome do papel, como gravado na coluna `role`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Procura um papel pelo nome, sem diferenciar maiúsculas/minúsculas
+    pub fn from_name(name: &str) -> Option<Role> {
+        let name = name.trim();
+        Role::ALL.into_iter().find(|role| role.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Role::User => tr!("usuário"),
+            Role::Admin => tr!("administrador"),
+        };
+        f.pad(label)
+    }
+}
+
+/// Ação restrita, concedida aos papéis pela tabela `role_permissions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// Listar todos os usuários
+    ListUsers,
+    /// Excluir, restaurar e purgar usuários
+    DeleteUser,
+    /// Consultar o log de auditoria
+    ViewAudit,
+    /// Promover e rebaixar usuários e alterar as permissões dos papéis
+    ManageRoles,
+    /// Ativar, desativar e desbloquear contas, e redefinir senhas de outros usuários
+    ManageAccounts,
+    /// Alterar o limite de cadastros e a validade das senhas
+    ManageSettings,
+    /// Ver as estatísticas do sistema
+    ViewStats,
+}
+
+impl Permission {
+    /// Todas as permissões
+    pub const ALL: [Permission; 7] = [
+        Permission::ListUsers,
+        Permission::DeleteUser,
+        Permission::ViewAudit,
+        Permission::ManageRoles,
+        Permission::ManageAccounts,
+        Permission::ManageSettings,
+        Permission::ViewStats,
+    ];
+
+    /// Nome da permissão, como gravado no banco
+    pub fn name(&self) -> &'static str {
+        match self {
+            Permission::ListUsers => "list_users",
+            Permission::DeleteUser => "delete_user",
+            Permission::ViewAudit => "view_audit",
+            Permission::ManageRoles => "manage_roles",
+            Permission::ManageAccounts => "manage_accounts",
+            Permission::ManageSettings => "manage_settings",
+            Permission::ViewStats => "view_stats",
+        }
+    }
+
+    /// Procura uma permissão pelo nome, sem diferenciar maiúsculas/minúsculas
+    pub fn from_name(name: &str) -> Option<Permission> {
+        let name = name.trim();
+        Permission::ALL.into_iter().find(|permission| permission.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Descrição para exibir ao administrador
+    pub fn description(&self) -> &'static str {
+        match self {
+            Permission::ListUsers => tr!("Listar todos os usuários"),
+            Permission::DeleteUser => tr!("Excluir, restaurar e purgar usuários"),
+            Permission::ViewAudit => tr!("Consultar o log de auditoria"),
+            Permission::ManageRoles => tr!("Promover/rebaixar usuários e alterar permissões"),
+            Permission::ManageAccounts => tr!("Ativar, desbloquear e redefinir senhas de contas"),
+            Permission::ManageSettings => tr!("Alterar limites e validade das senhas"),
+            Permission::ViewStats => tr!("Ver estatísticas do sistema"),
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// Verifica se o usuário é um administrador ativo
+pub fn is_admin(store: &dyn UserStore, username: &str) -> AuthResult<bool> {
+    Ok(store.get_role(username)? == Some(Role::Admin) && store.get_status(username)? == Some(AccountStatus::Active))
+}
+
+/// Permissões de um usuário ativo, pelas do seu papel (vazio se a conta não existe ou está desativada)
+pub fn user_permissions(store: &dyn UserStore, username: &str) -> AuthResult<Vec<Permission>> {
+    if store.get_status(username)? != Some(AccountStatus::Active) {
+        return Ok(Vec::new());
+    }
+    match store.get_role(username)? {
+        Some(role) => store.role_permissions(role),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Garante que `username` tem a permissão, retornando `PermissionDenied` se não tiver
+pub fn require_permission(store: &dyn UserStore, username: &str, permission: Permission) -> AuthResult<()> {
+    if !user_permissions(store, username)?.contains(&permission) {
+        return Err(AuthError::PermissionDenied(message!("'{}' não tem a permissão '{}'", username, permission)));
+    }
+    Ok(())
+}
+
+/// Garante que o dono da sessão tem a permissão.
+///
+/// A permissão é consultada no banco a cada chamada, então promoções,
+/// rebaixamentos e mudanças nas permissões valem sem novo login.
+pub fn authorize(store: &dyn UserStore, session: &Session, permission: Permission) -> AuthResult<()> {
+    require_permission(store, &session.username, permission)
+}
+
+/// Concede ou revoga uma permissão de um papel, registrando a mudança.
+///
+/// Quem chama é responsável por autorizar `actor`. Administradores nunca perdem
+/// `manage_roles`, para que sempre haja quem desfaça uma mudança errada.
+pub fn set_permission(
+    store: &dyn UserStore,
+    actor: &str,
+    role: Role,
+    permission: Permission,
+    granted: bool,
+) -> AuthResult<()> {
+    if role == Role::Admin && permission == Permission::ManageRoles && !granted {
+        return validation_error!("O papel {} não pode perder a permissão '{}'", role, permission);
+    }
+    if store.role_permissions(role)?.contains(&permission) == granted {
+        return if granted {
+            validation_error!("O papel {} já tem a permissão '{}'", role, permission)
+        } else {
+            validation_error!("O papel {} não tem a permissão '{}'", role, permission)
+        };
+    }
+
+    store.set_role_permission(role, permission, granted)?;
+    let action = if granted { "grant_permission" } else { "revoke_permission" };
+    audit::record(store, actor, action, Some(&format!("{}:{}", role.name(), permission.name())))
+}
+
+/// Troca o papel de um usuário, registrando a promoção ou o rebaixamento.
+///
+/// Quem chama é responsável por autorizar `actor`. O último administrador
+/// ativo não pode ser rebaixado, para que o sistema não fique sem nenhum.
+pub fn set_role(store: &dyn UserStore, actor: &str, username: &str, role: Role) -> AuthResult<()> {
+    let Some(current) = store.get_role(username)? else {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    };
+    if current == role {
+        return validation_error!("'{}' já é {}", username, role);
+    }
+    if current == Role::Admin && is_admin(store, username)? && store.count_admins()? <= 1 {
+        return validation_error!("'{}' é o único administrador ativo e não pode ser rebaixado", username);
+    }
+
+    store.set_role(username, role)?;
+    let action = match role {
+        Role::Admin => "promote_user",
+        Role::User => "demote_user",
+    };
+    audit::record(store, actor, action, Some(username))
+}
+
+/// Ativa ou desativa uma conta, registrando a mudança.
+///
+/// Quem chama é responsável por autorizar `actor`. Como no rebaixamento, o último
+/// administrador ativo não pode ser desativado.
+pub fn set_status(store: &dyn UserStore, actor: &str, username: &str, status: AccountStatus) -> AuthResult<()> {
+    let Some(current) = store.get_status(username)? else {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    };
+    if current == status {
+        return validation_error!("A conta '{}' já está {}", username, status);
+    }
+    if status == AccountStatus::Disabled && is_admin(store, username)? && store.count_admins()? <= 1 {
+        return validation_error!("'{}' é o único administrador ativo e não pode ser desativado", username);
+    }
+
+    store.set_status(username, status)?;
+    let action = match status {
+        AccountStatus::Active => "enable_user",
+        AccountStatus::Disabled => "disable_user",
+    };
+    audit::record(store, actor, action, Some(username))
+}
@@ -0,0 +1,189 @@
+//! Códigos de recuperação de uso único.
+//!
+//! Um único conjunto por conta serve para duas coisas: redefinir a senha esquecida
+//! ("Esqueci minha senha") e, em contas com dois fatores, substituir o código do
+//! aplicativo autenticador. Cada código vale uma vez, para qualquer um dos dois usos.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use crate::audit;
+use crate::auth::{dummy_hash_operation, hash_password, validate_password_strength, verify_password};
+use crate::config;
+use crate::error::{AuthError, AuthResult};
+use crate::events::{self, Event};
+use crate::store::UserStore;
+use crate::{message, validation};
+
+/// Quantidade de códigos gerados a cada emissão
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Tamanho de cada código (sem o separador)
+const RECOVERY_CODE_LEN: usize = 10;
+
+/// Alfabeto sem caracteres ambíguos (0/O, 1/I/L)
+const RECOVERY_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// Gera um código aleatório no formato XXXXX-XXXXX
+fn generate_code() -> String {
+    let mut bytes = [0u8; RECOVERY_CODE_LEN];
+    OsRng.fill_bytes(&mut bytes);
+
+    let chars: String = bytes
+        .iter()
+        .map(|b| RECOVERY_ALPHABET[*b as usize % RECOVERY_ALPHABET.len()] as char)
+        .collect();
+
+    format!("{}-{}", &chars[..RECOVERY_CODE_LEN / 2], &chars[RECOVERY_CODE_LEN / 2..])
+}
+
+/// Normaliza um código digitado (remove separadores e ignora maiúsculas/minúsculas)
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Se o código normalizado tem o formato de um código emitido, sem precisar consultar o banco
+fn is_well_formed(normalized: &str) -> bool {
+    normalized.len() == RECOVERY_CODE_LEN && normalized.bytes().all(|b| RECOVERY_ALPHABET.contains(&b))
+}
+
+/// Gera um novo conjunto de códigos de recuperação, invalidando os anteriores.
+///
+/// Os códigos em texto puro são retornados apenas uma vez; no banco ficam só os hashes.
+pub fn generate_recovery_codes(store: &dyn UserStore, username: &str) -> AuthResult<Vec<String>> {
+    if !store.user_exists(username)? {
+        return Err(AuthError::NotFound(message!("Usuário '{}'", username)));
+    }
+
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| generate_code()).collect();
+    let hashes = codes
+        .iter()
+        .map(|code| hash_password(store, &normalize_code(code)))
+        .collect::<AuthResult<Vec<_>>>()?;
+
+    store.transaction(&mut |tx| {
+        tx.replace_recovery_codes(username, &hashes)?;
+        audit::record(tx, username, "generate_recovery_codes", Some(username))
+    })?;
+    Ok(codes)
+}
+
+/// Quantos códigos de recuperação do usuário ainda não foram usados
+pub fn remaining_recovery_codes(store: &dyn UserStore, username: &str) -> AuthResult<usize> {
+    Ok(store.unused_recovery_codes(username)?.len())
+}
+
+/// Consome um código de recuperação do usuário, retornando se ele era válido
+pub(crate) fn consume_recovery_code(store: &dyn UserStore, username: &str, code: &str) -> AuthResult<bool> {
+    let normalized = normalize_code(code);
+    if !is_well_formed(&normalized) {
+        return Ok(false);
+    }
+    for (code_id, code_hash) in store.unused_recovery_codes(username)? {
+        if verify_password(&normalized, &code_hash)? {
+            // Outra operação pode ter consumido o mesmo código entre a leitura e a escrita
+            return store.consume_recovery_code(code_id);
+        }
+    }
+    Ok(false)
+}
+
+/// Faz o mesmo trabalho que conferir o código contra um conjunto completo, sem consultar
+/// conta nenhuma, para que o tempo de resposta não revele se o usuário existe
+pub(crate) fn dummy_recovery_code_check(store: &dyn UserStore, code: &str) {
+    if is_well_formed(&normalize_code(code)) {
+        for _ in 0..RECOVERY_CODE_COUNT {
+            dummy_hash_operation(store);
+        }
+    }
+}
+
+/// Redefine a senha usando um código de recuperação de uso único
+pub fn reset_password_with_recovery_code(
+    store: &dyn UserStore,
+    username: &str,
+    code: &str,
+    new_password: &str,
+) -> AuthResult<()> {
+    let invalid = || validation!("Código de recuperação inválido");
+    if !is_well_formed(&normalize_code(code)) {
+        return Err(invalid());
+    }
+
+    // Validar a nova senha antes de gastar o código
+    validate_password_strength(new_password, &config::current().password, &[username])?;
+
+    if !store.user_exists(username)? {
+        dummy_recovery_code_check(store, code);
+        return Err(invalid());
+    }
+    if !consume_recovery_code(store, username, code)? {
+        return Err(invalid());
+    }
+
+    let new_hash = hash_password(store, new_password)?;
+    store.set_password_hash(username, &new_hash, true)?;
+    audit::record(store, username, "reset_password_with_recovery_code", Some(username))?;
+    events::emit(Event::PasswordChanged { username: username.to_string() }, Utc::now());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{login_user, LoginOutcome};
+    use crate::testing::{database, register, NEW_PASSWORD};
+
+    #[test]
+    fn consumed_code_is_rejected_on_reuse() {
+        let db = database();
+        register(&db, "alice");
+        let codes = generate_recovery_codes(&db, "alice").unwrap();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+
+        reset_password_with_recovery_code(&db, "alice", &codes[0], NEW_PASSWORD).unwrap();
+        assert_eq!(login_user(&db, "alice", NEW_PASSWORD).unwrap(), LoginOutcome::Success);
+        assert_eq!(remaining_recovery_codes(&db, "alice").unwrap(), RECOVERY_CODE_COUNT - 1);
+
+        let err = reset_password_with_recovery_code(&db, "alice", &codes[0], NEW_PASSWORD).unwrap_err();
+        assert!(matches!(err, AuthError::Validation(_)), "{:?}", err);
+        // Também não serve mais no lugar do segundo fator: o conjunto é um só
+        assert!(!consume_recovery_code(&db, "alice", &codes[0]).unwrap());
+    }
+
+    #[test]
+    fn malformed_code_is_a_validation_error() {
+        let db = database();
+        register(&db, "alice");
+        generate_recovery_codes(&db, "alice").unwrap();
+
+        for code in ["", "ABC", "ABCDE-FGHJK-MNPQR", "ABCDE-FGHI1", "ÁBCDE-FGHJK"] {
+            let err = reset_password_with_recovery_code(&db, "alice", code, NEW_PASSWORD).unwrap_err();
+            assert!(matches!(err, AuthError::Validation(_)), "{}: {:?}", code, err);
+        }
+        assert_eq!(remaining_recovery_codes(&db, "alice").unwrap(), RECOVERY_CODE_COUNT);
+    }
+
+    #[test]
+    fn codes_are_accepted_regardless_of_case_and_separator() {
+        let db = database();
+        register(&db, "alice");
+        let codes = generate_recovery_codes(&db, "alice").unwrap();
+
+        let typed = codes[1].replace('-', " ").to_lowercase();
+        reset_password_with_recovery_code(&db, "alice", &typed, NEW_PASSWORD).unwrap();
+    }
+
+    #[test]
+    fn new_codes_invalidate_the_previous_set() {
+        let db = database();
+        register(&db, "alice");
+        let old = generate_recovery_codes(&db, "alice").unwrap();
+        generate_recovery_codes(&db, "alice").unwrap();
+
+        assert!(!consume_recovery_code(&db, "alice", &old[0]).unwrap());
+    }
+}
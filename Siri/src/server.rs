@@ -0,0 +1,262 @@
+//! Servidor HTTP (`siri serve`), que expõe o `AuthService` como uma API REST.
+//!
+//! As respostas usam o mesmo envelope do `--json`: `{"status":"ok","data":...}` ou
+//! `{"status":"error","error":{"code":...,"kind":...,"message":...}}`, com o status HTTP
+//! escolhido pela classe do erro. O hash das senhas é demorado de propósito, então
+//! cada requisição roda em uma thread de bloqueio, fora do executor assíncrono.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
+use crate::commands::user_json;
+use crate::error::{AuthError, AuthResult, ErrorKind};
+use crate::{backup, config, health, metrics};
+use crate::output::{error_json, success_json};
+use crate::rbac::Permission;
+use crate::service::AuthService;
+use crate::session::Session;
+use crate::user::TimestampDisplay;
+use crate::{outln, tr, validation};
+
+/// Endereço padrão do servidor: apenas a própria máquina
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:8080";
+
+/// O serviço compartilhado entre as requisições, que rodam em paralelo sobre o pool do banco
+type SharedService = Arc<AuthService>;
+
+/// Corpo de `POST /register`
+#[derive(Deserialize)]
+struct RegisterRequest {
+    username: String,
+    password: Zeroizing<String>,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+/// Corpo de `POST /login`
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: Zeroizing<String>,
+    /// Código do aplicativo autenticador (ou de recuperação), para contas com dois fatores
+    #[serde(default)]
+    totp: Option<Zeroizing<String>>,
+}
+
+/// Corpo de `POST /change-password`
+#[derive(Deserialize)]
+struct ChangePasswordRequest {
+    username: String,
+    old_password: Zeroizing<String>,
+    new_password: Zeroizing<String>,
+}
+
+/// Status HTTP correspondente a cada classe de erro
+fn status_code(kind: ErrorKind) -> StatusCode {
+    match kind {
+        ErrorKind::Validation => StatusCode::BAD_REQUEST,
+        ErrorKind::InvalidCredentials | ErrorKind::SecondFactorRequired | ErrorKind::SessionExpired => {
+            StatusCode::UNAUTHORIZED
+        }
+        ErrorKind::PermissionDenied | ErrorKind::AccountDisabled | ErrorKind::PasswordExpired => {
+            StatusCode::FORBIDDEN
+        }
+        ErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ErrorKind::AccountLocked => StatusCode::LOCKED,
+        ErrorKind::Database | ErrorKind::Input | ErrorKind::PasswordHashing => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Resposta de erro no envelope JSON
+fn error_response(err: &AuthError) -> Response {
+    let status = status_code(err.kind());
+    if status.is_server_error() {
+        log::error!("erro na API HTTP: {}", err);
+    }
+    (status, Json(error_json(err))).into_response()
+}
+
+/// Lê o corpo JSON, transformando um corpo inválido em erro de validação
+fn parse_body<T>(body: Result<Json<T>, JsonRejection>) -> AuthResult<T> {
+    body.map(|Json(value)| value)
+        .map_err(|rejection| validation!("Corpo da requisição inválido: {}", rejection.body_text()))
+}
+
+/// Executa uma operação do serviço em uma thread de bloqueio e monta a resposta
+async fn call<F>(service: SharedService, status: StatusCode, operation: F) -> Response
+where
+    F: FnOnce(&AuthService) -> AuthResult<Value> + Send + 'static,
+{
+    let result = tokio::task::spawn_blocking(move || operation(&service)).await;
+
+    match result {
+        Ok(Ok(data)) => (status, Json(success_json(&data))).into_response(),
+        Ok(Err(err)) => error_response(&err),
+        Err(err) => error_response(&AuthError::Input(std::io::Error::other(err))),
+    }
+}
+
+/// Token (de sessão ou JWT) do cabeçalho `Authorization: Bearer <token>`
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim().to_string())
+}
+
+/// Retoma a sessão do token e a renova; token ausente é recusado como credencial
+/// inválida, e token expirado, encerrado ou desconhecido como sessão expirada
+fn authenticate(service: &AuthService, token: Option<&str>) -> AuthResult<Session> {
+    let token = token.ok_or(AuthError::InvalidCredentials)?;
+    let mut session = service.validate_session(token)?.ok_or(AuthError::SessionExpired)?;
+    if !service.session_active(&mut session)? {
+        return Err(AuthError::SessionExpired);
+    }
+    service.touch_session(&mut session)?;
+    Ok(session)
+}
+
+/// `POST /register`: cadastra um usuário
+async fn register(State(service): State<SharedService>, body: Result<Json<RegisterRequest>, JsonRejection>) -> Response {
+    let request = match parse_body(body) {
+        Ok(request) => request,
+        Err(err) => return error_response(&err),
+    };
+    call(service, StatusCode::CREATED, move |service| {
+        service.register(&request.username, &request.password, request.email.as_deref())?;
+        Ok(json!({ "username": request.username }))
+    })
+    .await
+}
+
+/// `POST /login`: confere as credenciais e abre uma sessão, retornando o token
+async fn login(State(service): State<SharedService>, body: Result<Json<LoginRequest>, JsonRejection>) -> Response {
+    let request = match parse_body(body) {
+        Ok(request) => request,
+        Err(err) => return error_response(&err),
+    };
+    call(service, StatusCode::OK, move |service| {
+        service
+            .login_with_second_factor(&request.username, &request.password, request.totp.as_ref().map(|code| code.as_str()))?
+            .into_result()?;
+
+        let session = service.open_session(&request.username)?;
+        let jwt = service.issue_token(&request.username)?;
+        Ok(json!({
+            "username": session.username,
+            "token": session.token,
+            "expires_at": TimestampDisplay::Utc.format(session.expires_at),
+            "jwt": jwt,
+        }))
+    })
+    .await
+}
+
+/// `POST /change-password`: troca a senha, exigindo a senha atual
+async fn change_password(
+    State(service): State<SharedService>,
+    body: Result<Json<ChangePasswordRequest>, JsonRejection>,
+) -> Response {
+    let request = match parse_body(body) {
+        Ok(request) => request,
+        Err(err) => return error_response(&err),
+    };
+    call(service, StatusCode::OK, move |service| {
+        service.change_password(&request.username, &request.old_password, &request.new_password)?;
+        Ok(json!({ "username": request.username }))
+    })
+    .await
+}
+
+/// `GET /users`: lista os usuários, para sessões com a permissão `list_users`
+async fn list_users(State(service): State<SharedService>, headers: HeaderMap) -> Response {
+    let token = bearer_token(&headers);
+    call(service, StatusCode::OK, move |service| {
+        let session = authenticate(service, token.as_deref())?;
+        service.authorize(&session, Permission::ListUsers)?;
+        let users = service.store().list_users()?;
+        Ok(Value::Array(users.iter().map(user_json).collect()))
+    })
+    .await
+}
+
+/// `GET /verify`: confere o JWT do cabeçalho `Authorization` e devolve suas reivindicações
+async fn verify(State(service): State<SharedService>, headers: HeaderMap) -> Response {
+    let token = bearer_token(&headers);
+    call(service, StatusCode::OK, move |service| {
+        let claims = service.verify_token(token.as_deref().ok_or(AuthError::InvalidCredentials)?)?;
+        Ok(json!({
+            "username": claims.sub,
+            "role": claims.role,
+            "issuer": claims.iss,
+            "issued_at": TimestampDisplay::Utc.format(claims.issued_at()),
+            "expires_at": TimestampDisplay::Utc.format(claims.expires_at()),
+        }))
+    })
+    .await
+}
+
+/// `GET /healthz`: o relatório de `siri health`, com 503 se alguma verificação falhar
+async fn healthz(State(service): State<SharedService>) -> Response {
+    let report = tokio::task::spawn_blocking(move || health::check(&service)).await;
+
+    match report {
+        Ok(report) => {
+            let status = if report.healthy() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+            (status, Json(success_json(&report.to_json()))).into_response()
+        }
+        Err(err) => error_response(&AuthError::Input(std::io::Error::other(err))),
+    }
+}
+
+/// `GET /metrics`: contadores do processo no formato do Prometheus
+async fn metrics() -> Response {
+    ([(header::CONTENT_TYPE, metrics::CONTENT_TYPE)], metrics::render()).into_response()
+}
+
+/// Só a rota `/metrics`, para processos que não servem a API (ex.: `siri daemon`)
+pub fn metrics_router() -> Router {
+    Router::new().route("/metrics", get(metrics))
+}
+
+/// Rotas da API sobre um serviço já aberto, que precisa ter chaves JWT (`with_jwt`)
+pub fn router(service: SharedService) -> Router {
+    Router::new()
+        .route("/register", post(register))
+        .route("/login", post(login))
+        .route("/change-password", post(change_password))
+        .route("/users", get(list_users))
+        .route("/verify", get(verify))
+        .route("/healthz", get(healthz))
+        .with_state(service)
+        .merge(metrics_router())
+}
+
+/// Atende a API no endereço informado até o processo receber Ctrl+C
+pub fn serve(service: AuthService, listen: SocketAddr) -> AuthResult<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    runtime.block_on(async move {
+        let listener = tokio::net::TcpListener::bind(listen).await?;
+        let address = listener.local_addr()?;
+        log::info!("servidor HTTP ouvindo em {}", address);
+        outln!("🌐 {}", tr!("Servidor HTTP ouvindo em http://{} (Ctrl+C para encerrar)", address));
+
+        let service = Arc::new(service);
+        backup::schedule(Arc::clone(&service), &config::current().backup)?;
+
+        axum::serve(listener, router(service))
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await?;
+        log::info!("servidor HTTP encerrado");
+        Ok(())
+    })
+}
@@ -0,0 +1,392 @@
+use crate::auth::{
+    change_password, create_reset_token, delete_own_account, login_second_factor, login_user_with_clock, reauthenticate,
+    redeem_reset_token, register_user_with_email, LoginOutcome, RESET_TOKEN_TTL,
+};
+use crate::clock::{Clock, SystemClock};
+use crate::config::{self, SessionPolicy};
+use crate::db::Database;
+use crate::email::{request_password_reset, send_verification_email, verify_email};
+use crate::error::{AuthError, AuthResult};
+use crate::groups::{is_member, user_groups};
+use crate::jwt::{JwtKeys, TokenClaims};
+use crate::metrics;
+use crate::mailer::{ConsoleMailer, Mailer};
+use crate::postgres_store::PostgresStore;
+use crate::profile::{update_profile, Profile};
+use crate::rbac::{authorize, Permission};
+use crate::session::{end_session, is_session_active, resume_session, start_session, touch_session, Session};
+use crate::store::{LoginAttempt, UserStore};
+use crate::{message, validation_error};
+
+/// Onde o serviço guarda os usuários
+enum Storage {
+    Sqlite(Database),
+    /// Em uma caixa: o cliente do PostgreSQL é bem maior que a conexão SQLite
+    Postgres(Box<PostgresStore>),
+}
+
+/// Fachada das operações de autenticação sobre um banco aberto (SQLite ou PostgreSQL).
+///
+/// É o ponto de entrada para quem usa o crate como biblioteca; a CLI e os
+/// comandos não interativos são apenas interfaces sobre ele.
+pub struct AuthService {
+    storage: Storage,
+    clock: Box<dyn Clock>,
+    session_policy: SessionPolicy,
+    /// Origem gravada no histórico de logins
+    source: String,
+    /// Entrega os emails de verificação e de redefinição de senha
+    mailer: Box<dyn Mailer>,
+    /// Chaves dos tokens JWT (None = o serviço não emite nem confere JWTs)
+    jwt: Option<JwtKeys>,
+}
+
+/// Origem padrão das tentativas de login: o menu interativo
+const DEFAULT_SOURCE: &str = "terminal";
+
+impl AuthService {
+    /// Cria o serviço usando o relógio do sistema
+    pub fn new(db: Database) -> Self {
+        Self::with_clock(db, Box::new(SystemClock))
+    }
+
+    /// Cria o serviço com um relógio específico (útil em testes)
+    pub fn with_clock(db: Database, clock: Box<dyn Clock>) -> Self {
+        AuthService {
+            storage: Storage::Sqlite(db),
+            clock,
+            session_policy: config::current().session,
+            source: DEFAULT_SOURCE.to_string(),
+            mailer: Box::new(ConsoleMailer),
+            jwt: None,
+        }
+    }
+
+    /// Cria o serviço sobre um servidor PostgreSQL compartilhado
+    pub fn with_postgres(store: PostgresStore) -> Self {
+        AuthService {
+            storage: Storage::Postgres(Box::new(store)),
+            clock: Box::new(SystemClock),
+            session_policy: config::current().session,
+            source: DEFAULT_SOURCE.to_string(),
+            mailer: Box::new(ConsoleMailer),
+            jwt: None,
+        }
+    }
+
+    /// Define a política de expiração das sessões (padrão: a seção `[session]` da configuração)
+    pub fn with_session_policy(mut self, policy: SessionPolicy) -> Self {
+        self.session_policy = policy;
+        self
+    }
+
+    /// Define a origem gravada no histórico de logins (padrão: "terminal")
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Define como os emails são entregues (padrão: `ConsoleMailer`, que só os exibe)
+    pub fn with_mailer(mut self, mailer: Box<dyn Mailer>) -> Self {
+        self.mailer = mailer;
+        self
+    }
+
+    /// Define as chaves usadas para emitir e conferir tokens JWT
+    pub fn with_jwt(mut self, keys: JwtKeys) -> Self {
+        self.jwt = Some(keys);
+        self
+    }
+
+    /// Armazenamento de usuários usado pelo serviço
+    pub fn store(&self) -> &dyn UserStore {
+        match &self.storage {
+            Storage::Sqlite(db) => db,
+            Storage::Postgres(store) => store.as_ref(),
+        }
+    }
+
+    /// Banco SQLite usado pelo serviço, para recursos que ainda dependem dele
+    /// (administração, exportação e estatísticas)
+    pub fn database(&self) -> AuthResult<&Database> {
+        match &self.storage {
+            Storage::Sqlite(db) => Ok(db),
+            Storage::Postgres(_) => validation_error!("Recurso disponível apenas com o backend SQLite"),
+        }
+    }
+
+    /// Relógio usado pelo serviço
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Registra um novo usuário, com email opcional
+    pub fn register(&self, username: &str, password: &str, email: Option<&str>) -> AuthResult<()> {
+        register_user_with_email(self.store(), username, password, email)?;
+        log::info!("usuário '{}' registrado via {}", username, self.source);
+        Ok(())
+    }
+
+    /// Tenta autenticar um usuário
+    pub fn login(&self, username: &str, password: &str) -> AuthResult<LoginOutcome> {
+        let outcome = login_user_with_clock(self.store(), self.clock(), username, password)?;
+        self.record_attempt(username, &outcome)?;
+        Ok(outcome)
+    }
+
+    /// Confere a senha de um usuário já autenticado, sem registrar um login (ver `reauthenticate`)
+    pub fn verify_password(&self, username: &str, password: &str) -> AuthResult<bool> {
+        reauthenticate(self.store(), self.clock(), username, password)
+    }
+
+    /// Autentica com a senha e, se a conta exigir, com o código do segundo fator.
+    ///
+    /// Sem `code`, uma conta com dois fatores resulta em `RequiresSecondFactor`. O código
+    /// só é conferido depois que a senha foi aceita na mesma chamada.
+    pub fn login_with_second_factor(
+        &self,
+        username: &str,
+        password: &str,
+        code: Option<&str>,
+    ) -> AuthResult<LoginOutcome> {
+        let outcome = self.login(username, password)?;
+        match (outcome, code) {
+            (LoginOutcome::RequiresSecondFactor, Some(code)) => self.verify_second_factor(username, code),
+            _ => Ok(outcome),
+        }
+    }
+
+    /// Confere o código TOTP (ou de recuperação) depois de `login` retornar `RequiresSecondFactor`.
+    ///
+    /// Não confere a senha, então fica restrita ao crate: fora dele, o segundo fator
+    /// passa por `login_with_second_factor`, que exige a senha na mesma chamada.
+    pub(crate) fn verify_second_factor(&self, username: &str, code: &str) -> AuthResult<LoginOutcome> {
+        let outcome = login_second_factor(self.store(), self.clock(), username, code)?;
+        self.record_attempt(username, &outcome)?;
+        Ok(outcome)
+    }
+
+    /// Grava o resultado de uma etapa de login no histórico e, se deu certo, como último login
+    fn record_attempt(&self, username: &str, outcome: &LoginOutcome) -> AuthResult<()> {
+        // A senha vencida não impede a autenticação, apenas exige a troca em seguida
+        let success = matches!(outcome, LoginOutcome::Success | LoginOutcome::PasswordExpired);
+        let now = self.clock.now();
+        // A etapa intermediária do segundo fator não é um resultado final
+        if !matches!(outcome, LoginOutcome::RequiresSecondFactor) {
+            metrics::record_login(success);
+        }
+        if success {
+            log::info!("login de '{}' via {}: {}", username, self.source, outcome.name());
+            self.store().record_successful_login(username, now)?;
+        } else {
+            log::warn!("login recusado para '{}' via {}: {}", username, self.source, outcome.name());
+        }
+        self.store().record_login_attempt(&LoginAttempt {
+            username: username.to_string(),
+            success,
+            outcome: outcome.name().to_string(),
+            source: self.source.clone(),
+            attempted_at: now,
+        })
+    }
+
+    /// Últimas `limit` tentativas de login da conta, da mais recente para a mais antiga
+    pub fn login_history(&self, username: &str, limit: usize) -> AuthResult<Vec<LoginAttempt>> {
+        self.store().login_history(username, limit)
+    }
+
+    /// Autentica e, se o resultado for `Success`, abre uma sessão para o usuário.
+    ///
+    /// Contas com dois fatores retornam `RequiresSecondFactor` sem sessão; depois de
+    /// `verify_second_factor`, a sessão é aberta com `open_session`.
+    pub fn login_session(&self, username: &str, password: &str) -> AuthResult<(LoginOutcome, Option<Session>)> {
+        let outcome = self.login(username, password)?;
+        let session = match outcome {
+            LoginOutcome::Success => Some(self.open_session(username)?),
+            _ => None,
+        };
+        Ok((outcome, session))
+    }
+
+    /// Abre uma sessão para um usuário já autenticado (ex.: após trocar uma senha expirada)
+    pub fn open_session(&self, username: &str) -> AuthResult<Session> {
+        start_session(self.store(), username, self.clock.now(), self.session_policy)
+    }
+
+    /// Emite um JWT para um usuário já autenticado, com o papel atual dele
+    pub fn issue_token(&self, username: &str) -> AuthResult<String> {
+        let role = self
+            .store()
+            .get_role(username)?
+            .ok_or_else(|| AuthError::NotFound(message!("Usuário '{}'", username)))?;
+        self.jwt_keys()?.issue(username, role, self.clock.now())
+    }
+
+    /// Confere a assinatura, o emissor e a validade de um JWT emitido pelo Siri.
+    ///
+    /// Não consulta o banco: um token continua valendo até expirar, mesmo que a
+    /// conta seja desativada antes disso.
+    pub fn verify_token(&self, token: &str) -> AuthResult<TokenClaims> {
+        self.jwt_keys()?.verify(token, self.clock.now())
+    }
+
+    fn jwt_keys(&self) -> AuthResult<&JwtKeys> {
+        match &self.jwt {
+            Some(keys) => Ok(keys),
+            None => validation_error!("Tokens JWT não configurados neste serviço"),
+        }
+    }
+
+    /// Valida um token de sessão, retornando a sessão se ela ainda vale.
+    ///
+    /// Sessões expiradas por inatividade ou pela duração máxima são rejeitadas
+    /// e removidas do banco.
+    pub fn validate_session(&self, token: &str) -> AuthResult<Option<Session>> {
+        resume_session(self.store(), token, self.clock.now(), self.session_policy)
+    }
+
+    /// Verifica se a sessão continua valendo (não expirou nem foi encerrada)
+    pub fn session_active(&self, session: &mut Session) -> AuthResult<bool> {
+        is_session_active(self.store(), session, self.clock.now())
+    }
+
+    /// Renova a sessão após uma atividade do usuário
+    pub fn touch_session(&self, session: &mut Session) -> AuthResult<()> {
+        touch_session(self.store(), session, self.clock.now())
+    }
+
+    /// Garante que o dono da sessão tem a permissão, retornando `PermissionDenied` se não tiver
+    pub fn authorize(&self, session: &Session, permission: Permission) -> AuthResult<()> {
+        authorize(self.store(), session, permission)
+    }
+
+    /// Encerra a sessão, invalidando o token
+    pub fn logout(&self, session: &Session) -> AuthResult<()> {
+        end_session(self.store(), session)
+    }
+
+    /// Gera um token de redefinição de senha com a validade padrão (1 hora)
+    pub fn create_reset_token(&self, requested_by: &str, username: &str) -> AuthResult<String> {
+        create_reset_token(self.store(), self.clock(), requested_by, username, RESET_TOKEN_TTL)
+    }
+
+    /// Define uma nova senha com um token de redefinição, retornando o dono do token
+    pub fn redeem_reset_token(&self, token: &str, new_password: &str) -> AuthResult<String> {
+        let username = redeem_reset_token(self.store(), self.clock(), token, new_password)?;
+        log::info!("senha de '{}' redefinida com um token", username);
+        Ok(username)
+    }
+
+    /// Envia um token de redefinição de senha para o dono de um email verificado.
+    ///
+    /// Não informa se o email pertence a alguma conta.
+    pub fn request_password_reset(&self, email: &str) -> AuthResult<()> {
+        request_password_reset(self.store(), self.clock(), self.mailer.as_ref(), email)
+    }
+
+    /// Envia um token para confirmar o email pendente do usuário, retornando o endereço
+    pub fn send_verification_email(&self, username: &str) -> AuthResult<String> {
+        send_verification_email(self.store(), self.clock(), self.mailer.as_ref(), username)
+    }
+
+    /// Confirma um email com o token de verificação, retornando o dono da conta
+    pub fn verify_email(&self, token: &str) -> AuthResult<String> {
+        verify_email(self.store(), self.clock(), token)
+    }
+
+    /// Grupos de um usuário, em ordem alfabética
+    pub fn user_groups(&self, username: &str) -> AuthResult<Vec<String>> {
+        user_groups(self.store(), username)
+    }
+
+    /// Verifica se um usuário pertence a um grupo, para autorização por grupo
+    pub fn is_group_member(&self, username: &str, group: &str) -> AuthResult<bool> {
+        is_member(self.store(), username, group)
+    }
+
+    /// Troca a senha de um usuário, exigindo a senha atual
+    pub fn change_password(&self, username: &str, old_password: &str, new_password: &str) -> AuthResult<()> {
+        change_password(self.store(), self.clock(), username, old_password, new_password)?;
+        log::info!("senha de '{}' trocada", username);
+        Ok(())
+    }
+
+    /// Exclui definitivamente a própria conta, exigindo a senha atual
+    pub fn delete_own_account(&self, username: &str, password: &str) -> AuthResult<()> {
+        delete_own_account(self.store(), self.clock(), username, password)?;
+        log::info!("conta '{}' excluída pelo próprio usuário", username);
+        Ok(())
+    }
+
+    /// Substitui os dados de perfil de um usuário
+    pub fn update_profile(&self, username: &str, profile: &Profile) -> AuthResult<()> {
+        update_profile(self.store(), username, profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::generate_recovery_codes;
+    use crate::testing::{database, register, BCRYPT_HASH, PASSWORD};
+    use crate::totp::generate_secret;
+
+    /// Serviço com a conta `alice` usando dois fatores; retorna também os códigos de recuperação
+    fn two_factor_service() -> (AuthService, Vec<String>) {
+        let service = AuthService::new(database());
+        register(service.store(), "alice");
+        service.store().set_totp_secret("alice", Some(&generate_secret())).unwrap();
+        let codes = generate_recovery_codes(service.store(), "alice").unwrap();
+        (service, codes)
+    }
+
+    #[test]
+    fn second_factor_needs_the_password_in_the_same_call() {
+        let (service, codes) = two_factor_service();
+
+        let outcome = service.login_with_second_factor("alice", "Senha-Errada-99", Some(&codes[0])).unwrap();
+        assert_eq!(outcome, LoginOutcome::InvalidCredentials);
+        // O código não foi gasto por uma tentativa com a senha errada
+        assert_eq!(service.store().unused_recovery_codes("alice").unwrap().len(), codes.len());
+
+        let outcome = service.login_with_second_factor("alice", PASSWORD, None).unwrap();
+        assert_eq!(outcome, LoginOutcome::RequiresSecondFactor);
+
+        let outcome = service.login_with_second_factor("alice", PASSWORD, Some(&codes[0])).unwrap();
+        assert_eq!(outcome, LoginOutcome::Success);
+    }
+
+    #[test]
+    fn verifying_a_password_is_not_a_login() {
+        let service = AuthService::new(database());
+        service.store().insert_user("alice", BCRYPT_HASH, None).unwrap();
+        let stored_hash = || service.store().get_credentials("alice").unwrap().unwrap().password_hash;
+
+        assert!(service.verify_password("alice", PASSWORD).unwrap());
+        // Mais falhas do que o bloqueio admite, sem bloquear a conta
+        for _ in 0..=config::current().lockout.max_failed_attempts {
+            assert!(!service.verify_password("alice", "Senha-Errada-99").unwrap());
+        }
+        assert!(!service.verify_password("ninguem", PASSWORD).unwrap());
+
+        assert!(service.login_history("alice", 10).unwrap().is_empty());
+        assert_eq!(stored_hash(), BCRYPT_HASH);
+        let user = service.store().find_user("alice").unwrap().unwrap();
+        assert_eq!((user.last_login_at, user.login_count), (None, 0));
+
+        // O login de verdade continua livre, registra a tentativa e refaz o hash
+        assert_eq!(service.login("alice", PASSWORD).unwrap(), LoginOutcome::Success);
+        assert_eq!(service.login_history("alice", 10).unwrap().len(), 1);
+        assert_ne!(stored_hash(), BCRYPT_HASH);
+    }
+
+    #[test]
+    fn verifying_a_password_respects_locks() {
+        let service = AuthService::new(database());
+        register(service.store(), "alice");
+        let until = service.clock().now() + chrono::Duration::minutes(5);
+        service.store().lock_user("alice", until).unwrap();
+
+        assert!(!service.verify_password("alice", PASSWORD).unwrap());
+    }
+}
@@ -0,0 +1,214 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use crate::config::SessionPolicy;
+use crate::error::AuthResult;
+use crate::store::UserStore;
+
+/// Bytes aleatórios de cada token de sessão
+const TOKEN_BYTES: usize = 32;
+
+/// Gera um token aleatório (de sessão ou de redefinição de senha), em hexadecimal
+pub(crate) fn generate_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash do token guardado no banco. O token já tem entropia suficiente,
+/// então um SHA-256 basta: quem lê o banco não consegue reutilizar a sessão
+pub(crate) fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Sessão de um usuário autenticado
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Token que identifica a sessão; no banco fica apenas o seu hash
+    pub token: String,
+    pub username: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    policy: SessionPolicy,
+}
+
+impl Session {
+    /// Cria uma nova sessão a partir do instante do login
+    pub fn new(username: &str, now: DateTime<Utc>, policy: SessionPolicy) -> Self {
+        let mut session = Session {
+            token: generate_token(),
+            username: username.to_string(),
+            issued_at: now,
+            expires_at: now,
+            policy,
+        };
+        session.touch(now);
+        session
+    }
+
+    /// Verifica se a sessão expirou no instante informado, por inatividade ou
+    /// por ter atingido a duração máxima
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at || now - self.issued_at >= self.policy.max_lifetime
+    }
+
+    /// Renova a expiração da sessão após uma atividade do usuário, sem passar
+    /// da duração máxima contada a partir do login
+    pub fn touch(&mut self, now: DateTime<Utc>) {
+        self.expires_at = (now + self.policy.idle_timeout).min(self.issued_at + self.policy.max_lifetime);
+    }
+}
+
+/// Cria e grava uma sessão para um usuário que acabou de se autenticar,
+/// aproveitando para descartar as sessões já expiradas
+pub fn start_session(
+    store: &dyn UserStore,
+    username: &str,
+    now: DateTime<Utc>,
+    policy: SessionPolicy,
+) -> AuthResult<Session> {
+    store.purge_expired_sessions(now)?;
+    let session = Session::new(username, now, policy);
+    store.insert_session(&token_hash(&session.token), username, session.issued_at, session.expires_at)?;
+    Ok(session)
+}
+
+/// Retoma uma sessão gravada a partir do token (None se não existe ou expirou).
+///
+/// A duração máxima é conferida com a política informada, então reduzi-la vale
+/// também para sessões já abertas. Sessões expiradas são removidas do banco.
+pub fn resume_session(
+    store: &dyn UserStore,
+    token: &str,
+    now: DateTime<Utc>,
+    policy: SessionPolicy,
+) -> AuthResult<Option<Session>> {
+    let hash = token_hash(token);
+    let Some(stored) = store.find_session(&hash)? else {
+        return Ok(None);
+    };
+
+    let session = Session {
+        token: token.to_string(),
+        username: stored.username,
+        issued_at: stored.created_at,
+        expires_at: stored.expires_at,
+        policy,
+    };
+    if session.is_expired(now) {
+        store.delete_session(&hash)?;
+        return Ok(None);
+    }
+    Ok(Some(session))
+}
+
+/// Verifica se a sessão continua valendo: não expirou, não foi encerrada e o dono
+/// continua ativo. O nome é atualizado caso o usuário tenha sido renomeado
+pub fn is_session_active(store: &dyn UserStore, session: &mut Session, now: DateTime<Utc>) -> AuthResult<bool> {
+    if session.is_expired(now) {
+        return Ok(false);
+    }
+    match store.find_session(&token_hash(&session.token))? {
+        Some(stored) if now < stored.expires_at => {
+            session.username = stored.username;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Renova a expiração da sessão, também no banco
+pub fn touch_session(store: &dyn UserStore, session: &mut Session, now: DateTime<Utc>) -> AuthResult<()> {
+    session.touch(now);
+    store.extend_session(&token_hash(&session.token), session.expires_at)?;
+    Ok(())
+}
+
+/// Encerra a sessão (logout); o token deixa de valer imediatamente
+pub fn end_session(store: &dyn UserStore, session: &Session) -> AuthResult<()> {
+    store.delete_session(&token_hash(&session.token))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use chrono::{Duration, TimeZone};
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::db::Database;
+    use crate::service::AuthService;
+
+    fn policy() -> SessionPolicy {
+        SessionPolicy { idle_timeout: Duration::minutes(15), max_lifetime: Duration::hours(1) }
+    }
+
+    fn clock() -> Arc<MockClock> {
+        Arc::new(MockClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()))
+    }
+
+    #[test]
+    fn expires_after_idle_timeout() {
+        let clock = clock();
+        let session = Session::new("alice", clock.now(), policy());
+
+        clock.advance(Duration::minutes(14));
+        assert!(!session.is_expired(clock.now()));
+        clock.advance(Duration::minutes(1));
+        assert!(session.is_expired(clock.now()));
+    }
+
+    #[test]
+    fn activity_postpones_idle_expiry() {
+        let clock = clock();
+        let mut session = Session::new("alice", clock.now(), policy());
+
+        clock.advance(Duration::minutes(10));
+        session.touch(clock.now());
+        clock.advance(Duration::minutes(10));
+        assert!(!session.is_expired(clock.now()));
+    }
+
+    #[test]
+    fn activity_never_passes_max_lifetime() {
+        let clock = clock();
+        let start = clock.now();
+        let mut session = Session::new("alice", start, policy());
+
+        while clock.now() - start < Duration::minutes(55) {
+            clock.advance(Duration::minutes(5));
+            session.touch(clock.now());
+            assert!(!session.is_expired(clock.now()));
+        }
+        assert_eq!(session.expires_at, start + Duration::hours(1));
+        clock.set(start + Duration::hours(1));
+        assert!(session.is_expired(clock.now()));
+    }
+
+    #[test]
+    fn service_drops_sessions_expired_by_its_clock() {
+        let clock = clock();
+        let service =
+            AuthService::with_clock(Database::in_memory().unwrap(), Box::new(Arc::clone(&clock))).with_session_policy(policy());
+        service.store().insert_user("alice", "hash", None).unwrap();
+
+        let idle = service.open_session("alice").unwrap();
+        clock.advance(Duration::minutes(5));
+        let mut active = service.validate_session(&idle.token).unwrap().expect("sessão ainda válida");
+        service.touch_session(&mut active).unwrap();
+
+        clock.advance(Duration::minutes(16));
+        assert!(service.validate_session(&idle.token).unwrap().is_none());
+        // A sessão expirada é removida do banco
+        assert!(service.store().find_session(&token_hash(&idle.token)).unwrap().is_none());
+
+        let long = service.open_session("alice").unwrap();
+        for _ in 0..11 {
+            clock.advance(Duration::minutes(5));
+            let mut session = service.validate_session(&long.token).unwrap().expect("sessão ainda válida");
+            service.touch_session(&mut session).unwrap();
+        }
+        clock.advance(Duration::minutes(5));
+        assert!(service.validate_session(&long.token).unwrap().is_none());
+    }
+}
@@ -0,0 +1,36 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::error::AuthResult;
+
+/// Chave do limite de usuários cadastrados
+pub const MAX_USERS_KEY: &str = "max_users";
+
+/// Chave da idade máxima das senhas, em dias
+pub const PASSWORD_MAX_AGE_DAYS_KEY: &str = "password_max_age_days";
+
+/// Chaves dos parâmetros do Argon2 escolhidos pela calibração
+pub const ARGON2_M_COST_KEY: &str = "argon2_m_cost";
+pub const ARGON2_T_COST_KEY: &str = "argon2_t_cost";
+pub const ARGON2_P_COST_KEY: &str = "argon2_p_cost";
+
+/// Lê uma configuração persistida no banco
+pub fn get_setting(conn: &Connection, key: &str) -> AuthResult<Option<String>> {
+    // Lida em todo login (validade da senha), então fica no cache de instruções
+    let value = conn
+        .prepare_cached("SELECT value FROM settings WHERE key = ?1")?
+        .query_row([key], |row| row.get(0))
+        .optional()?;
+    Ok(value)
+}
+
+/// Grava (ou remove, com `None`) uma configuração persistida no banco
+pub fn set_setting(conn: &Connection, key: &str, value: Option<&str>) -> AuthResult<()> {
+    match value {
+        Some(value) => conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?,
+        None => conn.execute("DELETE FROM settings WHERE key = ?1", [key])?,
+    };
+    Ok(())
+}
@@ -0,0 +1,348 @@
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use crate::audit::{AuditEntry, AuditFilter};
+use crate::error::AuthResult;
+use crate::groups::Group;
+use crate::profile::Profile;
+use crate::rbac::{Permission, Role};
+use crate::tr;
+use crate::user::{AccountStatus, User};
+
+/// Dados de uma conta necessários para decidir o resultado do login
+#[derive(Debug, Clone)]
+pub struct StoredCredentials {
+    pub password_hash: String,
+    /// Última troca de senha (ou a criação da conta, se nunca trocou)
+    pub changed_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub status: AccountStatus,
+    pub must_change_password: bool,
+}
+
+/// Sessão persistida, localizada pelo hash do token
+#[derive(Debug, Clone)]
+pub struct StoredSession {
+    /// Nome atual do dono da sessão
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Uma tentativa de login registrada no histórico
+#[derive(Debug, Clone)]
+pub struct LoginAttempt {
+    /// Nome informado na tentativa
+    pub username: String,
+    /// Se as credenciais foram aceitas por completo
+    pub success: bool,
+    /// Nome estável do resultado (ver `LoginOutcome::name`)
+    pub outcome: String,
+    /// De onde veio a tentativa (ex.: "terminal", "comando")
+    pub source: String,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// Campo pelo qual a listagem de usuários é ordenada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum UserSort {
+    Id,
+    /// Nome de usuário (padrão)
+    #[default]
+    Username,
+    /// Data de cadastro
+    Created,
+    /// Último login; contas que nunca entraram ficam por último
+    LastLogin,
+}
+
+impl UserSort {
+    /// Todos os campos, na ordem em que o menu alterna entre eles
+    pub const ALL: [UserSort; 4] = [UserSort::Username, UserSort::Id, UserSort::Created, UserSort::LastLogin];
+
+    /// Nome para exibir ao usuário
+    pub fn label(&self) -> &'static str {
+        match self {
+            UserSort::Id => tr!("id"),
+            UserSort::Username => tr!("nome"),
+            UserSort::Created => tr!("data de cadastro"),
+            UserSort::LastLogin => tr!("último login"),
+        }
+    }
+
+    /// O campo seguinte de `ALL`, voltando ao primeiro depois do último
+    pub fn next(&self) -> UserSort {
+        let position = UserSort::ALL.iter().position(|sort| sort == self).unwrap_or_default();
+        UserSort::ALL[(position + 1) % UserSort::ALL.len()]
+    }
+}
+
+/// Ordem da listagem de usuários
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UserOrder {
+    pub sort: UserSort,
+    pub descending: bool,
+}
+
+impl UserOrder {
+    /// Cláusula `ORDER BY`, igual no SQLite e no PostgreSQL; o nome desempata
+    pub(crate) fn sql(&self) -> String {
+        let direction = if self.descending { "DESC" } else { "ASC" };
+        match self.sort {
+            UserSort::Id => format!("ORDER BY id {}", direction),
+            UserSort::Username => format!("ORDER BY username {}", direction),
+            UserSort::Created => format!("ORDER BY created_at {}, username", direction),
+            UserSort::LastLogin => format!("ORDER BY last_login_at IS NULL, last_login_at {}, username", direction),
+        }
+    }
+}
+
+/// Converte o padrão de busca de `search_users` em um padrão de `LIKE` com escape `\`.
+///
+/// `*` e `?` valem por qualquer sequência e por um caractere; sem eles, o padrão é
+/// procurado em qualquer parte do texto.
+pub(crate) fn like_pattern(pattern: &str) -> String {
+    let wildcard = pattern.contains(['*', '?']);
+    let mut like = String::with_capacity(pattern.len() + 2);
+    if !wildcard {
+        like.push('%');
+    }
+    for c in pattern.chars() {
+        match c {
+            '*' => like.push('%'),
+            '?' => like.push('_'),
+            '%' | '_' | '\\' => {
+                like.push('\\');
+                like.push(c);
+            }
+            _ => like.push(c),
+        }
+    }
+    if !wildcard {
+        like.push('%');
+    }
+    like
+}
+
+/// Armazenamento de usuários usado pelo `auth.rs` e pela CLI.
+///
+/// Contas excluídas de forma reversível (`delete_user`) ficam invisíveis para
+/// todos os métodos, exceto `restore_user`, `list_deleted_users`, `purge_deleted`
+/// e `release_username`. A implementação padrão usa SQLite (`Database`).
+pub trait UserStore {
+    /// Verifica se um usuário existe
+    fn user_exists(&self, username: &str) -> AuthResult<bool>;
+
+    /// Conta os usuários não excluídos
+    fn count_users(&self) -> AuthResult<u32>;
+
+    /// Insere um novo usuário; nomes e emails duplicados resultam em erro de validação.
+    ///
+    /// O primeiro usuário cadastrado se torna administrador.
+    fn insert_user(&self, username: &str, password_hash: &str, email: Option<&str>) -> AuthResult<()>;
+
+    /// Insere o usuário se o nome estiver livre, retornando se a inserção aconteceu
+    fn insert_user_if_absent(&self, username: &str, password_hash: &str) -> AuthResult<bool>;
+
+    /// Obtém o hash da senha de um usuário
+    fn get_password_hash(&self, username: &str) -> AuthResult<Option<String>>;
+
+    /// Obtém o hash e o estado da conta usados no login
+    fn get_credentials(&self, username: &str) -> AuthResult<Option<StoredCredentials>>;
+
+    /// Substitui o hash da senha. Com `rotated`, conta como uma troca de senha:
+    /// atualiza a data da troca e desfaz a troca obrigatória
+    fn set_password_hash(&self, username: &str, password_hash: &str, rotated: bool) -> AuthResult<bool>;
+
+    /// Soma uma falha de login, retornando o total de falhas seguidas
+    fn record_failed_attempt(&self, username: &str) -> AuthResult<u32>;
+
+    /// Bloqueia a conta até o instante informado, zerando as falhas
+    fn lock_user(&self, username: &str, until: DateTime<Utc>) -> AuthResult<()>;
+
+    /// Zera as falhas e remove o bloqueio após um login correto
+    fn clear_failed_attempts(&self, username: &str) -> AuthResult<()>;
+
+    /// Grava a data de um login bem-sucedido e soma um ao total de logins
+    fn record_successful_login(&self, username: &str, at: DateTime<Utc>) -> AuthResult<()>;
+
+    /// Busca o nome do usuário associado a um email (sem diferenciar maiúsculas/minúsculas)
+    fn find_by_email(&self, email: &str) -> AuthResult<Option<String>>;
+
+    /// Lista todos os usuários com informações de criação
+    fn list_users(&self) -> AuthResult<Vec<User>>;
+
+    /// Lista até `limit` usuários a partir da posição `offset`, na ordem informada
+    fn list_users_paged(&self, offset: usize, limit: usize, order: UserOrder) -> AuthResult<Vec<User>>;
+
+    /// Usuários cujo nome ou email casa com o padrão (ver `like_pattern`), sem
+    /// diferenciar maiúsculas/minúsculas, na ordem informada
+    fn search_users(&self, pattern: &str, order: UserOrder) -> AuthResult<Vec<User>>;
+
+    /// Busca um usuário ativo pelo nome
+    fn find_user(&self, username: &str) -> AuthResult<Option<User>>;
+
+    /// Renomeia um usuário (false se não existe; erro de validação se o nome novo está em uso)
+    fn rename_user(&self, current: &str, new_username: &str) -> AuthResult<bool>;
+
+    /// Substitui os campos do perfil de um usuário ativo. Um email diferente do atual
+    /// volta a ficar pendente de verificação; email em uso resulta em erro de validação
+    fn update_profile(&self, username: &str, profile: &Profile) -> AuthResult<bool>;
+
+    /// Exclui um usuário de forma reversível, encerrando suas sessões e descartando
+    /// os tokens pendentes na mesma transação; o histórico de logins fica para uma
+    /// eventual restauração
+    fn delete_user(&self, username: &str) -> AuthResult<bool>;
+
+    /// Apaga definitivamente uma conta ativa e tudo o que depende dela (sessões,
+    /// dois fatores, histórico de logins), registrando `action` por `actor` no log
+    /// de auditoria na mesma transação. Retorna false se o usuário não existe
+    fn erase_user(&self, username: &str, actor: &str, action: &str) -> AuthResult<bool>;
+
+    /// Restaura um usuário excluído que ainda não foi purgado
+    fn restore_user(&self, username: &str) -> AuthResult<bool>;
+
+    /// Lista os usuários excluídos e ainda não purgados, com a data da exclusão
+    fn list_deleted_users(&self) -> AuthResult<Vec<(String, DateTime<Utc>)>>;
+
+    /// Remove definitivamente os usuários excluídos antes do instante informado
+    fn purge_deleted(&self, older_than: DateTime<Utc>) -> AuthResult<usize>;
+
+    /// Remove definitivamente uma conta excluída antes do instante informado que ainda ocupa o nome
+    fn release_username(&self, username: &str, deleted_before: DateTime<Utc>) -> AuthResult<()>;
+
+    /// Ativa ou desativa uma conta sem apagar seus dados
+    fn set_status(&self, username: &str, status: AccountStatus) -> AuthResult<bool>;
+
+    /// Situação de uma conta (None se o usuário não existe)
+    fn get_status(&self, username: &str) -> AuthResult<Option<AccountStatus>>;
+
+    /// Papel de um usuário (None se o usuário não existe)
+    fn get_role(&self, username: &str) -> AuthResult<Option<Role>>;
+
+    /// Troca o papel de um usuário
+    fn set_role(&self, username: &str, role: Role) -> AuthResult<bool>;
+
+    /// Conta os administradores ativos
+    fn count_admins(&self) -> AuthResult<u32>;
+
+    /// Permissões concedidas a um papel
+    fn role_permissions(&self, role: Role) -> AuthResult<Vec<Permission>>;
+
+    /// Concede (`granted = true`) ou revoga uma permissão de um papel
+    fn set_role_permission(&self, role: Role, permission: Permission, granted: bool) -> AuthResult<()>;
+
+    /// Cria um grupo, rejeitando nomes já usados
+    fn insert_group(&self, name: &str) -> AuthResult<()>;
+
+    /// Apaga um grupo e suas associações
+    fn delete_group(&self, name: &str) -> AuthResult<bool>;
+
+    /// Verifica se um grupo existe
+    fn group_exists(&self, name: &str) -> AuthResult<bool>;
+
+    /// Lista os grupos em ordem alfabética
+    fn list_groups(&self) -> AuthResult<Vec<Group>>;
+
+    /// Adiciona um usuário ativo a um grupo (false se já era membro)
+    fn add_group_member(&self, group: &str, username: &str) -> AuthResult<bool>;
+
+    /// Remove um usuário de um grupo (false se não era membro)
+    fn remove_group_member(&self, group: &str, username: &str) -> AuthResult<bool>;
+
+    /// Membros ativos de um grupo, em ordem alfabética
+    fn group_members(&self, group: &str) -> AuthResult<Vec<String>>;
+
+    /// Grupos de um usuário ativo, em ordem alfabética
+    fn user_groups(&self, username: &str) -> AuthResult<Vec<String>>;
+
+    /// Grava uma sessão nova para um usuário ativo
+    fn insert_session(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()>;
+
+    /// Busca uma sessão pelo hash do token, desde que o dono continue ativo
+    fn find_session(&self, token_hash: &str) -> AuthResult<Option<StoredSession>>;
+
+    /// Sessões de um usuário, das mais recentes para as mais antigas
+    fn user_sessions(&self, username: &str) -> AuthResult<Vec<StoredSession>>;
+
+    /// Adia a expiração de uma sessão (false se ela não existe mais)
+    fn extend_session(&self, token_hash: &str, expires_at: DateTime<Utc>) -> AuthResult<bool>;
+
+    /// Invalida uma sessão
+    fn delete_session(&self, token_hash: &str) -> AuthResult<bool>;
+
+    /// Remove as sessões expiradas até o instante informado
+    fn purge_expired_sessions(&self, now: DateTime<Utc>) -> AuthResult<usize>;
+
+    /// Grava um token de redefinição de senha para um usuário ativo, invalidando
+    /// os tokens ainda não usados que ele já tinha
+    fn insert_password_reset(
+        &self,
+        token_hash: &str,
+        username: &str,
+        created_by: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()>;
+
+    /// Marca como usado um token de redefinição válido (não usado nem expirado),
+    /// retornando o nome do dono
+    fn consume_password_reset(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>>;
+
+    /// Grava um token de verificação para o email atual de um usuário ativo,
+    /// invalidando os tokens anteriores dele
+    fn insert_email_verification(&self, token_hash: &str, username: &str, expires_at: DateTime<Utc>) -> AuthResult<()>;
+
+    /// Consome um token de verificação válido e marca o email como verificado,
+    /// desde que a conta ainda tenha o endereço em que o token foi emitido.
+    /// Retorna o nome do dono
+    fn consume_email_verification(&self, token_hash: &str, now: DateTime<Utc>) -> AuthResult<Option<String>>;
+
+    /// Obtém o segredo TOTP do usuário (None se a autenticação em dois fatores está desligada)
+    fn get_totp_secret(&self, username: &str) -> AuthResult<Option<String>>;
+
+    /// Grava o segredo TOTP do usuário, ou o remove com `None`
+    fn set_totp_secret(&self, username: &str, secret: Option<&str>) -> AuthResult<()>;
+
+    /// Registra o passo de tempo de um código TOTP aceito. Retorna false se um código
+    /// desse passo (ou de um posterior) já foi usado, impedindo a reutilização
+    fn claim_totp_step(&self, username: &str, step: i64) -> AuthResult<bool>;
+
+    /// Substitui os códigos de recuperação do usuário pelos hashes informados
+    /// (uma lista vazia apenas remove os atuais)
+    fn replace_recovery_codes(&self, username: &str, code_hashes: &[String]) -> AuthResult<()>;
+
+    /// Lista o id e o hash dos códigos de recuperação ainda não usados
+    fn unused_recovery_codes(&self, username: &str) -> AuthResult<Vec<(i64, String)>>;
+
+    /// Marca um código de recuperação como usado (false se já tinha sido usado)
+    fn consume_recovery_code(&self, code_id: i64) -> AuthResult<bool>;
+
+    /// Registra uma tentativa de login, associando-a à conta se o nome existir
+    fn record_login_attempt(&self, attempt: &LoginAttempt) -> AuthResult<()>;
+
+    /// Últimas tentativas de login da conta, da mais recente para a mais antiga
+    fn login_history(&self, username: &str, limit: usize) -> AuthResult<Vec<LoginAttempt>>;
+
+    /// Registra uma ação no log de auditoria
+    fn record_audit(&self, actor: &str, action: &str, target: Option<&str>) -> AuthResult<()>;
+
+    /// Consulta o log de auditoria, das entradas mais recentes para as mais antigas
+    fn audit_log(&self, filter: &AuditFilter) -> AuthResult<Vec<AuditEntry>>;
+
+    /// Lê uma configuração persistida
+    fn get_setting(&self, key: &str) -> AuthResult<Option<String>>;
+
+    /// Grava (ou remove, com `None`) uma configuração persistida
+    fn set_setting(&self, key: &str, value: Option<&str>) -> AuthResult<()>;
+
+    /// Executa `operation` em uma transação: se ela retornar erro, nada do que fez é gravado.
+    ///
+    /// A operação deve usar só o armazenamento que recebe, preso à transação; dentro
+    /// de outra transação, ela vira um savepoint.
+    fn transaction(&self, operation: &mut dyn FnMut(&dyn UserStore) -> AuthResult<()>) -> AuthResult<()>;
+}
@@ -0,0 +1,363 @@
+use std::fmt;
+use serde::Deserialize;
+use crate::tr;
+
+/// Classificação da força de uma senha, pela estimativa de tentativas para adivinhá-la
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrengthScore {
+    Weak,
+    Fair,
+    Strong,
+    VeryStrong,
+}
+
+impl fmt::Display for StrengthScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            StrengthScore::Weak => tr!("Fraca"),
+            StrengthScore::Fair => tr!("Razoável"),
+            StrengthScore::Strong => tr!("Forte"),
+            StrengthScore::VeryStrong => tr!("Muito forte"),
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Resultado da estimativa de força de uma senha
+#[derive(Debug, Clone)]
+pub struct StrengthReport {
+    pub score: StrengthScore,
+    /// Estimativa, em bits, do número de tentativas para adivinhar a senha
+    pub guess_bits: f64,
+    /// Sugestões para fortalecer a senha, uma por padrão fraco encontrado
+    pub hints: Vec<&'static str>,
+}
+
+/// Senhas e palavras mais comuns em vazamentos, da mais para a menos frequente.
+///
+/// Quanto mais cedo na lista, menos tentativas um atacante gasta até chegar nela.
+const COMMON_WORDS: &[&str] = &[
+    "password", "senha", "123456", "qwerty", "admin", "abc123", "letmein", "welcome", "monkey", "dragon",
+    "football", "iloveyou", "master", "sunshine", "princess", "login", "starwars", "passw0rd", "shadow",
+    "superman", "michael", "batman", "trustno1", "baseball", "whatever", "freedom", "hello", "charlie",
+    "jesus", "ninja", "mustang", "access", "flower", "secret", "root", "test", "teste", "usuario", "user",
+    "brasil", "brazil", "amor", "teamo", "flamengo", "corinthians", "palmeiras", "santos", "gremio",
+    "vasco", "saopaulo", "cruzeiro", "familia", "deus", "jesuscristo", "mudar", "mudar123", "trocar",
+    "acesso", "entrar", "sistema", "bemvindo", "segredo", "minhasenha", "mariana", "gabriel", "lucas",
+    "pedro", "maria", "joao", "jose", "ana", "carlos", "paulo", "rafael", "daniel", "bruno", "felipe",
+    "amanda", "juliana", "fernanda", "beatriz", "camila", "larissa", "leticia", "vitoria", "futebol",
+    "computador", "internet", "casa", "gato", "cachorro", "love", "money", "summer", "winter", "spring",
+    "autumn", "google", "apple", "samsung", "facebook", "instagram", "linux", "windows", "computer",
+    "killer", "pokemon", "naruto", "soccer", "hockey", "hunter", "ranger", "buster", "thomas", "robert",
+    "jordan", "andrew", "joshua", "matthew", "jennifer", "jessica", "ashley", "nicole", "pepper",
+    "cheese", "coffee", "cookie", "banana", "orange", "purple", "silver", "golden", "diamond", "forever",
+    "friend", "family", "mother", "father", "angel", "happy", "lucky", "magic", "music", "rock", "star",
+    "siri", "ferrugem", "rust", "forte", "fraca", "nova", "novasenha", "temp", "temporaria", "default",
+];
+
+/// Fileiras do teclado, para detectar sequências como "qwerty" ou "asdf"
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Substituições "l33t" comuns, desfeitas antes de procurar palavras conhecidas
+const LEET: &[(char, char)] = &[
+    ('@', 'a'), ('4', 'a'), ('3', 'e'), ('1', 'i'), ('!', 'i'), ('0', 'o'), ('$', 's'), ('5', 's'), ('7', 't'), ('+', 't'),
+];
+
+/// Anos plausíveis em senhas (nascimento, formatura...)
+const YEAR_RANGE: std::ops::RangeInclusive<u32> = 1900..=2039;
+
+/// Limites de bits de cada classificação (~10⁶, 10⁸ e 10¹⁰ tentativas)
+const FAIR_BITS: f64 = 20.0;
+const STRONG_BITS: f64 = 27.0;
+const VERY_STRONG_BITS: f64 = 34.0;
+
+/// Tipo de padrão reconhecido em um trecho da senha
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pattern {
+    Common { leet: bool },
+    UserInput,
+    Sequence,
+    Repeat,
+    Year,
+}
+
+impl Pattern {
+    /// Sugestão exibida quando o padrão aparece na senha
+    fn hint(&self) -> &'static str {
+        match self {
+            Pattern::Common { leet: false } => tr!("Evite senhas e palavras comuns, como 'senha' ou 'admin'."),
+            Pattern::Common { leet: true } => {
+                tr!("Trocar letras por símbolos parecidos ('@' por 'a', '0' por 'o') não engana um atacante.")
+            }
+            Pattern::UserInput => tr!("Não use o nome de usuário ou o email na senha."),
+            Pattern::Sequence => tr!("Evite sequências como 'abc', '123' ou 'qwerty'."),
+            Pattern::Repeat => tr!("Evite repetições como 'aaa' ou 'abcabc'."),
+            Pattern::Year => tr!("Evite anos e datas, que são fáceis de adivinhar."),
+        }
+    }
+}
+
+/// Um trecho `[start, end)` da senha reconhecido como padrão, com seu custo em bits
+struct Match {
+    start: usize,
+    end: usize,
+    bits: f64,
+    pattern: Pattern,
+}
+
+/// Estima quantas tentativas um atacante precisaria para adivinhar a senha.
+///
+/// A senha é decomposta na combinação mais barata de padrões conhecidos (palavras
+/// comuns, dados do próprio usuário, sequências, repetições e anos) e trechos
+/// aleatórios, no estilo do zxcvbn. `user_inputs` são dados da conta, como o nome
+/// de usuário e o email, que não devem aparecer na senha.
+pub fn estimate_strength(password: &str, user_inputs: &[&str]) -> StrengthReport {
+    let chars: Vec<char> = password.chars().collect();
+    let n = chars.len();
+    let char_bits = f64::from(charset_size(password).max(1)).log2();
+
+    let mut matches = Vec::new();
+    dictionary_matches(&chars, user_inputs, &mut matches);
+    sequence_matches(&chars, &mut matches);
+    repeat_matches(&chars, char_bits, &mut matches);
+    year_matches(&chars, &mut matches);
+
+    // best[j]: menor custo para cobrir os j primeiros caracteres, e como se chegou lá
+    let mut best: Vec<(f64, Option<usize>)> = vec![(f64::INFINITY, None); n + 1];
+    best[0] = (0.0, None);
+    for j in 1..=n {
+        best[j] = (best[j - 1].0 + char_bits, None);
+        for (index, m) in matches.iter().enumerate().filter(|(_, m)| m.end == j) {
+            let cost = best[m.start].0 + m.bits;
+            if cost < best[j].0 {
+                best[j] = (cost, Some(index));
+            }
+        }
+    }
+
+    let mut hints = Vec::new();
+    let mut j = n;
+    while j > 0 {
+        match best[j].1 {
+            Some(index) => {
+                let m = &matches[index];
+                if !hints.contains(&m.pattern.hint()) {
+                    hints.push(m.pattern.hint());
+                }
+                j = m.start;
+            }
+            None => j -= 1,
+        }
+    }
+    hints.reverse();
+
+    let guess_bits = best[n].0;
+    let score = if guess_bits < FAIR_BITS {
+        StrengthScore::Weak
+    } else if guess_bits < STRONG_BITS {
+        StrengthScore::Fair
+    } else if guess_bits < VERY_STRONG_BITS {
+        StrengthScore::Strong
+    } else {
+        StrengthScore::VeryStrong
+    };
+    // Uma senha forte não precisa de conselhos, mesmo que contenha algum padrão
+    if score >= StrengthScore::Strong {
+        hints.clear();
+    } else {
+        hints.push(tr!("Use uma senha mais longa: algumas palavras incomuns juntas são fortes e fáceis de lembrar."));
+    }
+
+    StrengthReport { score, guess_bits, hints }
+}
+
+/// Calcula uma pontuação de força da senha
+pub fn password_strength_score(password: &str) -> StrengthScore {
+    estimate_strength(password, &[]).score
+}
+
+/// Tamanho do alfabeto que um ataque de força bruta precisaria cobrir
+fn charset_size(password: &str) -> u32 {
+    let mut pool = 0u32;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if password.chars().any(|c| c.is_ascii_punctuation() || c == ' ') {
+        pool += 33;
+    }
+    if !password.is_ascii() {
+        pool += 100;
+    }
+    pool
+}
+
+/// Bits extras pelas maiúsculas de uma palavra: capitalizar só a primeira letra
+/// (ou todas) é o que todo mundo faz
+fn uppercase_bits(word: &[char]) -> f64 {
+    let upper = word.iter().filter(|c| c.is_uppercase()).count();
+    if upper == 0 {
+        0.0
+    } else if upper == word.len() || (upper == 1 && word[0].is_uppercase()) {
+        1.0
+    } else {
+        upper.min(word.len() - upper) as f64
+    }
+}
+
+/// Palavras comuns e dados do usuário, com e sem as substituições "l33t"
+fn dictionary_matches(chars: &[char], user_inputs: &[&str], matches: &mut Vec<Match>) {
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    if lower.len() != chars.len() {
+        return;
+    }
+    let unleeted: Vec<char> = lower
+        .iter()
+        .map(|c| LEET.iter().find(|(from, _)| from == c).map_or(*c, |(_, to)| *to))
+        .collect();
+
+    let user_words: Vec<String> = user_inputs
+        .iter()
+        .flat_map(|input| input.to_lowercase().split(['@', '.', '_', '-', ' ']).map(str::to_string).collect::<Vec<_>>())
+        .filter(|word| word.chars().count() >= 3)
+        .collect();
+
+    for start in 0..chars.len() {
+        for end in (start + 3)..=chars.len() {
+            let plain: String = lower[start..end].iter().collect();
+            let unleet: String = unleeted[start..end].iter().collect();
+            let leet = plain != unleet;
+            let extra = uppercase_bits(&chars[start..end]) + if leet { 1.0 } else { 0.0 };
+
+            if user_words.iter().any(|word| *word == plain || *word == unleet) {
+                matches.push(Match { start, end, bits: 1.0 + extra, pattern: Pattern::UserInput });
+            }
+            let rank = COMMON_WORDS.iter().position(|word| *word == plain || *word == unleet);
+            if let Some(rank) = rank {
+                let bits = ((rank + 2) as f64).log2() + extra;
+                matches.push(Match { start, end, bits, pattern: Pattern::Common { leet } });
+            }
+        }
+    }
+}
+
+/// Sequências alfabéticas, numéricas ou do teclado, em qualquer sentido
+fn sequence_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let is_step = |a: char, b: char, step: i32| {
+        a.is_ascii_alphanumeric() && b.is_ascii_alphanumeric() && (b as i32 - a as i32) == step
+    };
+    let in_keyboard_row = |text: &str| {
+        let reversed: String = text.chars().rev().collect();
+        KEYBOARD_ROWS.iter().any(|row| row.contains(text) || row.contains(&reversed))
+    };
+
+    for start in 0..lower.len() {
+        for end in (start + 3)..=lower.len() {
+            let run = &lower[start..end];
+            let ascending = run.windows(2).all(|pair| is_step(pair[0], pair[1], 1));
+            let descending = run.windows(2).all(|pair| is_step(pair[0], pair[1], -1));
+            let text: String = run.iter().collect();
+            if !(ascending || descending || in_keyboard_row(&text)) {
+                continue;
+            }
+
+            let start_choices: f64 = if run[0].is_ascii_digit() { 10.0 } else { 26.0 };
+            let direction_bits = if ascending { 0.0 } else { 1.0 };
+            let bits = start_choices.log2() + (run.len() as f64).log2() + direction_bits;
+            matches.push(Match { start, end, bits, pattern: Pattern::Sequence });
+        }
+    }
+}
+
+/// Um mesmo bloco repetido ("aaa", "abcabc")
+fn repeat_matches(chars: &[char], char_bits: f64, matches: &mut Vec<Match>) {
+    for start in 0..chars.len() {
+        for block in 1..=(chars.len() - start) / 2 {
+            let mut times = 1;
+            while start + (times + 1) * block <= chars.len()
+                && chars[start..start + block] == chars[start + times * block..start + (times + 1) * block]
+            {
+                times += 1;
+                if block * times >= 3 {
+                    let bits = block as f64 * char_bits + (times as f64).log2();
+                    matches.push(Match { start, end: start + block * times, bits, pattern: Pattern::Repeat });
+                }
+            }
+        }
+    }
+}
+
+/// Anos com quatro dígitos
+fn year_matches(chars: &[char], matches: &mut Vec<Match>) {
+    for start in 0..chars.len().saturating_sub(3) {
+        let digits: String = chars[start..start + 4].iter().collect();
+        let is_year = digits.chars().all(|c| c.is_ascii_digit())
+            && digits.parse::<u32>().is_ok_and(|year| YEAR_RANGE.contains(&year));
+        if is_year {
+            let bits = f64::from(YEAR_RANGE.end() - YEAR_RANGE.start() + 1).log2();
+            matches.push(Match { start, end: start + 4, bits, pattern: Pattern::Year });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_and_patterned_passwords_are_weak() {
+        for password in ["password", "senha123", "qwerty", "aaaaaaaa", "P@ssw0rd", "abc12345"] {
+            assert_eq!(password_strength_score(password), StrengthScore::Weak, "{}", password);
+        }
+    }
+
+    #[test]
+    fn scores_follow_the_guess_bits() {
+        let cases = [
+            ("zqxv", StrengthScore::Weak),
+            ("zqxvw", StrengthScore::Fair),
+            ("zqxvwj", StrengthScore::Strong),
+            ("kx9#Tq", StrengthScore::VeryStrong),
+        ];
+        for (password, expected) in cases {
+            let report = estimate_strength(password, &[]);
+            assert_eq!(report.score, expected, "{} ({:.1} bits)", password, report.guess_bits);
+        }
+    }
+
+    #[test]
+    fn bucket_edges_match_the_thresholds() {
+        let bucket = |bits: f64| match bits {
+            b if b < FAIR_BITS => StrengthScore::Weak,
+            b if b < STRONG_BITS => StrengthScore::Fair,
+            b if b < VERY_STRONG_BITS => StrengthScore::Strong,
+            _ => StrengthScore::VeryStrong,
+        };
+        for password in ["a", "zqxv", "zqxvw", "zqxvwj", "kx9#Tq", "correct horse battery staple"] {
+            let report = estimate_strength(password, &[]);
+            assert_eq!(report.score, bucket(report.guess_bits), "{}", password);
+        }
+    }
+
+    #[test]
+    fn user_inputs_weaken_the_password() {
+        let without = estimate_strength("mariazinha2024", &[]);
+        let with = estimate_strength("mariazinha2024", &["mariazinha", "maria@example.com"]);
+        assert!(with.guess_bits < without.guess_bits);
+        assert!(with.hints.contains(&tr!("Não use o nome de usuário ou o email na senha.")));
+    }
+
+    #[test]
+    fn strong_passwords_get_no_hints() {
+        let report = estimate_strength("correct horse battery staple", &[]);
+        assert!(report.score >= StrengthScore::Strong);
+        assert!(report.hints.is_empty());
+    }
+}
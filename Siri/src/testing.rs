@@ -0,0 +1,29 @@
+//! Apoio aos testes: bancos em memória com um Argon2 barato e contas prontas.
+
+use crate::auth::{register_user, Argon2Config};
+use crate::db::Database;
+use crate::store::UserStore;
+
+/// Senha que cumpre a política padrão
+pub(crate) const PASSWORD: &str = "Cavalo-Bateria-Grampo-42";
+
+/// Outra senha válida, para trocas e redefinições
+pub(crate) const NEW_PASSWORD: &str = "Outra-Frase-Bem-Longa-77";
+
+/// Hash bcrypt (custo 4) de `PASSWORD`, como chegaria importado de outro sistema
+pub(crate) const BCRYPT_HASH: &str = "$2b$04$PQEJSNJBJjnsHKTfNH.NneCZzZnyeCpojk98QNaeTEeZi3U49GN3K";
+
+/// Parâmetros mínimos do Argon2: os testes conferem o comportamento, não o custo
+pub(crate) const CHEAP_ARGON2: Argon2Config = Argon2Config { m_cost: 8, t_cost: 1, p_cost: 1 };
+
+/// Banco em memória, já migrado, que gera hashes com `CHEAP_ARGON2`
+pub(crate) fn database() -> Database {
+    let db = Database::in_memory().expect("banco em memória");
+    CHEAP_ARGON2.save(&db).expect("parâmetros do Argon2");
+    db
+}
+
+/// Cadastra uma conta com `PASSWORD`
+pub(crate) fn register(store: &dyn UserStore, username: &str) {
+    register_user(store, username, PASSWORD).expect("cadastro");
+}
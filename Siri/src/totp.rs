@@ -0,0 +1,187 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base32::Alphabet;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use sha1::Sha1;
+use crate::audit;
+use crate::error::{AuthError, AuthResult};
+use crate::recovery::{consume_recovery_code, dummy_recovery_code_check, generate_recovery_codes};
+use crate::store::UserStore;
+use crate::{message, validation, validation_error};
+
+/// Emissor exibido no aplicativo autenticador
+pub const ISSUER: &str = "Siri Ferrugem";
+
+/// Quantidade de dígitos de cada código
+pub const DIGITS: u32 = 6;
+
+/// Duração de cada código, em segundos
+pub const STEP_SECONDS: i64 = 30;
+
+/// Passos aceitos antes e depois do atual, para tolerar relógios levemente dessincronizados
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+
+/// Tamanho do segredo (160 bits, o recomendado pela RFC 4226)
+const SECRET_BYTES: usize = 20;
+
+/// Alfabeto Base32 usado nos segredos, sem padding como esperam os autenticadores
+const SECRET_ALPHABET: Alphabet = Alphabet::Rfc4648 { padding: false };
+
+/// Segredo fixo do caminho de conta inexistente (20 bytes nulos, em Base32)
+const DUMMY_SECRET: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+/// Gera um segredo TOTP aleatório, em Base32
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    base32::encode(SECRET_ALPHABET, &bytes)
+}
+
+/// Passo de tempo TOTP que contém o instante informado
+fn time_step(now: DateTime<Utc>) -> i64 {
+    now.timestamp().div_euclid(STEP_SECONDS)
+}
+
+/// Calcula o código HOTP (RFC 4226) de um passo
+fn code_at(key: &[u8], step: i64) -> AuthResult<u32> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key)
+        .map_err(|e| AuthError::PasswordHashing(message!("segredo TOTP inválido: {}", e)))?;
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Truncamento dinâmico: 31 bits a partir do deslocamento indicado pelo último byte
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let value = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]])
+        & 0x7fff_ffff;
+    Ok(value % 10u32.pow(DIGITS))
+}
+
+/// Procura o passo, dentro da tolerância, cujo código é o informado
+fn matching_step(secret: &str, code: &str, now: DateTime<Utc>) -> AuthResult<Option<i64>> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+    let Ok(code) = code.parse::<u32>() else {
+        return Ok(None);
+    };
+    let key = base32::decode(SECRET_ALPHABET, secret)
+        .ok_or_else(|| AuthError::PasswordHashing(message!("segredo TOTP corrompido")))?;
+
+    let current = time_step(now);
+    for step in (current - ALLOWED_DRIFT_STEPS)..=(current + ALLOWED_DRIFT_STEPS) {
+        if code_at(&key, step)? == code {
+            return Ok(Some(step));
+        }
+    }
+    Ok(None)
+}
+
+/// Codifica um trecho de URI, mantendo apenas caracteres não reservados
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// URI `otpauth://` para cadastrar o segredo em um aplicativo autenticador
+pub fn provisioning_uri(username: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{user}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = percent_encode(ISSUER),
+        user = percent_encode(username),
+        secret = secret,
+        digits = DIGITS,
+        period = STEP_SECONDS,
+    )
+}
+
+/// Desenha um QR code com caracteres de bloco, para exibir no terminal
+pub fn render_qr(data: &str) -> AuthResult<String> {
+    let code = QrCode::new(data.as_bytes())
+        .map_err(|e| validation!("Não foi possível gerar o QR code: {}", e))?;
+    Ok(code.render::<Dense1x2>().quiet_zone(true).build())
+}
+
+/// Verifica se o usuário tem a autenticação em dois fatores ativada
+pub fn is_totp_enabled(store: &dyn UserStore, username: &str) -> AuthResult<bool> {
+    Ok(store.get_totp_secret(username)?.is_some())
+}
+
+/// Ativa a autenticação em dois fatores depois que o usuário comprova, com um
+/// código válido, que cadastrou o segredo no aplicativo.
+///
+/// Retorna os códigos de recuperação emitidos junto, que não serão exibidos novamente
+/// e substituem os que a conta já tinha.
+pub fn enable_totp(
+    store: &dyn UserStore,
+    username: &str,
+    secret: &str,
+    code: &str,
+    now: DateTime<Utc>,
+) -> AuthResult<Vec<String>> {
+    let Some(step) = matching_step(secret, code, now)? else {
+        return validation_error!("Código inválido. Confira o relógio do aparelho e tente novamente");
+    };
+    store.set_totp_secret(username, Some(secret))?;
+    store.claim_totp_step(username, step)?;
+    audit::record(store, username, "enable_totp", Some(username))?;
+    generate_recovery_codes(store, username)
+}
+
+/// Desativa a autenticação em dois fatores, exigindo um código atual ou de recuperação.
+///
+/// Os códigos de recuperação continuam valendo para redefinir a senha.
+pub fn disable_totp(store: &dyn UserStore, username: &str, code: &str, now: DateTime<Utc>) -> AuthResult<()> {
+    if !verify_second_factor(store, username, code, now)? {
+        return validation_error!("Código inválido");
+    }
+    store.set_totp_secret(username, None)?;
+    audit::record(store, username, "disable_totp", Some(username))
+}
+
+/// Confere o segundo fator: um código do aplicativo ou, no lugar dele, um código de recuperação
+pub fn verify_second_factor(store: &dyn UserStore, username: &str, code: &str, now: DateTime<Utc>) -> AuthResult<bool> {
+    let code = code.trim();
+    if is_totp_code(code) {
+        return verify_totp(store, username, code, now);
+    }
+    if !is_totp_enabled(store, username)? {
+        return Ok(false);
+    }
+    consume_recovery_code(store, username, code)
+}
+
+/// Faz o mesmo trabalho que `verify_second_factor` sem consultar conta nenhuma, para
+/// que o tempo de resposta não revele se o usuário existe
+pub(crate) fn dummy_second_factor(store: &dyn UserStore, code: &str, now: DateTime<Utc>) {
+    let code = code.trim();
+    if is_totp_code(code) {
+        let _ = matching_step(DUMMY_SECRET, code, now);
+    } else {
+        dummy_recovery_code_check(store, code);
+    }
+}
+
+/// Se o código tem o formato de um código do aplicativo (e não de recuperação)
+fn is_totp_code(code: &str) -> bool {
+    code.len() == DIGITS as usize && code.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Confere o código TOTP do usuário. Cada código vale uma única vez: um código já
+/// aceito (ou anterior a ele) é rejeitado mesmo dentro da janela de tempo
+pub fn verify_totp(store: &dyn UserStore, username: &str, code: &str, now: DateTime<Utc>) -> AuthResult<bool> {
+    let Some(secret) = store.get_totp_secret(username)? else {
+        return Ok(false);
+    };
+    match matching_step(&secret, code, now)? {
+        Some(step) => store.claim_totp_step(username, step),
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,464 @@
+//! Interface de tela cheia (`siri --tui`).
+//!
+//! Três painéis — usuários, detalhes da conta selecionada e log de auditoria — e
+//! formulários sobrepostos para cadastrar um usuário e trocar a senha. Tudo passa
+//! pelo `AuthService`, com as mesmas regras do menu interativo.
+
+use std::io::{self, IsTerminal};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use zeroize::Zeroizing;
+use crate::audit::{self, AuditEntry, AuditFilter};
+use crate::error::{AuthError, AuthResult, ErrorKind};
+use crate::output::{self, Stream};
+use crate::service::AuthService;
+use crate::totp::is_totp_enabled;
+use crate::user::{EmailStatus, TimestampDisplay, User};
+use crate::config;
+use crate::{tr, validation_error};
+
+/// Molduras em ASCII, para o modo texto puro
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// Painel que recebe as setas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Users,
+    Audit,
+}
+
+/// Formulários disponíveis
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FormKind {
+    Register,
+    /// Troca de senha do usuário indicado
+    ChangePassword(String),
+}
+
+/// Um campo de formulário; campos secretos aparecem como '*'
+struct Field {
+    label: &'static str,
+    value: Zeroizing<String>,
+    secret: bool,
+}
+
+impl Field {
+    fn text(label: &'static str) -> Self {
+        Field { label, value: Zeroizing::new(String::new()), secret: false }
+    }
+
+    fn secret(label: &'static str) -> Self {
+        Field { label, value: Zeroizing::new(String::new()), secret: true }
+    }
+}
+
+/// Formulário aberto sobre os painéis
+struct Form {
+    kind: FormKind,
+    fields: Vec<Field>,
+    active: usize,
+}
+
+impl Form {
+    fn register() -> Self {
+        let fields = vec![
+            Field::text(tr!("Usuário")),
+            Field::secret(tr!("Senha")),
+            Field::secret(tr!("Confirme a senha")),
+            Field::text(tr!("Email (opcional)")),
+        ];
+        Form { kind: FormKind::Register, fields, active: 0 }
+    }
+
+    fn change_password(username: &str) -> Self {
+        let fields = vec![
+            Field::secret(tr!("Senha atual")),
+            Field::secret(tr!("Nova senha")),
+            Field::secret(tr!("Confirme a nova senha")),
+        ];
+        Form { kind: FormKind::ChangePassword(username.to_string()), fields, active: 0 }
+    }
+
+    fn title(&self) -> String {
+        match &self.kind {
+            FormKind::Register => tr!("Novo usuário").to_string(),
+            FormKind::ChangePassword(username) => tr!("Trocar a senha de '{}'", username),
+        }
+    }
+
+    fn value(&self, index: usize) -> &str {
+        &self.fields[index].value
+    }
+}
+
+/// Mensagem do rodapé, com o resultado da última ação
+struct Status {
+    success: bool,
+    text: String,
+}
+
+/// Estado da interface
+struct App<'a> {
+    service: &'a AuthService,
+    users: Vec<User>,
+    users_state: TableState,
+    audit: Vec<AuditEntry>,
+    audit_state: TableState,
+    focus: Pane,
+    form: Option<Form>,
+    status: Option<Status>,
+    display: TimestampDisplay,
+    quit: bool,
+}
+
+/// Abre a interface de tela cheia e só volta quando o usuário sai
+pub fn run(service: &AuthService) -> AuthResult<()> {
+    if !io::stdout().is_terminal() {
+        return validation_error!("O modo --tui requer um terminal");
+    }
+
+    let mut app = App::new(service)?;
+    let mut terminal = ratatui::try_init()?;
+    let result = app.event_loop(&mut terminal);
+    ratatui::try_restore()?;
+    result
+}
+
+impl<'a> App<'a> {
+    fn new(service: &'a AuthService) -> AuthResult<Self> {
+        let mut app = App {
+            service,
+            users: Vec::new(),
+            users_state: TableState::default(),
+            audit: Vec::new(),
+            audit_state: TableState::default(),
+            focus: Pane::Users,
+            form: None,
+            status: None,
+            display: config::current().display.timezone,
+            quit: false,
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> AuthResult<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Relê usuários e auditoria, mantendo a seleção no mesmo usuário quando possível
+    fn reload(&mut self) -> AuthResult<()> {
+        let selected = self.selected_user().map(|user| user.username.clone());
+        let store = self.service.store();
+        self.users = store.list_users()?;
+        self.audit = audit::search(store, &AuditFilter::default())?;
+
+        let index = selected
+            .and_then(|username| self.users.iter().position(|user| user.username == username))
+            .or_else(|| (!self.users.is_empty()).then_some(0));
+        self.users_state.select(index);
+        if self.audit_state.selected().is_none_or(|i| i >= self.audit.len()) {
+            self.audit_state.select((!self.audit.is_empty()).then_some(0));
+        }
+        Ok(())
+    }
+
+    fn selected_user(&self) -> Option<&User> {
+        self.users_state.selected().and_then(|index| self.users.get(index))
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> AuthResult<()> {
+        if self.form.is_some() {
+            return self.handle_form_key(key);
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Pane::Users => Pane::Audit,
+                    Pane::Audit => Pane::Users,
+                };
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.focused_state().select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.focused_state().select_previous(),
+            KeyCode::Home => self.focused_state().select_first(),
+            KeyCode::End => self.focused_state().select_last(),
+            KeyCode::Char('n') => {
+                self.status = None;
+                self.form = Some(Form::register());
+            }
+            KeyCode::Char('p') => {
+                self.status = None;
+                self.form = self.selected_user().map(|user| Form::change_password(&user.username));
+            }
+            KeyCode::Char('r') => {
+                self.reload()?;
+                self.status = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn focused_state(&mut self) -> &mut TableState {
+        match self.focus {
+            Pane::Users => &mut self.users_state,
+            Pane::Audit => &mut self.audit_state,
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent) -> AuthResult<()> {
+        let Some(form) = self.form.as_mut() else {
+            return Ok(());
+        };
+        let last = form.fields.len() - 1;
+
+        match key.code {
+            KeyCode::Esc => {
+                self.form = None;
+                self.status = Some(Status { success: false, text: tr!("Operação cancelada.").to_string() });
+            }
+            KeyCode::Tab | KeyCode::Down => form.active = (form.active + 1) % form.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => form.active = (form.active + last) % form.fields.len(),
+            KeyCode::Enter if form.active < last => form.active += 1,
+            KeyCode::Enter => {
+                if let Some(form) = self.form.take() {
+                    self.submit(form)?;
+                }
+            }
+            KeyCode::Backspace => {
+                form.fields[form.active].value.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                form.fields[form.active].value.push(c);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Envia um formulário; erros de validação voltam ao formulário, com a mensagem no rodapé
+    fn submit(&mut self, mut form: Form) -> AuthResult<()> {
+        let result = match &form.kind {
+            FormKind::Register if form.value(1) != form.value(2) => validation_error!("As senhas não coincidem."),
+            FormKind::Register => {
+                let username = form.value(0).trim();
+                let email = Some(form.value(3).trim()).filter(|email| !email.is_empty());
+                self.service
+                    .register(username, form.value(1), email)
+                    .map(|_| tr!("Usuário '{}' registrado com sucesso!", username))
+            }
+            FormKind::ChangePassword(_) if form.value(1) != form.value(2) => {
+                validation_error!("As senhas não coincidem.")
+            }
+            FormKind::ChangePassword(username) => self
+                .service
+                .change_password(username, form.value(0), form.value(1))
+                .map(|_| tr!("Senha alterada com sucesso!").to_string()),
+        };
+
+        match result {
+            Ok(text) => {
+                self.status = Some(Status { success: true, text });
+                self.reload()?;
+            }
+            Err(err) => {
+                let text = match err {
+                    err if err.kind() == ErrorKind::Validation => err.detail(),
+                    AuthError::PermissionDenied(msg) => msg.to_string(),
+                    AuthError::InvalidCredentials | AuthError::AccountLocked(_) | AuthError::NotFound(_) => err.to_string(),
+                    err => return Err(err),
+                };
+                self.status = Some(Status { success: false, text });
+                // As senhas são redigitadas; usuário e email ficam
+                for field in form.fields.iter_mut().filter(|field| field.secret) {
+                    field.value.clear();
+                }
+                form.active = form.fields.iter().position(|field| field.secret).unwrap_or(0);
+                self.form = Some(form);
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(main);
+        let [details, audit] = Layout::vertical([Constraint::Length(13), Constraint::Min(0)]).areas(right);
+
+        self.draw_users(frame, left);
+        self.draw_details(frame, details);
+        self.draw_audit(frame, audit);
+        self.draw_footer(frame, footer);
+        if let Some(form) = &self.form {
+            draw_form(frame, form, main);
+        }
+    }
+
+    fn draw_users(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.users.iter().map(|user| {
+            Row::new([format!("#{}", user.id), user.username.clone(), user.role.to_string(), user.status.to_string()])
+        });
+        let widths = [Constraint::Length(5), Constraint::Min(10), Constraint::Length(13), Constraint::Length(10)];
+        let table = Table::new(rows, widths)
+            .header(header([tr!("ID"), tr!("Usuário"), tr!("Papel"), tr!("Situação")]))
+            .block(pane(tr!("Usuários"), self.focus == Pane::Users))
+            .row_highlight_style(highlight());
+        frame.render_stateful_widget(table, area, &mut self.users_state);
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: Rect) {
+        let block = pane(tr!("Detalhes da conta"), false);
+        let Some(user) = self.selected_user() else {
+            frame.render_widget(Paragraph::new(tr!("Nenhum usuário cadastrado.")).block(block), area);
+            return;
+        };
+
+        // Grupos e segundo fator vêm de outras tabelas; uma falha aqui não derruba a tela
+        let store = self.service.store();
+        let groups = store.user_groups(&user.username).map(|groups| groups.join(", ")).unwrap_or_default();
+        let two_factor = is_totp_enabled(store, &user.username).unwrap_or_default();
+        let email = match (&user.email, user.email_status()) {
+            (Some(email), EmailStatus::Verified) => tr!("{} (confirmado)", email),
+            (Some(email), _) => tr!("{} (pendente)", email),
+            (None, _) => "-".to_string(),
+        };
+        let yes_no = |value: bool| if value { tr!("sim") } else { tr!("não") };
+        let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+        let lines = [
+            (tr!("Usuário"), user.username.clone()),
+            (tr!("Nome"), or_dash(&user.display_name)),
+            (tr!("Email"), email),
+            (tr!("Telefone"), or_dash(&user.phone)),
+            (tr!("Papel"), user.role.to_string()),
+            (tr!("Situação"), user.status.to_string()),
+            (tr!("Grupos"), if groups.is_empty() { "-".to_string() } else { groups }),
+            (tr!("Dois fatores"), yes_no(two_factor).to_string()),
+            (tr!("Criada em"), self.display.format(user.created_at)),
+            (tr!("Último login"), user.last_login_at.map_or_else(|| "-".to_string(), |at| self.display.format(at))),
+            (tr!("Logins"), user.login_count.to_string()),
+        ];
+        let lines: Vec<Line> = lines
+            .into_iter()
+            .map(|(label, value)| Line::from(vec![Span::styled(format!("{}: ", label), label_style()), Span::raw(value)]))
+            .collect();
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn draw_audit(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.audit.iter().map(|entry| {
+            Row::new([
+                self.display.format(entry.created_at),
+                entry.actor.clone(),
+                entry.action.clone(),
+                entry.target.clone().unwrap_or_else(|| "-".to_string()),
+            ])
+        });
+        let widths = [Constraint::Length(25), Constraint::Length(16), Constraint::Min(12), Constraint::Length(16)];
+        let table = Table::new(rows, widths)
+            .header(header([tr!("Quando"), tr!("Autor"), tr!("Ação"), tr!("Alvo")]))
+            .block(pane(tr!("Auditoria"), self.focus == Pane::Audit))
+            .row_highlight_style(highlight());
+        frame.render_stateful_widget(table, area, &mut self.audit_state);
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let line = match (&self.status, &self.form) {
+            (Some(status), _) => {
+                let color = if status.success { Color::Green } else { Color::Red };
+                Line::styled(status.text.as_str(), colored(color))
+            }
+            (None, Some(_)) => Line::raw(tr!("Tab: próximo campo | Enter: confirmar | Esc: cancelar")),
+            (None, None) => Line::raw(tr!(
+                "Setas: navegar | Tab: trocar painel | n: novo usuário | p: trocar senha | r: recarregar | q: sair"
+            )),
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+}
+
+fn draw_form(frame: &mut Frame, form: &Form, area: Rect) {
+    let height = form.fields.len() as u16 + 2;
+    let [_, area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(height), Constraint::Fill(1)]).areas(area);
+    let [_, area, _] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Length(60), Constraint::Fill(1)]).areas(area);
+
+    let label_width = form.fields.iter().map(|field| field.label.chars().count()).max().unwrap_or_default() + 2;
+    let lines: Vec<Line> = form
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let value = if field.secret { "*".repeat(field.value.chars().count()) } else { field.value.to_string() };
+            let style = if index == form.active { label_style() } else { Style::default() };
+            Line::from(vec![Span::styled(format!("{:<width$}", format!("{}:", field.label), width = label_width), style), Span::raw(value)])
+        })
+        .collect();
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(pane(&form.title(), true)), area);
+
+    let active = &form.fields[form.active];
+    let column = area.x + 1 + label_width as u16 + active.value.chars().count() as u16;
+    frame.set_cursor_position((column.min(area.right().saturating_sub(2)), area.y + 1 + form.active as u16));
+}
+
+/// Moldura de painel, destacada quando o painel tem o foco
+fn pane(title: &str, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(format!(" {} ", title));
+    let block = if output::is_plain() { block.border_set(ASCII_BORDER) } else { block };
+    if focused {
+        block.border_style(colored(Color::Cyan).add_modifier(Modifier::BOLD))
+    } else {
+        block
+    }
+}
+
+fn header<const N: usize>(titles: [&str; N]) -> Row<'static> {
+    Row::new(titles.map(str::to_string)).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn highlight() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+fn label_style() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
+}
+
+/// Cor de primeiro plano, respeitando `--no-color` e `NO_COLOR`
+fn colored(color: Color) -> Style {
+    if output::color_enabled(Stream::Stdout) {
+        Style::default().fg(color)
+    } else {
+        Style::default()
+    }
+}
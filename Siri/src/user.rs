@@ -0,0 +1,122 @@
+use std::fmt;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::Deserialize;
+use crate::rbac::Role;
+use crate::tr;
+
+/// Usuário cadastrado, com as datas já convertidas do formato do SQLite
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub email: Option<String>,
+    /// Quando o email atual foi confirmado (None enquanto pendente)
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub role: Role,
+    pub status: AccountStatus,
+    /// Campos opcionais do perfil (ver `profile::Profile`)
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub bio: Option<String>,
+    /// Último login bem-sucedido (None se nunca entrou)
+    pub last_login_at: Option<DateTime<Utc>>,
+    /// Quantidade de logins bem-sucedidos
+    pub login_count: u32,
+}
+
+/// Situação de uma conta, gravada na coluna `status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccountStatus {
+    /// Conta em uso normal (padrão)
+    #[default]
+    Active,
+    /// Conta desativada por um administrador: os dados ficam, mas o login é recusado
+    Disabled,
+}
+
+impl AccountStatus {
+    /// Todas as situações
+    pub const ALL: [AccountStatus; 2] = [AccountStatus::Active, AccountStatus::Disabled];
+
+    /// Nome da situação, como gravado na coluna `status`
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Disabled => "disabled",
+        }
+    }
+
+    /// Converte o valor da coluna; uma situação desconhecida não permite o login
+    pub fn from_name(name: &str) -> AccountStatus {
+        let name = name.trim();
+        AccountStatus::ALL
+            .into_iter()
+            .find(|status| status.name().eq_ignore_ascii_case(name))
+            .unwrap_or(AccountStatus::Disabled)
+    }
+
+    pub fn is_active(&self) -> bool {
+        *self == AccountStatus::Active
+    }
+}
+
+impl fmt::Display for AccountStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            AccountStatus::Active => tr!("ativa"),
+            AccountStatus::Disabled => tr!("desativada"),
+        };
+        f.pad(label)
+    }
+}
+
+/// Estado do email de uma conta
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailStatus {
+    /// A conta não tem email cadastrado
+    Missing,
+    /// Email cadastrado, aguardando a confirmação por token
+    Pending,
+    /// Email confirmado
+    Verified,
+}
+
+impl User {
+    /// Estado da verificação do email da conta
+    pub fn email_status(&self) -> EmailStatus {
+        match (&self.email, self.email_verified_at) {
+            (None, _) => EmailStatus::Missing,
+            (Some(_), None) => EmailStatus::Pending,
+            (Some(_), Some(_)) => EmailStatus::Verified,
+        }
+    }
+}
+
+/// Seção `[display]` do arquivo de configuração
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Fuso das datas exibidas: "local" (padrão) ou "utc"; o JSON usa sempre UTC
+    pub timezone: TimestampDisplay,
+}
+
+/// Fuso usado para exibir datas ao usuário
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampDisplay {
+    Utc,
+    /// Fuso horário da máquina (padrão)
+    #[default]
+    Local,
+}
+
+impl TimestampDisplay {
+    /// Formata o instante em RFC 3339 no fuso escolhido
+    pub fn format(&self, value: DateTime<Utc>) -> String {
+        match self {
+            TimestampDisplay::Utc => value.to_rfc3339_opts(SecondsFormat::Secs, true),
+            TimestampDisplay::Local => value.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Secs, false),
+        }
+    }
+}